@@ -2,32 +2,45 @@ use std::collections::HashMap;
 
 use crate::{
     commands::{
+        guild_scores::view_all_components,
         input_validation::validate_search_term,
+        media_tabs::{MediaTabsView, media_tab_components},
         response::CommandResponse,
         traits::{AniListSource, MediaDataSource},
     },
     models::{
-        anilist_anime::Anime, anilist_common::TitleVariant, settings::TitleDisplayPreference,
-        transformers::Transformers, user_media_list::MediaListData,
+        anilist_anime::Anime,
+        anilist_common::TitleVariant,
+        settings::{StreamingRegion, TitleDisplayPreference},
+        transformers::Transformers,
+        user_media_list::MediaListData,
     },
     utils::{
         channel::is_nsfw_channel,
-        guild::{get_current_guild_members, get_guild_data_for_media},
+        guild::get_guild_data_for_media,
         privacy::configure_sentry_scope,
-        settings::resolve_title_display_preference,
-        statics::{NOT_FOUND_ANIME, NSFW_NOT_ALLOWED},
+        settings::{
+            resolve_spoiler_preference, resolve_streaming_region, resolve_title_display_preference,
+        },
+        spoilers::{parse_spoilers_option, spoilers_option},
+        statics::{ANILIST_LOOKUP_FAILED, NOT_FOUND_ANIME, NSFW_NOT_ALLOWED},
     },
 };
 
 use serde_json::json;
 use serenity::{
-    all::{CommandInteraction, CreateCommandOption, EditInteractionResponse},
+    all::{
+        CommandDataOption, CommandDataOptionValue, CommandInteraction, CreateCommandOption,
+        EditInteractionResponse,
+    },
     builder::CreateCommand,
     client::Context,
     model::application::CommandOptionType,
 };
 
-use tracing::{info, instrument};
+use tracing::{error, info, instrument};
+
+const SEARCH_OPTION: &str = "search";
 
 pub fn register() -> CreateCommand {
     CreateCommand::new("anime")
@@ -35,11 +48,14 @@ pub fn register() -> CreateCommand {
         .add_option(
             CreateCommandOption::new(
                 CommandOptionType::String,
-                "search",
+                SEARCH_OPTION,
                 "AniList ID or anime search term",
             )
             .required(true),
         )
+        .add_option(spoilers_option(
+            "Show description spoilers (defaults to your /settings choice)",
+        ))
 }
 
 // ── Core logic (transport-agnostic) ─────────────────────────────────────
@@ -57,6 +73,8 @@ pub fn handle_anime(
     guild_members_data: Option<HashMap<u64, MediaListData>>,
     title_variant: Option<TitleVariant>,
     title_preference: TitleDisplayPreference,
+    allow_spoilers: bool,
+    streaming_region: StreamingRegion,
 ) -> CommandResponse {
     match anime {
         None => CommandResponse::Content(NOT_FOUND_ANIME.to_string()),
@@ -65,12 +83,25 @@ pub fn handle_anime(
                 guild_members_data,
                 title_variant,
                 title_preference,
+                allow_spoilers,
+                streaming_region,
             );
             CommandResponse::Embed(Box::new(embed))
         }
     }
 }
 
+/// The `search` option, wherever it sits among the others.
+fn parse_search_option(options: &[CommandDataOption]) -> Option<String> {
+    options
+        .iter()
+        .find(|option| option.name == SEARCH_OPTION)
+        .and_then(|option| match &option.value {
+            CommandDataOptionValue::String(search_term) => Some(search_term.clone()),
+            _ => None,
+        })
+}
+
 // ── Serenity adapter (thin wrapper) ─────────────────────────────────────
 
 #[instrument(name = "command.anime.run", skip(ctx, interaction))]
@@ -80,15 +111,12 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
     let user = &interaction.user;
 
     // Validate the required "search" option up-front.
-    let Some(serenity::all::CommandDataOptionValue::String(search_term)) =
-        interaction.data.options.first().map(|opt| &opt.value)
-    else {
+    let Some(search_term) = parse_search_option(&interaction.data.options) else {
         let builder = EditInteractionResponse::new()
             .content("Tell me which anime to look up with `search:<name or AniList ID>`.");
         let _ = interaction.edit_response(&ctx.http, builder).await;
         return;
     };
-    let search_term = search_term.clone();
 
     if let Err(err) = validate_search_term(&search_term) {
         let builder = EditInteractionResponse::new().content(format!(
@@ -102,13 +130,25 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
 
     info!("Got command 'anime' with search_term: {search_term}");
 
-    let (fetch_result, title_preference): (Option<(Anime, TitleVariant)>, TitleDisplayPreference) = tokio::join!(
-        AniListSource.fetch_anime(&search_term),
+    let requested_spoilers = parse_spoilers_option(&interaction.data.options);
+
+    let source = AniListSource::from_context(ctx).await;
+    let (fetch_result, title_preference, allow_spoilers, streaming_region) = tokio::join!(
+        source.fetch_anime(&search_term),
         resolve_title_display_preference(ctx, user.id, interaction.guild_id),
+        resolve_spoiler_preference(ctx, user.id, requested_spoilers),
+        resolve_streaming_region(ctx, user.id, interaction.guild_id),
     );
     let (anime_result, title_variant): (Option<Anime>, Option<TitleVariant>) = match fetch_result {
-        Some((anime, variant)) => (Some(anime), Some(variant)),
-        None => (None, None),
+        Ok(Some((anime, variant))) => (Some(anime), Some(variant)),
+        Ok(None) => (None, None),
+        Err(err) => {
+            error!(error = %err, "Failed to fetch anime");
+            let message = err.user_message().unwrap_or(ANILIST_LOOKUP_FAILED);
+            let builder = EditInteractionResponse::new().content(message);
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
     };
 
     // Block adult content in non-NSFW channels.
@@ -125,30 +165,31 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
     let guild_members_data = match &anime_result {
         None => None,
         Some(anime_response) => {
-            let guild_members = get_current_guild_members(ctx, interaction);
-            if guild_members.is_empty() {
-                info!("No users found in guild");
-                None
-            } else {
-                let data = get_guild_data_for_media(
-                    ctx,
-                    anime_response,
-                    interaction.guild_id,
-                    guild_members,
-                )
-                .await;
-                info!("Guild members data: {} entries", data.len());
-                if data.is_empty() { None } else { Some(data) }
-            }
+            let data = get_guild_data_for_media(ctx, anime_response, interaction.guild_id).await;
+            info!("Guild members data: {} entries", data.len());
+            if data.is_empty() { None } else { Some(data) }
         }
     };
 
+    // Tabs for the stats, relations and staff pages; guild scores also get a
+    // "View all" button that opens the full, sortable list.
+    let mut components = anime_result
+        .as_ref()
+        .and_then(|anime| MediaTabsView::overview(anime, title_variant, allow_spoilers))
+        .map(media_tab_components)
+        .unwrap_or_default();
+    if let (Some(anime), Some(_)) = (&anime_result, &guild_members_data) {
+        components.extend(view_all_components(anime.get_type(), anime.get_id()));
+    }
+
     // Delegate to the transport-agnostic core logic.
     let response = handle_anime(
         anime_result,
         guild_members_data,
         title_variant,
         title_preference,
+        allow_spoilers,
+        streaming_region,
     );
 
     // Map the CommandResponse to the appropriate Discord API call.
@@ -158,7 +199,9 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
             interaction.edit_response(&ctx.http, builder).await
         }
         CommandResponse::Embed(embed) => {
-            let builder = EditInteractionResponse::new().embed(*embed);
+            let builder = EditInteractionResponse::new()
+                .embed(*embed)
+                .components(components);
             interaction.edit_response(&ctx.http, builder).await
         }
         CommandResponse::Message(text) => {
@@ -173,6 +216,7 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::statics::STALE_DATA_NOTICE;
 
     /// Helper: build a minimal `Anime` from JSON for testing.
     fn sample_anime() -> Anime {
@@ -221,9 +265,28 @@ mod tests {
         TitleDisplayPreference::Matched
     }
 
+    #[test]
+    fn search_option_is_found_by_name_when_spoilers_come_first() {
+        let options: Vec<CommandDataOption> = serde_json::from_value(serde_json::json!([
+            { "name": "spoilers", "type": 3, "value": "allow" },
+            { "name": "search", "type": 3, "value": "One Piece" }
+        ]))
+        .unwrap();
+
+        assert_eq!(parse_search_option(&options).as_deref(), Some("One Piece"));
+        assert_eq!(parse_spoilers_option(&options), Some(true));
+    }
+
     #[test]
     fn anime_not_found_returns_content_with_message() {
-        let response = handle_anime(None, None, None, matched_title_preference());
+        let response = handle_anime(
+            None,
+            None,
+            None,
+            matched_title_preference(),
+            false,
+            StreamingRegion::All,
+        );
 
         assert!(response.is_content(), "expected Content variant");
         assert_eq!(response.unwrap_content(), NOT_FOUND_ANIME);
@@ -231,7 +294,14 @@ mod tests {
 
     #[test]
     fn anime_success_returns_embed() {
-        let response = handle_anime(Some(sample_anime()), None, None, matched_title_preference());
+        let response = handle_anime(
+            Some(sample_anime()),
+            None,
+            None,
+            matched_title_preference(),
+            false,
+            StreamingRegion::All,
+        );
 
         assert!(
             response.is_embed(),
@@ -244,7 +314,14 @@ mod tests {
 
     #[test]
     fn anime_success_with_no_guild_data_still_returns_embed() {
-        let response = handle_anime(Some(sample_anime()), None, None, matched_title_preference());
+        let response = handle_anime(
+            Some(sample_anime()),
+            None,
+            None,
+            matched_title_preference(),
+            false,
+            StreamingRegion::All,
+        );
 
         assert!(response.is_embed());
     }
@@ -343,6 +420,8 @@ mod tests {
             None,
             Some(TitleVariant::English),
             matched_title_preference(),
+            false,
+            StreamingRegion::All,
         );
 
         let (title, footer) = embed_title_and_footer(response);
@@ -357,6 +436,8 @@ mod tests {
             None,
             Some(TitleVariant::Romaji),
             matched_title_preference(),
+            false,
+            StreamingRegion::All,
         );
 
         let (title, footer) = embed_title_and_footer(response);
@@ -371,6 +452,8 @@ mod tests {
             None,
             None,
             matched_title_preference(),
+            false,
+            StreamingRegion::All,
         );
 
         let (title, footer) = embed_title_and_footer(response);
@@ -385,6 +468,8 @@ mod tests {
             None,
             Some(TitleVariant::Romaji),
             TitleDisplayPreference::English,
+            false,
+            StreamingRegion::All,
         );
 
         let (title, footer) = embed_title_and_footer(response);
@@ -399,6 +484,8 @@ mod tests {
             None,
             Some(TitleVariant::English),
             TitleDisplayPreference::Romaji,
+            false,
+            StreamingRegion::All,
         );
 
         let (title, footer) = embed_title_and_footer(response);
@@ -413,6 +500,8 @@ mod tests {
             None,
             Some(TitleVariant::English),
             TitleDisplayPreference::Native,
+            false,
+            StreamingRegion::All,
         );
 
         let (title, footer) = embed_title_and_footer(response);
@@ -427,10 +516,30 @@ mod tests {
             None,
             Some(TitleVariant::English),
             TitleDisplayPreference::Native,
+            false,
+            StreamingRegion::All,
         );
 
         let (title, footer) = embed_title_and_footer(response);
         assert_eq!(title, "Haibane Renmei");
         assert_eq!(footer, "Haibane Renmei");
     }
+
+    #[test]
+    fn stale_anime_notes_it_may_be_out_of_date_in_footer() {
+        let mut anime = anime_with_distinct_titles();
+        anime.mark_stale();
+
+        let response = handle_anime(
+            Some(anime),
+            None,
+            Some(TitleVariant::Romaji),
+            matched_title_preference(),
+            false,
+            StreamingRegion::All,
+        );
+
+        let (_, footer) = embed_title_and_footer(response);
+        assert_eq!(footer, format!("Attack on Titan • {STALE_DATA_NOTICE}"));
+    }
 }
@@ -1,125 +1,35 @@
-pub const FETCH_ANIME_BY_ID: &str = "
-query ($id: Int) {
-  Media (id: $id, type: ANIME) {
-    type
-    id
-    idMal
-    isAdult
-    title {
-      romaji
-      english
-      native
-    }
-    synonyms
-    season
-    seasonYear
-    format
-    status
-    episodes
-    nextAiringEpisode {
-      episode
-    }
-    duration
-    genres
-    source
-    coverImage {
-      extraLarge
-      large
-      medium
-      color
-    }
-    averageScore
-    studios {
-      edges {
-        id
-        isMain
-      }
-      nodes {
-        id
-        name
-      }
-    }
-    siteUrl
-    externalLinks {
-      url
-      type
-    }
-    trailer {
-      id
-      site
-    }
-    description
-    tags {
-      name
-    }
-  }
-}
-";
+use std::sync::LazyLock;
+
+use crate::utils::requests::{
+    batch::IdBatcher,
+    fragments::{cover_image, media_core, media_search, studios, tags},
+    query::Selection,
+};
 
-pub const FETCH_ANIME: &str = "
-query ($page: Int, $perPage: Int, $search: String) {
-  Page(page: $page, perPage: $perPage) {
-    pageInfo {
-      total
-      currentPage
-      lastPage
-      hasNextPage
-      perPage
-    }
-    media(search: $search) {
-      type
-      id
-      idMal
-      isAdult
-      title {
-        romaji
-        english
-        native
-      }
-      synonyms
-      season
-      seasonYear
-      format
-      status
-      episodes
-      nextAiringEpisode {
-        episode
-      }
-      duration
-      genres
-      source
-      coverImage {
-        extraLarge
-        large
-        medium
-        color
-      }
-      averageScore
-      studios {
-        edges {
-          id
-          isMain
-        }
-        nodes {
-          id
-          name
-        }
-      }
-      siteUrl
-      externalLinks {
-        url
-        type
-      }
-      trailer {
-        id
-        site
-      }
-      description
-      tags {
-        name
-      }
-    }
-  }
+fn anime_fields() -> Selection {
+    Selection::new()
+        .spread(&media_core())
+        .spread(&cover_image())
+        .spread(&studios())
+        .spread(&tags())
+        .fields(&["idMal", "season", "seasonYear", "episodes"])
+        .object(
+            "nextAiringEpisode",
+            Selection::new().fields(&["episode", "airingAt"]),
+        )
+        .fields(&["duration", "source"])
+        .object(
+            "externalLinks",
+            Selection::new().fields(&["url", "type", "site", "language", "isDisabled", "notes"]),
+        )
+        .object("trailer", Selection::new().fields(&["id", "site"]))
+        .field("description")
 }
 
-";
+/// Batched `Media(id: …, type: ANIME)` lookups.
+pub static ANIME_BY_ID: LazyLock<IdBatcher> =
+    LazyLock::new(|| IdBatcher::new("Media", &[("type", "ANIME")], anime_fields()));
+
+pub fn fetch_anime() -> String {
+    media_search("ANIME", anime_fields()).render()
+}
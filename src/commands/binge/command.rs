@@ -0,0 +1,656 @@
+//! `/binge`: how long it takes to watch (or read) the rest of a title.
+//!
+//! Anime use AniList's episode count and length; manga use chapters (or
+//! volumes when the chapter count is unknown) at an estimated reading pace.
+//! Linked users start from their AniList progress unless they pick a start.
+
+use crate::{
+    commands::{
+        input_validation::validate_search_term,
+        response::CommandResponse,
+        traits::{AniListSource, MediaDataSource},
+    },
+    models::{
+        anilist_anime::Anime, anilist_common::TitleVariant, anilist_manga::Manga,
+        db::oauth_credential::OAuthCredential, media_type::MediaType,
+        settings::TitleDisplayPreference, transformers::Transformers,
+    },
+    utils::{
+        cache::get_cache_from_context,
+        channel::is_nsfw_channel,
+        database::get_pool_from_context,
+        formatter::{bold, format_runtime},
+        interaction::{anilist_type, respond},
+        media_list::fetch_user_media_list_entry,
+        privacy::{configure_sentry_scope, hash_user_id},
+        requests::graphql::AniListError,
+        settings::resolve_title_display_preference,
+        statics::{ANILIST_LOOKUP_FAILED, NOT_FOUND_ANIME, NOT_FOUND_MANGA, NSFW_NOT_ALLOWED},
+    },
+};
+
+use serde_json::json;
+use serenity::{
+    all::{
+        CommandDataOption, CommandDataOptionValue, CommandInteraction, CreateCommandOption,
+        CreateEmbed, CreateEmbedFooter, EditInteractionResponse, UserId,
+    },
+    builder::CreateCommand,
+    client::Context,
+    model::application::CommandOptionType,
+};
+use tracing::{error, info, instrument, warn};
+
+const SEARCH_OPTION: &str = "search";
+const TYPE_OPTION: &str = "type";
+const FROM_OPTION: &str = "from_episode";
+const SPEED_OPTION: &str = "speed";
+const SKIP_OP_ED_OPTION: &str = "skip_op_ed";
+const ANIME_TYPE: &str = "anime";
+const MANGA_TYPE: &str = "manga";
+
+/// Minutes saved per episode by skipping the opening and ending.
+const OPENING_ENDING_MINUTES: u32 = 3;
+/// Episodes shorter than this rarely have a full opening and ending.
+const MIN_SKIPPABLE_EPISODE_MINUTES: u32 = 10;
+/// Estimated reading time for one manga chapter.
+const MINUTES_PER_CHAPTER: u32 = 5;
+/// Estimated chapters per volume, for manga without a chapter count.
+const CHAPTERS_PER_VOLUME: u32 = 9;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("binge")
+        .description("Work out how long it takes to binge the rest of an anime or manga")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                SEARCH_OPTION,
+                "AniList ID or search term",
+            )
+            .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, TYPE_OPTION, "Anime or manga")
+                .add_string_choice("Anime", ANIME_TYPE)
+                .add_string_choice("Manga", MANGA_TYPE),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                FROM_OPTION,
+                "Episode (or chapter) to start from; defaults to your AniList progress",
+            )
+            .min_int_value(1),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Number,
+                SPEED_OPTION,
+                "Playback or reading speed, e.g. 1.5",
+            )
+            .min_number_value(0.5)
+            .max_number_value(3.0),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            SKIP_OP_ED_OPTION,
+            "Skip each episode's opening and ending",
+        ))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BingeRequest {
+    pub search_term: String,
+    pub media_type: MediaType,
+    pub options: BingeOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BingeOptions {
+    /// First episode or chapter to count, overriding the user's progress.
+    pub from: Option<u32>,
+    pub speed: f64,
+    pub skip_op_ed: bool,
+}
+
+impl Default for BingeOptions {
+    fn default() -> Self {
+        Self {
+            from: None,
+            speed: 1.0,
+            skip_op_ed: false,
+        }
+    }
+}
+
+/// Where the count starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartingPoint {
+    Beginning,
+    /// The `from_episode` option.
+    Requested(u32),
+    /// Episodes or chapters already on the user's AniList list.
+    ListProgress(u32),
+}
+
+impl StartingPoint {
+    #[instrument(name = "command.binge.starting_point")]
+    pub fn resolve(from: Option<u32>, list_progress: Option<u32>) -> Self {
+        match (from, list_progress) {
+            (Some(from), _) => Self::Requested(from),
+            (None, Some(progress)) if progress > 0 => Self::ListProgress(progress),
+            _ => Self::Beginning,
+        }
+    }
+
+    /// Episodes or chapters already behind the user.
+    fn done(self) -> u32 {
+        match self {
+            Self::Beginning => 0,
+            Self::Requested(from) => from.saturating_sub(1),
+            Self::ListProgress(progress) => progress,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BingePlan {
+    /// Episodes or chapters still to go.
+    pub remaining: u32,
+    pub start: StartingPoint,
+    pub minutes: u32,
+    /// Set when a manga's chapter count was estimated from its volumes.
+    pub estimated_from_volumes: Option<u32>,
+}
+
+#[instrument(name = "command.binge.parse_options", skip(options))]
+fn parse_binge_options(options: &[CommandDataOption]) -> Option<BingeRequest> {
+    let mut search_term = None;
+    let mut media_type = MediaType::Anime;
+    let mut binge_options = BingeOptions::default();
+
+    for option in options {
+        match (option.name.as_str(), &option.value) {
+            (SEARCH_OPTION, CommandDataOptionValue::String(value)) => {
+                search_term = Some(value.clone());
+            }
+            (TYPE_OPTION, CommandDataOptionValue::String(value)) if value == MANGA_TYPE => {
+                media_type = MediaType::Manga;
+            }
+            (FROM_OPTION, CommandDataOptionValue::Integer(value)) => {
+                binge_options.from = u32::try_from(*value).ok().filter(|from| *from > 0);
+            }
+            (SPEED_OPTION, CommandDataOptionValue::Number(value)) => {
+                binge_options.speed = value.clamp(0.5, 3.0);
+            }
+            (SKIP_OP_ED_OPTION, CommandDataOptionValue::Boolean(value)) => {
+                binge_options.skip_op_ed = *value;
+            }
+            _ => {}
+        }
+    }
+
+    Some(BingeRequest {
+        search_term: search_term?,
+        media_type,
+        options: binge_options,
+    })
+}
+
+#[instrument(name = "command.binge.at_speed")]
+fn at_speed(minutes: u32, speed: f64) -> u32 {
+    (f64::from(minutes) / speed).round() as u32
+}
+
+/// `None` when AniList doesn't know the episode count or length.
+#[instrument(name = "command.binge.plan_anime", skip(anime))]
+pub fn plan_anime_binge(
+    anime: &Anime,
+    options: &BingeOptions,
+    list_progress: Option<u32>,
+) -> Option<BingePlan> {
+    let released = anime.released_episodes()?;
+    let mut per_episode = anime.duration()?;
+    if options.skip_op_ed && per_episode >= MIN_SKIPPABLE_EPISODE_MINUTES {
+        per_episode -= OPENING_ENDING_MINUTES;
+    }
+
+    let start = StartingPoint::resolve(options.from, list_progress);
+    let remaining = released.saturating_sub(start.done());
+
+    Some(BingePlan {
+        remaining,
+        start,
+        minutes: at_speed(remaining * per_episode, options.speed),
+        estimated_from_volumes: None,
+    })
+}
+
+/// `None` when AniList knows neither the chapter nor the volume count.
+#[instrument(name = "command.binge.plan_manga", skip(manga))]
+pub fn plan_manga_binge(
+    manga: &Manga,
+    options: &BingeOptions,
+    list_progress: Option<u32>,
+) -> Option<BingePlan> {
+    let (chapters, estimated_from_volumes) = match (manga.chapters(), manga.volumes()) {
+        (Some(chapters), _) => (chapters, None),
+        (None, Some(volumes)) => (volumes * CHAPTERS_PER_VOLUME, Some(volumes)),
+        (None, None) => return None,
+    };
+
+    let start = StartingPoint::resolve(options.from, list_progress);
+    let remaining = chapters.saturating_sub(start.done());
+
+    Some(BingePlan {
+        remaining,
+        start,
+        minutes: at_speed(remaining * MINUTES_PER_CHAPTER, options.speed),
+        estimated_from_volumes,
+    })
+}
+
+pub fn handle_binge<T: Transformers>(
+    media: &T,
+    plan: Option<BingePlan>,
+    options: &BingeOptions,
+    title_variant: Option<TitleVariant>,
+    title_preference: TitleDisplayPreference,
+) -> CommandResponse {
+    let is_anime = media.get_type() == "anime";
+    let title = media.transform_preferred_title(title_variant, title_preference);
+    let Some(plan) = plan else {
+        return CommandResponse::Content(format!(
+            "AniList doesn't list enough {} details for {title} to estimate a binge yet.",
+            if is_anime { "episode" } else { "chapter" }
+        ));
+    };
+    if plan.remaining == 0 {
+        return CommandResponse::Content(format!("You're all caught up on {title}."));
+    }
+
+    let unit = if is_anime { "episode" } else { "chapter" };
+    let plural = if plan.remaining == 1 { "" } else { "s" };
+    let start = match plan.start {
+        StartingPoint::Beginning => "from the start".to_string(),
+        StartingPoint::Requested(from) => format!("starting at {unit} {from}"),
+        StartingPoint::ListProgress(progress) => format!(
+            "starting at {unit} {} (from your AniList progress)",
+            progress + 1
+        ),
+    };
+
+    let mut lines = vec![format!(
+        "{} to go, {start}.",
+        bold(&format!("{} {unit}{plural}", plan.remaining))
+    )];
+    if let Some(volumes) = plan.estimated_from_volumes {
+        lines.push(format!(
+            "Chapter count estimated from {volumes} volumes at about {CHAPTERS_PER_VOLUME} chapters each."
+        ));
+    }
+
+    let mut how = Vec::new();
+    if (options.speed - 1.0).abs() > f64::EPSILON {
+        how.push(format!("at {}× speed", options.speed));
+    }
+    if is_anime && options.skip_op_ed {
+        how.push("skipping openings and endings".to_string());
+    }
+    if !is_anime {
+        how.push(format!("at about {MINUTES_PER_CHAPTER} minutes a chapter"));
+    }
+    let total = format!("≈ {}", bold(&format_runtime(plan.minutes)));
+    lines.push(if how.is_empty() {
+        format!("{total} total.")
+    } else {
+        format!("{total} total, {}.", how.join(", "))
+    });
+
+    let mut embed = CreateEmbed::new()
+        .color(media.transform_color())
+        .title(format!("Binge plan: {title}"))
+        .url(media.transform_anilist())
+        .description(lines.join("\n"))
+        .footer(CreateEmbedFooter::new(if is_anime {
+            "Based on AniList episode counts and lengths"
+        } else {
+            "Reading times are estimates"
+        }));
+    let thumbnail = media.transform_thumbnail();
+    if !thumbnail.is_empty() {
+        embed = embed.thumbnail(thumbnail);
+    }
+
+    CommandResponse::Embed(Box::new(embed))
+}
+
+/// The user's progress on `media_id`, when they have linked AniList and it is
+/// on their list. Lookup failures just mean starting from the beginning.
+#[instrument(name = "command.binge.list_progress", skip(ctx))]
+async fn linked_list_progress(
+    ctx: &Context,
+    user_id: UserId,
+    media_id: u32,
+    media_type: &MediaType,
+) -> Option<u32> {
+    let database_pool = get_pool_from_context(ctx).await?;
+    let profile = match OAuthCredential::get_by_discord_id(user_id, &database_pool).await {
+        Ok(profile) => profile?,
+        Err(err) => {
+            warn!(
+                error = %err,
+                discord_user_id = %hash_user_id(user_id.get()),
+                "Failed to fetch linked AniList account"
+            );
+            return None;
+        }
+    };
+
+    let cache = get_cache_from_context(ctx).await;
+    match fetch_user_media_list_entry(
+        cache.as_ref(),
+        profile.anilist_id,
+        anilist_type(media_type),
+        media_id,
+    )
+    .await
+    {
+        Ok(entry) => entry.and_then(|entry| entry.progress),
+        Err(err) => {
+            warn!(error = %err, "Failed to fetch AniList list entry for /binge");
+            None
+        }
+    }
+}
+
+#[instrument(name = "command.binge.run", skip(ctx, interaction))]
+pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
+    let _ = interaction.defer(&ctx.http).await;
+
+    let Some(request) = parse_binge_options(&interaction.data.options) else {
+        respond(
+            ctx,
+            interaction,
+            "Tell me what to binge with `search:<name or AniList ID>`.",
+        )
+        .await;
+        return;
+    };
+
+    if let Err(err) = validate_search_term(&request.search_term) {
+        respond(
+            ctx,
+            interaction,
+            &format!("I couldn't use that search: {err}. Try a title or AniList ID."),
+        )
+        .await;
+        return;
+    }
+
+    let user_id = interaction.user.id;
+    configure_sentry_scope(
+        "Binge",
+        user_id.get(),
+        Some(json!({
+            "type": request.media_type.as_ref(),
+            "search": request.search_term,
+        })),
+    );
+    info!(
+        media_type = ?request.media_type,
+        "Got command 'binge' with search_term: {}", request.search_term
+    );
+
+    let source = AniListSource::from_context(ctx).await;
+    let title_preference =
+        resolve_title_display_preference(ctx, user_id, interaction.guild_id).await;
+    let response = match request.media_type {
+        MediaType::Anime => match source.fetch_anime(&request.search_term).await {
+            Ok(Some((anime, title_variant))) => {
+                if let Some(message) = adult_block(ctx, interaction, &anime).await {
+                    message
+                } else {
+                    let progress = match request.options.from {
+                        Some(_) => None,
+                        None => {
+                            linked_list_progress(ctx, user_id, anime.get_id(), &MediaType::Anime)
+                                .await
+                        }
+                    };
+                    let plan = plan_anime_binge(&anime, &request.options, progress);
+                    handle_binge(
+                        &anime,
+                        plan,
+                        &request.options,
+                        Some(title_variant),
+                        title_preference,
+                    )
+                }
+            }
+            Ok(None) => CommandResponse::Content(NOT_FOUND_ANIME.to_string()),
+            Err(err) => lookup_failed(&err),
+        },
+        MediaType::Manga => match source.fetch_manga(&request.search_term).await {
+            Ok(Some((manga, title_variant))) => {
+                if let Some(message) = adult_block(ctx, interaction, &manga).await {
+                    message
+                } else {
+                    let progress = match request.options.from {
+                        Some(_) => None,
+                        None => {
+                            linked_list_progress(ctx, user_id, manga.get_id(), &MediaType::Manga)
+                                .await
+                        }
+                    };
+                    let plan = plan_manga_binge(&manga, &request.options, progress);
+                    handle_binge(
+                        &manga,
+                        plan,
+                        &request.options,
+                        Some(title_variant),
+                        title_preference,
+                    )
+                }
+            }
+            Ok(None) => CommandResponse::Content(NOT_FOUND_MANGA.to_string()),
+            Err(err) => lookup_failed(&err),
+        },
+    };
+
+    let builder = match response {
+        CommandResponse::Content(text) | CommandResponse::Message(text) => {
+            EditInteractionResponse::new().content(text)
+        }
+        CommandResponse::Embed(embed) => EditInteractionResponse::new().embed(*embed),
+    };
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+#[instrument]
+fn lookup_failed(err: &AniListError) -> CommandResponse {
+    error!(error = %err, "Failed to fetch media for binge");
+    CommandResponse::Content(
+        err.user_message()
+            .unwrap_or(ANILIST_LOOKUP_FAILED)
+            .to_string(),
+    )
+}
+
+#[instrument(skip(ctx, interaction, media))]
+async fn adult_block<T: Transformers>(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    media: &T,
+) -> Option<CommandResponse> {
+    (media.is_adult() && !is_nsfw_channel(ctx, interaction.channel_id, interaction.guild_id).await)
+        .then(|| CommandResponse::Content(NSFW_NOT_ALLOWED.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anime(episodes: u32, duration: u32) -> Anime {
+        serde_json::from_value(json!({
+            "type": "ANIME",
+            "id": 1,
+            "idMal": null,
+            "isAdult": false,
+            "title": { "romaji": "Cowboy Bebop", "english": null, "native": null },
+            "synonyms": null,
+            "season": null,
+            "seasonYear": null,
+            "format": "TV",
+            "status": "FINISHED",
+            "episodes": episodes,
+            "nextAiringEpisode": null,
+            "duration": duration,
+            "genres": [],
+            "source": null,
+            "coverImage": { "extraLarge": null, "large": null, "medium": null, "color": null },
+            "averageScore": null,
+            "studios": null,
+            "siteUrl": "https://anilist.co/anime/1",
+            "externalLinks": null,
+            "trailer": null,
+            "description": null,
+            "tags": []
+        }))
+        .expect("anime should deserialize")
+    }
+
+    fn manga(chapters: Option<u32>, volumes: Option<u32>) -> Manga {
+        serde_json::from_value(json!({
+            "type": "MANGA",
+            "id": 2,
+            "idMal": null,
+            "isAdult": false,
+            "title": { "romaji": "Yotsuba To!", "english": null, "native": null },
+            "synonyms": null,
+            "startDate": null,
+            "endDate": null,
+            "format": "MANGA",
+            "status": "RELEASING",
+            "chapters": chapters,
+            "volumes": volumes,
+            "genres": [],
+            "source": null,
+            "coverImage": { "extraLarge": null, "large": null, "medium": null, "color": null },
+            "averageScore": null,
+            "staff": null,
+            "siteUrl": "https://anilist.co/manga/2",
+            "description": null,
+            "tags": []
+        }))
+        .expect("manga should deserialize")
+    }
+
+    #[test]
+    fn options_need_a_search_and_default_to_normal_speed() {
+        let options: Vec<CommandDataOption> = serde_json::from_value(json!([
+            { "name": "search", "type": 3, "value": "Cowboy Bebop" },
+            { "name": "skip_op_ed", "type": 5, "value": true }
+        ]))
+        .expect("options deserialize");
+
+        assert_eq!(
+            parse_binge_options(&options),
+            Some(BingeRequest {
+                search_term: "Cowboy Bebop".to_string(),
+                media_type: MediaType::Anime,
+                options: BingeOptions {
+                    skip_op_ed: true,
+                    ..BingeOptions::default()
+                },
+            })
+        );
+        assert_eq!(parse_binge_options(&[]), None);
+    }
+
+    #[test]
+    fn anime_plans_start_after_list_progress_and_apply_options() {
+        let options = BingeOptions {
+            from: None,
+            speed: 1.5,
+            skip_op_ed: true,
+        };
+
+        let plan = plan_anime_binge(&anime(26, 24), &options, Some(6)).unwrap();
+
+        assert_eq!(plan.remaining, 20);
+        assert_eq!(plan.start, StartingPoint::ListProgress(6));
+        assert_eq!(plan.minutes, 280);
+    }
+
+    #[test]
+    fn requested_start_overrides_list_progress() {
+        let options = BingeOptions {
+            from: Some(20),
+            ..BingeOptions::default()
+        };
+
+        let plan = plan_anime_binge(&anime(26, 24), &options, Some(3)).unwrap();
+
+        assert_eq!(plan.remaining, 7);
+        assert_eq!(plan.minutes, 168);
+    }
+
+    #[test]
+    fn manga_without_chapters_are_estimated_from_volumes() {
+        let plan = plan_manga_binge(&manga(None, Some(2)), &BingeOptions::default(), None).unwrap();
+
+        assert_eq!(plan.remaining, 18);
+        assert_eq!(plan.minutes, 90);
+        assert_eq!(plan.estimated_from_volumes, Some(2));
+        assert_eq!(
+            plan_manga_binge(&manga(None, None), &BingeOptions::default(), None),
+            None
+        );
+    }
+
+    #[test]
+    fn binge_embed_describes_the_plan() {
+        let media = anime(26, 24);
+        let options = BingeOptions {
+            from: None,
+            speed: 1.5,
+            skip_op_ed: true,
+        };
+        let plan = plan_anime_binge(&media, &options, Some(6));
+
+        let response = handle_binge(
+            &media,
+            plan,
+            &options,
+            None,
+            TitleDisplayPreference::Matched,
+        );
+
+        let value = serde_json::to_value(response.unwrap_embed()).expect("embed serializes");
+        assert_eq!(value["title"], "Binge plan: Cowboy Bebop");
+        assert_eq!(
+            value["description"],
+            "**20 episodes** to go, starting at episode 7 (from your AniList progress).\n≈ **4h 40m** total, at 1.5× speed, skipping openings and endings."
+        );
+    }
+
+    #[test]
+    fn finished_titles_are_reported_as_caught_up() {
+        let media = anime(12, 24);
+        let plan = plan_anime_binge(&media, &BingeOptions::default(), Some(12));
+
+        let response = handle_binge(
+            &media,
+            plan,
+            &BingeOptions::default(),
+            None,
+            TitleDisplayPreference::Matched,
+        );
+
+        assert_eq!(
+            response.unwrap_content(),
+            "You're all caught up on Cowboy Bebop."
+        );
+    }
+}
@@ -7,8 +7,11 @@ use crate::{
     models::anilist_character::Character,
     utils::{
         channel::is_nsfw_channel,
+        guild::get_guild_favourites_for_character,
         privacy::configure_sentry_scope,
-        statics::{NOT_FOUND_CHARACTER, NSFW_NOT_ALLOWED},
+        requests::graphql::AniListError,
+        spoilers::{parse_spoilers_option, spoilers_option},
+        statics::{ANILIST_LOOKUP_FAILED, NOT_FOUND_CHARACTER, NSFW_NOT_ALLOWED},
     },
 };
 
@@ -22,12 +25,9 @@ use serenity::{
     client::Context,
     model::application::CommandOptionType,
 };
-use tracing::{info, instrument};
+use tracing::{error, info, instrument};
 
 const SEARCH_OPTION: &str = "search";
-const SPOILERS_OPTION: &str = "spoilers";
-const ALLOW_SPOILERS: &str = "allow";
-const DISALLOW_SPOILERS: &str = "disallow";
 
 pub fn register() -> CreateCommand {
     CreateCommand::new("character")
@@ -41,14 +41,8 @@ pub fn register() -> CreateCommand {
             .required(true),
         )
         .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::String,
-                SPOILERS_OPTION,
-                "Whether to include spoiler aliases and spoiler description content",
-            )
-            .add_string_choice("Allow", ALLOW_SPOILERS)
-            .add_string_choice("Disallow", DISALLOW_SPOILERS)
-            .required(true),
+            spoilers_option("Whether to include spoiler aliases and spoiler description content")
+                .required(true),
         )
 }
 
@@ -60,31 +54,34 @@ fn parse_character_options(options: &[CommandDataOption]) -> Option<(String, boo
             CommandDataOptionValue::String(search_term) => Some(search_term.clone()),
             _ => None,
         })?;
-    let allow_spoilers = options
-        .iter()
-        .find(|option| option.name == SPOILERS_OPTION)
-        .and_then(|option| match &option.value {
-            CommandDataOptionValue::String(value) => match value.as_str() {
-                ALLOW_SPOILERS => Some(true),
-                DISALLOW_SPOILERS => Some(false),
-                _ => None,
-            },
-            _ => None,
-        })?;
+    let allow_spoilers = parse_spoilers_option(options)?;
 
     Some((search_term, allow_spoilers))
 }
 
+/// What to tell the user when AniList couldn't be asked, as opposed to
+/// having no such character.
+fn lookup_failed_message(err: &AniListError) -> &'static str {
+    err.user_message().unwrap_or(ANILIST_LOOKUP_FAILED)
+}
+
+/// `guild_favourites` lists the Discord IDs of guild members who favourited
+/// the character; the adapter fetches it separately.
 pub fn handle_character(
     character: Option<Character>,
     allow_adult_media: bool,
     allow_spoilers: bool,
+    guild_favourites: Option<Vec<u64>>,
 ) -> CommandResponse {
     match character {
         None => CommandResponse::Content(NOT_FOUND_CHARACTER.to_string()),
-        Some(character_response) => CommandResponse::Embed(Box::new(
-            character_response.transform_response_embed(allow_adult_media, allow_spoilers),
-        )),
+        Some(character_response) => {
+            CommandResponse::Embed(Box::new(character_response.transform_response_embed(
+                allow_adult_media,
+                allow_spoilers,
+                guild_favourites,
+            )))
+        }
     }
 }
 
@@ -116,9 +113,19 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
         "Got command 'character' with search_term: {search_term}, allow_spoilers: {allow_spoilers}"
     );
 
-    let character_result = AniListSource
+    let character_result = match AniListSource::from_context(ctx)
+        .await
         .fetch_character(&search_term, allow_spoilers)
-        .await;
+        .await
+    {
+        Ok(character) => character,
+        Err(err) => {
+            error!(error = %err, "Failed to fetch character");
+            let builder = EditInteractionResponse::new().content(lookup_failed_message(&err));
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
+    };
     let allow_adult_media = if character_result
         .as_ref()
         .is_some_and(Character::has_adult_media)
@@ -137,7 +144,28 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
         return;
     }
 
-    let response = handle_character(character_result, allow_adult_media, allow_spoilers);
+    // Gather guild favourites when the character was found.
+    let guild_favourites = match &character_result {
+        None => None,
+        Some(character) => {
+            let favourited_by =
+                get_guild_favourites_for_character(ctx, interaction.guild_id, character.get_id())
+                    .await;
+            info!("Guild favourites: {} members", favourited_by.len());
+            if favourited_by.is_empty() {
+                None
+            } else {
+                Some(favourited_by)
+            }
+        }
+    };
+
+    let response = handle_character(
+        character_result,
+        allow_adult_media,
+        allow_spoilers,
+        guild_favourites,
+    );
 
     let _result = match response {
         CommandResponse::Content(text) => {
@@ -158,6 +186,7 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{requests::anilist::AniListRequestError, statics::ANILIST_RATE_LIMITED};
 
     fn sample_character() -> Character {
         serde_json::from_value(serde_json::json!({
@@ -186,15 +215,27 @@ mod tests {
 
     #[test]
     fn character_not_found_returns_content_with_message() {
-        let response = handle_character(None, false, false);
+        let response = handle_character(None, false, false, None);
 
         assert!(response.is_content(), "expected Content variant");
         assert_eq!(response.unwrap_content(), NOT_FOUND_CHARACTER);
     }
 
+    #[test]
+    fn upstream_failures_are_not_reported_as_not_found() {
+        let rate_limited = AniListError::Request(AniListRequestError::RateLimited {
+            retry_after: std::time::Duration::from_secs(60),
+        });
+        let invalid = AniListError::InvalidResponse("boom".to_string());
+
+        assert_eq!(lookup_failed_message(&rate_limited), ANILIST_RATE_LIMITED);
+        assert_eq!(lookup_failed_message(&invalid), ANILIST_LOOKUP_FAILED);
+        assert_ne!(lookup_failed_message(&rate_limited), NOT_FOUND_CHARACTER);
+    }
+
     #[test]
     fn character_success_returns_embed() {
-        let response = handle_character(Some(sample_character()), false, false);
+        let response = handle_character(Some(sample_character()), false, false, None);
 
         assert!(
             response.is_embed(),
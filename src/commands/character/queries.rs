@@ -1,85 +1,61 @@
-pub const FETCH_CHARACTER_BY_ID: &str = "
-query ($id: Int) {
-  Character(id: $id) {
-    id
-    name {
-      full
-      native
-      alternative
-      alternativeSpoiler
-      userPreferred
-    }
-    image {
-      large
-      medium
-    }
-    description(asHtml: true)
-    gender
-    dateOfBirth {
-      year
-      month
-      day
-    }
-    age
-    bloodType
-    favourites
-    siteUrl
-    media(page: 1, perPage: 5, sort: POPULARITY_DESC) {
-      nodes {
-        id
-        type
-        title {
-          romaji
-          english
-        }
-        siteUrl
-        isAdult
-      }
-    }
-  }
+use std::sync::LazyLock;
+
+use crate::utils::requests::{
+    batch::IdBatcher,
+    fragments::fuzzy_date,
+    query::{Field, Query, Selection, VariableType},
+};
+
+fn character_fields() -> Selection {
+    Selection::new()
+        .field("id")
+        .object(
+            "name",
+            Selection::new().fields(&[
+                "full",
+                "native",
+                "alternative",
+                "alternativeSpoiler",
+                "userPreferred",
+            ]),
+        )
+        .object("image", Selection::new().fields(&["large", "medium"]))
+        .field(Field::new("description").arg("asHtml", true))
+        .field("gender")
+        .object("dateOfBirth", fuzzy_date())
+        .fields(&["age", "bloodType", "favourites", "siteUrl"])
+        .field(
+            Field::new("media")
+                .arg("page", 1)
+                .arg("perPage", 5)
+                .arg("sort", "POPULARITY_DESC")
+                .select(
+                    Selection::new().object(
+                        "nodes",
+                        Selection::new()
+                            .fields(&["id", "type"])
+                            .object("title", Selection::new().fields(&["romaji", "english"]))
+                            .fields(&["siteUrl", "isAdult"]),
+                    ),
+                ),
+        )
 }
-";
 
-pub const FETCH_CHARACTER: &str = "
-query ($search: String) {
-  Page(page: 1, perPage: 10) {
-    characters(search: $search) {
-      id
-      name {
-        full
-        native
-        alternative
-        alternativeSpoiler
-        userPreferred
-      }
-      image {
-        large
-        medium
-      }
-      description(asHtml: true)
-      gender
-      dateOfBirth {
-        year
-        month
-        day
-      }
-      age
-      bloodType
-      favourites
-      siteUrl
-      media(page: 1, perPage: 5, sort: POPULARITY_DESC) {
-        nodes {
-          id
-          type
-          title {
-            romaji
-            english
-          }
-          siteUrl
-          isAdult
-        }
-      }
-    }
-  }
+/// Batched `Character(id: …)` lookups.
+pub static CHARACTER_BY_ID: LazyLock<IdBatcher> =
+    LazyLock::new(|| IdBatcher::new("Character", &[], character_fields()));
+
+pub fn fetch_character() -> String {
+    Query::new()
+        .variable("search", VariableType::String)
+        .field(
+            Field::new("Page").arg("page", 1).arg("perPage", 10).select(
+                Selection::new().field(
+                    Field::new("characters")
+                        .arg("search", "$search")
+                        .select(character_fields()),
+                ),
+            ),
+        )
+        .render()
 }
-";
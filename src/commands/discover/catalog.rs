@@ -0,0 +1,123 @@
+//! AniList's genre and tag lists, fetched once a day and shared by
+//! `/discover` autocomplete and filter validation.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::json;
+use tracing::{Instrument, info, instrument, warn};
+
+use crate::{
+    models::anilist_discover::DiscoverCatalog,
+    utils::{
+        cache::{Cache, DISCOVER_CATALOG_TTL, SharedCache},
+        requests::{
+            anilist::send_request,
+            graphql::{AniListError, classify},
+            query::{Field, Query, Selection},
+        },
+    },
+};
+
+const CATALOG_CACHE_KEY: &str = "discover:catalog";
+
+/// A background catalogue fetch is already running.
+static WARMING: AtomicBool = AtomicBool::new(false);
+
+#[instrument(name = "discover.catalog_query")]
+fn catalog_query() -> Query {
+    Query::new().field("GenreCollection").field(
+        Field::new("MediaTagCollection")
+            .select(Selection::new().fields(&["id", "name", "category", "isAdult"])),
+    )
+}
+
+/// The catalogue if it is cached, without asking AniList.
+#[instrument(name = "discover.cached_catalog", skip(cache))]
+pub async fn cached_catalog(cache: &dyn Cache) -> Option<DiscoverCatalog> {
+    cache
+        .get(CATALOG_CACHE_KEY)
+        .await
+        .and_then(|cached| serde_json::from_str::<DiscoverCatalog>(&cached).ok())
+}
+
+/// Every AniList genre and tag, cached for [`DISCOVER_CATALOG_TTL`].
+#[instrument(name = "discover.fetch_catalog", skip(cache))]
+pub async fn fetch_catalog(cache: &dyn Cache) -> Result<DiscoverCatalog, AniListError> {
+    if let Some(catalog) = cached_catalog(cache).await {
+        return Ok(catalog);
+    }
+
+    let catalog =
+        classify::<DiscoverCatalog>(send_request(catalog_query().request(json!({}))).await)?
+            .unwrap_or_default();
+    if catalog.genres.is_empty() && catalog.tags.is_empty() {
+        // Don't pin an empty answer for a whole day.
+        return Ok(catalog);
+    }
+
+    match serde_json::to_string(&catalog) {
+        Ok(encoded) => {
+            cache
+                .set(CATALOG_CACHE_KEY, &encoded, DISCOVER_CATALOG_TTL)
+                .await
+        }
+        Err(error) => warn!(error = %error, "Failed to encode discover catalog for caching"),
+    }
+    info!(
+        genres = catalog.genres.len(),
+        tags = catalog.tags.len(),
+        "Fetched discover catalog"
+    );
+    Ok(catalog)
+}
+
+/// Fetch the catalogue in the background so later lookups find it cached,
+/// unless a fetch is already running.
+#[instrument(name = "discover.warm_catalog", skip(cache))]
+pub fn warm_catalog_in_background(cache: SharedCache) {
+    if WARMING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    tokio::spawn(
+        async move {
+            if let Err(err) = fetch_catalog(cache.as_ref()).await {
+                warn!(error = %err, "Failed to warm discover catalog");
+            }
+            WARMING.store(false, Ordering::Release);
+        }
+        .in_current_span(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cache::InMemoryCache;
+
+    #[test]
+    fn catalog_query_requests_genres_and_tags() {
+        let query = catalog_query().render();
+
+        assert!(query.starts_with("query {\n  GenreCollection\n  MediaTagCollection {\n"));
+        assert!(query.contains("    isAdult\n"));
+    }
+
+    #[tokio::test]
+    async fn cached_catalog_is_served_without_querying_anilist() {
+        let cache = InMemoryCache::with_entry(
+            CATALOG_CACHE_KEY,
+            r#"{"GenreCollection":["Action"],"MediaTagCollection":[{"id":1,"name":"Isekai","category":null,"isAdult":false}]}"#,
+        );
+
+        let catalog = fetch_catalog(&cache).await.unwrap();
+
+        assert_eq!(catalog.genres, ["Action".to_string()]);
+        assert_eq!(catalog.tags[0].name, "Isekai");
+    }
+
+    #[tokio::test]
+    async fn cached_catalog_does_not_fall_back_to_anilist() {
+        assert!(cached_catalog(&InMemoryCache::default()).await.is_none());
+    }
+}
@@ -0,0 +1,983 @@
+//! `/discover`: browse AniList by genre, tag, year range, format, status,
+//! country of origin and score, most popular first.
+
+use crate::{
+    commands::{
+        discover::catalog::{cached_catalog, fetch_catalog, warm_catalog_in_background},
+        recommend::command::{FORMAT_CHOICES, STATUS_CHOICES, format_recommended_media_summary},
+        response::CommandResponse,
+    },
+    models::{
+        anilist_discover::{
+            ADULT_GENRE, DiscoverCatalog, DiscoverPage, DiscoverPageData, MediaTagDefinition,
+        },
+        media_type::MediaType,
+        settings::TitleDisplayPreference,
+    },
+    utils::{
+        cache::{Cache, DEFAULT_CACHE_TTL, get_cache_from_context},
+        channel::is_nsfw_channel,
+        formatter::{code, remove_underscores_and_titlecase, titlecase},
+        interaction::anilist_type,
+        privacy::configure_sentry_scope,
+        requests::{
+            anilist::send_request,
+            fragments::title,
+            graphql::{AniListError, check_response, parse_response},
+            query::{Field, Query, Selection, VariableType},
+        },
+        settings::resolve_title_display_preference,
+        statics::NSFW_NOT_ALLOWED,
+    },
+};
+
+use serde_json::{Value, json};
+use serenity::{
+    all::{
+        ButtonStyle, ChannelId, CommandDataOption, CommandDataOptionValue, CommandInteraction,
+        ComponentInteraction, CreateActionRow, CreateAutocompleteResponse, CreateButton,
+        CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, EditInteractionResponse, GuildId, UserId,
+    },
+    builder::CreateCommand,
+    client::Context,
+    model::application::CommandOptionType,
+};
+use tracing::{error, info, instrument, warn};
+
+const TYPE_OPTION: &str = "type";
+const GENRE_OPTION: &str = "genre";
+const TAG_OPTION: &str = "tag";
+const YEAR_FROM_OPTION: &str = "year_from";
+const YEAR_TO_OPTION: &str = "year_to";
+const FORMAT_OPTION: &str = "format";
+const STATUS_OPTION: &str = "status";
+const COUNTRY_OPTION: &str = "country";
+const MIN_SCORE_OPTION: &str = "min_score";
+const ANIME_TYPE: &str = "anime";
+const MANGA_TYPE: &str = "manga";
+
+/// Titles shown per page; each page is one AniList `Page` request.
+const DISCOVER_PAGE_SIZE: u32 = 10;
+const EARLIEST_YEAR: i64 = 1940;
+const LATEST_YEAR: i64 = 2100;
+
+const DISCOVER_COMPONENT_PREFIX: &str = "discover";
+const DISCOVER_COMPONENT_ID_PREFIX: &str = "discover:";
+const PREVIOUS_COMPONENT: &str = "prev";
+const NEXT_COMPONENT: &str = "next";
+/// Placeholder for an unset filter in component IDs.
+const NO_FILTER: &str = "-";
+
+const COUNTRY_CHOICES: &[(&str, &str)] = &[
+    ("Japan", "JP"),
+    ("South Korea", "KR"),
+    ("China", "CN"),
+    ("Taiwan", "TW"),
+];
+
+pub fn register() -> CreateCommand {
+    let format_option = FORMAT_CHOICES.iter().fold(
+        CreateCommandOption::new(CommandOptionType::String, FORMAT_OPTION, "Format"),
+        |option, (name, value)| option.add_string_choice(*name, *value),
+    );
+    let status_option = STATUS_CHOICES.iter().fold(
+        CreateCommandOption::new(CommandOptionType::String, STATUS_OPTION, "Release status"),
+        |option, (name, value)| option.add_string_choice(*name, *value),
+    );
+    let country_option = COUNTRY_CHOICES.iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            COUNTRY_OPTION,
+            "Country of origin",
+        ),
+        |option, (name, value)| option.add_string_choice(*name, *value),
+    );
+
+    CreateCommand::new("discover")
+        .description("Browse popular anime or manga by genre, tag, year and more")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, TYPE_OPTION, "Anime or manga")
+                .add_string_choice("Anime", ANIME_TYPE)
+                .add_string_choice("Manga", MANGA_TYPE),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, GENRE_OPTION, "AniList genre")
+                .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, TAG_OPTION, "AniList tag")
+                .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                YEAR_FROM_OPTION,
+                "Earliest start year",
+            )
+            .min_int_value(EARLIEST_YEAR as u64)
+            .max_int_value(LATEST_YEAR as u64),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                YEAR_TO_OPTION,
+                "Latest start year",
+            )
+            .min_int_value(EARLIEST_YEAR as u64)
+            .max_int_value(LATEST_YEAR as u64),
+        )
+        .add_option(format_option)
+        .add_option(status_option)
+        .add_option(country_option)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                MIN_SCORE_OPTION,
+                "Lowest AniList average score (1-100)",
+            )
+            .min_int_value(1)
+            .max_int_value(100),
+        )
+}
+
+/// The options as typed, before genre and tag are checked against AniList.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiscoverOptions {
+    pub manga: bool,
+    pub genre: Option<String>,
+    pub tag: Option<String>,
+    pub year_from: Option<u32>,
+    pub year_to: Option<u32>,
+    pub format: Option<String>,
+    pub status: Option<String>,
+    pub country: Option<String>,
+    pub min_score: Option<u32>,
+}
+
+/// Filters applied to AniList's `Page.media`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiscoverFilters {
+    pub genre: Option<String>,
+    pub tag: Option<MediaTagDefinition>,
+    pub year_from: Option<u32>,
+    pub year_to: Option<u32>,
+    /// AniList `MediaFormat`, e.g. `TV`.
+    pub format: Option<String>,
+    /// AniList `MediaStatus`, e.g. `FINISHED`.
+    pub status: Option<String>,
+    /// ISO 3166-1 alpha-2 country code, e.g. `JP`.
+    pub country: Option<String>,
+    pub min_score: Option<u32>,
+}
+
+impl DiscoverFilters {
+    fn is_adult(&self) -> bool {
+        self.genre.as_deref() == Some(ADULT_GENRE)
+            || self.tag.as_ref().is_some_and(MediaTagDefinition::is_adult)
+    }
+
+    /// Short description for the embed, e.g. `Action, Isekai, 2015–2020, TV`.
+    pub fn describe(&self) -> String {
+        let years = match (self.year_from, self.year_to) {
+            (Some(from), Some(to)) if from == to => Some(from.to_string()),
+            (Some(from), Some(to)) => Some(format!("{from}–{to}")),
+            (Some(from), None) => Some(format!("{from} onwards")),
+            (None, Some(to)) => Some(format!("up to {to}")),
+            (None, None) => None,
+        };
+        let country = self.country.as_deref().map(|code| {
+            COUNTRY_CHOICES
+                .iter()
+                .find(|(_, value)| *value == code)
+                .map_or_else(|| code.to_string(), |(name, _)| name.to_string())
+        });
+
+        [
+            self.genre.clone(),
+            self.tag.as_ref().map(|tag| tag.name.clone()),
+            years,
+            self.format.as_deref().map(remove_underscores_and_titlecase),
+            self.status.as_deref().map(remove_underscores_and_titlecase),
+            country,
+            self.min_score.map(|min_score| format!("{min_score}+")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// Which page of which filters to show. Encoded in the paging buttons'
+/// custom IDs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoverView {
+    pub media_type: MediaType,
+    pub filters: DiscoverFilters,
+    /// Zero-based page.
+    pub page: u32,
+}
+
+#[instrument(name = "command.discover.parse_options", skip(options))]
+fn parse_discover_options(options: &[CommandDataOption]) -> DiscoverOptions {
+    let mut parsed = DiscoverOptions::default();
+    let year = |value: i64| u32::try_from(value.clamp(EARLIEST_YEAR, LATEST_YEAR)).ok();
+
+    for option in options {
+        match (option.name.as_str(), &option.value) {
+            (TYPE_OPTION, CommandDataOptionValue::String(value)) => {
+                parsed.manga = value == MANGA_TYPE;
+            }
+            (GENRE_OPTION, CommandDataOptionValue::String(value)) => {
+                parsed.genre = Some(value.clone());
+            }
+            (TAG_OPTION, CommandDataOptionValue::String(value)) => {
+                parsed.tag = Some(value.clone());
+            }
+            (YEAR_FROM_OPTION, CommandDataOptionValue::Integer(value)) => {
+                parsed.year_from = year(*value);
+            }
+            (YEAR_TO_OPTION, CommandDataOptionValue::Integer(value)) => {
+                parsed.year_to = year(*value);
+            }
+            (FORMAT_OPTION, CommandDataOptionValue::String(value)) => {
+                parsed.format = Some(value.clone());
+            }
+            (STATUS_OPTION, CommandDataOptionValue::String(value)) => {
+                parsed.status = Some(value.clone());
+            }
+            (COUNTRY_OPTION, CommandDataOptionValue::String(value)) => {
+                parsed.country = Some(value.clone());
+            }
+            (MIN_SCORE_OPTION, CommandDataOptionValue::Integer(value)) => {
+                parsed.min_score = u32::try_from((*value).clamp(1, 100)).ok();
+            }
+            _ => {}
+        }
+    }
+
+    parsed
+}
+
+/// Check the genre and tag against AniList's catalogue. `Err` carries the
+/// message to show when one of them isn't known.
+#[instrument(name = "command.discover.resolve_filters", skip(options, catalog))]
+pub fn resolve_filters(
+    options: DiscoverOptions,
+    catalog: &DiscoverCatalog,
+) -> Result<DiscoverFilters, String> {
+    let genre = match options.genre.as_deref() {
+        Some(name) => Some(
+            catalog
+                .genre(name)
+                .ok_or_else(|| {
+                    format!("AniList doesn't have a genre called \"{name}\". Pick one from the suggestions.")
+                })?
+                .to_string(),
+        ),
+        None => None,
+    };
+    let tag = match options.tag.as_deref() {
+        Some(name) => Some(
+            catalog
+                .tag(name)
+                .ok_or_else(|| {
+                    format!("AniList doesn't have a tag called \"{name}\". Pick one from the suggestions.")
+                })?
+                .clone(),
+        ),
+        None => None,
+    };
+    let (year_from, year_to) = match (options.year_from, options.year_to) {
+        (Some(from), Some(to)) if from > to => (Some(to), Some(from)),
+        years => years,
+    };
+
+    Ok(DiscoverFilters {
+        genre,
+        tag,
+        year_from,
+        year_to,
+        format: options.format,
+        status: options.status,
+        country: options.country,
+        min_score: options.min_score,
+    })
+}
+
+#[instrument(name = "command.discover.is_component")]
+pub fn is_discover_component(custom_id: &str) -> bool {
+    custom_id.starts_with(DISCOVER_COMPONENT_ID_PREFIX)
+}
+
+/// The view's filters and page, `:`-separated. Tags are stored by ID to keep
+/// custom IDs under Discord's 100 character limit.
+#[instrument(name = "command.discover.view_key", skip(view))]
+fn discover_view_key(view: &DiscoverView) -> String {
+    let filters = &view.filters;
+    let optional = |value: Option<String>| value.unwrap_or_else(|| NO_FILTER.to_string());
+    let media_type = match view.media_type {
+        MediaType::Anime => ANIME_TYPE,
+        MediaType::Manga => MANGA_TYPE,
+    };
+
+    [
+        media_type.to_string(),
+        view.page.to_string(),
+        optional(filters.genre.clone()),
+        optional(filters.tag.as_ref().map(|tag| tag.id.to_string())),
+        optional(filters.year_from.map(|year| year.to_string())),
+        optional(filters.year_to.map(|year| year.to_string())),
+        optional(filters.format.clone()),
+        optional(filters.status.clone()),
+        optional(filters.country.clone()),
+        optional(filters.min_score.map(|score| score.to_string())),
+    ]
+    .join(":")
+}
+
+#[instrument(name = "command.discover.custom_id", skip(view))]
+pub fn discover_custom_id(control: &str, view: &DiscoverView) -> String {
+    format!(
+        "{DISCOVER_COMPONENT_PREFIX}:{control}:{}",
+        discover_view_key(view)
+    )
+}
+
+/// Whether a paging button's view has a tag, so rebuilding it needs the
+/// catalogue.
+#[instrument(name = "command.discover.component_filters_by_tag")]
+fn component_filters_by_tag(custom_id: &str) -> bool {
+    // prefix:control:type:page:genre:tag:...
+    custom_id
+        .split(':')
+        .nth(5)
+        .is_some_and(|raw_tag| raw_tag != NO_FILTER)
+}
+
+/// Rebuild the view from a paging button. The tag ID is looked up in
+/// `catalog`, so a tag AniList has since removed makes the ID invalid.
+#[instrument(name = "command.discover.parse_component_id", skip(catalog))]
+pub fn parse_discover_component_id(
+    custom_id: &str,
+    catalog: &DiscoverCatalog,
+) -> Option<DiscoverView> {
+    let parts = custom_id.split(':').collect::<Vec<_>>();
+    let optional = |raw: &str| (raw != NO_FILTER).then(|| raw.to_string());
+    let number = |raw: &str| -> Option<Option<u32>> {
+        match raw {
+            NO_FILTER => Some(None),
+            raw => raw.parse().ok().map(Some),
+        }
+    };
+
+    match parts.as_slice() {
+        [
+            DISCOVER_COMPONENT_PREFIX,
+            PREVIOUS_COMPONENT | NEXT_COMPONENT,
+            raw_media_type,
+            raw_page,
+            raw_genre,
+            raw_tag,
+            raw_year_from,
+            raw_year_to,
+            raw_format,
+            raw_status,
+            raw_country,
+            raw_min_score,
+        ] => {
+            let tag = match number(raw_tag)? {
+                Some(tag_id) => Some(catalog.tag_by_id(tag_id)?.clone()),
+                None => None,
+            };
+
+            Some(DiscoverView {
+                media_type: match *raw_media_type {
+                    ANIME_TYPE => MediaType::Anime,
+                    MANGA_TYPE => MediaType::Manga,
+                    _ => return None,
+                },
+                filters: DiscoverFilters {
+                    genre: optional(raw_genre),
+                    tag,
+                    year_from: number(raw_year_from)?,
+                    year_to: number(raw_year_to)?,
+                    format: optional(raw_format),
+                    status: optional(raw_status),
+                    country: optional(raw_country),
+                    min_score: number(raw_min_score)?,
+                },
+                page: raw_page.parse().ok()?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// A page of `media_type` titles, most popular first. Every filter is a
+/// variable; AniList ignores the ones sent as `null`.
+#[instrument(name = "command.discover.query")]
+fn discover_query(media_type: &str) -> Query {
+    Query::new()
+        .variable("page", VariableType::Int)
+        .variable("perPage", VariableType::Int)
+        .variable("genre", VariableType::String)
+        .variable("tag", VariableType::String)
+        .variable("startAfter", VariableType::Int)
+        .variable("startBefore", VariableType::Int)
+        .variable("format", VariableType::MediaFormat)
+        .variable("status", VariableType::MediaStatus)
+        .variable("country", VariableType::CountryCode)
+        .variable("scoreAbove", VariableType::Int)
+        .variable("isAdult", VariableType::Boolean)
+        .field(
+            Field::new("Page")
+                .arg("page", "$page")
+                .arg("perPage", "$perPage")
+                .select(
+                    Selection::new()
+                        .object("pageInfo", Selection::new().field("hasNextPage"))
+                        .field(
+                            Field::new("media")
+                                .arg("type", media_type)
+                                .arg("sort", "POPULARITY_DESC")
+                                .arg("genre", "$genre")
+                                .arg("tag", "$tag")
+                                .arg("startDate_greater", "$startAfter")
+                                .arg("startDate_lesser", "$startBefore")
+                                .arg("format", "$format")
+                                .arg("status", "$status")
+                                .arg("countryOfOrigin", "$country")
+                                .arg("averageScore_greater", "$scoreAbove")
+                                .arg("isAdult", "$isAdult")
+                                .select(
+                                    Selection::new()
+                                        .fields(&["type", "id", "isAdult"])
+                                        .object("title", title())
+                                        .fields(&[
+                                            "format",
+                                            "status",
+                                            "genres",
+                                            "averageScore",
+                                            "siteUrl",
+                                        ]),
+                                ),
+                        ),
+                ),
+        )
+}
+
+/// Query variables for `view`. Years become AniList fuzzy dates (`YYYYMMDD`,
+/// with `0000` for an unknown month and day), and the score bound is
+/// exclusive, so both are widened by one.
+#[instrument(name = "command.discover.variables", skip(view))]
+fn discover_variables(view: &DiscoverView, allow_adult_media: bool) -> Value {
+    let filters = &view.filters;
+    json!({
+        "page": view.page + 1,
+        "perPage": DISCOVER_PAGE_SIZE,
+        "genre": filters.genre,
+        "tag": filters.tag.as_ref().map(|tag| &tag.name),
+        "startAfter": filters.year_from.map(|year| year * 10_000 - 1),
+        "startBefore": filters.year_to.map(|year| (year + 1) * 10_000),
+        "format": filters.format,
+        "status": filters.status,
+        "country": filters.country,
+        "scoreAbove": filters.min_score.map(|score| score - 1),
+        "isAdult": (!allow_adult_media).then_some(false),
+    })
+}
+
+#[instrument(name = "command.discover.fetch_page", skip(cache, view), fields(page = view.page))]
+async fn fetch_discover_page(
+    cache: &dyn Cache,
+    view: &DiscoverView,
+    allow_adult_media: bool,
+) -> Result<DiscoverPage, AniListError> {
+    let cache_key = format!(
+        "discover:page:{}:{}",
+        discover_view_key(view),
+        if allow_adult_media { "all" } else { "sfw" }
+    );
+    let body = match cache.get(&cache_key).await {
+        Some(cached_value) => cached_value,
+        None => {
+            let request = discover_query(anilist_type(&view.media_type))
+                .request(discover_variables(view, allow_adult_media));
+            let body = check_response(send_request(request).await)?;
+            cache.set(&cache_key, &body, DEFAULT_CACHE_TTL).await;
+            body
+        }
+    };
+
+    Ok(parse_response::<DiscoverPageData>(&body)?
+        .and_then(|data| data.page)
+        .unwrap_or_default())
+}
+
+pub fn handle_discover(
+    results: &DiscoverPage,
+    view: &DiscoverView,
+    title_preference: TitleDisplayPreference,
+) -> CommandResponse {
+    let kind = match view.media_type {
+        MediaType::Anime => "anime",
+        MediaType::Manga => "manga",
+    };
+    if results.media().next().is_none() {
+        return CommandResponse::Content(if view.page == 0 {
+            format!("I couldn't find any {kind} matching those filters on AniList.")
+        } else {
+            format!("There are no more {kind} matching those filters.")
+        });
+    }
+
+    let filters = view.filters.describe();
+    let mut embed = CreateEmbed::new()
+        .title(format!("Discover {}", titlecase(kind)))
+        .description(if filters.is_empty() {
+            "The most popular titles on AniList.".to_string()
+        } else {
+            format!("Most popular on AniList for: {filters}")
+        })
+        .footer(CreateEmbedFooter::new(format!(
+            "Sorted by popularity • Page {}",
+            view.page + 1
+        )));
+
+    let offset = (view.page * DISCOVER_PAGE_SIZE) as usize;
+    for (index, media) in results.media().enumerate() {
+        let genres = media
+            .genres()
+            .iter()
+            .take(3)
+            .map(|genre| code(&titlecase(genre)))
+            .collect::<Vec<_>>()
+            .join(" - ");
+        let mut lines = vec![format_recommended_media_summary(media)];
+        if !genres.is_empty() {
+            lines.push(format!("Genres: {genres}"));
+        }
+
+        embed = embed.field(
+            format!(
+                "{}. {}",
+                offset + index + 1,
+                media.display_title(None, title_preference)
+            ),
+            lines.join("\n"),
+            false,
+        );
+    }
+
+    CommandResponse::Embed(Box::new(embed))
+}
+
+/// Previous/Next buttons, or nothing when everything fits on one page.
+#[instrument(name = "command.discover.components", skip(view))]
+pub fn discover_components(view: &DiscoverView, has_next_page: bool) -> Vec<CreateActionRow> {
+    if view.page == 0 && !has_next_page {
+        return Vec::new();
+    }
+
+    let previous = DiscoverView {
+        page: view.page.saturating_sub(1),
+        ..view.clone()
+    };
+    let next = DiscoverView {
+        page: view.page + 1,
+        ..view.clone()
+    };
+
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(discover_custom_id(PREVIOUS_COMPONENT, &previous))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(view.page == 0),
+        CreateButton::new(discover_custom_id(NEXT_COMPONENT, &next))
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(!has_next_page),
+    ])]
+}
+
+/// Fetch and render `view` as an interaction edit: the embed and its paging
+/// buttons, or a plain message that clears any earlier embed and buttons.
+#[instrument(name = "command.discover.response", skip(ctx, view))]
+async fn discover_response(
+    ctx: &Context,
+    user_id: UserId,
+    guild_id: Option<GuildId>,
+    channel_id: ChannelId,
+    view: &DiscoverView,
+) -> EditInteractionResponse {
+    let allow_adult_media = is_nsfw_channel(ctx, channel_id, guild_id).await;
+    if view.filters.is_adult() && !allow_adult_media {
+        return content_response(NSFW_NOT_ALLOWED.to_string());
+    }
+
+    let cache = get_cache_from_context(ctx).await;
+    let (results, title_preference) = tokio::join!(
+        fetch_discover_page(cache.as_ref(), view, allow_adult_media),
+        resolve_title_display_preference(ctx, user_id, guild_id),
+    );
+    let results = match results {
+        Ok(results) => results,
+        Err(err) => {
+            error!(error = %err, "Failed to fetch discover page");
+            return content_response(
+                err.user_message()
+                    .unwrap_or("I couldn't browse AniList right now. Please try again later.")
+                    .to_string(),
+            );
+        }
+    };
+
+    match handle_discover(&results, view, title_preference) {
+        CommandResponse::Content(text) | CommandResponse::Message(text) => content_response(text),
+        CommandResponse::Embed(embed) => EditInteractionResponse::new()
+            .embed(*embed)
+            .components(discover_components(view, results.has_next_page())),
+    }
+}
+
+#[instrument]
+fn content_response(text: String) -> EditInteractionResponse {
+    EditInteractionResponse::new()
+        .content(text)
+        .embeds(Vec::new())
+        .components(Vec::new())
+}
+
+#[instrument(name = "command.discover.run", skip(ctx, interaction))]
+pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
+    let _ = interaction.defer(&ctx.http).await;
+
+    let options = parse_discover_options(&interaction.data.options);
+    configure_sentry_scope(
+        "Discover",
+        interaction.user.id.get(),
+        Some(json!({
+            "manga": options.manga,
+            "genre": options.genre,
+            "tag": options.tag,
+        })),
+    );
+    info!(options = ?options, "Got command 'discover'");
+
+    let catalog = if options.genre.is_some() || options.tag.is_some() {
+        let cache = get_cache_from_context(ctx).await;
+        match fetch_catalog(cache.as_ref()).await {
+            Ok(catalog) => catalog,
+            Err(err) => {
+                error!(error = %err, "Failed to fetch discover catalog");
+                let message = err.user_message().unwrap_or(
+                    "I couldn't load AniList's genres and tags. Please try again later.",
+                );
+                let builder = EditInteractionResponse::new().content(message);
+                let _ = interaction.edit_response(&ctx.http, builder).await;
+                return;
+            }
+        }
+    } else {
+        DiscoverCatalog::default()
+    };
+
+    let media_type = if options.manga {
+        MediaType::Manga
+    } else {
+        MediaType::Anime
+    };
+    let filters = match resolve_filters(options, &catalog) {
+        Ok(filters) => filters,
+        Err(message) => {
+            let builder = EditInteractionResponse::new().content(message);
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
+    };
+
+    let view = DiscoverView {
+        media_type,
+        filters,
+        page: 0,
+    };
+    let builder = discover_response(
+        ctx,
+        interaction.user.id,
+        interaction.guild_id,
+        interaction.channel_id,
+        &view,
+    )
+    .await;
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+#[instrument(name = "command.discover.handle_component", skip(ctx, interaction))]
+pub async fn handle_component(ctx: &Context, interaction: &mut ComponentInteraction) {
+    configure_sentry_scope("Discover", interaction.user.id.get(), None);
+
+    if let Err(error) = interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        warn!(
+            error = %error,
+            custom_id = %interaction.data.custom_id,
+            "Failed to acknowledge discover component interaction"
+        );
+        return;
+    }
+
+    let catalog = if component_filters_by_tag(&interaction.data.custom_id) {
+        let cache = get_cache_from_context(ctx).await;
+        fetch_catalog(cache.as_ref()).await.unwrap_or_else(|err| {
+            warn!(error = %err, "Failed to fetch discover catalog for paging");
+            DiscoverCatalog::default()
+        })
+    } else {
+        DiscoverCatalog::default()
+    };
+    let Some(view) = parse_discover_component_id(&interaction.data.custom_id, &catalog) else {
+        let builder = CreateInteractionResponseFollowup::new()
+            .content("I don't recognize that control. Please run the command again.")
+            .ephemeral(true);
+        let _ = interaction.create_followup(&ctx.http, builder).await;
+        return;
+    };
+
+    let builder = discover_response(
+        ctx,
+        interaction.user.id,
+        interaction.guild_id,
+        interaction.channel_id,
+        &view,
+    )
+    .await;
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+/// Suggest genres or tags from AniList's catalogue as the user types.
+#[instrument(name = "command.discover.autocomplete", skip(ctx, interaction))]
+pub async fn autocomplete(ctx: &Context, interaction: &CommandInteraction) {
+    let Some(focused) = interaction.data.autocomplete() else {
+        return;
+    };
+
+    // Autocomplete has to answer within three seconds, so it never waits on
+    // AniList; the first keystrokes after a cold start just suggest nothing.
+    let cache = get_cache_from_context(ctx).await;
+    let catalog = match cached_catalog(cache.as_ref()).await {
+        Some(catalog) => catalog,
+        None => {
+            warm_catalog_in_background(cache);
+            DiscoverCatalog::default()
+        }
+    };
+    let allow_adult_media =
+        is_nsfw_channel(ctx, interaction.channel_id, interaction.guild_id).await;
+
+    let suggestions = match focused.name {
+        GENRE_OPTION => catalog.suggest_genres(focused.value, allow_adult_media),
+        TAG_OPTION => catalog.suggest_tags(focused.value, allow_adult_media),
+        _ => Vec::new(),
+    };
+    let response = suggestions
+        .into_iter()
+        .fold(CreateAutocompleteResponse::new(), |response, name| {
+            response.add_string_choice(name, name)
+        });
+    let _ = interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> DiscoverCatalog {
+        serde_json::from_value(json!({
+            "GenreCollection": ["Action", "Hentai", "Slice of Life"],
+            "MediaTagCollection": [
+                { "id": 244, "name": "Isekai", "category": "Setting-Universe", "isAdult": false },
+                { "id": 3, "name": "Nudity", "category": "Sexual Content", "isAdult": true }
+            ]
+        }))
+        .expect("catalog should deserialize")
+    }
+
+    fn options(value: serde_json::Value) -> Vec<CommandDataOption> {
+        serde_json::from_value(value).expect("options deserialize")
+    }
+
+    fn view() -> DiscoverView {
+        DiscoverView {
+            media_type: MediaType::Anime,
+            filters: resolve_filters(
+                DiscoverOptions {
+                    genre: Some("action".to_string()),
+                    tag: Some("Isekai".to_string()),
+                    year_from: Some(2020),
+                    year_to: Some(2015),
+                    format: Some("TV".to_string()),
+                    country: Some("JP".to_string()),
+                    min_score: Some(75),
+                    ..DiscoverOptions::default()
+                },
+                &catalog(),
+            )
+            .unwrap(),
+            page: 1,
+        }
+    }
+
+    #[test]
+    fn options_are_parsed_and_checked_against_the_catalog() {
+        let parsed = parse_discover_options(&options(json!([
+            { "name": "type", "type": 3, "value": "manga" },
+            { "name": "genre", "type": 3, "value": "slice of life" },
+            { "name": "min_score", "type": 4, "value": 80 }
+        ])));
+
+        assert!(parsed.manga);
+        assert_eq!(parsed.min_score, Some(80));
+        let filters = resolve_filters(parsed, &catalog()).unwrap();
+        assert_eq!(filters.genre.as_deref(), Some("Slice of Life"));
+
+        let unknown = DiscoverOptions {
+            tag: Some("Robots".to_string()),
+            ..DiscoverOptions::default()
+        };
+        assert_eq!(
+            resolve_filters(unknown, &catalog()),
+            Err(
+                "AniList doesn't have a tag called \"Robots\". Pick one from the suggestions."
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn reversed_years_are_swapped_and_described() {
+        let view = view();
+
+        assert_eq!(view.filters.year_from, Some(2015));
+        assert_eq!(view.filters.year_to, Some(2020));
+        assert_eq!(
+            view.filters.describe(),
+            "Action, Isekai, 2015–2020, TV, Japan, 75+"
+        );
+    }
+
+    #[test]
+    fn paging_custom_ids_round_trip() {
+        let view = view();
+
+        let custom_id = discover_custom_id(NEXT_COMPONENT, &view);
+
+        assert_eq!(
+            custom_id,
+            "discover:next:anime:1:Action:244:2015:2020:TV:-:JP:75"
+        );
+        assert!(custom_id.len() <= 100);
+        assert!(is_discover_component(&custom_id));
+        assert_eq!(
+            parse_discover_component_id(&custom_id, &catalog()),
+            Some(view)
+        );
+        assert_eq!(
+            parse_discover_component_id(&custom_id, &DiscoverCatalog::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn only_tagged_paging_buttons_need_the_catalog() {
+        let tagged = discover_custom_id(NEXT_COMPONENT, &view());
+        let untagged = discover_custom_id(
+            NEXT_COMPONENT,
+            &DiscoverView {
+                filters: DiscoverFilters {
+                    tag: None,
+                    ..view().filters
+                },
+                ..view()
+            },
+        );
+
+        assert!(component_filters_by_tag(&tagged));
+        assert!(!component_filters_by_tag(&untagged));
+        assert!(parse_discover_component_id(&untagged, &DiscoverCatalog::default()).is_some());
+    }
+
+    #[test]
+    fn variables_widen_exclusive_bounds() {
+        let variables = discover_variables(&view(), false);
+
+        assert_eq!(variables["page"], 2);
+        assert_eq!(variables["tag"], "Isekai");
+        assert_eq!(variables["startAfter"], 20_149_999);
+        assert_eq!(variables["startBefore"], 20_210_000);
+        assert_eq!(variables["scoreAbove"], 74);
+        assert_eq!(variables["status"], Value::Null);
+        assert_eq!(variables["isAdult"], false);
+        assert_eq!(discover_variables(&view(), true)["isAdult"], Value::Null);
+    }
+
+    #[test]
+    fn query_applies_every_filter_to_page_media() {
+        let query = discover_query("ANIME").render();
+
+        assert!(query.contains("$format: MediaFormat"));
+        assert!(query.contains("$country: CountryCode"));
+        assert!(query.contains(
+            "media(type: ANIME, sort: POPULARITY_DESC, genre: $genre, tag: $tag, startDate_greater: $startAfter"
+        ));
+    }
+
+    #[test]
+    fn empty_pages_explain_that_nothing_matched() {
+        let response = handle_discover(
+            &DiscoverPage::default(),
+            &view(),
+            TitleDisplayPreference::Matched,
+        );
+
+        assert_eq!(
+            response.unwrap_content(),
+            "There are no more anime matching those filters."
+        );
+    }
+
+    #[test]
+    fn results_are_numbered_across_pages() {
+        let results: DiscoverPage = serde_json::from_value(json!({
+            "pageInfo": { "hasNextPage": true },
+            "media": [{
+                "type": "ANIME",
+                "id": 1,
+                "isAdult": false,
+                "title": { "romaji": "Mushoku Tensei", "english": null, "native": null },
+                "format": "TV",
+                "status": "FINISHED",
+                "genres": ["Action", "Fantasy"],
+                "averageScore": 84,
+                "siteUrl": "https://anilist.co/anime/1"
+            }]
+        }))
+        .unwrap();
+
+        let response = handle_discover(&results, &view(), TitleDisplayPreference::Matched);
+
+        let value = serde_json::to_value(response.unwrap_embed()).expect("embed serializes");
+        assert_eq!(value["title"], "Discover Anime");
+        assert_eq!(value["fields"][0]["name"], "11. Mushoku Tensei");
+        assert_eq!(value["footer"]["text"], "Sorted by popularity • Page 2");
+        assert_eq!(
+            discover_components(&view(), results.has_next_page()).len(),
+            1
+        );
+    }
+}
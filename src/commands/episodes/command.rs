@@ -0,0 +1,664 @@
+//! `/episodes`: an anime's episodes, a page at a time, with streaming links
+//! from AniList's `streamingEpisodes` and air dates from its airing
+//! schedule. Airing shows open on the page with the next episode.
+
+use crate::{
+    commands::{
+        episodes::queries::{EPISODES_PER_PAGE, fetch_episode_page},
+        input_validation::validate_search_term,
+        response::CommandResponse,
+        traits::{AniListSource, MediaDataSource},
+    },
+    models::{
+        anilist_anime::Anime,
+        anilist_common::TitleVariant,
+        anilist_episodes::{AiringSchedulePage, EpisodeMedia, EpisodePageData, EpisodeRow},
+        settings::TitleDisplayPreference,
+        transformers::Transformers,
+    },
+    utils::{
+        cache::{Cache, get_cache_from_context, ttl_for_media_status},
+        channel::is_nsfw_channel,
+        formatter::{bold, linker},
+        privacy::configure_sentry_scope,
+        requests::{
+            anilist::send_request,
+            graphql::{AniListError, check_response, parse_response},
+        },
+        settings::resolve_title_display_preference,
+        statics::{
+            ANILIST_LOOKUP_FAILED, ANILIST_STATUS_RELEASING, NOT_FOUND_ANIME, NSFW_NOT_ALLOWED,
+        },
+    },
+};
+
+use chrono::Utc;
+use serde_json::json;
+use serenity::{
+    all::{
+        ButtonStyle, ChannelId, CommandDataOptionValue, CommandInteraction, ComponentInteraction,
+        CreateActionRow, CreateButton, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+        GuildId, UserId,
+    },
+    builder::CreateCommand,
+    client::Context,
+    model::application::CommandOptionType,
+};
+use tracing::{error, info, instrument, warn};
+
+const SEARCH_OPTION: &str = "search";
+const EPISODES_COMPONENT_PREFIX: &str = "episodes";
+const EPISODES_COMPONENT_ID_PREFIX: &str = "episodes:";
+const PREVIOUS_COMPONENT: &str = "prev";
+const NEXT_COMPONENT: &str = "next";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("episodes")
+        .description("List an anime's episodes with streaming links and air dates")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                SEARCH_OPTION,
+                "AniList ID or anime search term",
+            )
+            .required(true),
+        )
+}
+
+/// Which page of which anime's episodes to show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpisodesView {
+    pub media_id: u32,
+    pub page: u32,
+}
+
+impl EpisodesView {
+    /// Episode numbers on this page, inclusive.
+    pub fn episode_range(self) -> (u32, u32) {
+        let first = self.page * EPISODES_PER_PAGE + 1;
+        (first, first + EPISODES_PER_PAGE - 1)
+    }
+}
+
+#[instrument(name = "command.episodes.is_component")]
+pub fn is_episodes_component(custom_id: &str) -> bool {
+    custom_id.starts_with(EPISODES_COMPONENT_ID_PREFIX)
+}
+
+#[instrument(name = "command.episodes.custom_id")]
+pub fn episodes_custom_id(control: &str, view: EpisodesView) -> String {
+    format!(
+        "{EPISODES_COMPONENT_PREFIX}:{control}:{}:{}",
+        view.media_id, view.page
+    )
+}
+
+#[instrument(name = "command.episodes.parse_component_id")]
+pub fn parse_episodes_component_id(custom_id: &str) -> Option<EpisodesView> {
+    let parts = custom_id.split(':').collect::<Vec<_>>();
+
+    match parts.as_slice() {
+        [
+            EPISODES_COMPONENT_PREFIX,
+            PREVIOUS_COMPONENT | NEXT_COMPONENT,
+            raw_media_id,
+            raw_page,
+        ] => Some(EpisodesView {
+            media_id: raw_media_id.parse().ok()?,
+            page: raw_page.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+/// The page `/episodes` opens on: the one with the next episode for airing
+/// shows, otherwise the first.
+#[instrument(name = "command.episodes.starting_page", skip(anime))]
+pub fn starting_page(anime: &Anime) -> u32 {
+    if anime.get_status() != Some(ANILIST_STATUS_RELEASING) {
+        return 0;
+    }
+    anime
+        .released_episodes()
+        .map_or(0, |released| released / EPISODES_PER_PAGE)
+}
+
+#[instrument(name = "command.episodes.page_count", skip(media))]
+pub fn page_count(media: &EpisodeMedia) -> u32 {
+    media.episode_count().div_ceil(EPISODES_PER_PAGE)
+}
+
+/// The page's streaming episodes and airing schedule, from the cache when
+/// someone has paged here before.
+#[instrument(name = "command.episodes.fetch_page", skip(cache))]
+async fn fetch_episodes(
+    cache: &dyn Cache,
+    view: EpisodesView,
+) -> Result<Option<EpisodePageData>, AniListError> {
+    let cache_key = format!(
+        "{EPISODES_COMPONENT_PREFIX}:{}:{}",
+        view.media_id, view.page
+    );
+    if let Some(cached_value) = cache.get(&cache_key).await {
+        match parse_response::<EpisodePageData>(&cached_value) {
+            Ok(data) => return Ok(data),
+            Err(err) => warn!(error = %err, cache_key, "Ignoring unreadable cached episode page"),
+        }
+    }
+
+    let (first, last) = view.episode_range();
+    let json = json!({
+        "query": fetch_episode_page(),
+        "variables": { "id": view.media_id, "after": first - 1, "before": last + 1 },
+    });
+    let body = check_response(send_request(json).await)?;
+    let data = parse_response::<EpisodePageData>(&body)?;
+    if let Some(media) = data.as_ref().and_then(|data| data.media.as_ref()) {
+        let ttl = ttl_for_media_status(Some(media.media.status_text()));
+        cache.set(&cache_key, &body, ttl).await;
+    }
+
+    Ok(data)
+}
+
+// ── Core logic (transport-agnostic) ─────────────────────────────────────
+
+/// Render one page of episodes. `now` is a Unix timestamp, used to tell
+/// aired episodes from upcoming ones.
+pub fn handle_episodes(
+    media: Option<&EpisodeMedia>,
+    schedule: &AiringSchedulePage,
+    view: EpisodesView,
+    title_variant: Option<TitleVariant>,
+    title_preference: TitleDisplayPreference,
+    now: i64,
+) -> CommandResponse {
+    let Some(media) = media else {
+        return CommandResponse::Content(NOT_FOUND_ANIME.to_string());
+    };
+    let title = media.media.display_title(title_variant, title_preference);
+    let pages = page_count(media);
+    if pages == 0 {
+        return CommandResponse::Content(format!(
+            "AniList doesn't have an episode list for {} yet.",
+            bold(&title)
+        ));
+    }
+
+    let (first, last) = view.episode_range();
+    let rows = media.episode_rows(schedule, first, last);
+    if rows.is_empty() {
+        return CommandResponse::Content(format!(
+            "{} only has {} episodes.",
+            bold(&title),
+            media.episode_count()
+        ));
+    }
+
+    let color = media
+        .cover_image
+        .as_ref()
+        .and_then(|cover| cover.color.as_deref())
+        .and_then(|color| i32::from_str_radix(color.trim_start_matches('#'), 16).ok())
+        .unwrap_or(0x0000ff);
+    let thumbnail = rows
+        .iter()
+        .find_map(|row| {
+            row.streaming
+                .and_then(|episode| episode.thumbnail.as_deref())
+        })
+        .or_else(|| {
+            media
+                .cover_image
+                .as_ref()
+                .and_then(|cover| cover.large.as_deref().or(cover.medium.as_deref()))
+        });
+    let lines = rows
+        .iter()
+        .map(|row| format_episode_row(row, now))
+        .collect::<Vec<_>>();
+
+    let mut embed = CreateEmbed::new()
+        .color(color)
+        .title(format!("{title} • Episodes"))
+        .url(media.media.site_url())
+        .description(lines.join("\n"))
+        .footer(CreateEmbedFooter::new(format!(
+            "Page {}/{pages} • {} episodes",
+            view.page + 1,
+            media.episode_count()
+        )));
+    if let Some(thumbnail) = thumbnail {
+        embed = embed.thumbnail(thumbnail);
+    }
+
+    CommandResponse::Embed(Box::new(embed))
+}
+
+/// e.g. `**Ep. 5** · [Title](…) (Crunchyroll) · airs <t:…:R>`.
+fn format_episode_row(row: &EpisodeRow, now: i64) -> String {
+    let mut parts = vec![bold(&format!("Ep. {}", row.number))];
+
+    if let Some(episode) = row.streaming {
+        let link = match (episode.name(), episode.url.as_deref()) {
+            (Some(name), Some(url)) => Some(linker(name, url)),
+            (Some(name), None) => Some(name.to_string()),
+            (None, Some(url)) => Some(linker("Watch", url)),
+            (None, None) => None,
+        };
+        if let Some(link) = link {
+            parts.push(match &episode.site {
+                Some(site) => format!("{link} ({site})"),
+                None => link,
+            });
+        }
+    }
+
+    match row.airing_at {
+        Some(airing_at) if airing_at > now => parts.push(format!("airs <t:{airing_at}:R>")),
+        Some(airing_at) => parts.push(format!("aired <t:{airing_at}:d>")),
+        None if parts.len() == 1 => parts.push("no details yet".to_string()),
+        None => {}
+    }
+
+    parts.join(" · ")
+}
+
+#[instrument(name = "command.episodes.components")]
+pub fn episodes_components(view: EpisodesView, page_count: u32) -> Vec<CreateActionRow> {
+    if page_count <= 1 {
+        return Vec::new();
+    }
+
+    let previous = EpisodesView {
+        page: view.page.saturating_sub(1),
+        ..view
+    };
+    let next = EpisodesView {
+        page: (view.page + 1).min(page_count - 1),
+        ..view
+    };
+
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(episodes_custom_id(PREVIOUS_COMPONENT, previous))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(view.page == 0),
+        CreateButton::new(episodes_custom_id(NEXT_COMPONENT, next))
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(view.page + 1 >= page_count),
+    ])]
+}
+
+// ── Serenity adapter (thin wrapper) ─────────────────────────────────────
+
+/// Fetch and render `view` as an interaction edit: the embed and its paging
+/// buttons, or a plain message that clears any earlier embed and buttons.
+#[instrument(name = "command.episodes.response", skip(ctx))]
+async fn episodes_response(
+    ctx: &Context,
+    user_id: UserId,
+    guild_id: Option<GuildId>,
+    channel_id: ChannelId,
+    view: EpisodesView,
+    title_variant: Option<TitleVariant>,
+) -> EditInteractionResponse {
+    let cache = get_cache_from_context(ctx).await;
+    let (data, title_preference) = tokio::join!(
+        fetch_episodes(cache.as_ref(), view),
+        resolve_title_display_preference(ctx, user_id, guild_id),
+    );
+    let data = match data {
+        Ok(data) => data,
+        Err(err) => {
+            error!(error = %err, media_id = view.media_id, "Failed to fetch episodes");
+            return content_response(
+                err.user_message()
+                    .unwrap_or(
+                        "I couldn't load the episode list right now. Please try again later.",
+                    )
+                    .to_string(),
+            );
+        }
+    };
+    let media = data.as_ref().and_then(|data| data.media.as_ref());
+    if media.is_some_and(|media| media.media.is_adult())
+        && !is_nsfw_channel(ctx, channel_id, guild_id).await
+    {
+        return content_response(NSFW_NOT_ALLOWED.to_string());
+    }
+    let schedule = data
+        .as_ref()
+        .and_then(|data| data.schedule.clone())
+        .unwrap_or_default();
+
+    match handle_episodes(
+        media,
+        &schedule,
+        view,
+        title_variant,
+        title_preference,
+        Utc::now().timestamp(),
+    ) {
+        CommandResponse::Content(text) | CommandResponse::Message(text) => content_response(text),
+        CommandResponse::Embed(embed) => EditInteractionResponse::new()
+            .content("")
+            .embed(*embed)
+            .components(episodes_components(view, media.map_or(0, page_count))),
+    }
+}
+
+#[instrument]
+fn content_response(text: String) -> EditInteractionResponse {
+    EditInteractionResponse::new()
+        .content(text)
+        .embeds(Vec::new())
+        .components(Vec::new())
+}
+
+#[instrument(name = "command.episodes.run", skip(ctx, interaction))]
+pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
+    let _ = interaction.defer(&ctx.http).await;
+
+    let user = &interaction.user;
+
+    let Some(CommandDataOptionValue::String(search_term)) =
+        interaction.data.options.first().map(|opt| &opt.value)
+    else {
+        let builder = EditInteractionResponse::new()
+            .content("Tell me which anime to list with `search:<name or AniList ID>`.");
+        let _ = interaction.edit_response(&ctx.http, builder).await;
+        return;
+    };
+    let search_term = search_term.clone();
+
+    if let Err(err) = validate_search_term(&search_term) {
+        let builder = EditInteractionResponse::new().content(format!(
+            "I couldn't use that search: {err}. Try an anime title or AniList ID."
+        ));
+        let _ = interaction.edit_response(&ctx.http, builder).await;
+        return;
+    }
+
+    configure_sentry_scope("Episodes", user.id.get(), Some(json!(search_term.clone())));
+    info!("Got command 'episodes' with search_term: {search_term}");
+
+    let source = AniListSource::from_context(ctx).await;
+    let (anime, title_variant) = match source.fetch_anime(&search_term).await {
+        Ok(Some(found)) => found,
+        Ok(None) => {
+            let builder = EditInteractionResponse::new().content(NOT_FOUND_ANIME);
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
+        Err(err) => {
+            error!(error = %err, "Failed to fetch anime for episodes");
+            let builder = EditInteractionResponse::new()
+                .content(err.user_message().unwrap_or(ANILIST_LOOKUP_FAILED));
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
+    };
+
+    let view = EpisodesView {
+        media_id: anime.get_id(),
+        page: starting_page(&anime),
+    };
+    let builder = episodes_response(
+        ctx,
+        user.id,
+        interaction.guild_id,
+        interaction.channel_id,
+        view,
+        Some(title_variant),
+    )
+    .await;
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+#[instrument(name = "command.episodes.handle_component", skip(ctx, interaction))]
+pub async fn handle_component(ctx: &Context, interaction: &mut ComponentInteraction) {
+    configure_sentry_scope("Episodes", interaction.user.id.get(), None);
+
+    let Some(view) = parse_episodes_component_id(&interaction.data.custom_id) else {
+        let builder = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content("I don't recognize that control. Please run the command again.")
+                .ephemeral(true),
+        );
+        let _ = interaction.create_response(&ctx.http, builder).await;
+        return;
+    };
+
+    if let Err(error) = interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        warn!(
+            error = %error,
+            custom_id = %interaction.data.custom_id,
+            "Failed to acknowledge episodes component interaction"
+        );
+        return;
+    }
+
+    let builder = episodes_response(
+        ctx,
+        interaction.user.id,
+        interaction.guild_id,
+        interaction.channel_id,
+        view,
+        None,
+    )
+    .await;
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cache::InMemoryCache;
+
+    const NOW: i64 = 1_800_000_000;
+
+    fn page_data(status: &str, episodes: Option<u32>) -> EpisodePageData {
+        serde_json::from_value(json!({
+            "Media": {
+                "type": "ANIME",
+                "id": 21,
+                "isAdult": false,
+                "title": { "romaji": "One Piece", "english": null, "native": null },
+                "format": "TV",
+                "status": status,
+                "genres": [],
+                "averageScore": null,
+                "siteUrl": "https://anilist.co/anime/21",
+                "coverImage": { "extraLarge": null, "large": "https://example.com/cover.jpg", "medium": null, "color": null },
+                "episodes": episodes,
+                "nextAiringEpisode": { "episode": 13, "airingAt": NOW + 3600 },
+                "streamingEpisodes": [
+                    { "title": "Episode 11 - Eleven", "thumbnail": "https://example.com/11.jpg", "url": "https://example.com/11", "site": "Crunchyroll" },
+                    { "title": "Episode 12 - Twelve", "thumbnail": null, "url": null, "site": null }
+                ]
+            },
+            "Page": {
+                "airingSchedules": [
+                    { "episode": 11, "airingAt": NOW - 7 * 86400 },
+                    { "episode": 12, "airingAt": NOW - 3600 }
+                ]
+            }
+        }))
+        .expect("episode page should deserialize")
+    }
+
+    fn anime(status: &str, next_episode: Option<u32>) -> Anime {
+        serde_json::from_value(json!({
+            "type": "ANIME",
+            "id": 21,
+            "idMal": null,
+            "isAdult": false,
+            "title": { "romaji": "One Piece", "english": null, "native": null },
+            "synonyms": null,
+            "season": null,
+            "seasonYear": null,
+            "format": "TV",
+            "status": status,
+            "episodes": 24,
+            "nextAiringEpisode": next_episode.map(|episode| json!({ "episode": episode })),
+            "duration": 24,
+            "genres": [],
+            "source": null,
+            "coverImage": { "extraLarge": null, "large": null, "medium": null, "color": null },
+            "averageScore": null,
+            "studios": null,
+            "siteUrl": "https://anilist.co/anime/21",
+            "externalLinks": null,
+            "trailer": null,
+            "description": null,
+            "tags": []
+        }))
+        .expect("anime should deserialize")
+    }
+
+    #[test]
+    fn custom_ids_round_trip() {
+        let view = EpisodesView {
+            media_id: 21,
+            page: 3,
+        };
+        let custom_id = episodes_custom_id(NEXT_COMPONENT, view);
+
+        assert_eq!(custom_id, "episodes:next:21:3");
+        assert!(is_episodes_component(&custom_id));
+        assert_eq!(parse_episodes_component_id(&custom_id), Some(view));
+        assert_eq!(parse_episodes_component_id("episodes:last:21:3"), None);
+    }
+
+    #[test]
+    fn airing_shows_open_on_the_next_episode() {
+        assert_eq!(starting_page(&anime(ANILIST_STATUS_RELEASING, Some(13))), 1);
+        assert_eq!(starting_page(&anime(ANILIST_STATUS_RELEASING, Some(10))), 0);
+        assert_eq!(starting_page(&anime("FINISHED", None)), 0);
+    }
+
+    #[test]
+    fn page_lists_links_air_dates_and_countdowns() {
+        let data = page_data(ANILIST_STATUS_RELEASING, Some(24));
+        let view = EpisodesView {
+            media_id: 21,
+            page: 1,
+        };
+
+        let embed = serde_json::to_value(
+            handle_episodes(
+                data.media.as_ref(),
+                data.schedule.as_ref().unwrap(),
+                view,
+                None,
+                TitleDisplayPreference::Matched,
+                NOW,
+            )
+            .unwrap_embed(),
+        )
+        .expect("embed serializes");
+        let lines = embed["description"]
+            .as_str()
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>();
+
+        assert_eq!(embed["title"], "One Piece • Episodes");
+        assert_eq!(embed["footer"]["text"], "Page 2/3 • 24 episodes");
+        assert_eq!(embed["thumbnail"]["url"], "https://example.com/11.jpg");
+        assert_eq!(
+            lines[0],
+            format!(
+                "**Ep. 11** · [Eleven](https://example.com/11) (Crunchyroll) · aired <t:{}:d>",
+                NOW - 7 * 86400
+            )
+        );
+        assert_eq!(
+            lines[1],
+            format!("**Ep. 12** · Twelve · aired <t:{}:d>", NOW - 3600)
+        );
+        assert_eq!(lines[2], format!("**Ep. 13** · airs <t:{}:R>", NOW + 3600));
+        assert_eq!(lines[3], "**Ep. 14** · no details yet");
+        assert_eq!(lines.len(), 10);
+    }
+
+    #[test]
+    fn media_without_episodes_gets_a_message() {
+        let media = serde_json::from_value::<EpisodeMedia>(json!({
+            "type": "ANIME",
+            "id": 21,
+            "isAdult": false,
+            "title": { "romaji": "One Piece", "english": null, "native": null },
+            "format": "TV",
+            "status": "NOT_YET_RELEASED",
+            "genres": [],
+            "averageScore": null,
+            "siteUrl": "https://anilist.co/anime/21",
+            "coverImage": null,
+            "episodes": null,
+            "nextAiringEpisode": null,
+            "streamingEpisodes": []
+        }))
+        .expect("media should deserialize");
+
+        let response = handle_episodes(
+            Some(&media),
+            &AiringSchedulePage::default(),
+            EpisodesView {
+                media_id: 21,
+                page: 0,
+            },
+            None,
+            TitleDisplayPreference::Matched,
+            NOW,
+        );
+
+        assert_eq!(
+            response.unwrap_content(),
+            "AniList doesn't have an episode list for **One Piece** yet."
+        );
+    }
+
+    #[test]
+    fn paging_buttons_only_appear_with_several_pages() {
+        let view = EpisodesView {
+            media_id: 21,
+            page: 0,
+        };
+
+        assert!(episodes_components(view, 1).is_empty());
+        let rows = serde_json::to_value(episodes_components(view, 3)).expect("serializes");
+        let buttons = rows[0]["components"].as_array().unwrap();
+        assert_eq!(buttons[0]["disabled"], true);
+        assert_eq!(buttons[1]["custom_id"], "episodes:next:21:1");
+    }
+
+    #[tokio::test]
+    async fn cached_pages_are_served_without_querying_anilist() {
+        let cache = InMemoryCache::with_entry(
+            "episodes:21:0",
+            r#"{"data":{"Media":{"type":"ANIME","id":21,"isAdult":false,"title":{"romaji":"One Piece","english":null,"native":null},"format":"TV","status":"FINISHED","genres":[],"averageScore":null,"siteUrl":"https://anilist.co/anime/21","coverImage":null,"episodes":3,"nextAiringEpisode":null,"streamingEpisodes":[]},"Page":{"airingSchedules":[]}}}"#,
+        );
+
+        let data = fetch_episodes(
+            &cache,
+            EpisodesView {
+                media_id: 21,
+                page: 0,
+            },
+        )
+        .await
+        .expect("cached page should parse")
+        .expect("data should be present");
+
+        assert_eq!(data.media.map(|media| media.episode_count()), Some(3));
+    }
+}
@@ -0,0 +1,50 @@
+use crate::utils::requests::{
+    fragments::{cover_image, media_summary},
+    query::{Field, Query, Selection, VariableType},
+};
+
+use tracing::instrument;
+
+/// Episodes listed per `/episodes` page.
+pub const EPISODES_PER_PAGE: u32 = 10;
+
+/// An anime's streaming episodes plus the airing schedule for episodes
+/// `$after + 1 ..= $before - 1`.
+#[instrument]
+pub fn fetch_episode_page() -> String {
+    Query::new()
+        .variable("id", VariableType::Int)
+        .variable("after", VariableType::Int)
+        .variable("before", VariableType::Int)
+        .field(
+            Field::new("Media")
+                .arg("id", "$id")
+                .arg("type", "ANIME")
+                .select(
+                    media_summary()
+                        .spread(&cover_image())
+                        .field("episodes")
+                        .object(
+                            "nextAiringEpisode",
+                            Selection::new().fields(&["episode", "airingAt"]),
+                        )
+                        .object(
+                            "streamingEpisodes",
+                            Selection::new().fields(&["title", "thumbnail", "url", "site"]),
+                        ),
+                ),
+        )
+        .field(
+            Field::new("Page").arg("perPage", EPISODES_PER_PAGE).select(
+                Selection::new().field(
+                    Field::new("airingSchedules")
+                        .arg("mediaId", "$id")
+                        .arg("episode_greater", "$after")
+                        .arg("episode_lesser", "$before")
+                        .arg("sort", "[EPISODE]")
+                        .select(Selection::new().fields(&["episode", "airingAt"])),
+                ),
+            ),
+        )
+        .render()
+}
@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+
+use crate::{
+    models::user_media_list::{
+        GuildScoreSort, GuildScoreSummary, MediaListData, sort_guild_entries,
+    },
+    utils::{formatter::bold, guild::get_guild_data, privacy::configure_sentry_scope},
+};
+
+use serenity::{
+    all::{
+        ButtonStyle, ComponentInteraction, CreateActionRow, CreateAllowedMentions, CreateButton,
+        CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    },
+    client::Context,
+};
+use tracing::{instrument, warn};
+
+const GUILD_SCORES_COMPONENT_PREFIX: &str = "guild_scores";
+const GUILD_SCORES_COMPONENT_ID_PREFIX: &str = "guild_scores:";
+const OPEN_COMPONENT: &str = "open";
+const SORT_COMPONENT: &str = "sort";
+const PREVIOUS_COMPONENT: &str = "prev";
+const NEXT_COMPONENT: &str = "next";
+
+/// Members listed per page of the full view.
+pub const GUILD_SCORES_PAGE_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuildScoresControl {
+    /// The "View all" button on the `/anime` or `/manga` embed.
+    Open,
+    Sort,
+    Previous,
+    Next,
+}
+
+impl GuildScoresControl {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            OPEN_COMPONENT => Some(Self::Open),
+            SORT_COMPONENT => Some(Self::Sort),
+            PREVIOUS_COMPONENT => Some(Self::Previous),
+            NEXT_COMPONENT => Some(Self::Next),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => OPEN_COMPONENT,
+            Self::Sort => SORT_COMPONENT,
+            Self::Previous => PREVIOUS_COMPONENT,
+            Self::Next => NEXT_COMPONENT,
+        }
+    }
+}
+
+/// Which page of which media's guild scores to show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuildScoresView {
+    pub media_type: &'static str,
+    pub media_id: u32,
+    pub sort: GuildScoreSort,
+    pub page: usize,
+}
+
+/// A parsed component ID: the control that was used and the view it leads to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuildScoresComponentId {
+    pub control: GuildScoresControl,
+    pub view: GuildScoresView,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildScoresPage {
+    /// The requested view, with its page clamped to the pages that exist.
+    pub view: GuildScoresView,
+    pub page_count: usize,
+    pub content: String,
+}
+
+#[instrument(name = "command.guild_scores.is_component")]
+pub fn is_guild_scores_component(custom_id: &str) -> bool {
+    custom_id.starts_with(GUILD_SCORES_COMPONENT_ID_PREFIX)
+}
+
+#[instrument(name = "command.guild_scores.custom_id", skip(view), fields(control = %control.as_str()))]
+pub fn guild_scores_custom_id(control: GuildScoresControl, view: GuildScoresView) -> String {
+    format!(
+        "{GUILD_SCORES_COMPONENT_PREFIX}:{}:{}:{}:{}:{}",
+        control.as_str(),
+        view.media_type,
+        view.media_id,
+        view.sort.as_str(),
+        view.page
+    )
+}
+
+#[instrument(name = "command.guild_scores.parse_component_id")]
+pub fn parse_guild_scores_component_id(custom_id: &str) -> Option<GuildScoresComponentId> {
+    let parts = custom_id.split(':').collect::<Vec<_>>();
+
+    match parts.as_slice() {
+        [
+            GUILD_SCORES_COMPONENT_PREFIX,
+            raw_control,
+            raw_media_type,
+            raw_media_id,
+            raw_sort,
+            raw_page,
+        ] => Some(GuildScoresComponentId {
+            control: GuildScoresControl::parse(raw_control)?,
+            view: GuildScoresView {
+                media_type: parse_media_type(raw_media_type)?,
+                media_id: raw_media_id.parse().ok()?,
+                sort: GuildScoreSort::parse(raw_sort)?,
+                page: raw_page.parse().ok()?,
+            },
+        }),
+        _ => None,
+    }
+}
+
+#[instrument(name = "command.guild_scores.parse_media_type")]
+fn parse_media_type(raw: &str) -> Option<&'static str> {
+    match raw {
+        "anime" => Some("anime"),
+        "manga" => Some("manga"),
+        _ => None,
+    }
+}
+
+/// The "View all" button attached to `/anime` and `/manga` embeds that show
+/// guild scores.
+#[instrument(name = "command.guild_scores.view_all_components")]
+pub fn view_all_components(media_type: &str, media_id: u32) -> Vec<CreateActionRow> {
+    let Some(media_type) = parse_media_type(media_type) else {
+        return Vec::new();
+    };
+    let view = GuildScoresView {
+        media_type,
+        media_id,
+        sort: GuildScoreSort::Score,
+        page: 0,
+    };
+
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(guild_scores_custom_id(GuildScoresControl::Open, view))
+            .label("View all")
+            .style(ButtonStyle::Secondary),
+    ])]
+}
+
+#[instrument(name = "command.guild_scores.plan_page", skip(entries), fields(member_count = entries.len()))]
+pub fn plan_guild_scores_page(
+    view: GuildScoresView,
+    entries: HashMap<u64, MediaListData>,
+) -> GuildScoresPage {
+    let is_anime = view.media_type == "anime";
+    let summary = GuildScoreSummary::from_entries(&entries);
+    let sorted = sort_guild_entries(entries, view.sort);
+    let page_count = sorted.len().div_ceil(GUILD_SCORES_PAGE_SIZE).max(1);
+    let view = GuildScoresView {
+        page: view.page.min(page_count - 1),
+        ..view
+    };
+
+    let lines = sorted
+        .iter()
+        .enumerate()
+        .skip(view.page * GUILD_SCORES_PAGE_SIZE)
+        .take(GUILD_SCORES_PAGE_SIZE)
+        .map(|(index, (user_id, entry))| {
+            format!(
+                "`{}.` <@{user_id}>: {}",
+                index + 1,
+                entry.format_for_embed(is_anime)
+            )
+        })
+        .collect::<Vec<_>>();
+    let header = format!(
+        "Server scores, sorted by {} (page {}/{page_count})",
+        view.sort.as_str(),
+        view.page + 1
+    );
+
+    GuildScoresPage {
+        view,
+        page_count,
+        content: format!(
+            "{}\n{}\n\n{}",
+            bold(&header),
+            summary.format_for_embed(is_anime),
+            lines.join("\n")
+        ),
+    }
+}
+
+#[instrument(name = "command.guild_scores.handle_component", skip(ctx, interaction))]
+pub async fn handle_component(ctx: &Context, interaction: &mut ComponentInteraction) {
+    configure_sentry_scope("GuildScores", interaction.user.id.get(), None);
+
+    let Some(component_id) = parse_guild_scores_component_id(&interaction.data.custom_id) else {
+        let builder = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content("I don't recognize that control. Please run the command again.")
+                .ephemeral(true),
+        );
+        let _ = interaction.create_response(&ctx.http, builder).await;
+        return;
+    };
+
+    // "View all" opens a private list for whoever clicked it; the controls on
+    // that list then update it in place.
+    let acknowledgement = match component_id.control {
+        GuildScoresControl::Open => CreateInteractionResponse::Defer(
+            CreateInteractionResponseMessage::new().ephemeral(true),
+        ),
+        _ => CreateInteractionResponse::Acknowledge,
+    };
+    if let Err(error) = interaction
+        .create_response(&ctx.http, acknowledgement)
+        .await
+    {
+        warn!(
+            error = %error,
+            custom_id = %interaction.data.custom_id,
+            "Failed to acknowledge guild scores component interaction"
+        );
+        return;
+    }
+
+    let view = component_id.view;
+    let entries = get_guild_data(ctx, interaction.guild_id, view.media_id, view.media_type).await;
+    let builder = if entries.is_empty() {
+        EditInteractionResponse::new()
+            .content("No one in this server has this on their AniList list right now.")
+            .components(Vec::new())
+    } else {
+        let page = plan_guild_scores_page(view, entries);
+        EditInteractionResponse::new()
+            .content(page.content.clone())
+            .components(guild_scores_components(&page))
+    };
+
+    let builder = builder.allowed_mentions(CreateAllowedMentions::new());
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+#[instrument(name = "command.guild_scores.components", skip(page))]
+fn guild_scores_components(page: &GuildScoresPage) -> Vec<CreateActionRow> {
+    let view = page.view;
+    let sort_buttons = GuildScoreSort::ALL
+        .into_iter()
+        .map(|sort| {
+            let is_active = sort == view.sort;
+            let style = if is_active {
+                ButtonStyle::Secondary
+            } else {
+                ButtonStyle::Primary
+            };
+            let target = GuildScoresView {
+                sort,
+                page: 0,
+                ..view
+            };
+
+            CreateButton::new(guild_scores_custom_id(GuildScoresControl::Sort, target))
+                .label(sort.label())
+                .style(style)
+                .disabled(is_active)
+        })
+        .collect();
+    let mut rows = vec![CreateActionRow::Buttons(sort_buttons)];
+
+    if page.page_count > 1 {
+        let previous = GuildScoresView {
+            page: view.page.saturating_sub(1),
+            ..view
+        };
+        let next = GuildScoresView {
+            page: (view.page + 1).min(page.page_count - 1),
+            ..view
+        };
+        rows.push(CreateActionRow::Buttons(vec![
+            CreateButton::new(guild_scores_custom_id(
+                GuildScoresControl::Previous,
+                previous,
+            ))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(view.page == 0),
+            CreateButton::new(guild_scores_custom_id(GuildScoresControl::Next, next))
+                .label("Next")
+                .style(ButtonStyle::Secondary)
+                .disabled(view.page + 1 >= page.page_count),
+        ]));
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user_media_list::MediaListStatus;
+
+    fn view(page: usize) -> GuildScoresView {
+        GuildScoresView {
+            media_type: "anime",
+            media_id: 21,
+            sort: GuildScoreSort::Score,
+            page,
+        }
+    }
+
+    fn completed_entries(count: u64) -> HashMap<u64, MediaListData> {
+        (1..=count)
+            .map(|user_id| {
+                (
+                    user_id,
+                    MediaListData {
+                        status: Some(MediaListStatus::Completed),
+                        score: Some(user_id as u32),
+                        progress: Some(12),
+                        progress_volumes: None,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn custom_ids_round_trip() {
+        for control in [
+            GuildScoresControl::Open,
+            GuildScoresControl::Sort,
+            GuildScoresControl::Previous,
+            GuildScoresControl::Next,
+        ] {
+            let custom_id = guild_scores_custom_id(control, view(2));
+
+            assert!(is_guild_scores_component(&custom_id));
+            assert_eq!(
+                parse_guild_scores_component_id(&custom_id),
+                Some(GuildScoresComponentId {
+                    control,
+                    view: view(2)
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_custom_ids_are_rejected() {
+        assert_eq!(
+            parse_guild_scores_component_id("guild_scores:open:novel:21:score:0"),
+            None
+        );
+        assert_eq!(
+            parse_guild_scores_component_id("guild_scores:open:anime:21:rating:0"),
+            None
+        );
+        assert_eq!(
+            parse_guild_scores_component_id("guild_scores:open:anime:21:score"),
+            None
+        );
+        assert!(!is_guild_scores_component("settings:overview"));
+    }
+
+    #[test]
+    fn pages_list_members_in_sorted_order() {
+        let page = plan_guild_scores_page(view(1), completed_entries(12));
+
+        assert_eq!(page.page_count, 2);
+        assert!(
+            page.content
+                .starts_with("**Server scores, sorted by score (page 2/2)**\nServer mean:")
+        );
+        assert!(page.content.ends_with(
+            "`11.` <@2>: finished it, rated 2/100\n`12.` <@1>: finished it, rated 1/100"
+        ));
+    }
+
+    #[test]
+    fn out_of_range_pages_are_clamped() {
+        let page = plan_guild_scores_page(view(5), completed_entries(3));
+
+        assert_eq!(page.view.page, 0);
+        assert_eq!(page.page_count, 1);
+        assert!(page.content.contains("`1.` <@3>"));
+    }
+}
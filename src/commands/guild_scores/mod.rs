@@ -0,0 +1,3 @@
+pub mod command;
+
+pub use command::{handle_component, is_guild_scores_component, view_all_components};
@@ -33,12 +33,17 @@ pub async fn run(ctx: &Context, interaction: &CommandInteraction) {
         )
         .field(
             "Commands",
-            "`/anime search:<term or id>` - anime details\n`/manga search:<term or id>` - manga details\n`/search query:<description>` - natural-language anime/manga search\n`/recommend type:<anime|manga> search:<term or id>` - community recommendations\n`/character search:<term or id> spoilers:<allow|disallow>` - character details\n`/studio search:<term or id>` - production studio details\n`/songs search:<term or id>` - opening and ending themes\n`/settings` - preferences for titles, analytics, and guild scores\n`/register` - link or relink AniList\n`/unregister confirmation:<confirm|cancel>` - unlink AniList\n`/whoami` - show your linked AniList account\n`/ping` - bot health check\n`/help` - show this guide",
+            "`/anime search:<term or id> spoilers:<allow|disallow>` - anime details\n`/manga search:<term or id> spoilers:<allow|disallow>` - manga details\n`/search query:<description>` - natural-language anime/manga search\n`/recommend type:<anime|manga> search:<term or id>` - community recommendations (optional `search2`/`search3` and filters)\n`/recommend type:<anime|manga> mode:for-me` - picks based on your linked AniList list\n`/next max_hours:<hours> finished_only:<true|false>` - what to start next from your Planning/Paused list\n`/binge search:<term or id> speed:<1.5> skip_op_ed:<true|false>` - time left to binge a title\n`/discover genre:<genre> tag:<tag> year_from:<year>` - browse popular titles with filters\n`/episodes search:<term or id>` - episode list with streaming links and air dates\n`/character search:<term or id> spoilers:<allow|disallow>` - character details\n`/studio search:<term or id>` - production studio details\n`/songs search:<term or id>` - opening and ending themes",
+            false,
+        )
+        .field(
+            "Account",
+            "`/settings` - preferences for titles, analytics, guild scores, spoilers, and streaming region\n`/register` - link or relink AniList\n`/unregister confirmation:<confirm|cancel>` - unlink AniList\n`/whoami` - show your linked AniList account\n`/ping` - bot health check\n`/help` - show this guide",
             false,
         )
         .field(
             "Tips",
-            "Full titles, short titles, and AniList IDs all work. If you only remember a scene or premise, try `/search`. Run `/settings` to pick title language and privacy preferences. If your AniList link expires or you want to reconnect, run `/register` again.",
+            "Full titles, short titles, and AniList IDs all work. If you only remember a scene or premise, try `/search`. The tabs under `/anime` and `/manga` replies show stats, related titles, and staff. Run `/settings` to pick title language and privacy preferences. If your AniList link expires or you want to reconnect, run `/register` again.",
             false,
         )
         .footer(CreateEmbedFooter::new("Annie Mei"))
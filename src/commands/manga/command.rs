@@ -2,32 +2,43 @@ use std::collections::HashMap;
 
 use crate::{
     commands::{
+        guild_scores::view_all_components,
         input_validation::validate_search_term,
+        media_tabs::{MediaTabsView, media_tab_components},
         response::CommandResponse,
         traits::{AniListSource, MediaDataSource},
     },
     models::{
-        anilist_common::TitleVariant, anilist_manga::Manga, settings::TitleDisplayPreference,
-        transformers::Transformers, user_media_list::MediaListData,
+        anilist_common::TitleVariant,
+        anilist_manga::Manga,
+        settings::{StreamingRegion, TitleDisplayPreference},
+        transformers::Transformers,
+        user_media_list::MediaListData,
     },
     utils::{
         channel::is_nsfw_channel,
-        guild::{get_current_guild_members, get_guild_data_for_media},
+        guild::get_guild_data_for_media,
         privacy::configure_sentry_scope,
-        settings::resolve_title_display_preference,
-        statics::{NOT_FOUND_MANGA, NSFW_NOT_ALLOWED},
+        settings::{resolve_spoiler_preference, resolve_title_display_preference},
+        spoilers::{parse_spoilers_option, spoilers_option},
+        statics::{ANILIST_LOOKUP_FAILED, NOT_FOUND_MANGA, NSFW_NOT_ALLOWED},
     },
 };
 
 use serde_json::json;
 use serenity::{
-    all::{CommandInteraction, CreateCommandOption, EditInteractionResponse},
+    all::{
+        CommandDataOption, CommandDataOptionValue, CommandInteraction, CreateCommandOption,
+        EditInteractionResponse,
+    },
     builder::CreateCommand,
     client::Context,
     model::application::CommandOptionType,
 };
 
-use tracing::{info, instrument};
+use tracing::{error, info, instrument};
+
+const SEARCH_OPTION: &str = "search";
 
 pub fn register() -> CreateCommand {
     CreateCommand::new("manga")
@@ -35,11 +46,14 @@ pub fn register() -> CreateCommand {
         .add_option(
             CreateCommandOption::new(
                 CommandOptionType::String,
-                "search",
+                SEARCH_OPTION,
                 "AniList ID or manga search term",
             )
             .required(true),
         )
+        .add_option(spoilers_option(
+            "Show description spoilers (defaults to your /settings choice)",
+        ))
 }
 
 // ── Core logic (transport-agnostic) ─────────────────────────────────────
@@ -57,6 +71,7 @@ pub fn handle_manga(
     guild_members_data: Option<HashMap<u64, MediaListData>>,
     title_variant: Option<TitleVariant>,
     title_preference: TitleDisplayPreference,
+    allow_spoilers: bool,
 ) -> CommandResponse {
     match manga {
         None => CommandResponse::Content(NOT_FOUND_MANGA.to_string()),
@@ -65,12 +80,25 @@ pub fn handle_manga(
                 guild_members_data,
                 title_variant,
                 title_preference,
+                allow_spoilers,
+                StreamingRegion::All,
             );
             CommandResponse::Embed(Box::new(embed))
         }
     }
 }
 
+/// The `search` option, wherever it sits among the others.
+fn parse_search_option(options: &[CommandDataOption]) -> Option<String> {
+    options
+        .iter()
+        .find(|option| option.name == SEARCH_OPTION)
+        .and_then(|option| match &option.value {
+            CommandDataOptionValue::String(search_term) => Some(search_term.clone()),
+            _ => None,
+        })
+}
+
 // ── Serenity adapter (thin wrapper) ─────────────────────────────────────
 
 #[instrument(name = "command.manga.run", skip(ctx, interaction))]
@@ -80,15 +108,12 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
     let user = &interaction.user;
 
     // Validate the required "search" option up-front.
-    let Some(serenity::all::CommandDataOptionValue::String(search_term)) =
-        interaction.data.options.first().map(|opt| &opt.value)
-    else {
+    let Some(search_term) = parse_search_option(&interaction.data.options) else {
         let builder = EditInteractionResponse::new()
             .content("Tell me which manga to look up with `search:<name or AniList ID>`.");
         let _ = interaction.edit_response(&ctx.http, builder).await;
         return;
     };
-    let search_term = search_term.clone();
 
     if let Err(err) = validate_search_term(&search_term) {
         let builder = EditInteractionResponse::new().content(format!(
@@ -102,13 +127,24 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
 
     info!("Got command 'manga' with search_term: {search_term}");
 
-    let (fetch_result, title_preference): (Option<(Manga, TitleVariant)>, TitleDisplayPreference) = tokio::join!(
-        AniListSource.fetch_manga(&search_term),
+    let requested_spoilers = parse_spoilers_option(&interaction.data.options);
+
+    let source = AniListSource::from_context(ctx).await;
+    let (fetch_result, title_preference, allow_spoilers) = tokio::join!(
+        source.fetch_manga(&search_term),
         resolve_title_display_preference(ctx, user.id, interaction.guild_id),
+        resolve_spoiler_preference(ctx, user.id, requested_spoilers),
     );
     let (manga_result, title_variant): (Option<Manga>, Option<TitleVariant>) = match fetch_result {
-        Some((manga, variant)) => (Some(manga), Some(variant)),
-        None => (None, None),
+        Ok(Some((manga, variant))) => (Some(manga), Some(variant)),
+        Ok(None) => (None, None),
+        Err(err) => {
+            error!(error = %err, "Failed to fetch manga");
+            let message = err.user_message().unwrap_or(ANILIST_LOOKUP_FAILED);
+            let builder = EditInteractionResponse::new().content(message);
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
     };
 
     // Block adult content in non-NSFW channels.
@@ -125,30 +161,30 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
     let guild_members_data = match &manga_result {
         None => None,
         Some(manga_response) => {
-            let guild_members = get_current_guild_members(ctx, interaction);
-            if guild_members.is_empty() {
-                info!("No users found in guild");
-                None
-            } else {
-                let data = get_guild_data_for_media(
-                    ctx,
-                    manga_response,
-                    interaction.guild_id,
-                    guild_members,
-                )
-                .await;
-                info!("Guild members data: {} entries", data.len());
-                if data.is_empty() { None } else { Some(data) }
-            }
+            let data = get_guild_data_for_media(ctx, manga_response, interaction.guild_id).await;
+            info!("Guild members data: {} entries", data.len());
+            if data.is_empty() { None } else { Some(data) }
         }
     };
 
+    // Tabs for the stats, relations and staff pages; guild scores also get a
+    // "View all" button that opens the full, sortable list.
+    let mut components = manga_result
+        .as_ref()
+        .and_then(|manga| MediaTabsView::overview(manga, title_variant, allow_spoilers))
+        .map(media_tab_components)
+        .unwrap_or_default();
+    if let (Some(manga), Some(_)) = (&manga_result, &guild_members_data) {
+        components.extend(view_all_components(manga.get_type(), manga.get_id()));
+    }
+
     // Delegate to the transport-agnostic core logic.
     let response = handle_manga(
         manga_result,
         guild_members_data,
         title_variant,
         title_preference,
+        allow_spoilers,
     );
 
     // Map the CommandResponse to the appropriate Discord API call.
@@ -158,7 +194,9 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
             interaction.edit_response(&ctx.http, builder).await
         }
         CommandResponse::Embed(embed) => {
-            let builder = EditInteractionResponse::new().embed(*embed);
+            let builder = EditInteractionResponse::new()
+                .embed(*embed)
+                .components(components);
             interaction.edit_response(&ctx.http, builder).await
         }
         CommandResponse::Message(text) => {
@@ -217,9 +255,21 @@ mod tests {
         TitleDisplayPreference::Matched
     }
 
+    #[test]
+    fn search_option_is_found_by_name_when_spoilers_come_first() {
+        let options: Vec<CommandDataOption> = serde_json::from_value(serde_json::json!([
+            { "name": "spoilers", "type": 3, "value": "allow" },
+            { "name": "search", "type": 3, "value": "One Piece" }
+        ]))
+        .unwrap();
+
+        assert_eq!(parse_search_option(&options).as_deref(), Some("One Piece"));
+        assert_eq!(parse_spoilers_option(&options), Some(true));
+    }
+
     #[test]
     fn manga_not_found_returns_content_with_message() {
-        let response = handle_manga(None, None, None, matched_title_preference());
+        let response = handle_manga(None, None, None, matched_title_preference(), false);
 
         assert!(response.is_content(), "expected Content variant");
         assert_eq!(response.unwrap_content(), NOT_FOUND_MANGA);
@@ -227,7 +277,13 @@ mod tests {
 
     #[test]
     fn manga_success_returns_embed() {
-        let response = handle_manga(Some(sample_manga()), None, None, matched_title_preference());
+        let response = handle_manga(
+            Some(sample_manga()),
+            None,
+            None,
+            matched_title_preference(),
+            false,
+        );
 
         assert!(
             response.is_embed(),
@@ -238,7 +294,13 @@ mod tests {
 
     #[test]
     fn manga_success_with_no_guild_data_still_returns_embed() {
-        let response = handle_manga(Some(sample_manga()), None, None, matched_title_preference());
+        let response = handle_manga(
+            Some(sample_manga()),
+            None,
+            None,
+            matched_title_preference(),
+            false,
+        );
 
         assert!(response.is_embed());
     }
@@ -1,125 +1,39 @@
-pub const FETCH_MANGA_BY_ID: &str = "
-query ($id: Int) {
-  Media (id: $id, type: MANGA) {
-    type
-    id
-    idMal
-    isAdult
-    title {
-      romaji
-      english
-      native
-    }
-    synonyms
-		startDate {
-		  year
-		  month
-		  day
-		}
-    endDate {
-      year
-      month
-      day
-    }
-    format
-    status
-    chapters
-    volumes
-    genres
-    source
-    coverImage {
-      extraLarge
-      large
-      medium
-      color
-    }
-    averageScore
-    staff {
-      edges {
-        id
-        role
-      }
-      nodes {
-        id
-        name {
-          full
-        }
-        siteUrl
-      }
-    }
-    siteUrl
-    description
-    tags {
-      name
-    }
-  }
-}
-";
+use std::sync::LazyLock;
+
+use crate::utils::requests::{
+    batch::IdBatcher,
+    fragments::{cover_image, fuzzy_date, media_core, media_search, tags},
+    query::Selection,
+};
 
-pub const FETCH_MANGA: &str = "
-query ($page: Int, $perPage: Int, $search: String) {
-  Page(page: $page, perPage: $perPage) {
-    pageInfo {
-      total
-      currentPage
-      lastPage
-      hasNextPage
-      perPage
-    }
-    media(search: $search) {
-      type
-      id
-      idMal
-      isAdult
-      title {
-        romaji
-        english
-        native
-      }
-      synonyms
-      startDate {
-        year
-        month
-        day
-      }
-      endDate {
-        year
-        month
-        day
-      }
-      format
-      status
-      chapters
-      volumes
-      genres
-      source
-      coverImage {
-        extraLarge
-        large
-        medium
-        color
-      }
-      averageScore
-      staff {
-        edges {
-          id
-          role
-        }
-        nodes {
-          id
-          name {
-            full
-          }
-          siteUrl
-        }
-      }
-      siteUrl
-      description
-      tags {
-        name
-      }
-    }
-  }
+fn manga_fields() -> Selection {
+    Selection::new()
+        .spread(&media_core())
+        .spread(&cover_image())
+        .spread(&tags())
+        .field("idMal")
+        .object("startDate", fuzzy_date())
+        .object("endDate", fuzzy_date())
+        .fields(&["chapters", "volumes", "source"])
+        .object(
+            "staff",
+            Selection::new()
+                .object("edges", Selection::new().fields(&["id", "role"]))
+                .object(
+                    "nodes",
+                    Selection::new()
+                        .field("id")
+                        .object("name", Selection::new().field("full"))
+                        .field("siteUrl"),
+                ),
+        )
+        .field("description")
 }
 
-";
+/// Batched `Media(id: …, type: MANGA)` lookups.
+pub static MANGA_BY_ID: LazyLock<IdBatcher> =
+    LazyLock::new(|| IdBatcher::new("Media", &[("type", "MANGA")], manga_fields()));
+
+pub fn fetch_manga() -> String {
+    media_search("MANGA", manga_fields()).render()
+}
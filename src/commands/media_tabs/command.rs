@@ -0,0 +1,932 @@
+//! Tab buttons on `/anime` and `/manga` replies. The overview tab is the
+//! usual media embed; the stats, relations and staff tabs are fetched the
+//! first time someone opens them and cached alongside the media.
+
+use std::collections::HashMap;
+
+use crate::{
+    commands::{
+        anime::command::handle_anime,
+        guild_scores::view_all_components,
+        manga::command::handle_manga,
+        media_tabs::queries::{fetch_relations, fetch_staff, fetch_stats},
+        response::CommandResponse,
+        traits::{AniListSource, MediaDataSource},
+    },
+    models::{
+        anilist_common::TitleVariant,
+        anilist_media_details::{
+            CharacterEdge, MediaDetails, MediaDetailsData, MediaRank, Person, ScoreDistribution,
+            StaffEdge,
+        },
+        media_type::MediaType,
+        settings::{StreamingRegion, TitleDisplayPreference},
+        transformers::Transformers,
+        user_media_list::MediaListData,
+    },
+    utils::{
+        cache::{Cache, get_cache_from_context, ttl_for_media_status},
+        channel::is_nsfw_channel,
+        formatter::{bold, format_count, linker, remove_underscores_and_titlecase, titlecase},
+        guild::get_guild_data_for_media,
+        interaction::anilist_type,
+        privacy::configure_sentry_scope,
+        requests::{
+            anilist::send_request,
+            graphql::{AniListError, check_response, parse_response},
+        },
+        settings::{resolve_streaming_region, resolve_title_display_preference},
+        statics::{ANILIST_LOOKUP_FAILED, EMPTY_STR, NOT_FOUND_ANIME, NOT_FOUND_MANGA},
+    },
+};
+
+use serde_json::json;
+use serenity::{
+    all::{
+        ButtonStyle, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed,
+        CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+        EditInteractionResponse, GuildId,
+    },
+    client::Context,
+};
+use tracing::{error, instrument, warn};
+
+const MEDIA_TABS_COMPONENT_PREFIX: &str = "media_tabs";
+const MEDIA_TABS_COMPONENT_ID_PREFIX: &str = "media_tabs:";
+const SPOILERS_SHOWN: &str = "show";
+const SPOILERS_HIDDEN: &str = "hide";
+const NO_TITLE_VARIANT: &str = "none";
+
+/// Rankings listed on the stats tab.
+const MAX_RANKINGS: usize = 6;
+/// Width, in blocks, of the longest score distribution bar.
+const SCORE_BAR_WIDTH: u32 = 12;
+/// Related titles listed before summarising the rest.
+const MAX_RELATIONS: usize = 20;
+const DISCORD_EMBED_FIELD_LIMIT: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaTab {
+    Overview,
+    Stats,
+    Relations,
+    Staff,
+}
+
+impl MediaTab {
+    pub const ALL: [Self; 4] = [Self::Overview, Self::Stats, Self::Relations, Self::Staff];
+
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "overview" => Some(Self::Overview),
+            "stats" => Some(Self::Stats),
+            "relations" => Some(Self::Relations),
+            "staff" => Some(Self::Staff),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Overview => "overview",
+            Self::Stats => "stats",
+            Self::Relations => "relations",
+            Self::Staff => "staff",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Overview => "Overview",
+            Self::Stats => "Stats",
+            Self::Relations => "Relations",
+            Self::Staff => "Staff",
+        }
+    }
+}
+
+/// Which tab of which media to show, plus the title match and spoiler choice
+/// the original reply was rendered with. Title language and streaming region
+/// are not kept: each tab is rendered with the settings of whoever clicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaTabsView {
+    pub tab: MediaTab,
+    pub media_type: &'static str,
+    pub media_id: u32,
+    pub title_variant: Option<TitleVariant>,
+    pub allow_spoilers: bool,
+}
+
+impl MediaTabsView {
+    /// The overview tab, as first sent by `/anime` or `/manga`.
+    pub fn overview<T: Transformers>(
+        media: &T,
+        title_variant: Option<TitleVariant>,
+        allow_spoilers: bool,
+    ) -> Option<Self> {
+        Some(Self {
+            tab: MediaTab::Overview,
+            media_type: parse_media_type(media.get_type())?,
+            media_id: media.get_id(),
+            title_variant,
+            allow_spoilers,
+        })
+    }
+}
+
+#[instrument(name = "command.media_tabs.is_component")]
+pub fn is_media_tabs_component(custom_id: &str) -> bool {
+    custom_id.starts_with(MEDIA_TABS_COMPONENT_ID_PREFIX)
+}
+
+#[instrument(name = "command.media_tabs.custom_id", skip(view), fields(tab = %view.tab.as_str()))]
+pub fn media_tabs_custom_id(view: MediaTabsView) -> String {
+    format!(
+        "{MEDIA_TABS_COMPONENT_PREFIX}:{}:{}:{}:{}:{}",
+        view.tab.as_str(),
+        view.media_type,
+        view.media_id,
+        title_variant_as_str(view.title_variant),
+        if view.allow_spoilers {
+            SPOILERS_SHOWN
+        } else {
+            SPOILERS_HIDDEN
+        }
+    )
+}
+
+#[instrument(name = "command.media_tabs.parse_component_id")]
+pub fn parse_media_tabs_component_id(custom_id: &str) -> Option<MediaTabsView> {
+    let parts = custom_id.split(':').collect::<Vec<_>>();
+
+    match parts.as_slice() {
+        [
+            MEDIA_TABS_COMPONENT_PREFIX,
+            raw_tab,
+            raw_media_type,
+            raw_media_id,
+            raw_title_variant,
+            raw_spoilers,
+        ] => Some(MediaTabsView {
+            tab: MediaTab::parse(raw_tab)?,
+            media_type: parse_media_type(raw_media_type)?,
+            media_id: raw_media_id.parse().ok()?,
+            title_variant: parse_title_variant(raw_title_variant)?,
+            allow_spoilers: match *raw_spoilers {
+                SPOILERS_SHOWN => true,
+                SPOILERS_HIDDEN => false,
+                _ => return None,
+            },
+        }),
+        _ => None,
+    }
+}
+
+#[instrument(name = "command.media_tabs.parse_media_type")]
+fn parse_media_type(raw: &str) -> Option<&'static str> {
+    match raw {
+        "anime" => Some("anime"),
+        "manga" => Some("manga"),
+        _ => None,
+    }
+}
+
+fn title_variant_as_str(title_variant: Option<TitleVariant>) -> &'static str {
+    match title_variant {
+        Some(TitleVariant::English) => "english",
+        Some(TitleVariant::Romaji) => "romaji",
+        Some(TitleVariant::Native) => "native",
+        None => NO_TITLE_VARIANT,
+    }
+}
+
+/// `Some(None)` for a reply that matched no particular variant; `None` for
+/// an unrecognised value.
+fn parse_title_variant(raw: &str) -> Option<Option<TitleVariant>> {
+    match raw {
+        "english" => Some(Some(TitleVariant::English)),
+        "romaji" => Some(Some(TitleVariant::Romaji)),
+        "native" => Some(Some(TitleVariant::Native)),
+        NO_TITLE_VARIANT => Some(None),
+        _ => None,
+    }
+}
+
+/// One button per tab, with the open tab disabled.
+#[instrument(name = "command.media_tabs.components", skip(view))]
+pub fn media_tab_components(view: MediaTabsView) -> Vec<CreateActionRow> {
+    let buttons = MediaTab::ALL
+        .into_iter()
+        .map(|tab| {
+            let is_active = tab == view.tab;
+            let style = if is_active {
+                ButtonStyle::Secondary
+            } else {
+                ButtonStyle::Primary
+            };
+
+            CreateButton::new(media_tabs_custom_id(MediaTabsView { tab, ..view }))
+                .label(tab.label())
+                .style(style)
+                .disabled(is_active)
+        })
+        .collect();
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+#[instrument(name = "command.media_tabs.tab_media_type")]
+fn tab_media_type(media_type: &str) -> MediaType {
+    if media_type == "manga" {
+        MediaType::Manga
+    } else {
+        MediaType::Anime
+    }
+}
+
+/// The fields behind `tab`, from the cache when someone has opened it before.
+#[instrument(name = "command.media_tabs.fetch_details", skip(cache))]
+async fn fetch_media_details(
+    cache: &dyn Cache,
+    view: MediaTabsView,
+) -> Result<Option<MediaDetails>, AniListError> {
+    let query = match view.tab {
+        MediaTab::Stats => fetch_stats(anilist_type(&tab_media_type(view.media_type))),
+        MediaTab::Relations => fetch_relations(anilist_type(&tab_media_type(view.media_type))),
+        MediaTab::Staff => fetch_staff(anilist_type(&tab_media_type(view.media_type))),
+        // The overview is the regular media embed, cached by its own fetcher.
+        MediaTab::Overview => return Ok(None),
+    };
+    let cache_key = format!(
+        "{MEDIA_TABS_COMPONENT_PREFIX}:{}:{}:{}",
+        view.tab.as_str(),
+        view.media_type,
+        view.media_id
+    );
+
+    if let Some(cached_value) = cache.get(&cache_key).await {
+        match parse_response::<MediaDetailsData>(&cached_value) {
+            Ok(data) => return Ok(data.and_then(|data| data.media)),
+            Err(err) => warn!(error = %err, cache_key, "Ignoring unreadable cached media tab"),
+        }
+    }
+
+    let json = json!({ "query": query, "variables": { "id": view.media_id } });
+    let body = check_response(send_request(json).await)?;
+    let details = parse_response::<MediaDetailsData>(&body)?.and_then(|data| data.media);
+    if let Some(details) = &details {
+        let ttl = ttl_for_media_status(Some(details.media.status_text()));
+        cache.set(&cache_key, &body, ttl).await;
+    }
+
+    Ok(details)
+}
+
+// ── Core logic (transport-agnostic) ─────────────────────────────────────
+
+/// Render the stats, relations or staff tab from already-fetched details.
+///
+/// Adult related titles are left out unless `allow_adult_media` is set.
+pub fn handle_media_tab(
+    details: Option<&MediaDetails>,
+    view: MediaTabsView,
+    title_preference: TitleDisplayPreference,
+    allow_adult_media: bool,
+) -> CommandResponse {
+    let Some(details) = details else {
+        let not_found = if view.media_type == "manga" {
+            NOT_FOUND_MANGA
+        } else {
+            NOT_FOUND_ANIME
+        };
+        return CommandResponse::Content(not_found.to_string());
+    };
+
+    let embed = tab_embed(details, view, title_preference);
+    let embed = match view.tab {
+        MediaTab::Stats => stats_embed(details, embed),
+        MediaTab::Relations => relations_embed(
+            details,
+            embed,
+            view.title_variant,
+            title_preference,
+            allow_adult_media,
+        ),
+        MediaTab::Staff => staff_embed(details, embed),
+        MediaTab::Overview => embed,
+    };
+
+    CommandResponse::Embed(Box::new(embed))
+}
+
+/// Colour, title, link and cover shared by every tab.
+fn tab_embed(
+    details: &MediaDetails,
+    view: MediaTabsView,
+    title_preference: TitleDisplayPreference,
+) -> CreateEmbed {
+    let color = details
+        .color()
+        .and_then(|color| i32::from_str_radix(color.trim_start_matches('#'), 16).ok())
+        .unwrap_or(0x0000ff);
+    let embed = CreateEmbed::new()
+        .color(color)
+        .title(
+            details
+                .media
+                .display_title(view.title_variant, title_preference),
+        )
+        .url(details.media.site_url())
+        .footer(CreateEmbedFooter::new(format!(
+            "{} • {}",
+            titlecase(view.media_type),
+            view.tab.label()
+        )));
+
+    match details.thumbnail() {
+        Some(thumbnail) => embed.thumbnail(thumbnail),
+        None => embed,
+    }
+}
+
+fn stats_embed(details: &MediaDetails, embed: CreateEmbed) -> CreateEmbed {
+    let count = |count: Option<u32>| count.map_or_else(|| EMPTY_STR.to_string(), format_count);
+    let score = details
+        .media
+        .average_score()
+        .map_or_else(|| EMPTY_STR.to_string(), |score| format!("{score}/100"));
+    let statuses = details
+        .status_distribution()
+        .iter()
+        .map(|status| {
+            format!(
+                "{}: {}",
+                remove_underscores_and_titlecase(&status.status),
+                format_count(status.amount)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    embed
+        .fields(vec![
+            ("Average Score", score, true),
+            ("Popularity", count(details.popularity), true),
+            ("Favourites", count(details.favourites), true),
+        ])
+        .field("Rankings", format_rankings(details.rankings()), false)
+        .field(
+            "Score Distribution",
+            format_score_distribution(&details.score_distribution()),
+            false,
+        )
+        .field("Status Distribution", or_empty(statuses.join("\n")), false)
+}
+
+/// e.g. `#3 Highest Rated Fall 2023`.
+fn format_rankings<'a>(rankings: impl Iterator<Item = &'a MediaRank>) -> String {
+    let lines = rankings
+        .take(MAX_RANKINGS)
+        .map(|ranking| {
+            let mut label = titlecase(&ranking.context);
+            if !ranking.all_time.unwrap_or(false) {
+                if let Some(season) = &ranking.season {
+                    label = format!("{label} {}", titlecase(&season.to_lowercase()));
+                }
+                if let Some(year) = ranking.year {
+                    label = format!("{label} {year}");
+                }
+            }
+            format!("{} {label}", bold(&format!("#{}", ranking.rank)))
+        })
+        .collect::<Vec<_>>();
+
+    or_empty(lines.join("\n"))
+}
+
+/// A bar chart of how users scored the media, longest bar for the most
+/// common score.
+fn format_score_distribution(scores: &[ScoreDistribution]) -> String {
+    let Some(most) = scores.iter().map(|bucket| bucket.amount).max() else {
+        return EMPTY_STR.to_string();
+    };
+    if most == 0 {
+        return EMPTY_STR.to_string();
+    }
+
+    let lines = scores
+        .iter()
+        .map(|bucket| {
+            let width = (bucket.amount * SCORE_BAR_WIDTH).div_ceil(most) as usize;
+            format!(
+                "{:>3} {:<bar_width$} {}",
+                bucket.score,
+                "█".repeat(width),
+                format_count(bucket.amount),
+                bar_width = SCORE_BAR_WIDTH as usize
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!("```\n{}\n```", lines.join("\n"))
+}
+
+fn relations_embed(
+    details: &MediaDetails,
+    embed: CreateEmbed,
+    title_variant: Option<TitleVariant>,
+    title_preference: TitleDisplayPreference,
+    allow_adult_media: bool,
+) -> CreateEmbed {
+    let related = details
+        .relations()
+        .filter_map(|edge| Some((edge.relation_type.as_deref(), edge.node.as_ref()?)))
+        .filter(|(_, media)| allow_adult_media || !media.is_adult())
+        .collect::<Vec<_>>();
+    if related.is_empty() {
+        return embed.description("AniList doesn't list any related titles.");
+    }
+
+    let mut lines = related
+        .iter()
+        .take(MAX_RELATIONS)
+        .map(|(relation, media)| {
+            let relation =
+                relation.map_or_else(|| "Related".to_string(), remove_underscores_and_titlecase);
+            let title = media.display_title(title_variant, title_preference);
+            format!(
+                "{} · {} ({})",
+                bold(&relation),
+                linker(&title, media.site_url()),
+                remove_underscores_and_titlecase(media.format_text())
+            )
+        })
+        .collect::<Vec<_>>();
+    if related.len() > MAX_RELATIONS {
+        lines.push(format!("…and {} more", related.len() - MAX_RELATIONS));
+    }
+
+    embed.description(lines.join("\n"))
+}
+
+fn staff_embed(details: &MediaDetails, embed: CreateEmbed) -> CreateEmbed {
+    let characters = details
+        .characters()
+        .filter_map(format_character)
+        .collect::<Vec<_>>();
+    let staff = details.staff().filter_map(format_staff).collect::<Vec<_>>();
+
+    embed
+        .field("Characters", join_within_field(&characters), false)
+        .field("Staff", join_within_field(&staff), false)
+}
+
+fn format_person(person: &Person) -> Option<String> {
+    let name = person.full_name()?;
+    Some(match &person.site_url {
+        Some(url) => linker(name, url),
+        None => name.to_string(),
+    })
+}
+
+/// e.g. `[Monkey D. Luffy](…) • Main — Mayumi Tanaka`.
+fn format_character(edge: &CharacterEdge) -> Option<String> {
+    let mut line = format_person(edge.node.as_ref()?)?;
+    if let Some(role) = &edge.role {
+        line = format!("{line} • {}", titlecase(&role.to_lowercase()));
+    }
+    if let Some(voice_actor) = edge.voice_actor().and_then(format_person) {
+        line = format!("{line} — {voice_actor}");
+    }
+    Some(line)
+}
+
+fn format_staff(edge: &StaffEdge) -> Option<String> {
+    let person = format_person(edge.node.as_ref()?)?;
+    Some(match &edge.role {
+        Some(role) => format!("{person} • {role}"),
+        None => person,
+    })
+}
+
+/// As many whole lines as fit in one embed field.
+fn join_within_field(lines: &[String]) -> String {
+    let mut joined = String::default();
+    for line in lines {
+        let separator = if joined.is_empty() { 0 } else { 1 };
+        if joined.chars().count() + separator + line.chars().count() > DISCORD_EMBED_FIELD_LIMIT {
+            break;
+        }
+        if separator == 1 {
+            joined.push('\n');
+        }
+        joined.push_str(line);
+    }
+    or_empty(joined)
+}
+
+fn or_empty(text: String) -> String {
+    if text.is_empty() {
+        EMPTY_STR.to_string()
+    } else {
+        text
+    }
+}
+
+// ── Serenity adapter (thin wrapper) ─────────────────────────────────────
+
+#[instrument(name = "command.media_tabs.handle_component", skip(ctx, interaction))]
+pub async fn handle_component(ctx: &Context, interaction: &mut ComponentInteraction) {
+    configure_sentry_scope("MediaTabs", interaction.user.id.get(), None);
+
+    let Some(view) = parse_media_tabs_component_id(&interaction.data.custom_id) else {
+        let builder = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content("I don't recognize that control. Please run the command again.")
+                .ephemeral(true),
+        );
+        let _ = interaction.create_response(&ctx.http, builder).await;
+        return;
+    };
+
+    if let Err(error) = interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        warn!(
+            error = %error,
+            custom_id = %interaction.data.custom_id,
+            "Failed to acknowledge media tabs component interaction"
+        );
+        return;
+    }
+
+    let title_preference =
+        resolve_title_display_preference(ctx, interaction.user.id, interaction.guild_id).await;
+    let mut components = media_tab_components(view);
+    let response = match view.tab {
+        MediaTab::Overview => {
+            let streaming_region =
+                resolve_streaming_region(ctx, interaction.user.id, interaction.guild_id).await;
+            let (response, has_guild_scores) = overview_response(
+                ctx,
+                interaction.guild_id,
+                view,
+                title_preference,
+                streaming_region,
+            )
+            .await;
+            if has_guild_scores {
+                components.extend(view_all_components(view.media_type, view.media_id));
+            }
+            response
+        }
+        _ => {
+            let cache = get_cache_from_context(ctx).await;
+            match fetch_media_details(cache.as_ref(), view).await {
+                Ok(details) => {
+                    let allow_adult_media = view.tab == MediaTab::Relations
+                        && is_nsfw_channel(ctx, interaction.channel_id, interaction.guild_id).await;
+                    handle_media_tab(details.as_ref(), view, title_preference, allow_adult_media)
+                }
+                Err(err) => {
+                    error!(error = %err, tab = view.tab.as_str(), "Failed to fetch media tab");
+                    CommandResponse::Content(
+                        err.user_message()
+                            .unwrap_or(
+                                "I couldn't load that tab right now. Please try again later.",
+                            )
+                            .to_string(),
+                    )
+                }
+            }
+        }
+    };
+
+    // Keep the tabs on error replies too, so the overview is one click away.
+    let builder = match response {
+        CommandResponse::Content(text) | CommandResponse::Message(text) => {
+            EditInteractionResponse::new()
+                .content(text)
+                .embeds(Vec::new())
+        }
+        CommandResponse::Embed(embed) => EditInteractionResponse::new().content("").embed(*embed),
+    };
+    let _ = interaction
+        .edit_response(&ctx.http, builder.components(components))
+        .await;
+}
+
+/// The regular `/anime` or `/manga` embed, and whether it shows guild scores.
+#[instrument(name = "command.media_tabs.overview", skip(ctx))]
+async fn overview_response(
+    ctx: &Context,
+    guild_id: Option<GuildId>,
+    view: MediaTabsView,
+    title_preference: TitleDisplayPreference,
+    streaming_region: StreamingRegion,
+) -> (CommandResponse, bool) {
+    let source = AniListSource::from_context(ctx).await;
+    let search_term = view.media_id.to_string();
+
+    let (response, has_guild_scores) = if view.media_type == "manga" {
+        let manga = match source.fetch_manga(&search_term).await {
+            Ok(manga) => manga.map(|(manga, _)| manga),
+            Err(err) => return (lookup_failed(&err), false),
+        };
+        let guild_members_data = guild_members_data(ctx, manga.as_ref(), guild_id).await;
+        let has_guild_scores = guild_members_data.is_some();
+        (
+            handle_manga(
+                manga,
+                guild_members_data,
+                view.title_variant,
+                title_preference,
+                view.allow_spoilers,
+            ),
+            has_guild_scores,
+        )
+    } else {
+        let anime = match source.fetch_anime(&search_term).await {
+            Ok(anime) => anime.map(|(anime, _)| anime),
+            Err(err) => return (lookup_failed(&err), false),
+        };
+        let guild_members_data = guild_members_data(ctx, anime.as_ref(), guild_id).await;
+        let has_guild_scores = guild_members_data.is_some();
+        (
+            handle_anime(
+                anime,
+                guild_members_data,
+                view.title_variant,
+                title_preference,
+                view.allow_spoilers,
+                streaming_region,
+            ),
+            has_guild_scores,
+        )
+    };
+
+    (response, has_guild_scores)
+}
+
+#[instrument(name = "command.media_tabs.lookup_failed")]
+fn lookup_failed(err: &AniListError) -> CommandResponse {
+    error!(error = %err, "Failed to fetch media for the overview tab");
+    CommandResponse::Content(
+        err.user_message()
+            .unwrap_or(ANILIST_LOOKUP_FAILED)
+            .to_string(),
+    )
+}
+
+async fn guild_members_data<T: Transformers>(
+    ctx: &Context,
+    media: Option<&T>,
+    guild_id: Option<GuildId>,
+) -> Option<HashMap<u64, MediaListData>> {
+    let data = get_guild_data_for_media(ctx, media?, guild_id).await;
+    if data.is_empty() { None } else { Some(data) }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cache::InMemoryCache;
+
+    fn view(tab: MediaTab) -> MediaTabsView {
+        MediaTabsView {
+            tab,
+            media_type: "anime",
+            media_id: 21,
+            title_variant: Some(TitleVariant::English),
+            allow_spoilers: false,
+        }
+    }
+
+    fn sample_details(extra: serde_json::Value) -> MediaDetails {
+        let mut media = json!({
+            "type": "ANIME",
+            "id": 21,
+            "isAdult": false,
+            "title": { "romaji": "One Piece", "english": "One Piece", "native": "ワンピース" },
+            "format": "TV",
+            "status": "RELEASING",
+            "genres": ["Action"],
+            "averageScore": 88,
+            "siteUrl": "https://anilist.co/anime/21",
+            "coverImage": { "extraLarge": null, "large": "https://example.com/large.jpg", "medium": null, "color": "#e4a15d" }
+        });
+        for (key, value) in extra.as_object().expect("extra fields should be an object") {
+            media[key] = value.clone();
+        }
+        serde_json::from_value(media).expect("details should deserialize")
+    }
+
+    fn embed_json(response: CommandResponse) -> serde_json::Value {
+        serde_json::to_value(response.unwrap_embed()).expect("embed serializes")
+    }
+
+    #[test]
+    fn custom_ids_round_trip() {
+        for tab in MediaTab::ALL {
+            let view = MediaTabsView {
+                allow_spoilers: true,
+                title_variant: None,
+                ..view(tab)
+            };
+            let custom_id = media_tabs_custom_id(view);
+
+            assert!(is_media_tabs_component(&custom_id));
+            assert_eq!(parse_media_tabs_component_id(&custom_id), Some(view));
+        }
+        assert_eq!(
+            media_tabs_custom_id(view(MediaTab::Stats)),
+            "media_tabs:stats:anime:21:english:hide"
+        );
+        assert_eq!(
+            parse_media_tabs_component_id("media_tabs:stats:novel:21:english:hide"),
+            None
+        );
+        assert_eq!(
+            parse_media_tabs_component_id("media_tabs:stats:anime:21:english"),
+            None
+        );
+    }
+
+    #[test]
+    fn tab_row_disables_the_open_tab() {
+        let rows = serde_json::to_value(media_tab_components(view(MediaTab::Relations)))
+            .expect("components serialize");
+        let buttons = rows[0]["components"].as_array().unwrap();
+
+        assert_eq!(buttons.len(), 4);
+        assert_eq!(buttons[2]["label"], "Relations");
+        assert_eq!(buttons[2]["disabled"], true);
+        assert_eq!(buttons[0]["disabled"], false);
+        assert_eq!(
+            buttons[0]["custom_id"],
+            "media_tabs:overview:anime:21:english:hide"
+        );
+    }
+
+    #[test]
+    fn stats_tab_shows_counts_rankings_and_distributions() {
+        let details = sample_details(json!({
+            "popularity": 563218,
+            "favourites": 81234,
+            "rankings": [
+                { "rank": 3, "type": "RATED", "allTime": false, "context": "highest rated", "season": "FALL", "year": 2023 },
+                { "rank": 12, "type": "POPULAR", "allTime": true, "context": "most popular all time", "season": null, "year": null }
+            ],
+            "stats": {
+                "scoreDistribution": [
+                    { "score": 100, "amount": 4000 },
+                    { "score": 50, "amount": 1000 }
+                ],
+                "statusDistribution": [
+                    { "status": "PLANNING", "amount": 1200 },
+                    { "status": "CURRENT", "amount": 300000 }
+                ]
+            }
+        }));
+
+        let embed = embed_json(handle_media_tab(
+            Some(&details),
+            view(MediaTab::Stats),
+            TitleDisplayPreference::Matched,
+            false,
+        ));
+        let fields = embed["fields"].as_array().unwrap();
+
+        assert_eq!(embed["title"], "One Piece");
+        assert_eq!(embed["footer"]["text"], "Anime • Stats");
+        assert_eq!(fields[1]["value"], "563,218");
+        assert_eq!(fields[2]["value"], "81,234");
+        assert_eq!(
+            fields[3]["value"],
+            "**#3** Highest Rated Fall 2023\n**#12** Most Popular All Time"
+        );
+        assert_eq!(
+            fields[4]["value"],
+            "```\n 50 ███          1,000\n100 ████████████ 4,000\n```"
+        );
+        assert_eq!(fields[5]["value"], "Current: 300,000\nPlanning: 1,200");
+    }
+
+    #[test]
+    fn relations_tab_lists_related_titles_and_hides_adult_ones() {
+        let related = |id: u32, relation: &str, is_adult: bool| {
+            json!({
+                "relationType": relation,
+                "node": {
+                    "type": "ANIME",
+                    "id": id,
+                    "isAdult": is_adult,
+                    "title": { "romaji": format!("Related {id}"), "english": null, "native": null },
+                    "format": "MOVIE",
+                    "status": "FINISHED",
+                    "genres": [],
+                    "averageScore": null,
+                    "siteUrl": format!("https://anilist.co/anime/{id}")
+                }
+            })
+        };
+        let details = sample_details(json!({
+            "relations": { "edges": [
+                related(1, "SIDE_STORY", false),
+                null,
+                related(2, "ALTERNATIVE", true)
+            ] }
+        }));
+
+        let embed = embed_json(handle_media_tab(
+            Some(&details),
+            view(MediaTab::Relations),
+            TitleDisplayPreference::Matched,
+            false,
+        ));
+
+        assert_eq!(
+            embed["description"],
+            "**Side Story** · [Related 1](https://anilist.co/anime/1) (Movie)"
+        );
+
+        let empty = sample_details(json!({ "relations": { "edges": [] } }));
+        let embed = embed_json(handle_media_tab(
+            Some(&empty),
+            view(MediaTab::Relations),
+            TitleDisplayPreference::Matched,
+            false,
+        ));
+        assert_eq!(
+            embed["description"],
+            "AniList doesn't list any related titles."
+        );
+    }
+
+    #[test]
+    fn staff_tab_lists_characters_with_voice_actors_and_staff_roles() {
+        let details = sample_details(json!({
+            "characters": { "edges": [{
+                "role": "MAIN",
+                "node": { "name": { "full": "Monkey D. Luffy" }, "siteUrl": "https://anilist.co/character/40" },
+                "voiceActors": [{ "name": { "full": "Mayumi Tanaka" }, "siteUrl": null }]
+            }] },
+            "staff": { "edges": [
+                { "role": "Original Creator", "node": { "name": { "full": "Eiichiro Oda" }, "siteUrl": null } },
+                { "role": "Director", "node": { "name": { "full": "" }, "siteUrl": null } }
+            ] }
+        }));
+
+        let embed = embed_json(handle_media_tab(
+            Some(&details),
+            view(MediaTab::Staff),
+            TitleDisplayPreference::Matched,
+            false,
+        ));
+        let fields = embed["fields"].as_array().unwrap();
+
+        assert_eq!(
+            fields[0]["value"],
+            "[Monkey D. Luffy](https://anilist.co/character/40) • Main — Mayumi Tanaka"
+        );
+        assert_eq!(fields[1]["value"], "Eiichiro Oda • Original Creator");
+    }
+
+    #[test]
+    fn long_credit_lists_stop_at_whole_lines() {
+        let lines = vec!["x".repeat(600), "y".repeat(600)];
+
+        assert_eq!(join_within_field(&lines), "x".repeat(600));
+        assert_eq!(join_within_field(&[]), EMPTY_STR);
+    }
+
+    #[test]
+    fn missing_media_is_not_found() {
+        let response = handle_media_tab(
+            None,
+            view(MediaTab::Stats),
+            TitleDisplayPreference::Matched,
+            false,
+        );
+
+        assert_eq!(response.unwrap_content(), NOT_FOUND_ANIME);
+    }
+
+    #[tokio::test]
+    async fn cached_tabs_are_served_without_querying_anilist() {
+        let cache = InMemoryCache::with_entry(
+            "media_tabs:staff:anime:21",
+            r#"{"data":{"Media":{"type":"ANIME","id":21,"isAdult":false,"title":{"romaji":"One Piece","english":null,"native":null},"format":"TV","status":"RELEASING","genres":[],"averageScore":null,"siteUrl":"https://anilist.co/anime/21","staff":{"edges":[]}}}}"#,
+        );
+
+        let details = fetch_media_details(&cache, view(MediaTab::Staff))
+            .await
+            .expect("cached tab should parse")
+            .expect("media should be present");
+
+        assert_eq!(details.staff().count(), 0);
+        assert_eq!(details.media.id(), Some(21));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod command;
+pub mod queries;
+
+pub use command::{MediaTabsView, handle_component, is_media_tabs_component, media_tab_components};
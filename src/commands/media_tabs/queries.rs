@@ -0,0 +1,99 @@
+use crate::utils::requests::{
+    fragments::{cover_image, media_by_id, media_summary},
+    query::{Field, Selection},
+};
+
+use tracing::instrument;
+
+/// Characters and staff listed on the staff tab.
+pub const CREDITS_PER_TAB: u32 = 10;
+
+fn tab_header() -> Selection {
+    media_summary().spread(&cover_image())
+}
+
+fn stats_fields() -> Selection {
+    tab_header()
+        .fields(&["popularity", "favourites"])
+        .object(
+            "rankings",
+            Selection::new().fields(&["rank", "allTime", "context", "season", "year"]),
+        )
+        .object(
+            "stats",
+            Selection::new()
+                .object(
+                    "scoreDistribution",
+                    Selection::new().fields(&["score", "amount"]),
+                )
+                .object(
+                    "statusDistribution",
+                    Selection::new().fields(&["status", "amount"]),
+                ),
+        )
+}
+
+fn relations_fields() -> Selection {
+    tab_header().object(
+        "relations",
+        Selection::new().object(
+            "edges",
+            Selection::new()
+                .field(Field::new("relationType").arg("version", 2))
+                .object("node", media_summary()),
+        ),
+    )
+}
+
+fn person() -> Selection {
+    Selection::new()
+        .object("name", Selection::new().field("full"))
+        .field("siteUrl")
+}
+
+fn staff_fields() -> Selection {
+    tab_header()
+        .field(
+            Field::new("characters")
+                .arg("sort", "[ROLE, RELEVANCE, ID]")
+                .arg("perPage", CREDITS_PER_TAB)
+                .select(
+                    Selection::new().object(
+                        "edges",
+                        Selection::new()
+                            .field("role")
+                            .object("node", person())
+                            .field(
+                                Field::new("voiceActors")
+                                    .arg("language", "JAPANESE")
+                                    .arg("sort", "[RELEVANCE, ID]")
+                                    .select(person()),
+                            ),
+                    ),
+                ),
+        )
+        .field(
+            Field::new("staff")
+                .arg("sort", "[RELEVANCE, ID]")
+                .arg("perPage", CREDITS_PER_TAB)
+                .select(Selection::new().object(
+                    "edges",
+                    Selection::new().field("role").object("node", person()),
+                )),
+        )
+}
+
+#[instrument]
+pub fn fetch_stats(media_type: &str) -> String {
+    media_by_id(media_type, stats_fields()).render()
+}
+
+#[instrument]
+pub fn fetch_relations(media_type: &str) -> String {
+    media_by_id(media_type, relations_fields()).render()
+}
+
+#[instrument]
+pub fn fetch_staff(media_type: &str) -> String {
+    media_by_id(media_type, staff_fields()).render()
+}
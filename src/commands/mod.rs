@@ -1,8 +1,14 @@
 pub mod anime;
+pub mod binge;
 pub mod character;
+pub mod discover;
+pub mod episodes;
+pub mod guild_scores;
 pub mod help;
 pub mod input_validation;
 pub mod manga;
+pub mod media_tabs;
+pub mod next;
 pub mod ping;
 pub mod recommend;
 pub mod register;
@@ -0,0 +1,526 @@
+//! `/next`: picks from a linked user's Planning and Paused lists.
+//!
+//! The list entries carry the media details needed to rank and describe them
+//! (average score, episodes, length, airing status), so this needs no lookups
+//! beyond the user's cached list.
+
+use std::cmp::Reverse;
+
+use crate::{
+    commands::response::CommandResponse,
+    models::{
+        anilist_anime::{format_duration, format_episodes},
+        db::oauth_credential::OAuthCredential,
+        media_type::MediaType,
+        settings::TitleDisplayPreference,
+        user_media_list::{MediaListEntry, MediaListEntryMedia, MediaListStatus},
+    },
+    utils::{
+        cache::get_cache_from_context,
+        channel::is_nsfw_channel,
+        formatter::{format_runtime, linker, remove_underscores_and_titlecase},
+        interaction::{anilist_type, linked_profile, respond},
+        media_list::fetch_user_media_list,
+        privacy::configure_sentry_scope,
+        settings::resolve_title_display_preference,
+        statics::EMPTY_STR,
+    },
+};
+
+use serde_json::json;
+use serenity::{
+    all::{
+        CommandDataOption, CommandDataOptionValue, CommandInteraction, CreateCommandOption,
+        CreateEmbed, CreateEmbedFooter, EditInteractionResponse,
+    },
+    builder::CreateCommand,
+    client::Context,
+    model::application::CommandOptionType,
+};
+use tracing::{error, info, instrument};
+
+const TYPE_OPTION: &str = "type";
+const MAX_HOURS_OPTION: &str = "max_hours";
+const FINISHED_ONLY_OPTION: &str = "finished_only";
+const ANIME_TYPE: &str = "anime";
+const MANGA_TYPE: &str = "manga";
+const NEXT_PICK_LIMIT: usize = 5;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("next")
+        .description("Pick what to watch or read next from your AniList Planning and Paused lists")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, TYPE_OPTION, "Anime or manga")
+                .add_string_choice("Anime", ANIME_TYPE)
+                .add_string_choice("Manga", MANGA_TYPE),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                MAX_HOURS_OPTION,
+                "Only anime you can finish in this many hours",
+            )
+            .min_int_value(1)
+            .max_int_value(500),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            FINISHED_ONLY_OPTION,
+            "Only titles that have finished airing or publishing",
+        ))
+}
+
+/// Optional limits on which entries are suggested.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NextConstraints {
+    /// Longest remaining watch time, in hours. Anime only.
+    pub max_hours: Option<u32>,
+    pub finished_only: bool,
+}
+
+impl NextConstraints {
+    pub fn matches(&self, entry: &MediaListEntry) -> bool {
+        let Some(media) = entry.media.as_ref() else {
+            return false;
+        };
+
+        (!self.finished_only || media.is_finished())
+            && self.max_hours.is_none_or(|max_hours| {
+                entry
+                    .remaining_minutes()
+                    .is_some_and(|minutes| minutes <= max_hours * 60)
+            })
+    }
+
+    /// Short description for the embed footer, e.g. `Under 5h, finished only`.
+    pub fn describe(&self) -> String {
+        [
+            self.max_hours
+                .map(|max_hours| format!("Under {max_hours}h")),
+            self.finished_only.then(|| "finished only".to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NextRequest {
+    pub media_type: MediaType,
+    pub constraints: NextConstraints,
+}
+
+#[instrument(name = "command.next.parse_options", skip(options))]
+fn parse_next_options(options: &[CommandDataOption]) -> NextRequest {
+    let mut request = NextRequest {
+        media_type: MediaType::Anime,
+        constraints: NextConstraints::default(),
+    };
+
+    for option in options {
+        match (option.name.as_str(), &option.value) {
+            (TYPE_OPTION, CommandDataOptionValue::String(value)) if value == MANGA_TYPE => {
+                request.media_type = MediaType::Manga;
+            }
+            (MAX_HOURS_OPTION, CommandDataOptionValue::Integer(value)) => {
+                request.constraints.max_hours = u32::try_from(*value).ok();
+            }
+            (FINISHED_ONLY_OPTION, CommandDataOptionValue::Boolean(value)) => {
+                request.constraints.finished_only = *value;
+            }
+            _ => {}
+        }
+    }
+
+    request
+}
+
+/// Planning and Paused entries that fit `constraints`, best first: highest
+/// AniList average score, then the least left to watch.
+#[instrument(name = "command.next.pick", skip(entries), fields(entry_count = entries.len()))]
+pub fn pick_next<'a>(
+    entries: &'a [MediaListEntry],
+    constraints: &NextConstraints,
+    allow_adult_media: bool,
+) -> Vec<&'a MediaListEntry> {
+    let mut picks = entries
+        .iter()
+        .filter(|entry| {
+            matches!(
+                entry.status,
+                Some(MediaListStatus::Planning | MediaListStatus::Paused)
+            )
+        })
+        .filter(|entry| {
+            entry
+                .media
+                .as_ref()
+                .is_some_and(|media| allow_adult_media || !media.is_adult())
+        })
+        .filter(|entry| constraints.matches(entry))
+        .collect::<Vec<_>>();
+
+    picks.sort_by_key(|entry| {
+        (
+            Reverse(entry.media.as_ref().and_then(|media| media.average_score)),
+            entry.remaining_minutes().unwrap_or(u32::MAX),
+            entry.media_id,
+        )
+    });
+    picks
+}
+
+pub fn handle_next(
+    profile: &OAuthCredential,
+    request: &NextRequest,
+    entries: &[MediaListEntry],
+    title_preference: TitleDisplayPreference,
+    allow_adult_media: bool,
+) -> CommandResponse {
+    let is_anime = request.media_type == MediaType::Anime;
+    if !is_anime && request.constraints.max_hours.is_some() {
+        return CommandResponse::Content(
+            "`max_hours` only works for anime. Try `finished_only` for manga instead.".to_string(),
+        );
+    }
+
+    let noun = if is_anime { "anime" } else { "manga" };
+    let has_candidates = entries.iter().any(|entry| {
+        matches!(
+            entry.status,
+            Some(MediaListStatus::Planning | MediaListStatus::Paused)
+        )
+    });
+    if !has_candidates {
+        return CommandResponse::Content(format!(
+            "Your AniList Planning and Paused {noun} lists are empty. Add something to plan to {} first.",
+            if is_anime { "watch" } else { "read" }
+        ));
+    }
+
+    let picks = pick_next(entries, &request.constraints, allow_adult_media);
+    if picks.is_empty() {
+        return CommandResponse::Content(format!(
+            "Nothing on your Planning or Paused {noun} lists fits those limits. Try loosening them."
+        ));
+    }
+
+    let mut footer = "From your AniList Planning and Paused lists".to_string();
+    if !request.constraints.describe().is_empty() {
+        footer.push_str(&format!(" • {}", request.constraints.describe()));
+    }
+
+    let mut embed = CreateEmbed::new()
+        .title(format!("Up next for {}", profile.anilist_display_name()))
+        .url(profile.anilist_profile_url())
+        .footer(CreateEmbedFooter::new(footer));
+    for (index, entry) in picks.iter().take(NEXT_PICK_LIMIT).enumerate() {
+        let Some(media) = entry.media.as_ref() else {
+            continue;
+        };
+        embed = embed.field(
+            format!("{}. {}", index + 1, media.display_title(title_preference)),
+            format_next_pick(entry, media, is_anime),
+            false,
+        );
+    }
+
+    CommandResponse::Embed(Box::new(embed))
+}
+
+#[instrument(skip(entry, media))]
+fn format_next_pick(entry: &MediaListEntry, media: &MediaListEntryMedia, is_anime: bool) -> String {
+    let unit = if is_anime { "episode" } else { "chapter" };
+    let list_line = match (entry.status, entry.progress) {
+        (Some(MediaListStatus::Paused), Some(progress)) if progress > 0 => {
+            format!("Paused at {unit} {progress}")
+        }
+        (Some(MediaListStatus::Paused), _) => "Paused".to_string(),
+        _ => "On your Planning list".to_string(),
+    };
+
+    let mut summary = Vec::new();
+    if let Some(site_url) = media.site_url.as_deref() {
+        summary.push(linker("AniList", site_url));
+    }
+    let descriptor = [media.format.as_deref(), media.status.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(remove_underscores_and_titlecase)
+        .collect::<Vec<_>>()
+        .join(" / ");
+    if !descriptor.is_empty() {
+        summary.push(descriptor);
+    }
+    if let Some(score) = media.average_score {
+        summary.push(format!("Audience score: {score}/100"));
+    }
+
+    let mut length = Vec::new();
+    if is_anime {
+        let episodes = format_episodes(
+            media.status.as_deref(),
+            media.episodes,
+            media.next_airing_episode.as_ref(),
+        );
+        if episodes != EMPTY_STR {
+            length.push(format!("Episodes: {episodes}"));
+        }
+        let duration = format_duration(media.duration);
+        if duration != EMPTY_STR {
+            length.push(format!("Duration: {duration}"));
+        }
+        if let Some(minutes) = entry.remaining_minutes().filter(|minutes| *minutes > 0) {
+            length.push(format!("About {} left", format_runtime(minutes)));
+        }
+    } else if let Some(chapters) = media.chapters {
+        length.push(format!("Chapters: {chapters}"));
+    }
+
+    [list_line, summary.join(" • "), length.join(" • ")]
+        .into_iter()
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[instrument(name = "command.next.run", skip(ctx, interaction))]
+pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
+    let _ = interaction.defer(&ctx.http).await;
+
+    let request = parse_next_options(&interaction.data.options);
+    let user_id = interaction.user.id;
+    configure_sentry_scope(
+        "Next",
+        user_id.get(),
+        Some(json!({
+            "type": request.media_type.as_ref(),
+            "max_hours": request.constraints.max_hours,
+            "finished_only": request.constraints.finished_only,
+        })),
+    );
+    info!(media_type = ?request.media_type, "Got command 'next'");
+
+    let Some(profile) = linked_profile(
+        ctx,
+        interaction,
+        "Link your AniList account with `/register` so I can read your Planning list.",
+    )
+    .await
+    else {
+        return;
+    };
+
+    let cache = get_cache_from_context(ctx).await;
+    let (entries, title_preference, allow_adult_media) = tokio::join!(
+        fetch_user_media_list(
+            cache.as_ref(),
+            profile.anilist_id,
+            anilist_type(&request.media_type),
+        ),
+        resolve_title_display_preference(ctx, user_id, interaction.guild_id),
+        is_nsfw_channel(ctx, interaction.channel_id, interaction.guild_id),
+    );
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(err) => {
+            error!(error = %err, "Failed to fetch AniList list for /next");
+            respond(
+                ctx,
+                interaction,
+                err.user_message().unwrap_or(
+                    "I couldn't read your AniList list right now. Please try again later.",
+                ),
+            )
+            .await;
+            return;
+        }
+    };
+
+    let builder = match handle_next(
+        &profile,
+        &request,
+        &entries,
+        title_preference,
+        allow_adult_media,
+    ) {
+        CommandResponse::Content(text) | CommandResponse::Message(text) => {
+            EditInteractionResponse::new().content(text)
+        }
+        CommandResponse::Embed(embed) => EditInteractionResponse::new().embed(*embed),
+    };
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        media_id: u32,
+        status: MediaListStatus,
+        progress: u32,
+        media: serde_json::Value,
+    ) -> MediaListEntry {
+        serde_json::from_value(json!({
+            "mediaId": media_id,
+            "status": status,
+            "score": 0,
+            "progress": progress,
+            "media": media,
+        }))
+        .expect("list entry should deserialize")
+    }
+
+    fn anime(
+        title: &str,
+        status: &str,
+        episodes: u32,
+        duration: u32,
+        score: u32,
+    ) -> serde_json::Value {
+        json!({
+            "genres": [],
+            "title": { "romaji": title, "english": null, "native": null },
+            "isAdult": false,
+            "format": "TV",
+            "status": status,
+            "episodes": episodes,
+            "duration": duration,
+            "averageScore": score,
+            "siteUrl": "https://anilist.co/anime/1"
+        })
+    }
+
+    fn profile() -> OAuthCredential {
+        OAuthCredential {
+            discord_user_id: "1".to_string(),
+            anilist_id: 7,
+            anilist_username: Some("AniUser".to_string()),
+        }
+    }
+
+    #[test]
+    fn options_default_to_anime_without_limits() {
+        let options: Vec<CommandDataOption> = serde_json::from_value(json!([
+            { "name": "max_hours", "type": 4, "value": 5 },
+            { "name": "finished_only", "type": 5, "value": true }
+        ]))
+        .expect("options deserialize");
+
+        assert_eq!(
+            parse_next_options(&options),
+            NextRequest {
+                media_type: MediaType::Anime,
+                constraints: NextConstraints {
+                    max_hours: Some(5),
+                    finished_only: true,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn picks_respect_limits_and_rank_by_score() {
+        let entries = vec![
+            entry(
+                1,
+                MediaListStatus::Planning,
+                0,
+                anime("Long", "FINISHED", 50, 24, 90),
+            ),
+            entry(
+                2,
+                MediaListStatus::Paused,
+                10,
+                anime("Paused", "FINISHED", 12, 24, 80),
+            ),
+            entry(
+                3,
+                MediaListStatus::Planning,
+                0,
+                anime("Airing", "RELEASING", 12, 24, 95),
+            ),
+            entry(
+                4,
+                MediaListStatus::Completed,
+                0,
+                anime("Done", "FINISHED", 1, 24, 99),
+            ),
+            entry(
+                5,
+                MediaListStatus::Planning,
+                0,
+                anime("Short", "FINISHED", 3, 24, 85),
+            ),
+        ];
+        let constraints = NextConstraints {
+            max_hours: Some(5),
+            finished_only: true,
+        };
+
+        let picks = pick_next(&entries, &constraints, false)
+            .iter()
+            .map(|entry| entry.media_id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(picks, vec![5, 2]);
+    }
+
+    #[test]
+    fn paused_picks_show_progress_and_time_left() {
+        let entries = vec![entry(
+            2,
+            MediaListStatus::Paused,
+            4,
+            anime("Cowboy Bebop", "FINISHED", 26, 24, 86),
+        )];
+        let request = NextRequest {
+            media_type: MediaType::Anime,
+            constraints: NextConstraints::default(),
+        };
+
+        let response = handle_next(
+            &profile(),
+            &request,
+            &entries,
+            TitleDisplayPreference::Matched,
+            false,
+        );
+
+        let value = serde_json::to_value(response.unwrap_embed()).expect("embed serializes");
+        assert_eq!(value["title"], "Up next for AniUser");
+        assert_eq!(value["fields"][0]["name"], "1. Cowboy Bebop");
+        assert_eq!(
+            value["fields"][0]["value"],
+            "Paused at episode 4\n[AniList](https://anilist.co/anime/1) • TV / Finished • Audience score: 86/100\nEpisodes: 26 • Duration: 24 mins • About 8h 48m left"
+        );
+    }
+
+    #[test]
+    fn hour_limits_are_rejected_for_manga() {
+        let request = NextRequest {
+            media_type: MediaType::Manga,
+            constraints: NextConstraints {
+                max_hours: Some(3),
+                finished_only: false,
+            },
+        };
+
+        let response = handle_next(
+            &profile(),
+            &request,
+            &[],
+            TitleDisplayPreference::Matched,
+            false,
+        );
+
+        assert!(
+            response
+                .unwrap_content()
+                .starts_with("`max_hours` only works for anime.")
+        );
+    }
+}
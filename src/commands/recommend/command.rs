@@ -1,13 +1,22 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::{
     commands::{
         input_validation::validate_search_term,
-        recommend::queries::{fetch_recommendations_by_id, fetch_recommendations_by_search},
+        recommend::{
+            for_me::run_for_me,
+            queries::{
+                ANIME_RECOMMENDATIONS_BY_ID, MANGA_RECOMMENDATIONS_BY_ID,
+                fetch_recommendations_by_search, fetch_recommendations_page,
+            },
+            ranking::{RankedRecommendation, RecommendationFilters, merge_recommendations},
+        },
         response::CommandResponse,
     },
     models::{
         anilist_common::TitleVariant,
         anilist_recommendation::{
-            Recommendation, RecommendationMedia, RecommendationMediaResponse, RecommendedMedia,
+            RecommendationConnection, RecommendationMedia, RecommendationPageData, RecommendedMedia,
         },
         media_response::FetchResponse as SearchResponse,
         media_type::MediaType,
@@ -15,37 +24,131 @@ use crate::{
         transformers::Transformers,
     },
     utils::{
+        cache::{Cache, DEFAULT_CACHE_TTL, SharedCache, get_cache_from_context},
         channel::is_nsfw_channel,
-        fetch_by_arguments::{fetch_by_id, fetch_by_name},
+        fetch_by_arguments::fetch_by_name,
         formatter::{code, linker, remove_underscores_and_titlecase, titlecase},
+        interaction::anilist_type,
         privacy::configure_sentry_scope,
-        redis::{check_cache, try_to_cache_response},
+        requests::{
+            anilist::send_request,
+            batch::IdBatcher,
+            graphql::{AniListError, check_response, parse_response},
+        },
         settings::resolve_title_display_preference,
-        statics::{EMPTY_STR, NOT_FOUND_ANIME, NOT_FOUND_MANGA, NSFW_NOT_ALLOWED},
+        single_flight::ANILIST_FLIGHTS,
+        statics::{
+            ANILIST_LOOKUP_FAILED, EMPTY_STR, NOT_FOUND_ANIME, NOT_FOUND_MANGA, NSFW_NOT_ALLOWED,
+        },
     },
 };
 
-use redis::RedisResult;
 use serde_json::json;
 use serenity::{
     all::{
-        CommandDataOption, CommandDataOptionValue, CommandInteraction, CreateCommandOption,
-        CreateEmbed, CreateEmbedFooter, EditInteractionResponse,
+        ButtonStyle, CommandDataOption, CommandDataOptionValue, CommandInteraction,
+        ComponentInteraction, CreateActionRow, CreateButton, CreateCommandOption, CreateEmbed,
+        CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+        EditInteractionResponse,
     },
     builder::CreateCommand,
     client::Context,
     model::application::CommandOptionType,
 };
-use tokio::task;
-use tracing::{error, info, instrument};
+use tokio::task::JoinSet;
+use tracing::{Instrument, error, info, instrument, warn};
 
 const TYPE_OPTION: &str = "type";
 const SEARCH_OPTION: &str = "search";
+const SECOND_SEARCH_OPTION: &str = "search2";
+const THIRD_SEARCH_OPTION: &str = "search3";
+const MODE_OPTION: &str = "mode";
+const FORMAT_OPTION: &str = "format";
+const GENRE_OPTION: &str = "genre";
+const MIN_SCORE_OPTION: &str = "min_score";
+const STATUS_OPTION: &str = "status";
 const ANIME_TYPE: &str = "anime";
 const MANGA_TYPE: &str = "manga";
-const RECOMMENDATION_LIMIT: usize = 5;
+const TITLE_MODE: &str = "title";
+const FOR_ME_MODE: &str = "for-me";
+/// Recommendations shown per page of the embed.
+const RECOMMENDATION_PAGE_SIZE: usize = 5;
+/// AniList recommendation pages loaded per seed title.
+const MAX_RECOMMENDATION_PAGES: u32 = 3;
+
+const RECOMMEND_COMPONENT_PREFIX: &str = "recommend";
+const RECOMMEND_COMPONENT_ID_PREFIX: &str = "recommend:";
+const PREVIOUS_COMPONENT: &str = "prev";
+const NEXT_COMPONENT: &str = "next";
+/// Placeholder for an unset filter in component IDs.
+const NO_FILTER: &str = "-";
+
+pub const FORMAT_CHOICES: &[(&str, &str)] = &[
+    ("TV", "TV"),
+    ("TV Short", "TV_SHORT"),
+    ("Movie", "MOVIE"),
+    ("Special", "SPECIAL"),
+    ("OVA", "OVA"),
+    ("ONA", "ONA"),
+    ("Music", "MUSIC"),
+    ("Manga", "MANGA"),
+    ("Light Novel", "NOVEL"),
+    ("One Shot", "ONE_SHOT"),
+];
+const GENRE_CHOICES: &[&str] = &[
+    "Action",
+    "Adventure",
+    "Comedy",
+    "Drama",
+    "Ecchi",
+    "Fantasy",
+    "Horror",
+    "Mahou Shoujo",
+    "Mecha",
+    "Music",
+    "Mystery",
+    "Psychological",
+    "Romance",
+    "Sci-Fi",
+    "Slice of Life",
+    "Sports",
+    "Supernatural",
+    "Thriller",
+];
+pub const STATUS_CHOICES: &[(&str, &str)] = &[
+    ("Finished", "FINISHED"),
+    ("Releasing", "RELEASING"),
+    ("Not Yet Released", "NOT_YET_RELEASED"),
+    ("Cancelled", "CANCELLED"),
+    ("Hiatus", "HIATUS"),
+];
 
 pub fn register() -> CreateCommand {
+    let format_option = FORMAT_CHOICES.iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            FORMAT_OPTION,
+            "Only recommend titles in this format",
+        ),
+        |option, (name, value)| option.add_string_choice(*name, *value),
+    );
+    let genre_option = GENRE_CHOICES.iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            GENRE_OPTION,
+            "Only recommend titles in this genre",
+        ),
+        |option, genre| option.add_string_choice(*genre, *genre),
+    );
+    let status_option = STATUS_CHOICES.iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            STATUS_OPTION,
+            "Only recommend titles with this release status",
+        ),
+        |option, (name, value)| option.add_string_choice(*name, *value),
+    );
+
     CreateCommand::new("recommend")
         .description("Find community recommendations for an anime or manga")
         .add_option(
@@ -58,96 +161,339 @@ pub fn register() -> CreateCommand {
             .add_string_choice("Manga", MANGA_TYPE)
             .required(true),
         )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            SEARCH_OPTION,
+            "AniList ID or search term (not needed for \"For me\")",
+        ))
         .add_option(
             CreateCommandOption::new(
                 CommandOptionType::String,
-                SEARCH_OPTION,
-                "AniList ID or search term",
+                MODE_OPTION,
+                "Recommend from titles, or from your linked AniList list",
             )
-            .required(true),
+            .add_string_choice("From a title", TITLE_MODE)
+            .add_string_choice("For me", FOR_ME_MODE),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            SECOND_SEARCH_OPTION,
+            "A second title to blend in",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            THIRD_SEARCH_OPTION,
+            "A third title to blend in",
+        ))
+        .add_option(format_option)
+        .add_option(genre_option)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                MIN_SCORE_OPTION,
+                "Lowest AniList average score (0-100)",
+            )
+            .min_int_value(0)
+            .max_int_value(100),
         )
+        .add_option(status_option)
 }
 
-#[instrument(name = "command.recommend.parse_options", skip(options))]
-fn parse_recommend_options(options: &[CommandDataOption]) -> Option<(MediaType, String)> {
-    let media_type = options
+/// What `/recommend` was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecommendRequest {
+    /// Community recommendations merged across up to three seed titles.
+    Title {
+        media_type: MediaType,
+        search_terms: Vec<String>,
+        filters: RecommendationFilters,
+    },
+    /// Personal recommendations from the user's linked AniList list.
+    ForMe { media_type: MediaType },
+}
+
+#[instrument(name = "command.recommend.string_option", skip(options))]
+fn string_option(options: &[CommandDataOption], name: &str) -> Option<String> {
+    options
         .iter()
-        .find(|option| option.name == TYPE_OPTION)
+        .find(|option| option.name == name)
         .and_then(|option| match &option.value {
-            CommandDataOptionValue::String(value) => match value.as_str() {
-                ANIME_TYPE => Some(MediaType::Anime),
-                MANGA_TYPE => Some(MediaType::Manga),
-                _ => None,
-            },
+            CommandDataOptionValue::String(value) => Some(value.clone()),
+            _ => None,
+        })
+}
+
+#[instrument(name = "command.recommend.parse_options", skip(options))]
+fn parse_recommend_options(options: &[CommandDataOption]) -> Option<RecommendRequest> {
+    let media_type =
+        string_option(options, TYPE_OPTION).and_then(|value| match value.as_str() {
+            ANIME_TYPE => Some(MediaType::Anime),
+            MANGA_TYPE => Some(MediaType::Manga),
             _ => None,
         })?;
 
-    let search_term = options
+    let mode = string_option(options, MODE_OPTION);
+    if mode.as_deref() == Some(FOR_ME_MODE) {
+        return Some(RecommendRequest::ForMe { media_type });
+    }
+
+    let search_terms = [SEARCH_OPTION, SECOND_SEARCH_OPTION, THIRD_SEARCH_OPTION]
+        .into_iter()
+        .filter_map(|name| string_option(options, name))
+        .collect::<Vec<_>>();
+    if search_terms.is_empty() {
+        return None;
+    }
+
+    let min_score = options
         .iter()
-        .find(|option| option.name == SEARCH_OPTION)
-        .and_then(|option| match &option.value {
-            CommandDataOptionValue::String(search_term) => Some(search_term.clone()),
+        .find(|option| option.name == MIN_SCORE_OPTION)
+        .and_then(|option| match option.value {
+            CommandDataOptionValue::Integer(value) => u32::try_from(value.clamp(0, 100)).ok(),
             _ => None,
-        })?;
+        });
+
+    Some(RecommendRequest::Title {
+        media_type,
+        search_terms,
+        filters: RecommendationFilters {
+            format: string_option(options, FORMAT_OPTION),
+            genre: string_option(options, GENRE_OPTION),
+            min_score,
+            status: string_option(options, STATUS_OPTION),
+        },
+    })
+}
 
-    Some((media_type, search_term))
+/// Which page of which seeds' merged recommendations to show. Encoded in the
+/// paging buttons' custom IDs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecommendationView {
+    pub media_type: MediaType,
+    pub seed_ids: Vec<u32>,
+    pub filters: RecommendationFilters,
+    pub page: usize,
+}
+
+#[instrument(name = "command.recommend.is_component")]
+pub fn is_recommend_component(custom_id: &str) -> bool {
+    custom_id.starts_with(RECOMMEND_COMPONENT_ID_PREFIX)
+}
+
+#[instrument(name = "command.recommend.custom_id", skip(view))]
+pub fn recommend_custom_id(control: &str, view: &RecommendationView) -> String {
+    let media_type = match view.media_type {
+        MediaType::Anime => ANIME_TYPE,
+        MediaType::Manga => MANGA_TYPE,
+    };
+    let seed_ids = view
+        .seed_ids
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    let filters = &view.filters;
+
+    format!(
+        "{RECOMMEND_COMPONENT_PREFIX}:{control}:{media_type}:{seed_ids}:{}:{}:{}:{}:{}",
+        view.page,
+        filters.format.as_deref().unwrap_or(NO_FILTER),
+        filters.genre.as_deref().unwrap_or(NO_FILTER),
+        filters
+            .min_score
+            .map_or_else(|| NO_FILTER.to_string(), |min_score| min_score.to_string()),
+        filters.status.as_deref().unwrap_or(NO_FILTER),
+    )
+}
+
+#[instrument(name = "command.recommend.parse_component_id")]
+pub fn parse_recommend_component_id(custom_id: &str) -> Option<RecommendationView> {
+    let parts = custom_id.split(':').collect::<Vec<_>>();
+    let optional = |raw: &str| (raw != NO_FILTER).then(|| raw.to_string());
+
+    match parts.as_slice() {
+        [
+            RECOMMEND_COMPONENT_PREFIX,
+            PREVIOUS_COMPONENT | NEXT_COMPONENT,
+            raw_media_type,
+            raw_seed_ids,
+            raw_page,
+            raw_format,
+            raw_genre,
+            raw_min_score,
+            raw_status,
+        ] => {
+            let seed_ids = raw_seed_ids
+                .split(',')
+                .map(|raw| raw.parse().ok())
+                .collect::<Option<Vec<u32>>>()?;
+            let min_score = match *raw_min_score {
+                NO_FILTER => None,
+                raw => Some(raw.parse().ok()?),
+            };
+
+            Some(RecommendationView {
+                media_type: match *raw_media_type {
+                    ANIME_TYPE => MediaType::Anime,
+                    MANGA_TYPE => MediaType::Manga,
+                    _ => return None,
+                },
+                seed_ids,
+                filters: RecommendationFilters {
+                    format: optional(raw_format),
+                    genre: optional(raw_genre),
+                    min_score,
+                    status: optional(raw_status),
+                },
+                page: raw_page.parse().ok()?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Every seed, or `None` when any of them could not be found.
+fn found_seeds(seeds: &[Option<RecommendationMedia>]) -> Option<Vec<&RecommendationMedia>> {
+    if seeds.is_empty() {
+        return None;
+    }
+    seeds.iter().map(Option::as_ref).collect()
+}
+
+/// The seeds' recommendations merged into one ranking, best first.
+#[instrument(name = "command.recommend.rank", skip(seeds, filters), fields(seed_count = seeds.len()))]
+pub fn rank_recommendations<'a>(
+    seeds: &[&'a RecommendationMedia],
+    filters: &RecommendationFilters,
+    allow_adult_media: bool,
+) -> Vec<RankedRecommendation<'a>> {
+    merge_recommendations(
+        seeds.iter().map(|seed| (*seed, 1.0)),
+        &HashSet::new(),
+        filters,
+        allow_adult_media,
+    )
+}
+
+#[instrument(name = "command.recommend.page_count")]
+fn recommendation_page_count(result_count: usize) -> usize {
+    result_count.div_ceil(RECOMMENDATION_PAGE_SIZE).max(1)
 }
 
 pub fn handle_recommend(
-    media: Option<RecommendationMedia>,
+    seeds: &[Option<RecommendationMedia>],
     media_type: MediaType,
     title_variant: Option<TitleVariant>,
     title_preference: TitleDisplayPreference,
     allow_adult_media: bool,
+    filters: &RecommendationFilters,
+    page: usize,
 ) -> CommandResponse {
-    let Some(media) = media else {
+    let Some(seeds) = found_seeds(seeds) else {
         return CommandResponse::Content(not_found_message(&media_type).to_string());
     };
 
-    if media.is_adult() && !allow_adult_media {
+    if seeds.iter().any(|seed| seed.is_adult()) && !allow_adult_media {
         return CommandResponse::Content(NSFW_NOT_ALLOWED.to_string());
     }
 
-    let recommendations = media
-        .recommendations()
-        .filter_map(|recommendation| {
-            let recommended_media = recommendation.recommended_media()?;
-            if recommended_media.is_adult() && !allow_adult_media {
-                return None;
-            }
-            Some((recommendation, recommended_media))
-        })
-        .take(RECOMMENDATION_LIMIT)
-        .collect::<Vec<_>>();
+    let recommendations = rank_recommendations(&seeds, filters, allow_adult_media);
 
     if recommendations.is_empty() {
-        return CommandResponse::Content(format!(
-            "I couldn't find community recommendations for {} yet.",
-            media.transform_preferred_title(title_variant, title_preference)
-        ));
+        let titles = seed_titles(&seeds, title_variant, title_preference);
+        return CommandResponse::Content(if filters.is_empty() {
+            format!("I couldn't find community recommendations for {titles} yet.")
+        } else {
+            format!(
+                "I couldn't find community recommendations for {titles} matching those filters."
+            )
+        });
     }
 
+    let page_count = recommendation_page_count(recommendations.len());
+    let page = page.min(page_count - 1);
+
     CommandResponse::Embed(Box::new(recommendations_embed(
-        &media,
+        &seeds,
         &recommendations,
+        RecommendationEmbedPage {
+            page,
+            page_count,
+            filters,
+        },
         title_variant,
         title_preference,
     )))
 }
 
+/// Previous/Next buttons, or nothing when everything fits on one page.
+#[instrument(name = "command.recommend.components", skip(view))]
+pub fn recommendation_components(
+    view: &RecommendationView,
+    result_count: usize,
+) -> Vec<CreateActionRow> {
+    let page_count = recommendation_page_count(result_count);
+    if page_count <= 1 {
+        return Vec::new();
+    }
+
+    let page = view.page.min(page_count - 1);
+    let previous = RecommendationView {
+        page: page.saturating_sub(1),
+        ..view.clone()
+    };
+    let next = RecommendationView {
+        page: page + 1,
+        ..view.clone()
+    };
+
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(recommend_custom_id(PREVIOUS_COMPONENT, &previous))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        CreateButton::new(recommend_custom_id(NEXT_COMPONENT, &next))
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= page_count),
+    ])]
+}
+
 #[instrument(name = "command.recommend.run", skip(ctx, interaction))]
 pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
     let _ = interaction.defer(&ctx.http).await;
 
-    let Some((media_type, search_term)) = parse_recommend_options(&interaction.data.options) else {
-        let builder = EditInteractionResponse::new().content(
-            "Choose anime or manga, then tell me what to recommend from with `search:<name or AniList ID>`.",
-        );
-        let _ = interaction.edit_response(&ctx.http, builder).await;
-        return;
+    let (media_type, search_terms, filters) = match parse_recommend_options(
+        &interaction.data.options,
+    ) {
+        Some(RecommendRequest::Title {
+            media_type,
+            search_terms,
+            filters,
+        }) => (media_type, search_terms, filters),
+        Some(RecommendRequest::ForMe { media_type }) => {
+            configure_sentry_scope(
+                "Recommend",
+                interaction.user.id.get(),
+                Some(json!({ "type": media_type.as_ref(), "mode": FOR_ME_MODE })),
+            );
+            info!(media_type = ?media_type, "Got command 'recommend' for the user's list");
+            run_for_me(ctx, interaction, media_type).await;
+            return;
+        }
+        None => {
+            let builder = EditInteractionResponse::new().content(
+                    "Choose anime or manga, then tell me what to recommend from with `search:<name or AniList ID>`, or pick `mode:For me`.",
+                );
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
     };
 
-    if let Err(err) = validate_search_term(&search_term) {
+    if let Some(err) = search_terms
+        .iter()
+        .find_map(|search_term| validate_search_term(search_term).err())
+    {
         let builder = EditInteractionResponse::new().content(format!(
             "I couldn't use that search: {err}. Try a title or AniList ID."
         ));
@@ -160,162 +506,479 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
         interaction.user.id.get(),
         Some(json!({
             "type": media_type.as_ref(),
-            "search": search_term,
+            "search": search_terms,
         })),
     );
 
     info!(
         media_type = ?media_type,
-        "Got command 'recommend' with search_term: {search_term}"
+        seed_count = search_terms.len(),
+        "Got command 'recommend' with search_terms: {search_terms:?}"
     );
 
-    let (fetch_result, title_preference) = tokio::join!(
-        fetch_recommendation_media(&search_term, media_type.clone()),
+    let cache = get_cache_from_context(ctx).await;
+    let (fetch_results, title_preference) = tokio::join!(
+        fetch_seed_media(&cache, &search_terms, &media_type),
         resolve_title_display_preference(ctx, interaction.user.id, interaction.guild_id),
     );
-    let (media, title_variant) = match fetch_result {
-        Some((media, variant)) => (Some(media), Some(variant)),
-        None => (None, None),
+    let fetch_results = match fetch_results.into_iter().collect::<Result<Vec<_>, _>>() {
+        Ok(results) => results,
+        Err(err) => {
+            error!(error = %err, "Failed to fetch recommendation seeds");
+            let builder = EditInteractionResponse::new()
+                .content(err.user_message().unwrap_or(ANILIST_LOOKUP_FAILED));
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
     };
+    let title_variant = fetch_results
+        .first()
+        .and_then(|result| result.as_ref())
+        .map(|(_, variant)| *variant);
+    let mut seeds = fetch_results
+        .into_iter()
+        .map(|result| result.map(|(media, _)| media))
+        .collect::<Vec<_>>();
+    let seed_ids = seeds
+        .iter()
+        .flatten()
+        .map(|seed| seed.get_id())
+        .collect::<Vec<_>>();
+    if found_seeds(&seeds).is_some() {
+        fetch_more_recommendations(&cache, &mut seeds, &media_type).await;
+        store_recommendation_seeds(cache.as_ref(), &media_type, &seed_ids, &seeds).await;
+    }
+
     let allow_adult_media =
         is_nsfw_channel(ctx, interaction.channel_id, interaction.guild_id).await;
+    let view = RecommendationView {
+        media_type: media_type.clone(),
+        seed_ids,
+        filters,
+        page: 0,
+    };
+
+    let builder = recommendation_response(
+        &seeds,
+        view,
+        title_variant,
+        title_preference,
+        allow_adult_media,
+    );
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+/// Render `view` as an interaction edit: the embed and its paging buttons, or
+/// a plain message that clears any earlier embed and buttons.
+#[instrument(name = "command.recommend.response", skip(seeds, view), fields(page = view.page))]
+fn recommendation_response(
+    seeds: &[Option<RecommendationMedia>],
+    view: RecommendationView,
+    title_variant: Option<TitleVariant>,
+    title_preference: TitleDisplayPreference,
+    allow_adult_media: bool,
+) -> EditInteractionResponse {
     let response = handle_recommend(
-        media,
-        media_type,
+        seeds,
+        view.media_type.clone(),
         title_variant,
         title_preference,
         allow_adult_media,
+        &view.filters,
+        view.page,
     );
 
-    let _result = match response {
+    match response {
         CommandResponse::Content(text) | CommandResponse::Message(text) => {
-            let builder = EditInteractionResponse::new().content(text);
-            interaction.edit_response(&ctx.http, builder).await
+            EditInteractionResponse::new()
+                .content(text)
+                .embeds(Vec::new())
+                .components(Vec::new())
         }
         CommandResponse::Embed(embed) => {
-            let builder = EditInteractionResponse::new().embed(*embed);
-            interaction.edit_response(&ctx.http, builder).await
+            let result_count = found_seeds(seeds).map_or(0, |seeds| {
+                rank_recommendations(&seeds, &view.filters, allow_adult_media).len()
+            });
+            EditInteractionResponse::new()
+                .embed(*embed)
+                .components(recommendation_components(&view, result_count))
+        }
+    }
+}
+
+#[instrument(name = "command.recommend.handle_component", skip(ctx, interaction))]
+pub async fn handle_component(ctx: &Context, interaction: &mut ComponentInteraction) {
+    configure_sentry_scope("Recommend", interaction.user.id.get(), None);
+
+    let Some(view) = parse_recommend_component_id(&interaction.data.custom_id) else {
+        let builder = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content("I don't recognize that control. Please run the command again.")
+                .ephemeral(true),
+        );
+        let _ = interaction.create_response(&ctx.http, builder).await;
+        return;
+    };
+
+    if let Err(error) = interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        warn!(
+            error = %error,
+            custom_id = %interaction.data.custom_id,
+            "Failed to acknowledge recommendation component interaction"
+        );
+        return;
+    }
+
+    let cache = get_cache_from_context(ctx).await;
+    let (seeds, title_preference, allow_adult_media) = tokio::join!(
+        load_recommendation_seeds(&cache, &view.seed_ids, &view.media_type),
+        resolve_title_display_preference(ctx, interaction.user.id, interaction.guild_id),
+        is_nsfw_channel(ctx, interaction.channel_id, interaction.guild_id),
+    );
+    let seeds = match seeds {
+        Ok(seeds) => seeds,
+        Err(err) => {
+            error!(error = %err, "Failed to reload recommendation seeds");
+            let builder = EditInteractionResponse::new()
+                .content(err.user_message().unwrap_or(ANILIST_LOOKUP_FAILED))
+                .embeds(Vec::new())
+                .components(Vec::new());
+            let _ = interaction.edit_response(&ctx.http, builder).await;
+            return;
+        }
+    };
+
+    let builder = recommendation_response(&seeds, view, None, title_preference, allow_adult_media);
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+/// The seeds behind a recommendation view, with every recommendation page
+/// loaded. Served from the cache [`store_recommendation_seeds`] fills, so
+/// paging doesn't refetch them.
+#[instrument(name = "command.recommend.load_seeds", skip(cache, seed_ids), fields(seed_count = seed_ids.len()))]
+async fn load_recommendation_seeds(
+    cache: &SharedCache,
+    seed_ids: &[u32],
+    media_type: &MediaType,
+) -> Result<Vec<Option<RecommendationMedia>>, AniListError> {
+    let cache_key = recommendation_seeds_cache_key(media_type, seed_ids);
+    if let Some(seeds) = cache
+        .get(&cache_key)
+        .await
+        .and_then(|cached| serde_json::from_str(&cached).ok())
+    {
+        info!("Cache hit for {:#?}", cache_key);
+        return Ok(seeds);
+    }
+    info!("Cache miss for {:#?}", cache_key);
+
+    let mut seeds = load_recommendation_media(seed_ids, media_type)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    fetch_more_recommendations(cache, &mut seeds, media_type).await;
+    store_recommendation_seeds(cache.as_ref(), media_type, seed_ids, &seeds).await;
+    Ok(seeds)
+}
+
+#[instrument(name = "command.recommend.store_seeds", skip(cache, seed_ids, seeds))]
+async fn store_recommendation_seeds(
+    cache: &dyn Cache,
+    media_type: &MediaType,
+    seed_ids: &[u32],
+    seeds: &[Option<RecommendationMedia>],
+) {
+    if let Ok(encoded) = serde_json::to_string(seeds) {
+        cache
+            .set(
+                &recommendation_seeds_cache_key(media_type, seed_ids),
+                &encoded,
+                DEFAULT_CACHE_TTL,
+            )
+            .await;
+    }
+}
+
+#[instrument]
+fn recommendation_seeds_cache_key(media_type: &MediaType, seed_ids: &[u32]) -> String {
+    let seed_ids = seed_ids
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("recommendation:seeds:{}:{seed_ids}", media_type.as_ref())
+}
+
+/// Look up each search term concurrently, keeping their order. Missing titles
+/// are `Ok(None)`.
+#[instrument(name = "command.recommend.fetch_seeds", skip(cache, search_terms), fields(seed_count = search_terms.len()))]
+async fn fetch_seed_media(
+    cache: &SharedCache,
+    search_terms: &[String],
+    media_type: &MediaType,
+) -> Vec<Result<Option<(RecommendationMedia, TitleVariant)>, AniListError>> {
+    let mut lookups = JoinSet::new();
+    for (index, search_term) in search_terms.iter().cloned().enumerate() {
+        let cache = cache.clone();
+        let media_type = media_type.clone();
+        lookups.spawn(
+            async move {
+                let media =
+                    fetch_recommendation_media(cache.as_ref(), &search_term, media_type).await;
+                (index, media)
+            }
+            .in_current_span(),
+        );
+    }
+
+    let mut results = vec![Ok(None); search_terms.len()];
+    while let Some(result) = lookups.join_next().await {
+        if let Ok((index, media)) = result {
+            results[index] = media;
+        }
+    }
+    results
+}
+
+#[instrument]
+fn recommendations_batcher(media_type: &MediaType) -> &'static IdBatcher {
+    match media_type {
+        MediaType::Anime => &ANIME_RECOMMENDATIONS_BY_ID,
+        MediaType::Manga => &MANGA_RECOMMENDATIONS_BY_ID,
+    }
+}
+
+/// Load several titles' first recommendation page through one batched
+/// request, keeping their order. Missing titles are `Ok(None)`.
+#[instrument(name = "command.recommend.load_by_ids", skip(media_ids), fields(count = media_ids.len()))]
+pub async fn load_recommendation_media(
+    media_ids: &[u32],
+    media_type: &MediaType,
+) -> Vec<Result<Option<RecommendationMedia>, AniListError>> {
+    let batcher = recommendations_batcher(media_type);
+    let mut lookups = JoinSet::new();
+    for (index, media_id) in media_ids.iter().copied().enumerate() {
+        lookups.spawn(
+            async move {
+                let media = match batcher.load(media_id).await {
+                    Ok(Some(value)) => serde_json::from_value::<RecommendationMedia>(value)
+                        .map(Some)
+                        .map_err(|err| {
+                            error!(error = %err, media_id, "Failed to deserialize recommendations");
+                            AniListError::InvalidResponse(err.to_string())
+                        }),
+                    Ok(None) => Ok(None),
+                    Err(err) => {
+                        warn!(error = %err, media_id, "Failed to fetch recommendations");
+                        Err((*err).clone())
+                    }
+                };
+                (index, media)
+            }
+            .in_current_span(),
+        );
+    }
+
+    let mut loaded = vec![Ok(None); media_ids.len()];
+    while let Some(result) = lookups.join_next().await {
+        if let Ok((index, media)) = result {
+            loaded[index] = media;
+        }
+    }
+    loaded
+}
+
+/// Append each seed's AniList recommendation pages past the first, up to
+/// [`MAX_RECOMMENDATION_PAGES`]. Every seed and page is fetched at once.
+#[instrument(name = "command.recommend.fetch_more", skip(cache, seeds), fields(seed_count = seeds.len()))]
+async fn fetch_more_recommendations(
+    cache: &SharedCache,
+    seeds: &mut [Option<RecommendationMedia>],
+    media_type: &MediaType,
+) {
+    let mut lookups = JoinSet::new();
+    for (index, seed) in seeds.iter().enumerate() {
+        let Some(seed) = seed.as_ref().filter(|seed| seed.has_more_recommendations()) else {
+            continue;
+        };
+        let media_id = seed.get_id();
+        for page in 2..=MAX_RECOMMENDATION_PAGES {
+            let cache = cache.clone();
+            let media_type = media_type.clone();
+            lookups.spawn(
+                async move {
+                    let connection =
+                        fetch_recommendation_page(cache.as_ref(), media_id, &media_type, page)
+                            .await;
+                    (index, page, connection)
+                }
+                .in_current_span(),
+            );
+        }
+    }
+
+    let mut pages = HashMap::new();
+    while let Some(result) = lookups.join_next().await {
+        if let Ok((index, page, connection)) = result {
+            pages.insert((index, page), connection);
+        }
+    }
+
+    // Pages are appended in order, stopping at a failed page or the last one
+    // AniList has.
+    for (index, seed) in seeds.iter_mut().enumerate() {
+        let Some(seed) = seed else {
+            continue;
+        };
+        for page in 2..=MAX_RECOMMENDATION_PAGES {
+            if !seed.has_more_recommendations() {
+                break;
+            }
+            let Some(connection) = pages.remove(&(index, page)).flatten() else {
+                break;
+            };
+            seed.extend_recommendations(connection);
+        }
+    }
+}
+
+#[instrument(name = "command.recommend.fetch_page", skip(cache), fields(media_type = ?media_type))]
+async fn fetch_recommendation_page(
+    cache: &dyn Cache,
+    media_id: u32,
+    media_type: &MediaType,
+    page: u32,
+) -> Option<RecommendationConnection> {
+    let cache_key = recommendation_page_cache_key(media_type, media_id, page);
+    let body = match cache.get(&cache_key).await {
+        Some(cached_value) => cached_value,
+        None => {
+            let query = fetch_recommendations_page(anilist_type(media_type));
+            let json = json!({ "query": query, "variables": { "id": media_id, "page": page } });
+            let body = match check_response(send_request(json).await) {
+                Ok(body) => body,
+                Err(err) => {
+                    warn!(error = %err, media_id, page, "Failed to fetch recommendation page");
+                    return None;
+                }
+            };
+            cache.set(&cache_key, &body, DEFAULT_CACHE_TTL).await;
+            body
         }
     };
+
+    match parse_response::<RecommendationPageData>(&body) {
+        Ok(data) => data
+            .and_then(|data| data.media)
+            .map(|media| media.recommendations),
+        Err(err) => {
+            error!(error = %err, media_id, page, "Failed to deserialize recommendation page");
+            None
+        }
+    }
 }
 
-#[instrument(name = "command.recommend.fetch", fields(media_type = ?media_type, search_len = search_term.len()))]
+#[instrument]
+fn recommendation_page_cache_key(media_type: &MediaType, media_id: u32, page: u32) -> String {
+    format!(
+        "recommendation:page:{}:{media_id}:{page}",
+        media_type.as_ref()
+    )
+}
+
+#[instrument(name = "command.recommend.fetch", skip(cache), fields(media_type = ?media_type, search_len = search_term.len()))]
 async fn fetch_recommendation_media(
+    cache: &dyn Cache,
     search_term: &str,
     media_type: MediaType,
-) -> Option<(RecommendationMedia, TitleVariant)> {
+) -> Result<Option<(RecommendationMedia, TitleVariant)>, AniListError> {
     match search_term.parse::<u32>() {
         Ok(id) => fetch_recommendation_media_by_id(id, media_type).await,
-        Err(_) => fetch_recommendation_media_by_search(search_term, media_type).await,
+        Err(_) => fetch_recommendation_media_by_search(cache, search_term, media_type).await,
     }
 }
 
+/// ID seeds go through the same batched lookup as personal recommendation
+/// seeds, so several in one command share a request.
 #[instrument(name = "command.recommend.fetch_by_id", fields(media_type = ?media_type, id = id))]
 async fn fetch_recommendation_media_by_id(
     id: u32,
     media_type: MediaType,
-) -> Option<(RecommendationMedia, TitleVariant)> {
-    let query = fetch_recommendations_by_id(anilist_type(&media_type));
-    let fetched_data = match fetch_by_id(query, id).await {
-        Ok(data) => data,
-        Err(err) => {
-            error!(error = %err, id = id, "Failed to fetch AniList recommendations by id");
-            return None;
-        }
-    };
-    let response: RecommendationMediaResponse = match serde_json::from_str(&fetched_data) {
-        Ok(response) => response,
-        Err(err) => {
-            error!(error = %err, "Failed to deserialize AniList recommendation id response");
-            return None;
-        }
-    };
-
-    response
-        .data
-        .and_then(|data| data.media)
-        .map(|media| (media, TitleVariant::Romaji))
+) -> Result<Option<(RecommendationMedia, TitleVariant)>, AniListError> {
+    let media = load_recommendation_media(&[id], &media_type)
+        .await
+        .pop()
+        .unwrap_or(Ok(None))?;
+    Ok(media.map(|media| (media, TitleVariant::Romaji)))
 }
 
-#[instrument(name = "command.recommend.fetch_by_search", skip(search_term), fields(media_type = ?media_type, search_len = search_term.len()))]
+#[instrument(name = "command.recommend.fetch_by_search", skip(cache, search_term), fields(media_type = ?media_type, search_len = search_term.len()))]
 async fn fetch_recommendation_media_by_search(
+    cache: &dyn Cache,
     search_term: &str,
     media_type: MediaType,
-) -> Option<(RecommendationMedia, TitleVariant)> {
+) -> Result<Option<(RecommendationMedia, TitleVariant)>, AniListError> {
     let query = fetch_recommendations_by_search(anilist_type(&media_type));
     let cache_key = recommendation_cache_key(&media_type, search_term);
-    let cache_key_for_lookup = cache_key.clone();
 
-    let fetched_data = match task::spawn_blocking(move || {
-        read_cached_recommendation_response(cache_key_for_lookup)
-    })
-    .await
-    {
-        Ok(Ok(cached_value)) => {
+    let fetched_data = match cache.get(&cache_key).await {
+        Some(cached_value) => {
             info!("Cache hit for {:#?}", cache_key);
             cached_value
         }
-        Ok(Err(err)) => {
-            info!("Cache miss for {:#?} with error {:#?}", cache_key, err);
-            fetch_recommendations_from_network_and_cache(query, search_term.to_string(), cache_key)
-                .await?
-        }
-        Err(err) => {
-            error!(error = %err, "Failed to read AniList recommendation cache");
-            fetch_recommendations_from_network_and_cache(query, search_term.to_string(), cache_key)
-                .await?
+        None => {
+            info!("Cache miss for {:#?}", cache_key);
+            let Some(fetched_data) = fetch_recommendations_from_network_and_cache(
+                cache,
+                query,
+                search_term.to_string(),
+                cache_key,
+            )
+            .await?
+            else {
+                return Ok(None);
+            };
+            fetched_data
         }
     };
-    let response: SearchResponse<RecommendationMedia> = match serde_json::from_str(&fetched_data) {
-        Ok(response) => response,
-        Err(err) => {
+    let response: SearchResponse<RecommendationMedia> = serde_json::from_str(&fetched_data)
+        .map_err(|err| {
             error!(error = %err, "Failed to deserialize AniList recommendation search response");
-            return None;
-        }
-    };
+            AniListError::InvalidResponse(err.to_string())
+        })?;
 
-    response.fuzzy_match(search_term, media_type)
+    Ok(response.fuzzy_match(search_term, media_type))
 }
 
 #[instrument(
     name = "command.recommend.fetch_from_network_and_cache",
-    skip(query),
+    skip(cache, query),
     fields(cache_key = %cache_key, lookup_len = search_term.len())
 )]
 async fn fetch_recommendations_from_network_and_cache(
+    cache: &dyn Cache,
     query: String,
     search_term: String,
     cache_key: String,
-) -> Option<String> {
-    let response = match fetch_by_name(query, search_term).await {
-        Ok(data) => data,
-        Err(err) => {
-            error!(error = %err, "Failed to fetch AniList recommendations by search");
-            return None;
-        }
-    };
-
-    let cache_key_for_write = cache_key.clone();
-    let response_to_cache = response.clone();
-    if let Err(err) = task::spawn_blocking(move || {
-        write_cached_recommendation_response(cache_key_for_write, response_to_cache)
-    })
-    .await
-    {
-        error!(error = %err, cache_key = %cache_key, "Failed to cache AniList recommendation response");
-    }
-
-    Some(response)
-}
-
-#[instrument(name = "command.recommend.read_cache_blocking", skip(cache_key), fields(cache_key = %cache_key))]
-fn read_cached_recommendation_response(cache_key: String) -> RedisResult<String> {
-    check_cache(&cache_key)
-}
-
-#[instrument(name = "command.recommend.write_cache_blocking", skip(cache_key, response), fields(cache_key = %cache_key))]
-fn write_cached_recommendation_response(cache_key: String, response: String) {
-    try_to_cache_response(&cache_key, &response)
+) -> Result<Option<String>, AniListError> {
+    ANILIST_FLIGHTS
+        .run(&cache_key, || async {
+            let response = match fetch_by_name(query, search_term).await {
+                Ok(data) => data,
+                Err(err) => {
+                    error!(error = %err, "Failed to fetch AniList recommendations by search");
+                    return Err(err);
+                }
+            };
+
+            cache.set(&cache_key, &response, DEFAULT_CACHE_TTL).await;
+
+            Ok(Some(response))
+        })
+        .await
 }
 
 #[instrument]
@@ -323,38 +986,66 @@ fn recommendation_cache_key(media_type: &MediaType, search_term: &str) -> String
     format!("recommendation:{}:{search_term}", media_type.as_ref())
 }
 
-#[instrument(skip(media, recommendations))]
+/// Paging details shown in the embed footer.
+struct RecommendationEmbedPage<'a> {
+    page: usize,
+    page_count: usize,
+    filters: &'a RecommendationFilters,
+}
+
+#[instrument(skip(seeds, recommendations, page))]
 fn recommendations_embed(
-    media: &RecommendationMedia,
-    recommendations: &[(&Recommendation, &RecommendedMedia)],
+    seeds: &[&RecommendationMedia],
+    recommendations: &[RankedRecommendation],
+    page: RecommendationEmbedPage,
     title_variant: Option<TitleVariant>,
     title_preference: TitleDisplayPreference,
 ) -> CreateEmbed {
+    let Some(first_seed) = seeds.first() else {
+        return CreateEmbed::new();
+    };
+
+    let mut footer = format!(
+        "Recommendations from AniList community ratings • {}",
+        titlecase(first_seed.get_type())
+    );
+    if !page.filters.is_empty() {
+        footer.push_str(&format!(" • Filters: {}", page.filters.describe()));
+    }
+    if page.page_count > 1 {
+        footer.push_str(&format!(" • Page {}/{}", page.page + 1, page.page_count));
+    }
+
     let mut embed = CreateEmbed::new()
-        .color(media.transform_color())
+        .color(first_seed.transform_color())
         .title(format!(
             "Recommendations for {}",
-            media.transform_preferred_title(title_variant, title_preference)
+            seed_titles(seeds, title_variant, title_preference)
         ))
-        .url(media.transform_anilist())
-        .footer(CreateEmbedFooter::new(format!(
-            "Recommendations from AniList community ratings • {}",
-            titlecase(media.get_type())
-        )));
+        .url(first_seed.transform_anilist())
+        .footer(CreateEmbedFooter::new(footer));
 
-    let thumbnail = media.transform_thumbnail();
+    let thumbnail = first_seed.transform_thumbnail();
     if !thumbnail.is_empty() {
         embed = embed.thumbnail(thumbnail);
     }
 
-    for (index, (recommendation, recommended_media)) in recommendations.iter().enumerate() {
+    let show_sources = seeds.len() > 1;
+    for (index, recommendation) in recommendations
+        .iter()
+        .enumerate()
+        .skip(page.page * RECOMMENDATION_PAGE_SIZE)
+        .take(RECOMMENDATION_PAGE_SIZE)
+    {
         embed = embed.field(
             format!(
                 "{}. {}",
                 index + 1,
-                recommended_media.display_title(title_variant, title_preference)
+                recommendation
+                    .media
+                    .display_title(title_variant, title_preference)
             ),
-            format_recommendation(recommendation, recommended_media),
+            format_recommendation(recommendation, show_sources, title_preference),
             false,
         );
     }
@@ -362,12 +1053,33 @@ fn recommendations_embed(
     embed
 }
 
-#[instrument(skip(recommendation, recommended_media))]
+/// The seeds' titles joined with `+`. Only the first seed was matched by
+/// search, so only it uses `title_variant`.
+#[instrument(skip(seeds))]
+fn seed_titles(
+    seeds: &[&RecommendationMedia],
+    title_variant: Option<TitleVariant>,
+    title_preference: TitleDisplayPreference,
+) -> String {
+    seeds
+        .iter()
+        .enumerate()
+        .map(|(index, seed)| {
+            let variant = if index == 0 { title_variant } else { None };
+            seed.transform_preferred_title(variant, title_preference)
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+#[instrument(skip(recommendation))]
 fn format_recommendation(
-    recommendation: &Recommendation,
-    recommended_media: &RecommendedMedia,
+    recommendation: &RankedRecommendation,
+    show_sources: bool,
+    title_preference: TitleDisplayPreference,
 ) -> String {
-    let genres = recommended_media
+    let genres = recommendation
+        .media
         .genres()
         .iter()
         .take(3)
@@ -375,6 +1087,32 @@ fn format_recommendation(
         .collect::<Vec<_>>()
         .join(" - ");
 
+    let mut lines = vec![format_recommended_media_summary(recommendation.media)];
+
+    if let Some(rating) = recommendation.rating {
+        lines.push(format_recommendation_rating(rating));
+    }
+
+    if show_sources {
+        let sources = recommendation
+            .because
+            .iter()
+            .map(|seed| seed.transform_preferred_title(None, title_preference))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!("Recommended from {sources}"));
+    }
+
+    if !genres.is_empty() {
+        lines.push(format!("Genres: {genres}"));
+    }
+
+    lines.join("\n")
+}
+
+/// AniList link, type, format/status and audience score on one line.
+#[instrument(skip(recommended_media))]
+pub fn format_recommended_media_summary(recommended_media: &RecommendedMedia) -> String {
     let mut summary_parts = vec![
         linker("AniList", recommended_media.site_url()),
         titlecase(recommended_media.media_type()),
@@ -389,17 +1127,7 @@ fn format_recommendation(
         summary_parts.push(format!("Audience score: {score}/100"));
     }
 
-    let mut lines = vec![summary_parts.join(" • ")];
-
-    if let Some(rating) = recommendation.rating() {
-        lines.push(format_recommendation_rating(rating));
-    }
-
-    if !genres.is_empty() {
-        lines.push(format!("Genres: {genres}"));
-    }
-
-    lines.join("\n")
+    summary_parts.join(" • ")
 }
 
 #[instrument]
@@ -436,20 +1164,20 @@ fn not_found_message(media_type: &MediaType) -> &'static str {
     }
 }
 
-#[instrument]
-fn anilist_type(media_type: &MediaType) -> &'static str {
-    match media_type {
-        MediaType::Anime => "ANIME",
-        MediaType::Manga => "MANGA",
-    }
-}
-
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::*;
+    use crate::{models::anilist_recommendation::Recommendation, utils::cache::InMemoryCache};
 
     fn sample_media(is_adult: bool, recommendation_is_adult: bool) -> RecommendationMedia {
-        serde_json::from_value(serde_json::json!({
+        serde_json::from_value(sample_media_json(is_adult, recommendation_is_adult))
+            .expect("sample recommendation media should deserialize")
+    }
+
+    fn sample_media_json(is_adult: bool, recommendation_is_adult: bool) -> serde_json::Value {
+        serde_json::json!({
             "type": "ANIME",
             "id": 1,
             "isAdult": is_adult,
@@ -496,8 +1224,7 @@ mod tests {
                     }
                 }]
             }
-        }))
-        .expect("sample recommendation media should deserialize")
+        })
     }
 
     fn sample_media_without_recommendations() -> RecommendationMedia {
@@ -527,14 +1254,96 @@ mod tests {
         .expect("sample recommendation media should deserialize")
     }
 
+    #[tokio::test]
+    async fn search_is_served_from_injected_cache() {
+        let media = sample_media_json(false, false);
+        let payload = serde_json::json!({ "data": { "Page": { "media": [media] } } });
+        let cache = InMemoryCache::with_entry(
+            &recommendation_cache_key(&MediaType::Anime, "Cowboy Bebop"),
+            &payload.to_string(),
+        );
+
+        let (media, variant) =
+            fetch_recommendation_media_by_search(&cache, "Cowboy Bebop", MediaType::Anime)
+                .await
+                .ok()
+                .flatten()
+                .expect("cached search should resolve without a network call");
+
+        assert_eq!(media.get_id(), 1);
+        assert_eq!(variant, TitleVariant::English);
+    }
+
+    #[tokio::test]
+    async fn paging_reuses_the_seeds_stored_by_the_first_response() {
+        let cache: SharedCache = Arc::new(InMemoryCache::default());
+        let seeds = vec![Some(sample_media(false, false))];
+        store_recommendation_seeds(cache.as_ref(), &MediaType::Anime, &[1], &seeds).await;
+
+        let loaded = load_recommendation_seeds(&cache, &[1], &MediaType::Anime)
+            .await
+            .expect("stored seeds should load without a network call");
+
+        let loaded = found_seeds(&loaded).expect("the seed should be found");
+        assert_eq!(loaded[0].get_id(), 1);
+        assert_eq!(
+            loaded[0].recommendations().count(),
+            seeds[0].as_ref().unwrap().recommendations().count()
+        );
+    }
+
+    fn options(value: serde_json::Value) -> Vec<CommandDataOption> {
+        serde_json::from_value(value).expect("options deserialize")
+    }
+
+    #[test]
+    fn title_mode_is_the_default_and_needs_a_search() {
+        let parsed = parse_recommend_options(&options(serde_json::json!([
+            { "name": "type", "type": 3, "value": "anime" },
+            { "name": "search", "type": 3, "value": "Cowboy Bebop" }
+        ])));
+
+        assert_eq!(
+            parsed,
+            Some(RecommendRequest::Title {
+                media_type: MediaType::Anime,
+                search_terms: vec!["Cowboy Bebop".to_string()],
+                filters: RecommendationFilters::default(),
+            })
+        );
+        assert_eq!(
+            parse_recommend_options(&options(serde_json::json!([
+                { "name": "type", "type": 3, "value": "anime" }
+            ]))),
+            None
+        );
+    }
+
+    #[test]
+    fn for_me_mode_ignores_search() {
+        let parsed = parse_recommend_options(&options(serde_json::json!([
+            { "name": "type", "type": 3, "value": "manga" },
+            { "name": "mode", "type": 3, "value": "for-me" }
+        ])));
+
+        assert_eq!(
+            parsed,
+            Some(RecommendRequest::ForMe {
+                media_type: MediaType::Manga
+            })
+        );
+    }
+
     #[test]
     fn not_found_returns_type_specific_message() {
         let response = handle_recommend(
-            None,
+            &[None],
             MediaType::Manga,
             None,
             TitleDisplayPreference::Matched,
             true,
+            &RecommendationFilters::default(),
+            0,
         );
 
         assert!(response.is_content());
@@ -544,11 +1353,13 @@ mod tests {
     #[test]
     fn adult_base_media_is_blocked_when_adult_content_is_not_allowed() {
         let response = handle_recommend(
-            Some(sample_media(true, false)),
+            &[Some(sample_media(true, false))],
             MediaType::Anime,
             None,
             TitleDisplayPreference::Matched,
             false,
+            &RecommendationFilters::default(),
+            0,
         );
 
         assert!(response.is_content());
@@ -558,11 +1369,13 @@ mod tests {
     #[test]
     fn adult_recommendations_are_filtered_when_adult_content_is_not_allowed() {
         let response = handle_recommend(
-            Some(sample_media(false, true)),
+            &[Some(sample_media(false, true))],
             MediaType::Anime,
             None,
             TitleDisplayPreference::Matched,
             false,
+            &RecommendationFilters::default(),
+            0,
         );
 
         assert!(response.is_content());
@@ -576,11 +1389,13 @@ mod tests {
     #[test]
     fn no_recommendations_returns_content_message() {
         let response = handle_recommend(
-            Some(sample_media_without_recommendations()),
+            &[Some(sample_media_without_recommendations())],
             MediaType::Manga,
             Some(TitleVariant::English),
             TitleDisplayPreference::Matched,
             true,
+            &RecommendationFilters::default(),
+            0,
         );
 
         assert!(response.is_content());
@@ -593,11 +1408,13 @@ mod tests {
     #[test]
     fn successful_recommendations_return_embed() {
         let response = handle_recommend(
-            Some(sample_media(false, false)),
+            &[Some(sample_media(false, false))],
             MediaType::Anime,
             Some(TitleVariant::English),
             TitleDisplayPreference::Matched,
             false,
+            &RecommendationFilters::default(),
+            0,
         );
 
         assert!(response.is_embed());
@@ -643,10 +1460,21 @@ mod tests {
         let recommended_media = recommendation
             .recommended_media()
             .expect("recommended media should exist");
+        let ranked = RankedRecommendation {
+            media: recommended_media,
+            score: 0.0,
+            rating: recommendation.rating(),
+            because: vec![&media],
+        };
 
         let embed = recommendations_embed(
-            &media,
-            &[(&recommendation, recommended_media)],
+            &[&media],
+            &[ranked],
+            RecommendationEmbedPage {
+                page: 0,
+                page_count: 1,
+                filters: &RecommendationFilters::default(),
+            },
             None,
             TitleDisplayPreference::Matched,
         );
@@ -658,6 +1486,97 @@ mod tests {
         assert!(!field.contains("Genres:"));
     }
 
+    #[test]
+    fn title_mode_collects_extra_seeds_and_filters() {
+        let parsed = parse_recommend_options(&options(serde_json::json!([
+            { "name": "type", "type": 3, "value": "anime" },
+            { "name": "search", "type": 3, "value": "Cowboy Bebop" },
+            { "name": "search3", "type": 3, "value": "Trigun" },
+            { "name": "format", "type": 3, "value": "TV" },
+            { "name": "min_score", "type": 4, "value": 75 }
+        ])));
+
+        assert_eq!(
+            parsed,
+            Some(RecommendRequest::Title {
+                media_type: MediaType::Anime,
+                search_terms: vec!["Cowboy Bebop".to_string(), "Trigun".to_string()],
+                filters: RecommendationFilters {
+                    format: Some("TV".to_string()),
+                    min_score: Some(75),
+                    ..RecommendationFilters::default()
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn paging_custom_ids_round_trip() {
+        let view = RecommendationView {
+            media_type: MediaType::Manga,
+            seed_ids: vec![30002, 30013, 31706],
+            filters: RecommendationFilters {
+                format: Some("ONE_SHOT".to_string()),
+                genre: Some("Slice of Life".to_string()),
+                min_score: Some(100),
+                status: Some("NOT_YET_RELEASED".to_string()),
+            },
+            page: 12,
+        };
+
+        for control in [PREVIOUS_COMPONENT, NEXT_COMPONENT] {
+            let custom_id = recommend_custom_id(control, &view);
+
+            assert!(custom_id.len() <= 100);
+            assert!(is_recommend_component(&custom_id));
+            assert_eq!(parse_recommend_component_id(&custom_id), Some(view.clone()));
+        }
+        assert_eq!(
+            parse_recommend_component_id("recommend:next:novel:1:0:-:-:-:-"),
+            None
+        );
+        assert_eq!(
+            parse_recommend_component_id("recommend:next:anime:1:0:-:-:-"),
+            None
+        );
+    }
+
+    #[test]
+    fn filtered_searches_without_matches_mention_the_filters() {
+        let response = handle_recommend(
+            &[Some(sample_media(false, false))],
+            MediaType::Anime,
+            Some(TitleVariant::English),
+            TitleDisplayPreference::Matched,
+            false,
+            &RecommendationFilters {
+                format: Some("MOVIE".to_string()),
+                ..RecommendationFilters::default()
+            },
+            0,
+        );
+
+        assert_eq!(
+            response.unwrap_content(),
+            "I couldn't find community recommendations for Cowboy Bebop matching those filters."
+        );
+    }
+
+    #[test]
+    fn any_missing_seed_is_reported_as_not_found() {
+        let response = handle_recommend(
+            &[Some(sample_media(false, false)), None],
+            MediaType::Anime,
+            None,
+            TitleDisplayPreference::Matched,
+            false,
+            &RecommendationFilters::default(),
+            0,
+        );
+
+        assert_eq!(response.unwrap_content(), NOT_FOUND_ANIME);
+    }
+
     #[test]
     fn recommendation_rating_copy_handles_non_positive_scores() {
         assert_eq!(
@@ -670,11 +1589,13 @@ mod tests {
     #[test]
     fn recommendations_use_configured_title_preference() {
         let response = handle_recommend(
-            Some(sample_media(false, false)),
+            &[Some(sample_media(false, false))],
             MediaType::Anime,
             Some(TitleVariant::English),
             TitleDisplayPreference::Native,
             false,
+            &RecommendationFilters::default(),
+            0,
         );
 
         assert!(response.is_embed());
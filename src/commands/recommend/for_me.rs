@@ -0,0 +1,555 @@
+//! `/recommend mode:for-me`: recommendations drawn from a linked user's own
+//! highly scored completed titles instead of a single seed title.
+//!
+//! AniList community recommendation ratings are summed across the user's
+//! seeds (weighted by how the user scored each seed), titles already on the
+//! user's list are dropped, and the rest get a boost for genres the user
+//! tends to rate highly.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    commands::{
+        recommend::{
+            command::{format_recommended_media_summary, load_recommendation_media},
+            ranking::{
+                RankedRecommendation, RecommendationFilters, merge_recommendations, sort_ranked,
+            },
+        },
+        response::CommandResponse,
+    },
+    models::{
+        anilist_recommendation::RecommendationMedia,
+        db::oauth_credential::OAuthCredential,
+        media_type::MediaType,
+        settings::TitleDisplayPreference,
+        transformers::Transformers,
+        user_media_list::{MediaListEntry, MediaListStatus},
+    },
+    utils::{
+        cache::get_cache_from_context,
+        channel::is_nsfw_channel,
+        formatter::{code, titlecase},
+        interaction::{anilist_type, linked_profile, respond},
+        media_list::fetch_user_media_list,
+        requests::graphql::AniListError,
+        settings::resolve_title_display_preference,
+        statics::ANILIST_LOOKUP_FAILED,
+    },
+};
+
+use serenity::{
+    all::{CommandInteraction, CreateEmbed, CreateEmbedFooter, EditInteractionResponse},
+    client::Context,
+};
+use tracing::{error, info, instrument};
+
+/// Lowest score (out of 100) for a completed title to count as liked.
+pub const SEED_MIN_SCORE: u32 = 75;
+/// Liked titles whose recommendations are fetched, highest scored first.
+/// Each seed pulls a full page of AniList recommendations, so this is kept
+/// small.
+const MAX_SEEDS: usize = 5;
+/// How much genre affinity can raise a recommendation's score (up to +50%).
+const GENRE_AFFINITY_WEIGHT: f64 = 0.5;
+const PERSONAL_RECOMMENDATION_LIMIT: usize = 5;
+/// Seed titles named in each "Because you liked" line.
+const BECAUSE_TITLE_LIMIT: usize = 3;
+
+/// A liked title and the community recommendations AniList has for it.
+#[derive(Debug)]
+pub struct Seed {
+    pub media: RecommendationMedia,
+    pub user_score: u32,
+}
+
+/// Liked entries: completed with a score of at least [`SEED_MIN_SCORE`].
+#[instrument(name = "command.recommend.for_me.liked_entries", skip(entries))]
+fn liked_entries(entries: &[MediaListEntry]) -> impl Iterator<Item = &MediaListEntry> {
+    entries.iter().filter(|entry| {
+        entry.status == Some(MediaListStatus::Completed)
+            && entry.score.is_some_and(|score| score >= SEED_MIN_SCORE)
+    })
+}
+
+/// The liked titles to seed recommendations from, as `(media_id, score)`.
+#[instrument(name = "command.recommend.for_me.select_seeds", skip(entries), fields(entry_count = entries.len()))]
+pub fn select_seeds(entries: &[MediaListEntry]) -> Vec<(u32, u32)> {
+    let mut seeds = liked_entries(entries)
+        .map(|entry| (entry.media_id, entry.score.unwrap_or_default()))
+        .collect::<Vec<_>>();
+    seeds.sort_by(|(left_id, left_score), (right_id, right_score)| {
+        right_score.cmp(left_score).then(left_id.cmp(right_id))
+    });
+    seeds.truncate(MAX_SEEDS);
+    seeds
+}
+
+/// Share of liked titles carrying each genre.
+#[instrument(name = "command.recommend.for_me.genre_affinity", skip(entries))]
+fn genre_affinity(entries: &[MediaListEntry]) -> HashMap<&str, f64> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut liked = 0;
+    for entry in liked_entries(entries) {
+        liked += 1;
+        for genre in entry.genres() {
+            *counts.entry(genre.as_str()).or_default() += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|(genre, count)| (genre, count as f64 / liked as f64))
+        .collect()
+}
+
+/// Merge the seeds' recommendations into one ranking, best first.
+#[instrument(name = "command.recommend.for_me.rank", skip(seeds, entries), fields(seed_count = seeds.len(), entry_count = entries.len()))]
+pub fn rank_personal_recommendations<'a>(
+    seeds: &'a [Seed],
+    entries: &[MediaListEntry],
+    allow_adult_media: bool,
+) -> Vec<RankedRecommendation<'a>> {
+    let on_list = entries
+        .iter()
+        .map(|entry| entry.media_id)
+        .collect::<HashSet<_>>();
+    let merged = merge_recommendations(
+        seeds
+            .iter()
+            .map(|seed| (&seed.media, f64::from(seed.user_score) / 100.0)),
+        &on_list,
+        &RecommendationFilters::default(),
+        allow_adult_media,
+    );
+
+    let affinity = genre_affinity(entries);
+    let mut ranked = merged
+        .into_iter()
+        .filter(|candidate| candidate.score > 0.0)
+        .map(|mut candidate| {
+            let genres = candidate.media.genres();
+            if !genres.is_empty() {
+                let genre_score = genres
+                    .iter()
+                    .map(|genre| affinity.get(genre.as_str()).copied().unwrap_or_default())
+                    .sum::<f64>()
+                    / genres.len() as f64;
+                candidate.score *= 1.0 + GENRE_AFFINITY_WEIGHT * genre_score;
+            }
+            candidate
+        })
+        .collect::<Vec<_>>();
+    sort_ranked(&mut ranked);
+    ranked
+}
+
+pub fn handle_for_me(
+    profile: &OAuthCredential,
+    media_type: MediaType,
+    entries: &[MediaListEntry],
+    seeds: &[Seed],
+    title_preference: TitleDisplayPreference,
+    allow_adult_media: bool,
+) -> CommandResponse {
+    let noun = match media_type {
+        MediaType::Anime => "anime",
+        MediaType::Manga => "manga",
+    };
+    if select_seeds(entries).is_empty() {
+        return CommandResponse::Content(format!(
+            "I need at least one completed {noun} you've scored {SEED_MIN_SCORE}+ on AniList before I can recommend something for you."
+        ));
+    }
+
+    let ranked = rank_personal_recommendations(seeds, entries, allow_adult_media);
+    if ranked.is_empty() {
+        return CommandResponse::Content(format!(
+            "I couldn't find any {noun} you haven't already listed among the community recommendations for your favourites."
+        ));
+    }
+
+    CommandResponse::Embed(Box::new(personal_recommendations_embed(
+        profile,
+        &ranked[..ranked.len().min(PERSONAL_RECOMMENDATION_LIMIT)],
+        noun,
+        title_preference,
+    )))
+}
+
+#[instrument(skip(profile, recommendations))]
+fn personal_recommendations_embed(
+    profile: &OAuthCredential,
+    recommendations: &[RankedRecommendation],
+    noun: &str,
+    title_preference: TitleDisplayPreference,
+) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title(format!(
+            "{} picks for {}",
+            titlecase(noun),
+            profile.anilist_display_name()
+        ))
+        .url(profile.anilist_profile_url())
+        .footer(CreateEmbedFooter::new(
+            "Based on your AniList scores and community recommendations",
+        ));
+
+    if let Some(seed) = recommendations
+        .first()
+        .and_then(|recommendation| recommendation.because.first())
+    {
+        embed = embed.color(seed.transform_color());
+    }
+
+    for (index, recommendation) in recommendations.iter().enumerate() {
+        embed = embed.field(
+            format!(
+                "{}. {}",
+                index + 1,
+                recommendation.media.display_title(None, title_preference)
+            ),
+            format_personal_recommendation(recommendation, title_preference),
+            false,
+        );
+    }
+
+    embed
+}
+
+#[instrument(skip(recommendation))]
+fn format_personal_recommendation(
+    recommendation: &RankedRecommendation,
+    title_preference: TitleDisplayPreference,
+) -> String {
+    let because = recommendation
+        .because
+        .iter()
+        .take(BECAUSE_TITLE_LIMIT)
+        .map(|seed| seed.transform_preferred_title(None, title_preference))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let genres = recommendation
+        .media
+        .genres()
+        .iter()
+        .take(3)
+        .map(|genre| code(&titlecase(genre)))
+        .collect::<Vec<_>>()
+        .join(" - ");
+
+    let mut lines = vec![
+        format_recommended_media_summary(recommendation.media),
+        format!("Because you liked {because}"),
+    ];
+    if !genres.is_empty() {
+        lines.push(format!("Genres: {genres}"));
+    }
+
+    lines.join("\n")
+}
+
+/// Fetch each seed's recommendations through one batched request. Seeds that
+/// fail to load are skipped; the error is returned only when every seed
+/// failed.
+#[instrument(name = "command.recommend.for_me.fetch_seeds", skip(seed_ids), fields(seed_count = seed_ids.len()))]
+async fn fetch_seeds(
+    seed_ids: Vec<(u32, u32)>,
+    media_type: &MediaType,
+) -> Result<Vec<Seed>, AniListError> {
+    let media_ids = seed_ids
+        .iter()
+        .map(|(media_id, _)| *media_id)
+        .collect::<Vec<_>>();
+    let loaded = load_recommendation_media(&media_ids, media_type).await;
+
+    let mut seeds = Vec::with_capacity(loaded.len());
+    let mut last_error = None;
+    for ((_, user_score), media) in seed_ids.into_iter().zip(loaded) {
+        match media {
+            Ok(Some(media)) => seeds.push(Seed { media, user_score }),
+            Ok(None) => {}
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    match last_error {
+        Some(err) if seeds.is_empty() => Err(err),
+        _ => Ok(seeds),
+    }
+}
+
+#[instrument(name = "command.recommend.for_me.run", skip(ctx, interaction), fields(media_type = ?media_type))]
+pub async fn run_for_me(
+    ctx: &Context,
+    interaction: &mut CommandInteraction,
+    media_type: MediaType,
+) {
+    let user_id = interaction.user.id;
+
+    let Some(profile) = linked_profile(
+        ctx,
+        interaction,
+        "Link your AniList account with `/register` to get recommendations based on your list.",
+    )
+    .await
+    else {
+        return;
+    };
+
+    let cache = get_cache_from_context(ctx).await;
+    let entries = match fetch_user_media_list(
+        cache.as_ref(),
+        profile.anilist_id,
+        anilist_type(&media_type),
+    )
+    .await
+    {
+        Ok(entries) => entries,
+        Err(err) => {
+            error!(error = %err, "Failed to fetch AniList list for personal recommendations");
+            respond(
+                ctx,
+                interaction,
+                err.user_message().unwrap_or(
+                    "I couldn't read your AniList list right now. Please try again later.",
+                ),
+            )
+            .await;
+            return;
+        }
+    };
+
+    let seed_ids = select_seeds(&entries);
+    info!(
+        entries = entries.len(),
+        seeds = seed_ids.len(),
+        "Selected seeds for personal recommendations"
+    );
+    let (seeds, title_preference, allow_adult_media) = tokio::join!(
+        fetch_seeds(seed_ids.clone(), &media_type),
+        resolve_title_display_preference(ctx, user_id, interaction.guild_id),
+        is_nsfw_channel(ctx, interaction.channel_id, interaction.guild_id),
+    );
+
+    let seeds = match seeds {
+        Ok(seeds) => seeds,
+        Err(err) => {
+            error!(error = %err, "Failed to fetch seeds for personal recommendations");
+            respond(
+                ctx,
+                interaction,
+                err.user_message().unwrap_or(ANILIST_LOOKUP_FAILED),
+            )
+            .await;
+            return;
+        }
+    };
+
+    let response = handle_for_me(
+        &profile,
+        media_type,
+        &entries,
+        &seeds,
+        title_preference,
+        allow_adult_media,
+    );
+
+    let builder = match response {
+        CommandResponse::Content(text) | CommandResponse::Message(text) => {
+            EditInteractionResponse::new().content(text)
+        }
+        CommandResponse::Embed(embed) => EditInteractionResponse::new().embed(*embed),
+    };
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        media_id: u32,
+        status: MediaListStatus,
+        score: u32,
+        genres: &[&str],
+    ) -> MediaListEntry {
+        serde_json::from_value(serde_json::json!({
+            "mediaId": media_id,
+            "status": status,
+            "score": score,
+            "progress": null,
+            "media": { "genres": genres },
+        }))
+        .expect("list entry should deserialize")
+    }
+
+    fn recommended(id: u32, title: &str, rating: i32, genres: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "rating": rating,
+            "mediaRecommendation": {
+                "type": "ANIME",
+                "id": id,
+                "isAdult": false,
+                "title": { "romaji": title, "english": null, "native": null },
+                "format": "TV",
+                "status": "FINISHED",
+                "genres": genres,
+                "averageScore": 80,
+                "siteUrl": format!("https://anilist.co/anime/{id}")
+            }
+        })
+    }
+
+    fn seed(id: u32, title: &str, user_score: u32, nodes: Vec<serde_json::Value>) -> Seed {
+        let media = serde_json::from_value(serde_json::json!({
+            "type": "ANIME",
+            "id": id,
+            "isAdult": false,
+            "title": { "romaji": title, "english": null, "native": null },
+            "synonyms": [],
+            "format": "TV",
+            "status": "FINISHED",
+            "genres": [],
+            "coverImage": { "extraLarge": null, "large": null, "medium": null, "color": null },
+            "averageScore": 80,
+            "siteUrl": format!("https://anilist.co/anime/{id}"),
+            "recommendations": { "nodes": nodes }
+        }))
+        .expect("seed should deserialize");
+        Seed { media, user_score }
+    }
+
+    fn profile() -> OAuthCredential {
+        OAuthCredential {
+            discord_user_id: "1".to_string(),
+            anilist_id: 7,
+            anilist_username: Some("AniUser".to_string()),
+        }
+    }
+
+    #[test]
+    fn seeds_are_high_scored_completed_titles_best_first() {
+        let entries = vec![
+            entry(1, MediaListStatus::Completed, 80, &[]),
+            entry(2, MediaListStatus::Completed, 95, &[]),
+            entry(3, MediaListStatus::Completed, 60, &[]),
+            entry(4, MediaListStatus::Current, 100, &[]),
+        ];
+
+        assert_eq!(select_seeds(&entries), vec![(2, 95), (1, 80)]);
+    }
+
+    #[test]
+    fn seeds_are_capped_at_the_highest_scored_titles() {
+        let entries = (1..=8)
+            .map(|id| entry(id, MediaListStatus::Completed, 90 + id, &[]))
+            .collect::<Vec<_>>();
+
+        let seeds = select_seeds(&entries);
+
+        assert_eq!(seeds.len(), MAX_SEEDS);
+        assert_eq!(seeds.first(), Some(&(8, 98)));
+    }
+
+    #[test]
+    fn ranking_merges_seeds_and_skips_listed_titles() {
+        let entries = vec![
+            entry(1, MediaListStatus::Completed, 100, &["Drama"]),
+            entry(2, MediaListStatus::Completed, 80, &["Drama"]),
+            entry(30, MediaListStatus::Planning, 0, &[]),
+        ];
+        let seeds = vec![
+            seed(
+                1,
+                "First",
+                100,
+                vec![
+                    recommended(10, "Shared", 20, &["Action"]),
+                    recommended(30, "Planned", 90, &["Drama"]),
+                    recommended(40, "Disliked", -5, &["Drama"]),
+                ],
+            ),
+            seed(
+                2,
+                "Second",
+                80,
+                vec![recommended(10, "Shared", 20, &["Action"])],
+            ),
+        ];
+
+        let ranked = rank_personal_recommendations(&seeds, &entries, false);
+
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].media.id(), Some(10));
+        assert!((ranked[0].score - 36.0).abs() < f64::EPSILON);
+        assert_eq!(ranked[0].because.len(), 2);
+    }
+
+    #[test]
+    fn genre_affinity_lifts_titles_in_liked_genres() {
+        let entries = vec![entry(1, MediaListStatus::Completed, 100, &["Drama"])];
+        let seeds = vec![seed(
+            1,
+            "First",
+            100,
+            vec![
+                recommended(10, "Action Pick", 30, &["Action"]),
+                recommended(11, "Drama Pick", 25, &["Drama"]),
+            ],
+        )];
+
+        let ranked = rank_personal_recommendations(&seeds, &entries, false);
+
+        assert_eq!(ranked[0].media.id(), Some(11));
+        assert!((ranked[0].score - 37.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn users_without_liked_titles_are_told_what_is_needed() {
+        let response = handle_for_me(
+            &profile(),
+            MediaType::Manga,
+            &[entry(1, MediaListStatus::Completed, 50, &[])],
+            &[],
+            TitleDisplayPreference::Matched,
+            false,
+        );
+
+        assert!(
+            response
+                .unwrap_content()
+                .contains("completed manga you've scored 75+")
+        );
+    }
+
+    #[test]
+    fn personal_recommendations_return_embed() {
+        let entries = vec![entry(1, MediaListStatus::Completed, 90, &[])];
+        let seeds = vec![seed(
+            1,
+            "Cowboy Bebop",
+            90,
+            vec![recommended(205, "Samurai Champloo", 40, &["Action"])],
+        )];
+
+        let response = handle_for_me(
+            &profile(),
+            MediaType::Anime,
+            &entries,
+            &seeds,
+            TitleDisplayPreference::Matched,
+            false,
+        );
+
+        let value = serde_json::to_value(response.unwrap_embed()).expect("embed serializes");
+        assert_eq!(value["title"], "Anime picks for AniUser");
+        assert_eq!(value["fields"][0]["name"], "1. Samurai Champloo");
+        assert!(
+            value["fields"][0]["value"]
+                .as_str()
+                .unwrap()
+                .contains("Because you liked Cowboy Bebop")
+        );
+    }
+}
@@ -1,2 +1,4 @@
 pub mod command;
+pub mod for_me;
 pub mod queries;
+pub mod ranking;
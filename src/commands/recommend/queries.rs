@@ -1,71 +1,76 @@
+use std::sync::LazyLock;
+
+use crate::utils::requests::{
+    batch::IdBatcher,
+    fragments::{cover_image, media_by_id, media_core, media_search, title},
+    query::{Field, Selection, VariableType},
+};
+
 use tracing::instrument;
 
-const RECOMMENDATION_MEDIA_FIELDS: &str = "
-    type
-    id
-    isAdult
-    title {
-      romaji
-      english
-      native
-    }
-    synonyms
-    format
-    status
-    genres
-    coverImage {
-      extraLarge
-      large
-      medium
-      color
-    }
-    averageScore
-    siteUrl
-    recommendations(sort: RATING_DESC, page: 1, perPage: 10) {
-      nodes {
-        rating
-        mediaRecommendation {
-          type
-          isAdult
-          title {
-            romaji
-            english
-            native
-          }
-          format
-          status
-          genres
-          averageScore
-          siteUrl
-        }
-      }
-    }
-";
+/// Recommendation nodes fetched per page.
+pub const RECOMMENDATIONS_PER_PAGE: u32 = 25;
+
+/// `recommendations` sorted by rating, on the page given by `page` (a
+/// literal or a variable).
+fn recommendations_page(page: &str) -> Field {
+    Field::new("recommendations")
+        .arg("sort", "RATING_DESC")
+        .arg("page", page)
+        .arg("perPage", RECOMMENDATIONS_PER_PAGE)
+        .select(
+            Selection::new()
+                .object("pageInfo", Selection::new().field("hasNextPage"))
+                .object(
+                    "nodes",
+                    Selection::new().field("rating").object(
+                        "mediaRecommendation",
+                        Selection::new()
+                            .fields(&["type", "id", "isAdult"])
+                            .object("title", title())
+                            .fields(&["format", "status", "genres", "averageScore", "siteUrl"]),
+                    ),
+                ),
+        )
+}
+
+fn recommendation_media_fields() -> Selection {
+    Selection::new()
+        .spread(&media_core())
+        .spread(&cover_image())
+        .field(recommendations_page("1"))
+}
+
+/// Seeds per batched recommendation request. Each seed selects a full page of
+/// recommendations, so far fewer fit under AniList's complexity limit than the
+/// batcher's default.
+const RECOMMENDATIONS_MAX_BATCH: usize = 4;
+
+/// Batched recommendation lookups for seeds given by AniList ID, including
+/// the seeds of personal recommendations.
+pub static ANIME_RECOMMENDATIONS_BY_ID: LazyLock<IdBatcher> = LazyLock::new(|| {
+    IdBatcher::new("Media", &[("type", "ANIME")], recommendation_media_fields())
+        .with_max_batch(RECOMMENDATIONS_MAX_BATCH)
+});
 
+pub static MANGA_RECOMMENDATIONS_BY_ID: LazyLock<IdBatcher> = LazyLock::new(|| {
+    IdBatcher::new("Media", &[("type", "MANGA")], recommendation_media_fields())
+        .with_max_batch(RECOMMENDATIONS_MAX_BATCH)
+});
+
+/// A further page of a media's recommendations, past the first one the
+/// lookups above include.
 #[instrument]
-pub fn fetch_recommendations_by_id(media_type: &str) -> String {
-    format!(
-        "
-query ($id: Int) {{
-  Media(id: $id, type: {media_type}) {{
-{RECOMMENDATION_MEDIA_FIELDS}
-  }}
-}}
-"
+pub fn fetch_recommendations_page(media_type: &str) -> String {
+    media_by_id(
+        media_type,
+        Selection::new().field(recommendations_page("$page")),
     )
+    .variable("page", VariableType::Int)
+    .render()
 }
 
 #[instrument]
 pub fn fetch_recommendations_by_search(media_type: &str) -> String {
-    format!(
-        "
-query ($page: Int, $perPage: Int, $search: String) {{
-  Page(page: $page, perPage: $perPage) {{
-    media(search: $search, type: {media_type}) {{
-{RECOMMENDATION_MEDIA_FIELDS}
-    }}
-  }}
-}}
-"
-    )
+    media_search(media_type, recommendation_media_fields()).render()
 }
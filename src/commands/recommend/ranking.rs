@@ -0,0 +1,232 @@
+//! Merging community recommendations from one or more seed titles into a
+//! single ranking, shared by title-based and personal recommendations.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    models::{
+        anilist_recommendation::{RecommendationMedia, RecommendedMedia},
+        transformers::Transformers,
+    },
+    utils::formatter::remove_underscores_and_titlecase,
+};
+
+use tracing::instrument;
+
+/// Optional constraints on which recommended titles are shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecommendationFilters {
+    /// AniList `MediaFormat`, e.g. `TV` or `ONE_SHOT`.
+    pub format: Option<String>,
+    pub genre: Option<String>,
+    /// Lowest AniList average score (0-100).
+    pub min_score: Option<u32>,
+    /// AniList `MediaStatus`, e.g. `FINISHED`.
+    pub status: Option<String>,
+}
+
+impl RecommendationFilters {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn matches(&self, media: &RecommendedMedia) -> bool {
+        self.format
+            .as_deref()
+            .is_none_or(|format| media.format_text() == format)
+            && self.genre.as_deref().is_none_or(|genre| {
+                media
+                    .genres()
+                    .iter()
+                    .any(|candidate| candidate.eq_ignore_ascii_case(genre))
+            })
+            && self
+                .min_score
+                .is_none_or(|min_score| media.average_score().is_some_and(|s| s >= min_score))
+            && self
+                .status
+                .as_deref()
+                .is_none_or(|status| media.status_text() == status)
+    }
+
+    /// Short description for embed footers, e.g. `TV, Action, 75+, Finished`.
+    pub fn describe(&self) -> String {
+        [
+            self.format.as_deref().map(remove_underscores_and_titlecase),
+            self.genre.clone(),
+            self.min_score.map(|min_score| format!("{min_score}+")),
+            self.status.as_deref().map(remove_underscores_and_titlecase),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+#[derive(Debug)]
+pub struct RankedRecommendation<'a> {
+    pub media: &'a RecommendedMedia,
+    /// Ranking score: community ratings weighted per seed.
+    pub score: f64,
+    /// Sum of the community ratings, or `None` when AniList gave none.
+    pub rating: Option<i32>,
+    /// Seeds that recommended this title, in seed order.
+    pub because: Vec<&'a RecommendationMedia>,
+}
+
+/// Merge each seed's recommendations, weighting their community ratings by
+/// the seed's weight. Seeds themselves, `exclude`d titles, adult titles (when
+/// not allowed) and titles outside `filters` are dropped. Sorted best first;
+/// ties keep AniList's order.
+#[instrument(name = "command.recommend.merge", skip(seeds, exclude, filters))]
+pub fn merge_recommendations<'a>(
+    seeds: impl IntoIterator<Item = (&'a RecommendationMedia, f64)>,
+    exclude: &HashSet<u32>,
+    filters: &RecommendationFilters,
+    allow_adult_media: bool,
+) -> Vec<RankedRecommendation<'a>> {
+    let seeds = seeds.into_iter().collect::<Vec<_>>();
+    let seed_ids = seeds
+        .iter()
+        .map(|(seed, _)| seed.get_id())
+        .collect::<HashSet<_>>();
+    let mut ranked: Vec<RankedRecommendation<'a>> = Vec::new();
+    let mut positions: HashMap<u32, usize> = HashMap::new();
+
+    for (seed, weight) in seeds {
+        for recommendation in seed.recommendations() {
+            let Some(media) = recommendation.recommended_media() else {
+                continue;
+            };
+            let Some(media_id) = media.id() else {
+                continue;
+            };
+            if seed_ids.contains(&media_id)
+                || exclude.contains(&media_id)
+                || (media.is_adult() && !allow_adult_media)
+                || !filters.matches(media)
+            {
+                continue;
+            }
+
+            let position = *positions.entry(media_id).or_insert_with(|| {
+                ranked.push(RankedRecommendation {
+                    media,
+                    score: 0.0,
+                    rating: None,
+                    because: Vec::new(),
+                });
+                ranked.len() - 1
+            });
+            let entry = &mut ranked[position];
+            if let Some(rating) = recommendation.rating() {
+                entry.score += f64::from(rating) * weight;
+                entry.rating = Some(entry.rating.unwrap_or_default() + rating);
+            }
+            if !entry
+                .because
+                .iter()
+                .any(|known| known.get_id() == seed.get_id())
+            {
+                entry.because.push(seed);
+            }
+        }
+    }
+
+    sort_ranked(&mut ranked);
+    ranked
+}
+
+/// Best score first. The sort is stable, so equal scores keep their order.
+pub fn sort_ranked(ranked: &mut [RankedRecommendation]) {
+    ranked.sort_by(|left, right| right.score.total_cmp(&left.score));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed(id: u32, nodes: serde_json::Value) -> RecommendationMedia {
+        serde_json::from_value(serde_json::json!({
+            "type": "ANIME",
+            "id": id,
+            "isAdult": false,
+            "title": { "romaji": format!("Seed {id}"), "english": null, "native": null },
+            "synonyms": [],
+            "format": "TV",
+            "status": "FINISHED",
+            "genres": [],
+            "coverImage": { "extraLarge": null, "large": null, "medium": null, "color": null },
+            "averageScore": 80,
+            "siteUrl": format!("https://anilist.co/anime/{id}"),
+            "recommendations": { "nodes": nodes }
+        }))
+        .expect("seed should deserialize")
+    }
+
+    fn node(id: u32, rating: i32, format: &str, score: u32) -> serde_json::Value {
+        serde_json::json!({
+            "rating": rating,
+            "mediaRecommendation": {
+                "type": "ANIME",
+                "id": id,
+                "isAdult": false,
+                "title": { "romaji": format!("Pick {id}"), "english": null, "native": null },
+                "format": format,
+                "status": "FINISHED",
+                "genres": ["Action"],
+                "averageScore": score,
+                "siteUrl": format!("https://anilist.co/anime/{id}")
+            }
+        })
+    }
+
+    #[test]
+    fn recommendations_shared_by_seeds_are_merged() {
+        let first = seed(
+            1,
+            serde_json::json!([node(10, 5, "TV", 70), node(11, 8, "TV", 70)]),
+        );
+        let second = seed(
+            2,
+            serde_json::json!([node(10, 6, "TV", 70), node(1, 50, "TV", 70)]),
+        );
+
+        let ranked = merge_recommendations(
+            [(&first, 1.0), (&second, 1.0)],
+            &HashSet::new(),
+            &RecommendationFilters::default(),
+            false,
+        );
+
+        let ids = ranked.iter().map(|r| r.media.id()).collect::<Vec<_>>();
+        assert_eq!(ids, vec![Some(10), Some(11)]);
+        assert_eq!(ranked[0].rating, Some(11));
+        assert_eq!(ranked[0].because.len(), 2);
+    }
+
+    #[test]
+    fn filters_drop_non_matching_titles() {
+        let media = seed(
+            1,
+            serde_json::json!([
+                node(10, 5, "TV", 60),
+                node(11, 4, "MOVIE", 90),
+                node(12, 3, "TV", 90)
+            ]),
+        );
+        let filters = RecommendationFilters {
+            format: Some("TV".to_string()),
+            genre: Some("action".to_string()),
+            min_score: Some(75),
+            status: Some("FINISHED".to_string()),
+        };
+
+        let ranked = merge_recommendations([(&media, 1.0)], &HashSet::new(), &filters, false);
+
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].media.id(), Some(12));
+        assert_eq!(filters.describe(), "TV, action, 75+, Finished");
+    }
+}
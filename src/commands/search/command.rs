@@ -8,15 +8,23 @@ use crate::{
         traits::{AniListSource, MediaDataSource},
     },
     models::{
-        anilist_anime::Anime, anilist_common::TitleVariant, anilist_manga::Manga,
-        settings::TitleDisplayPreference, transformers::Transformers,
+        anilist_anime::Anime,
+        anilist_common::TitleVariant,
+        anilist_manga::Manga,
+        settings::{StreamingRegion, TitleDisplayPreference},
+        transformers::Transformers,
     },
     utils::{
         channel::is_nsfw_channel,
         llm::{LlmError, get_gemini_client_from_context},
         posthog::LlmTelemetryContext,
         privacy::{configure_sentry_scope, hash_discord_id, hash_user_id},
-        settings::{resolve_analytics_privacy_preference, resolve_title_display_preference},
+        requests::graphql::AniListError,
+        settings::{
+            resolve_analytics_privacy_preference, resolve_spoiler_preference,
+            resolve_streaming_region, resolve_title_display_preference,
+        },
+        statics::ANILIST_LOOKUP_FAILED,
         statics::ENV,
         statics::NSFW_NOT_ALLOWED,
     },
@@ -108,6 +116,9 @@ pub enum MediaSearchResult {
         intent: SearchIntent,
     },
     NotFound,
+    /// Nothing matched, but at least one lookup failed and may have hidden a
+    /// match.
+    LookupFailed(AniListError),
 }
 
 impl SearchIntent {
@@ -199,25 +210,34 @@ pub async fn fetch_search_result<S: MediaDataSource>(
         SearchMediaType::Anime => fetch_anime_candidates(source, intent).await,
         SearchMediaType::Manga => fetch_manga_candidates(source, intent).await,
         SearchMediaType::Unknown => {
+            let mut lookup_error = None;
             for search in intent.search_terms() {
-                if let Some((anime, title_variant)) = source.fetch_anime(&search).await {
-                    return MediaSearchResult::Anime {
-                        anime,
-                        title_variant: Some(title_variant),
-                        intent: intent.with_search(search),
-                    };
+                match source.fetch_anime(&search).await {
+                    Ok(Some((anime, title_variant))) => {
+                        return MediaSearchResult::Anime {
+                            anime,
+                            title_variant: Some(title_variant),
+                            intent: intent.with_search(search),
+                        };
+                    }
+                    Ok(None) => {}
+                    Err(err) => lookup_error = Some(err),
                 }
 
-                if let Some((manga, title_variant)) = source.fetch_manga(&search).await {
-                    return MediaSearchResult::Manga {
-                        manga,
-                        title_variant: Some(title_variant),
-                        intent: intent.with_search(search),
-                    };
+                match source.fetch_manga(&search).await {
+                    Ok(Some((manga, title_variant))) => {
+                        return MediaSearchResult::Manga {
+                            manga,
+                            title_variant: Some(title_variant),
+                            intent: intent.with_search(search),
+                        };
+                    }
+                    Ok(None) => {}
+                    Err(err) => lookup_error = Some(err),
                 }
             }
 
-            MediaSearchResult::NotFound
+            not_found(lookup_error)
         }
     }
 }
@@ -227,17 +247,22 @@ async fn fetch_anime_candidates<S: MediaDataSource>(
     source: &S,
     intent: SearchIntent,
 ) -> MediaSearchResult {
+    let mut lookup_error = None;
     for search in intent.search_terms() {
-        if let Some((anime, title_variant)) = source.fetch_anime(&search).await {
-            return MediaSearchResult::Anime {
-                anime,
-                title_variant: Some(title_variant),
-                intent: intent.with_search(search),
-            };
+        match source.fetch_anime(&search).await {
+            Ok(Some((anime, title_variant))) => {
+                return MediaSearchResult::Anime {
+                    anime,
+                    title_variant: Some(title_variant),
+                    intent: intent.with_search(search),
+                };
+            }
+            Ok(None) => {}
+            Err(err) => lookup_error = Some(err),
         }
     }
 
-    MediaSearchResult::NotFound
+    not_found(lookup_error)
 }
 
 #[instrument(name = "command.search.fetch_manga_candidates", skip(source, intent))]
@@ -245,23 +270,41 @@ async fn fetch_manga_candidates<S: MediaDataSource>(
     source: &S,
     intent: SearchIntent,
 ) -> MediaSearchResult {
+    let mut lookup_error = None;
     for search in intent.search_terms() {
-        if let Some((manga, title_variant)) = source.fetch_manga(&search).await {
-            return MediaSearchResult::Manga {
-                manga,
-                title_variant: Some(title_variant),
-                intent: intent.with_search(search),
-            };
+        match source.fetch_manga(&search).await {
+            Ok(Some((manga, title_variant))) => {
+                return MediaSearchResult::Manga {
+                    manga,
+                    title_variant: Some(title_variant),
+                    intent: intent.with_search(search),
+                };
+            }
+            Ok(None) => {}
+            Err(err) => lookup_error = Some(err),
         }
     }
 
-    MediaSearchResult::NotFound
+    not_found(lookup_error)
+}
+
+#[instrument(name = "command.search.not_found", skip(lookup_error))]
+fn not_found(lookup_error: Option<AniListError>) -> MediaSearchResult {
+    match lookup_error {
+        Some(err) => {
+            warn!(error = %err, "AniList lookup failed while searching");
+            MediaSearchResult::LookupFailed(err)
+        }
+        None => MediaSearchResult::NotFound,
+    }
 }
 
 #[instrument(name = "command.search.build_response", skip(result))]
 pub fn build_response(
     result: MediaSearchResult,
     title_preference: TitleDisplayPreference,
+    allow_spoilers: bool,
+    streaming_region: StreamingRegion,
 ) -> CommandResponse {
     match result {
         MediaSearchResult::Anime {
@@ -272,6 +315,8 @@ pub fn build_response(
             None,
             title_variant,
             title_preference,
+            allow_spoilers,
+            streaming_region,
         ))),
         MediaSearchResult::Manga {
             manga,
@@ -281,8 +326,15 @@ pub fn build_response(
             None,
             title_variant,
             title_preference,
+            allow_spoilers,
+            StreamingRegion::All,
         ))),
         MediaSearchResult::NotFound => CommandResponse::Content(NOT_FOUND_SEARCH.to_string()),
+        MediaSearchResult::LookupFailed(err) => CommandResponse::Content(
+            err.user_message()
+                .unwrap_or(ANILIST_LOOKUP_FAILED)
+                .to_string(),
+        ),
     }
 }
 
@@ -360,11 +412,13 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
             }
         };
 
-        fetch_search_result(&AniListSource, intent).await
+        fetch_search_result(&AniListSource::from_context(ctx).await, intent).await
     };
-    let (result, title_preference) = tokio::join!(
+    let (result, title_preference, allow_spoilers, streaming_region) = tokio::join!(
         search_result_future,
         resolve_title_display_preference(ctx, user.id, interaction.guild_id),
+        resolve_spoiler_preference(ctx, user.id, None),
+        resolve_streaming_region(ctx, user.id, interaction.guild_id),
     );
 
     match &result {
@@ -391,9 +445,9 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
         MediaSearchResult::Anime { intent, .. } | MediaSearchResult::Manga { intent, .. } => {
             Some(format_interpretation(intent))
         }
-        MediaSearchResult::NotFound => None,
+        MediaSearchResult::NotFound | MediaSearchResult::LookupFailed(_) => None,
     };
-    let response = build_response(result, title_preference);
+    let response = build_response(result, title_preference, allow_spoilers, streaming_region);
     let _result = match response {
         CommandResponse::Content(text) | CommandResponse::Message(text) => {
             let builder = EditInteractionResponse::new().content(match interpretation {
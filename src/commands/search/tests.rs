@@ -18,12 +18,28 @@ impl LlmClient for FakeLlm {
 
 struct FakeMediaSource {
     calls: Mutex<Vec<String>>,
+    error: Option<AniListError>,
 }
 
 impl FakeMediaSource {
     fn new() -> Self {
         Self {
             calls: Mutex::new(Vec::new()),
+            error: None,
+        }
+    }
+
+    fn failing(error: AniListError) -> Self {
+        Self {
+            calls: Mutex::new(Vec::new()),
+            error: Some(error),
+        }
+    }
+
+    fn result<T>(&self) -> Result<Option<T>, AniListError> {
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(None),
         }
     }
 
@@ -33,20 +49,26 @@ impl FakeMediaSource {
 }
 
 impl MediaDataSource for FakeMediaSource {
-    async fn fetch_anime(&self, search_term: &str) -> Option<(Anime, TitleVariant)> {
+    async fn fetch_anime(
+        &self,
+        search_term: &str,
+    ) -> Result<Option<(Anime, TitleVariant)>, AniListError> {
         self.calls
             .lock()
             .unwrap()
             .push(format!("anime:{search_term}"));
-        None
+        self.result()
     }
 
-    async fn fetch_manga(&self, search_term: &str) -> Option<(Manga, TitleVariant)> {
+    async fn fetch_manga(
+        &self,
+        search_term: &str,
+    ) -> Result<Option<(Manga, TitleVariant)>, AniListError> {
         self.calls
             .lock()
             .unwrap()
             .push(format!("manga:{search_term}"));
-        None
+        self.result()
     }
 }
 
@@ -275,6 +297,29 @@ async fn fetch_search_result_tries_both_for_unknown_intent() {
     assert_eq!(source.calls(), vec!["anime:monster", "manga:monster"]);
 }
 
+#[tokio::test]
+async fn fetch_search_result_reports_lookup_failures_after_trying_every_term() {
+    let source = FakeMediaSource::failing(AniListError::InvalidResponse("boom".to_string()));
+    let intent = SearchIntent {
+        media_type: SearchMediaType::Unknown,
+        search: "monster".to_string(),
+        candidates: vec!["Monster (2004)".to_string()],
+    };
+
+    let result = fetch_search_result(&source, intent).await;
+
+    assert!(matches!(result, MediaSearchResult::LookupFailed(_)));
+    assert_eq!(
+        source.calls(),
+        vec![
+            "anime:monster",
+            "manga:monster",
+            "anime:Monster (2004)",
+            "manga:Monster (2004)"
+        ]
+    );
+}
+
 #[tokio::test]
 async fn fetch_search_result_accepts_long_valid_fallback_term() {
     let source = FakeMediaSource::new();
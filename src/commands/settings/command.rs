@@ -541,7 +541,7 @@ fn format_guild_layer(
     value: Option<SettingValue>,
     guild_available: bool,
 ) -> String {
-    if key == SettingKey::AnalyticsPrivacy {
+    if key.is_user_only() {
         return "not applicable".to_string();
     }
 
@@ -573,7 +573,7 @@ fn format_allowed_values(key: SettingKey) -> String {
 #[instrument(name = "command.settings.components", skip(panel))]
 fn settings_panel_components(panel: &SettingsPanel) -> Vec<CreateActionRow> {
     let active = panel.category;
-    let mut rows = vec![CreateActionRow::Buttons(vec![
+    let mut buttons = vec![
         panel_button(
             SettingsPanelCategory::Overview,
             "Overview",
@@ -598,7 +598,25 @@ fn settings_panel_components(panel: &SettingsPanel) -> Vec<CreateActionRow> {
             settings_category_custom_id(SettingKey::GuildScores),
             active,
         ),
-    ])];
+        panel_button(
+            SettingsPanelCategory::Setting(SettingKey::Spoilers),
+            "Spoilers",
+            settings_category_custom_id(SettingKey::Spoilers),
+            active,
+        ),
+        panel_button(
+            SettingsPanelCategory::Setting(SettingKey::StreamingRegion),
+            "Streaming region",
+            settings_category_custom_id(SettingKey::StreamingRegion),
+            active,
+        ),
+    ];
+    // Discord fits five buttons in a row; split the categories evenly.
+    let second_row = buttons.split_off(buttons.len().div_ceil(2));
+    let mut rows = vec![
+        CreateActionRow::Buttons(buttons),
+        CreateActionRow::Buttons(second_row),
+    ];
 
     if let SettingsPanelCategory::Setting(key) = active
         && let Some(summary) = panel
@@ -662,7 +680,7 @@ fn setting_select_option(
 
 #[instrument(name = "command.settings.guild_select_available")]
 fn guild_select_available(key: SettingKey, guild_available: bool, can_manage_guild: bool) -> bool {
-    guild_available && can_manage_guild && key != SettingKey::AnalyticsPrivacy
+    guild_available && can_manage_guild && !key.is_user_only()
 }
 
 #[instrument(name = "command.settings.allowed_values_for_scope")]
@@ -673,7 +691,7 @@ fn allowed_values_for_scope(
     match (scope, key) {
         (SettingScope::User, SettingKey::GuildScores) => Some(&["enabled", "opted_out"]),
         (SettingScope::Guild, SettingKey::GuildScores) => Some(&["enabled", "disabled"]),
-        (SettingScope::Guild, SettingKey::AnalyticsPrivacy) => None,
+        (SettingScope::Guild, key) if key.is_user_only() => None,
         _ => Some(key.allowed_values()),
     }
 }
@@ -954,7 +972,7 @@ mod tests {
         let value = serde_json::to_value(settings_panel_components(&panel))
             .expect("components should serialize");
 
-        assert_eq!(value.as_array().expect("rows").len(), 3);
+        assert_eq!(value.as_array().expect("rows").len(), 4);
         assert!(value.to_string().contains(&settings_set_custom_id(
             SettingScope::User,
             SettingKey::TitleDisplay
@@ -978,7 +996,7 @@ mod tests {
         let value = serde_json::to_value(settings_panel_components(&panel))
             .expect("components should serialize");
 
-        assert_eq!(value.as_array().expect("rows").len(), 2);
+        assert_eq!(value.as_array().expect("rows").len(), 3);
         assert!(value.to_string().contains(&settings_set_custom_id(
             SettingScope::User,
             SettingKey::TitleDisplay
@@ -1001,7 +1019,7 @@ mod tests {
         let value = serde_json::to_value(settings_panel_components(&panel))
             .expect("components should serialize");
 
-        assert_eq!(value.as_array().expect("rows").len(), 2);
+        assert_eq!(value.as_array().expect("rows").len(), 3);
         assert!(value.to_string().contains(&settings_set_custom_id(
             SettingScope::User,
             SettingKey::AnalyticsPrivacy
@@ -1013,6 +1031,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn overview_buttons_split_across_two_rows() {
+        let panel = plan_settings_panel(
+            SettingsPanelCategory::Overview,
+            true,
+            true,
+            test_summaries(),
+        );
+
+        let value = serde_json::to_value(settings_panel_components(&panel))
+            .expect("components should serialize");
+        let rows = value.as_array().expect("rows");
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["components"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            rows[1]["components"][2]["custom_id"],
+            settings_category_custom_id(SettingKey::StreamingRegion)
+        );
+    }
+
     #[test]
     fn guild_scores_scope_values_prevent_invalid_combinations() {
         assert_eq!(
@@ -5,8 +5,10 @@ use crate::{
     },
     models::mal_response::{MalResponse, ParsedSong},
     utils::{
-        privacy::configure_sentry_scope, spotify::enrich_songs_with_spotify,
-        statics::NOT_FOUND_ANIME,
+        cache::{Cache, get_cache_from_context},
+        privacy::configure_sentry_scope,
+        spotify::enrich_songs_with_spotify,
+        statics::{ANILIST_LOOKUP_FAILED, MAL_UNAVAILABLE, NOT_FOUND_ANIME},
     },
 };
 
@@ -18,8 +20,7 @@ use serenity::{
     model::application::CommandOptionType,
 };
 
-use tokio::task;
-use tracing::{error, info, instrument};
+use tracing::{info, instrument};
 
 pub fn register() -> CreateCommand {
     CreateCommand::new("songs")
@@ -56,32 +57,18 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
         return;
     }
 
-    let response = SongFetcher(arg).await;
+    let cache = get_cache_from_context(ctx).await;
+    let response = SongFetcher(arg, &cache).await;
 
     let _songs_response = match response {
         SongFetchResult::Found(mal_response) => {
-            // Pure parsing — no I/O, no spawn_blocking needed
+            // Pure parsing — no I/O
             let openings = mal_response.parse_openings();
             let endings = mal_response.parse_endings();
 
-            // Narrow spawn_blocking: only the sync Spotify + Redis I/O
-            let (openings, endings) = match task::spawn_blocking(move || {
-                enrich_song_sections(openings, endings)
-            })
-            .await
-            {
-                Ok(result) => result,
-                Err(err) => {
-                    error!(error = %err, "spawn_blocking panicked during Spotify enrichment");
-                    let builder = EditInteractionResponse::new().content(
-                        "I found the anime, but something went wrong while checking theme song links. Please try again later.",
-                    );
-                    let _ = interaction.edit_response(&ctx.http, builder).await;
-                    return;
-                }
-            };
-
-            // Pure formatting — no I/O, no spawn_blocking needed
+            let (openings, endings) = enrich_song_sections(cache.as_ref(), openings, endings).await;
+
+            // Pure formatting — no I/O
             let builder = EditInteractionResponse::new().embed(
                 CreateEmbed::new()
                     .title(mal_response.transform_title())
@@ -104,12 +91,21 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
             let builder = EditInteractionResponse::new().content(NOT_FOUND_ANIME);
             interaction.edit_response(&ctx.http, builder).await
         }
+        SongFetchResult::AnimeLookupFailed(err) => {
+            let builder = EditInteractionResponse::new()
+                .content(err.user_message().unwrap_or(ANILIST_LOOKUP_FAILED));
+            interaction.edit_response(&ctx.http, builder).await
+        }
         SongFetchResult::AnimeNotFoundOnMal => {
             let builder = EditInteractionResponse::new().content(
                 "I found that anime on AniList, but couldn't find a MyAnimeList page for its theme songs.",
             );
             interaction.edit_response(&ctx.http, builder).await
         }
+        SongFetchResult::MalUnavailable => {
+            let builder = EditInteractionResponse::new().content(MAL_UNAVAILABLE);
+            interaction.edit_response(&ctx.http, builder).await
+        }
         SongFetchResult::FetchError => {
             let builder = EditInteractionResponse::new()
                 .content("I couldn't fetch theme song data right now. Please try again later.");
@@ -118,19 +114,21 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
     };
 }
 
-#[instrument(name = "songs.enrich_spotify_section", skip(openings, endings), fields(openings_len = openings.len(), endings_len = endings.len()))]
-fn enrich_song_sections(
+#[instrument(name = "songs.enrich_spotify_section", skip(cache, openings, endings), fields(openings_len = openings.len(), endings_len = endings.len()))]
+async fn enrich_song_sections(
+    cache: &dyn Cache,
     mut openings: Vec<ParsedSong>,
     mut endings: Vec<ParsedSong>,
 ) -> (Vec<ParsedSong>, Vec<ParsedSong>) {
-    enrich_songs_with_spotify(&mut openings);
-    enrich_songs_with_spotify(&mut endings);
+    enrich_songs_with_spotify(cache, &mut openings).await;
+    enrich_songs_with_spotify(cache, &mut endings).await;
     (openings, endings)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::cache::InMemoryCache;
 
     fn song_without_artist(song_name: &str, display_number: u32) -> ParsedSong {
         ParsedSong {
@@ -144,12 +142,13 @@ mod tests {
         }
     }
 
-    #[test]
-    fn enrich_song_sections_leaves_songs_without_artists_unchanged() {
+    #[tokio::test]
+    async fn enrich_song_sections_leaves_songs_without_artists_unchanged() {
         let openings = vec![song_without_artist("Opening Song", 1)];
         let endings = vec![song_without_artist("Ending Song", 2)];
 
-        let (openings, endings) = enrich_song_sections(openings, endings);
+        let (openings, endings) =
+            enrich_song_sections(&InMemoryCache::default(), openings, endings).await;
 
         assert_eq!(openings.len(), 1);
         assert_eq!(endings.len(), 1);
@@ -3,7 +3,14 @@ use crate::{
         anilist_anime::Anime, mal_response::MalResponse, media_type::MediaType as Type,
         transformers::Transformers,
     },
-    utils::{requests::my_anime_list, response_fetcher::fetcher as anime_fetcher},
+    utils::{
+        cache::SharedCache,
+        requests::{
+            graphql::AniListError,
+            my_anime_list::{self, MalRequestError},
+        },
+        response_fetcher::fetcher as anime_fetcher,
+    },
 };
 
 use serenity::all::CommandDataOptionValue;
@@ -12,15 +19,21 @@ use tracing::{error, info, instrument};
 pub enum SongFetchResult {
     Found(MalResponse),
     AnimeNotFound,
+    AnimeLookupFailed(AniListError),
     AnimeNotFoundOnMal,
+    MalUnavailable,
     FetchError,
 }
 
-#[instrument(name = "command.songs.fetcher", skip(args))]
-pub async fn fetcher(args: CommandDataOptionValue) -> SongFetchResult {
-    let anime_response = anime_fetcher::<Anime>(Type::Anime, args).await;
-    let Some((anime, _variant)) = anime_response else {
-        return SongFetchResult::AnimeNotFound;
+#[instrument(name = "command.songs.fetcher", skip(args, cache))]
+pub async fn fetcher(args: CommandDataOptionValue, cache: &SharedCache) -> SongFetchResult {
+    let anime = match anime_fetcher::<Anime>(Type::Anime, args, cache).await {
+        Ok(Some((anime, _variant))) => anime,
+        Ok(None) => return SongFetchResult::AnimeNotFound,
+        Err(err) => {
+            error!(error = %err, "Failed to fetch anime for theme songs");
+            return SongFetchResult::AnimeLookupFailed(err);
+        }
     };
 
     let Some(mal_id) = anime.get_mal_id() else {
@@ -30,6 +43,7 @@ pub async fn fetcher(args: CommandDataOptionValue) -> SongFetchResult {
 
     let mal_fetcher_response = match my_anime_list::send_request(mal_id).await {
         Ok(response) => response,
+        Err(MalRequestError::CircuitOpen) => return SongFetchResult::MalUnavailable,
         Err(err) => {
             error!(error = %err, mal_id = mal_id, "Failed to fetch MAL data for anime");
             return SongFetchResult::FetchError;
@@ -1,11 +1,10 @@
 use crate::{
     commands::{
-        input_validation::validate_search_term,
-        response::CommandResponse,
-        studio::fetcher::{StudioFetchError, fetch_studio},
+        input_validation::validate_search_term, response::CommandResponse,
+        studio::fetcher::fetch_studio,
     },
     models::anilist_studio::Studio,
-    utils::privacy::configure_sentry_scope,
+    utils::{privacy::configure_sentry_scope, requests::graphql::AniListError},
 };
 
 use serde_json::json;
@@ -49,13 +48,18 @@ fn parse_studio_options(options: &[CommandDataOption]) -> Option<String> {
         })
 }
 
-pub fn handle_studio(result: Result<Option<Studio>, StudioFetchError>) -> CommandResponse {
+pub fn handle_studio(result: Result<Option<Studio>, AniListError>) -> CommandResponse {
     match result {
         Ok(Some(studio)) => CommandResponse::Embed(Box::new(studio.transform_response_embed())),
         Ok(None) => CommandResponse::Content(NOT_FOUND_STUDIO.to_string()),
         Err(error) => {
             error!(error = %error, "Studio lookup failed");
-            CommandResponse::Content(STUDIO_LOOKUP_ERROR.to_string())
+            CommandResponse::Content(
+                error
+                    .user_message()
+                    .unwrap_or(STUDIO_LOOKUP_ERROR)
+                    .to_string(),
+            )
         }
     }
 }
@@ -108,6 +112,10 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{
+        requests::anilist::AniListRequestError,
+        statics::{ANILIST_RATE_LIMITED, ANILIST_UNAVAILABLE},
+    };
 
     fn sample_studio() -> Studio {
         serde_json::from_value(serde_json::json!({
@@ -155,11 +163,27 @@ mod tests {
 
     #[test]
     fn fetch_error_returns_retryable_content() {
-        let response = handle_studio(Err(StudioFetchError::InvalidResponse(
-            "bad JSON".to_string(),
-        )));
+        let response = handle_studio(Err(AniListError::InvalidResponse("bad JSON".to_string())));
 
         assert!(response.is_content());
         assert_eq!(response.unwrap_content(), STUDIO_LOOKUP_ERROR);
     }
+
+    #[test]
+    fn rate_limited_lookup_asks_user_to_retry_later() {
+        let response = handle_studio(Err(AniListError::Request(
+            AniListRequestError::RateLimited {
+                retry_after: std::time::Duration::from_secs(60),
+            },
+        )));
+
+        assert_eq!(response.unwrap_content(), ANILIST_RATE_LIMITED);
+    }
+
+    #[test]
+    fn open_circuit_tells_user_anilist_is_having_trouble() {
+        let response = handle_studio(Err(AniListError::Request(AniListRequestError::CircuitOpen)));
+
+        assert_eq!(response.unwrap_content(), ANILIST_UNAVAILABLE);
+    }
 }
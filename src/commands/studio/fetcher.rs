@@ -1,74 +1,35 @@
 use crate::{
     models::anilist_studio::Studio,
-    utils::requests::anilist::{AniListRequestError, send_request},
+    utils::requests::{
+        anilist::{AniListRequestError, send_request},
+        graphql::{AniListError, classify},
+        query::{Field, Query, Selection, VariableType},
+    },
 };
 
 use serde::Deserialize;
 use serde_json::{Value, json};
 use tracing::instrument;
 
-const STUDIO_FIELDS: &str = r#"
-    id
-    name
-    isAnimationStudio
-    favourites
-    siteUrl
-    media(page: 1, perPage: 5, sort: POPULARITY_DESC, isMain: true) {
-      nodes {
-        id
-        title {
-          romaji
-          english
-        }
-        siteUrl
-        isAdult
-      }
-    }
-"#;
-
-const FETCH_STUDIO_BY_ID: &str = r#"
-query ($id: Int) {
-  Studio(id: $id) {
-"#;
-
-const FETCH_STUDIO_BY_SEARCH: &str = r#"
-query ($search: String) {
-  Studio(search: $search) {
-"#;
-
-const QUERY_END: &str = "  }\n}\n";
-
-#[derive(Debug)]
-pub enum StudioFetchError {
-    Request(AniListRequestError),
-    InvalidResponse(String),
-    GraphQl(String),
-}
-
-impl std::fmt::Display for StudioFetchError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Request(error) => write!(f, "{error}"),
-            Self::InvalidResponse(error) => {
-                write!(f, "AniList returned an invalid studio response: {error}")
-            }
-            Self::GraphQl(error) => write!(f, "AniList returned a GraphQL error: {error}"),
-        }
-    }
-}
-
-impl std::error::Error for StudioFetchError {}
-
-impl From<AniListRequestError> for StudioFetchError {
-    fn from(error: AniListRequestError) -> Self {
-        Self::Request(error)
-    }
-}
-
-#[derive(Deserialize)]
-struct StudioResponse {
-    data: Option<StudioData>,
-    errors: Option<Vec<GraphQlError>>,
+fn studio_fields() -> Selection {
+    Selection::new()
+        .fields(&["id", "name", "isAnimationStudio", "favourites", "siteUrl"])
+        .field(
+            Field::new("media")
+                .arg("page", 1)
+                .arg("perPage", 5)
+                .arg("sort", "POPULARITY_DESC")
+                .arg("isMain", true)
+                .select(
+                    Selection::new().object(
+                        "nodes",
+                        Selection::new()
+                            .field("id")
+                            .object("title", Selection::new().fields(&["romaji", "english"]))
+                            .fields(&["siteUrl", "isAdult"]),
+                    ),
+                ),
+        )
 }
 
 #[derive(Deserialize)]
@@ -77,14 +38,8 @@ struct StudioData {
     studio: Option<Studio>,
 }
 
-#[derive(Deserialize)]
-struct GraphQlError {
-    message: String,
-    status: Option<u16>,
-}
-
 #[instrument(name = "anilist.studio.fetch", fields(search_len = search_term.len()))]
-pub async fn fetch_studio(search_term: &str) -> Result<Option<Studio>, StudioFetchError> {
+pub async fn fetch_studio(search_term: &str) -> Result<Option<Studio>, AniListError> {
     let request = build_request(search_term);
     parse_request_result(send_request(request).await)
 }
@@ -92,50 +47,29 @@ pub async fn fetch_studio(search_term: &str) -> Result<Option<Studio>, StudioFet
 #[instrument(name = "anilist.studio.parse_request_result", skip(result))]
 fn parse_request_result(
     result: Result<String, AniListRequestError>,
-) -> Result<Option<Studio>, StudioFetchError> {
-    match result {
-        Ok(response) => parse_response(&response),
-        Err(AniListRequestError::NonSuccessStatus { status: 404, .. }) => Ok(None),
-        Err(error) => Err(error.into()),
-    }
+) -> Result<Option<Studio>, AniListError> {
+    Ok(classify::<StudioData>(result)?.and_then(|data| data.studio))
 }
 
 #[instrument(name = "anilist.studio.build_request", skip(search_term))]
 fn build_request(search_term: &str) -> Value {
-    match search_term.parse::<u32>() {
-        Ok(id) => json!({
-            "query": format!("{FETCH_STUDIO_BY_ID}{STUDIO_FIELDS}{QUERY_END}"),
-            "variables": { "id": id },
-        }),
-        Err(_) => json!({
-            "query": format!("{FETCH_STUDIO_BY_SEARCH}{STUDIO_FIELDS}{QUERY_END}"),
-            "variables": { "search": search_term },
-        }),
-    }
-}
-
-#[instrument(name = "anilist.studio.parse_response", skip(response))]
-fn parse_response(response: &str) -> Result<Option<Studio>, StudioFetchError> {
-    let response: StudioResponse = serde_json::from_str(response)
-        .map_err(|error| StudioFetchError::InvalidResponse(error.to_string()))?;
-
-    if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
-        if errors.iter().all(|error| error.status == Some(404)) {
-            return Ok(None);
-        }
-
-        return Err(StudioFetchError::GraphQl(
-            errors
-                .into_iter()
-                .map(|error| error.message)
-                .collect::<Vec<_>>()
-                .join("; "),
-        ));
-    }
-
-    response.data.map(|data| data.studio).ok_or_else(|| {
-        StudioFetchError::InvalidResponse("response did not contain data".to_string())
-    })
+    let (argument, variable_type, variables) = match search_term.parse::<u32>() {
+        Ok(id) => ("id", VariableType::Int, json!({ "id": id })),
+        Err(_) => (
+            "search",
+            VariableType::String,
+            json!({ "search": search_term }),
+        ),
+    };
+
+    Query::new()
+        .variable(argument, variable_type)
+        .field(
+            Field::new("Studio")
+                .arg(argument, format!("${argument}"))
+                .select(studio_fields()),
+        )
+        .request(variables)
 }
 
 #[cfg(test)]
@@ -172,24 +106,27 @@ mod tests {
 
     #[test]
     fn null_studio_is_not_found() {
-        let studio = parse_response(r#"{"data":{"Studio":null}}"#).unwrap();
+        let studio = parse_request_result(Ok(r#"{"data":{"Studio":null}}"#.to_string())).unwrap();
 
         assert!(studio.is_none());
     }
 
     #[test]
     fn graphql_errors_are_not_treated_as_not_found() {
-        let error =
-            parse_response(r#"{"data":null,"errors":[{"message":"Unavailable"}]}"#).unwrap_err();
+        let error = parse_request_result(Ok(
+            r#"{"data":null,"errors":[{"message":"Unavailable"}]}"#.to_string(),
+        ))
+        .unwrap_err();
 
-        assert!(matches!(error, StudioFetchError::GraphQl(_)));
+        assert!(matches!(error, AniListError::GraphQl { .. }));
     }
 
     #[test]
     fn graphql_not_found_error_is_treated_as_not_found() {
-        let studio =
-            parse_response(r#"{"data":null,"errors":[{"message":"Not Found.","status":404}]}"#)
-                .unwrap();
+        let studio = parse_request_result(Ok(
+            r#"{"data":null,"errors":[{"message":"Not Found.","status":404}]}"#.to_string(),
+        ))
+        .unwrap();
 
         assert!(studio.is_none());
     }
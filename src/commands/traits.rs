@@ -28,9 +28,17 @@
 
 use std::future::Future;
 
-use crate::models::{
-    anilist_anime::Anime, anilist_character::Character, anilist_common::TitleVariant,
-    anilist_manga::Manga,
+use serenity::client::Context;
+
+use crate::{
+    models::{
+        anilist_anime::Anime, anilist_character::Character, anilist_common::TitleVariant,
+        anilist_manga::Manga,
+    },
+    utils::{
+        cache::{SharedCache, get_cache_from_context},
+        requests::graphql::AniListError,
+    },
 };
 
 /// Abstraction over media-data retrieval (AniList today, pluggable tomorrow).
@@ -45,64 +53,91 @@ use crate::models::{
 pub trait MediaDataSource: Send + Sync {
     /// Fetch anime data for the given search term (name **or** numeric ID).
     ///
-    /// Returns `None` when no matching anime is found.
+    /// Returns `Ok(None)` when no matching anime is found, and the AniList
+    /// error when the lookup itself failed.
     fn fetch_anime(
         &self,
         search_term: &str,
-    ) -> impl Future<Output = Option<(Anime, TitleVariant)>> + Send;
+    ) -> impl Future<Output = Result<Option<(Anime, TitleVariant)>, AniListError>> + Send;
 
     /// Fetch manga data for the given search term (name **or** numeric ID).
     ///
-    /// Returns `None` when no matching manga is found.
+    /// Returns `Ok(None)` when no matching manga is found, and the AniList
+    /// error when the lookup itself failed.
     fn fetch_manga(
         &self,
         search_term: &str,
-    ) -> impl Future<Output = Option<(Manga, TitleVariant)>> + Send;
+    ) -> impl Future<Output = Result<Option<(Manga, TitleVariant)>, AniListError>> + Send;
 }
 
 pub trait CharacterDataSource: Send + Sync {
     /// Fetch character data for the given search term (name **or** numeric ID).
     ///
-    /// Returns `None` when no matching character is found.
+    /// Returns `Ok(None)` when no matching character is found, and the
+    /// AniList error when the lookup itself failed.
     fn fetch_character(
         &self,
         search_term: &str,
         allow_spoilers: bool,
-    ) -> impl Future<Output = Option<Character>> + Send;
+    ) -> impl Future<Output = Result<Option<Character>, AniListError>> + Send;
 }
 
 /// Production [`MediaDataSource`] backed by the AniList GraphQL API.
 ///
 /// This delegates to the existing [`crate::utils::response_fetcher::fetcher`]
-/// pipeline, preserving current caching (Redis) and fuzzy-match behaviour.
-pub struct AniListSource;
+/// pipeline, preserving current caching and fuzzy-match behaviour. The cache
+/// is injected so tests can swap Redis for an in-memory implementation.
+pub struct AniListSource {
+    cache: SharedCache,
+}
+
+impl AniListSource {
+    pub fn new(cache: SharedCache) -> Self {
+        Self { cache }
+    }
+
+    /// Build a source backed by the cache stored in the Serenity `TypeMap`.
+    pub async fn from_context(ctx: &Context) -> Self {
+        Self::new(get_cache_from_context(ctx).await)
+    }
+}
 
 impl MediaDataSource for AniListSource {
-    async fn fetch_anime(&self, search_term: &str) -> Option<(Anime, TitleVariant)> {
+    async fn fetch_anime(
+        &self,
+        search_term: &str,
+    ) -> Result<Option<(Anime, TitleVariant)>, AniListError> {
         use crate::models::media_type::MediaType;
         use crate::utils::response_fetcher::fetcher;
         use serenity::all::CommandDataOptionValue;
 
         let arg = CommandDataOptionValue::String(search_term.to_string());
-        fetcher::<Anime>(MediaType::Anime, arg).await
+        fetcher::<Anime>(MediaType::Anime, arg, &self.cache).await
     }
 
-    async fn fetch_manga(&self, search_term: &str) -> Option<(Manga, TitleVariant)> {
+    async fn fetch_manga(
+        &self,
+        search_term: &str,
+    ) -> Result<Option<(Manga, TitleVariant)>, AniListError> {
         use crate::models::media_type::MediaType;
         use crate::utils::response_fetcher::fetcher;
         use serenity::all::CommandDataOptionValue;
 
         let arg = CommandDataOptionValue::String(search_term.to_string());
-        fetcher::<Manga>(MediaType::Manga, arg).await
+        fetcher::<Manga>(MediaType::Manga, arg, &self.cache).await
     }
 }
 
 impl CharacterDataSource for AniListSource {
-    async fn fetch_character(&self, search_term: &str, allow_spoilers: bool) -> Option<Character> {
+    async fn fetch_character(
+        &self,
+        search_term: &str,
+        allow_spoilers: bool,
+    ) -> Result<Option<Character>, AniListError> {
         use crate::utils::response_fetcher::character_fetcher;
         use serenity::all::CommandDataOptionValue;
 
         let arg = CommandDataOptionValue::String(search_term.to_string());
-        character_fetcher(arg, allow_spoilers).await
+        character_fetcher(arg, allow_spoilers, self.cache.as_ref()).await
     }
 }
@@ -2,6 +2,7 @@ use crate::{
     commands::response::CommandResponse,
     utils::{
         database::get_pool_from_context,
+        guild::forget_linked_accounts,
         privacy::{configure_sentry_scope, hash_user_id},
     },
 };
@@ -229,6 +230,10 @@ pub async fn run(ctx: &Context, interaction: &mut CommandInteraction) {
         }
     };
 
+    if matches!(outcome, UnregisterOutcome::Unlinked) {
+        forget_linked_accounts();
+    }
+
     let builder = match handle_unregister(outcome) {
         CommandResponse::Content(content) => EditInteractionResponse::new().content(content),
         CommandResponse::Embed(embed) => EditInteractionResponse::new().embed(*embed),
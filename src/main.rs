@@ -25,12 +25,14 @@ use serenity::{
 };
 
 use utils::{
+    cache::{CacheKey, MemoryCache, SharedCache, TieredCache},
     channel::is_nsfw_channel,
     database::{DatabasePoolKey, create_pool, run_migrations},
     llm::{GeminiClient, GeminiClientKey, configured_model_name},
     oauth::{OAuthContextConfigKey, load_context_config},
     posthog::{CommandTelemetryContext, PostHogClient},
     privacy::{hash_discord_id, hash_user_id, redact_url_credentials},
+    redis::RedisCache,
     statics::{DISCORD_TOKEN, ENV, SENTRY_DSN, SENTRY_TRACES_SAMPLE_RATE},
     tls::install_rustls_crypto_provider,
 };
@@ -87,6 +89,10 @@ impl EventHandler for Handler {
                         "anime" => commands::anime::command::run(&ctx, &mut command).await,
                         "search" => commands::search::command::run(&ctx, &mut command).await,
                         "recommend" => commands::recommend::command::run(&ctx, &mut command).await,
+                        "next" => commands::next::command::run(&ctx, &mut command).await,
+                        "binge" => commands::binge::command::run(&ctx, &mut command).await,
+                        "discover" => commands::discover::command::run(&ctx, &mut command).await,
+                        "episodes" => commands::episodes::command::run(&ctx, &mut command).await,
                         "character" => commands::character::command::run(&ctx, &mut command).await,
                         "studio" => commands::studio::command::run(&ctx, &mut command).await,
                         "register" => commands::register::command::run(&ctx, &mut command).await,
@@ -125,6 +131,26 @@ impl EventHandler for Handler {
 
                     if commands::settings::is_settings_component(&component.data.custom_id) {
                         commands::settings::handle_component(&ctx, &mut component).await;
+                    } else if commands::guild_scores::is_guild_scores_component(
+                        &component.data.custom_id,
+                    ) {
+                        commands::guild_scores::handle_component(&ctx, &mut component).await;
+                    } else if commands::recommend::command::is_recommend_component(
+                        &component.data.custom_id,
+                    ) {
+                        commands::recommend::command::handle_component(&ctx, &mut component).await;
+                    } else if commands::discover::command::is_discover_component(
+                        &component.data.custom_id,
+                    ) {
+                        commands::discover::command::handle_component(&ctx, &mut component).await;
+                    } else if commands::episodes::command::is_episodes_component(
+                        &component.data.custom_id,
+                    ) {
+                        commands::episodes::command::handle_component(&ctx, &mut component).await;
+                    } else if commands::media_tabs::is_media_tabs_component(
+                        &component.data.custom_id,
+                    ) {
+                        commands::media_tabs::handle_component(&ctx, &mut component).await;
                     } else {
                         let builder = CreateInteractionResponse::Message(
                             CreateInteractionResponseMessage::new()
@@ -139,6 +165,21 @@ impl EventHandler for Handler {
                 .instrument(component_span)
                 .await;
             }
+            Interaction::Autocomplete(autocomplete) => {
+                let autocomplete_span = info_span!(
+                    "discord.autocomplete",
+                    command_name = %autocomplete.data.name,
+                    user_id = %hash_user_id(autocomplete.user.id.get())
+                );
+
+                async {
+                    if autocomplete.data.name == "discover" {
+                        commands::discover::command::autocomplete(&ctx, &autocomplete).await;
+                    }
+                }
+                .instrument(autocomplete_span)
+                .await;
+            }
             _ => {}
         }
     }
@@ -153,6 +194,10 @@ impl EventHandler for Handler {
             commands::anime::command::register(),
             commands::search::command::register(),
             commands::recommend::command::register(),
+            commands::next::command::register(),
+            commands::binge::command::register(),
+            commands::discover::command::register(),
+            commands::episodes::command::register(),
             commands::character::command::register(),
             commands::studio::command::register(),
             commands::register::command::register(),
@@ -321,14 +366,22 @@ async fn main() {
         .await
         .expect("Failed to run database migrations");
 
+    info!("Connecting to Redis");
+    let remote_cache: Option<SharedCache> = match RedisCache::connect().await {
+        Ok(cache) => Some(Arc::new(cache)),
+        Err(error) => {
+            warn!(error = %error, "Redis not configured; using in-memory cache only");
+            None
+        }
+    };
+    let cache: SharedCache = Arc::new(TieredCache::new(MemoryCache::default(), remote_cache));
+
     info!("Loading OAuth configuration");
     let oauth_config = load_context_config().expect("Failed to load OAuth context config");
 
     let token = env::var(DISCORD_TOKEN).expect("Expected a token in the environment");
-    let intents = GatewayIntents::GUILD_MESSAGES
-        | GatewayIntents::DIRECT_MESSAGES
-        | GatewayIntents::GUILD_PRESENCES
-        | GatewayIntents::GUILDS;
+    let intents =
+        GatewayIntents::GUILD_MESSAGES | GatewayIntents::DIRECT_MESSAGES | GatewayIntents::GUILDS;
 
     info!("Creating Discord client");
     let mut client = Client::builder(&token, intents)
@@ -343,6 +396,7 @@ async fn main() {
         let mut data = client.data.write().await;
         data.insert::<DatabasePoolKey>(database_pool);
         data.insert::<OAuthContextConfigKey>(Arc::new(oauth_config));
+        data.insert::<CacheKey>(cache);
         if let Some(gemini_client) = gemini_client {
             data.insert::<GeminiClientKey>(gemini_client);
         }
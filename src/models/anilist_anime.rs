@@ -1,15 +1,35 @@
+use std::collections::BTreeMap;
+
 use crate::{
     models::{
         anilist_common::{CoverImage, ExternalLinks, Tag, Title},
+        settings::StreamingRegion,
         transformers::Transformers,
     },
     utils::{
-        formatter::{code, linker, titlecase},
+        formatter::{bold, code, format_approximate_runtime, linker, titlecase},
         statics::{ANILIST_STATUS_RELEASING, EMPTY_STR},
     },
 };
 
-use serde::Deserialize;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Discord's limit on an embed field value.
+const STREAMING_FIELD_LIMIT: usize = 1024;
+
+/// A name for streaming links AniList hasn't given a `site` for.
+fn known_site(url: &str) -> Option<&'static str> {
+    if url.contains("hbo") {
+        Some("HBO")
+    } else if url.contains("netflix") {
+        Some("Netflix")
+    } else if url.contains("crunchyroll") {
+        Some("Crunchyroll")
+    } else {
+        None
+    }
+}
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -39,6 +59,10 @@ pub struct Anime {
     trailer: Option<Trailer>,
     description: Option<String>,
     tags: Vec<Tag>,
+    /// Set when this entry was served from the stale cache tier because
+    /// AniList was unreachable.
+    #[serde(skip)]
+    stale: bool,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -66,10 +90,73 @@ pub struct Trailer {
     pub site: String,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct NextAiringEpisode {
     pub episode: Option<u32>,
+    /// Unix timestamp, in seconds.
+    pub airing_at: Option<i64>,
+}
+
+/// `Episode 8 airs <t:…:R>` for the next episode, which Discord shows in
+/// each viewer's own timezone. Nothing once that time has passed, since a
+/// cached entry can outlive it.
+pub fn format_next_airing(
+    next_airing_episode: Option<&NextAiringEpisode>,
+    now: i64,
+) -> Option<String> {
+    let next = next_airing_episode?;
+    let airing_at = next.airing_at.filter(|airing_at| *airing_at > now)?;
+    Some(match next.episode {
+        Some(episode) => format!("Episode {episode} airs <t:{airing_at}:R>"),
+        None => format!("Next episode airs <t:{airing_at}:R>"),
+    })
+}
+
+/// Episodes aired so far out of the total while airing (`7/12`), otherwise
+/// the total.
+pub fn format_episodes(
+    status: Option<&str>,
+    episodes: Option<u32>,
+    next_airing_episode: Option<&NextAiringEpisode>,
+) -> String {
+    if status == Some(ANILIST_STATUS_RELEASING)
+        && let Some(next_episode) = next_airing_episode.and_then(|next| next.episode)
+    {
+        let aired_episodes = next_episode.saturating_sub(1);
+
+        if let Some(total_episodes) = episodes {
+            return format!("{aired_episodes}/{total_episodes}");
+        }
+
+        return aired_episodes.to_string();
+    }
+
+    match episodes {
+        Some(episodes) => episodes.to_string(),
+        None => EMPTY_STR.to_string(),
+    }
+}
+
+/// Episodes out so far: those aired while airing, otherwise the total.
+pub fn released_episodes(
+    status: Option<&str>,
+    episodes: Option<u32>,
+    next_airing_episode: Option<&NextAiringEpisode>,
+) -> Option<u32> {
+    if status == Some(ANILIST_STATUS_RELEASING)
+        && let Some(next_episode) = next_airing_episode.and_then(|next| next.episode)
+    {
+        return Some(next_episode.saturating_sub(1));
+    }
+    episodes
+}
+
+pub fn format_duration(duration: Option<u32>) -> String {
+    match duration {
+        Some(duration) => format!("{duration} mins"),
+        None => EMPTY_STR.to_string(),
+    }
 }
 
 impl Anime {
@@ -93,30 +180,33 @@ impl Anime {
     }
 
     pub fn transform_episodes(&self) -> String {
-        if self.status.as_deref() == Some(ANILIST_STATUS_RELEASING)
-            && let Some(next_airing_episode) = &self.next_airing_episode
-            && let Some(next_episode) = next_airing_episode.episode
-        {
-            let aired_episodes = next_episode.saturating_sub(1);
-
-            if let Some(total_episodes) = self.episodes {
-                return format!("{aired_episodes}/{total_episodes}");
-            }
+        format_episodes(
+            self.status.as_deref(),
+            self.episodes,
+            self.next_airing_episode.as_ref(),
+        )
+    }
 
-            return aired_episodes.to_string();
-        }
+    pub fn transform_duration(&self) -> String {
+        format_duration(self.duration)
+    }
 
-        match &self.episodes {
-            Some(episodes) => episodes.to_string(),
-            None => EMPTY_STR.to_string(),
-        }
+    pub fn released_episodes(&self) -> Option<u32> {
+        released_episodes(
+            self.status.as_deref(),
+            self.episodes,
+            self.next_airing_episode.as_ref(),
+        )
     }
 
-    pub fn transform_duration(&self) -> String {
-        match &self.duration {
-            Some(duration) => format!("{duration} mins"),
-            None => EMPTY_STR.to_string(),
-        }
+    /// Minutes per episode.
+    pub fn duration(&self) -> Option<u32> {
+        self.duration
+    }
+
+    /// Minutes to watch every episode, planned or aired.
+    pub fn total_runtime(&self) -> Option<u32> {
+        Some(self.episodes? * self.duration?)
     }
 
     pub fn transform_studios(&self) -> String {
@@ -234,6 +324,14 @@ impl Transformers for Anime {
         &self.tags
     }
 
+    fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+
     fn transform_mal_id(&self) -> Option<String> {
         self.id_mal
             .map(|mal_id| format!("https://www.myanimelist.net/anime/{mal_id}"))
@@ -244,49 +342,81 @@ impl Transformers for Anime {
     }
 
     fn transform_episodes_chapters(&self) -> String {
-        self.transform_episodes()
+        match format_next_airing(self.next_airing_episode.as_ref(), Utc::now().timestamp()) {
+            Some(next_airing) => format!("{}\n{next_airing}", self.transform_episodes()),
+            None => self.transform_episodes(),
+        }
     }
 
     fn transform_duration_volumes(&self) -> String {
-        self.transform_duration()
+        match self.total_runtime().filter(|minutes| *minutes > 0) {
+            Some(minutes) => format!(
+                "{}\n{} total",
+                self.transform_duration(),
+                format_approximate_runtime(minutes)
+            ),
+            None => self.transform_duration(),
+        }
     }
 
     fn transform_studios_staff(&self) -> String {
         self.transform_studios()
     }
 
-    fn transform_links(&self) -> String {
-        let Some(links) = &self.external_links else {
-            return EMPTY_STR.to_string();
-        };
+    fn transform_links(&self, region: StreamingRegion) -> String {
+        let mut groups: BTreeMap<Option<&str>, Vec<String>> = BTreeMap::new();
+        let links = self.external_links.iter().flatten().filter(|link| {
+            link.url_type.eq_ignore_ascii_case("streaming")
+                && link.is_disabled != Some(true)
+                && region.includes(link.language.as_deref())
+        });
+        for link in links {
+            let Some(site) = link.site.as_deref().or_else(|| known_site(&link.url)) else {
+                continue;
+            };
+            let mut rendered = linker(site, &link.url);
+            if let Some(notes) = link
+                .notes
+                .as_deref()
+                .filter(|notes| !notes.trim().is_empty())
+            {
+                rendered.push_str(&format!(" ({})", notes.trim()));
+            }
+            let language = link
+                .language
+                .as_deref()
+                .filter(|_| region.language().is_none());
+            groups.entry(language).or_default().push(rendered);
+        }
 
-        if links.is_empty() {
+        // Untagged services sort first; with one group there's nothing to label.
+        if groups.is_empty() {
             return EMPTY_STR.to_string();
         }
-
-        let parsed_links: String = links
-            .iter()
-            .filter(|link| link.url_type.to_lowercase() == "streaming")
-            .filter_map(|link| {
-                let url = link.url.as_str();
-                if url.contains("hbo") {
-                    Some(linker("HBO", url))
-                } else if url.contains("netflix") {
-                    Some(linker("Netflix", url))
-                } else if url.contains("crunchyroll") {
-                    Some(linker("Crunchyroll", url))
-                } else {
-                    None
+        let labelled = groups.len() > 1;
+
+        // Add links one at a time so a long group still shows every whole link
+        // that fits, leaving room for the trailing `…`.
+        let mut field = String::new();
+        for (line, (language, links)) in groups.into_iter().enumerate() {
+            for (position, link) in links.iter().enumerate() {
+                let piece = match position {
+                    0 if labelled => format!("{}: {link}", bold(language.unwrap_or("Global"))),
+                    0 => link.clone(),
+                    _ => format!(" {link}"),
+                };
+                let separator = if position == 0 && line > 0 { "\n" } else { "" };
+                if field.chars().count() + separator.len() + piece.chars().count() + 2
+                    > STREAMING_FIELD_LIMIT
+                {
+                    field.push_str(if position == 0 { "\n…" } else { " …" });
+                    return field.trim_start().to_string();
                 }
-            })
-            .collect::<Vec<String>>()
-            .join(" ");
-
-        if parsed_links.is_empty() {
-            EMPTY_STR.to_string()
-        } else {
-            parsed_links
+                field.push_str(separator);
+                field.push_str(&piece);
+            }
         }
+        field
     }
 
     fn transform_trailer(&self) -> String {
@@ -318,12 +448,35 @@ impl Transformers for Anime {
 
 #[cfg(test)]
 mod tests {
-    use super::Anime;
+    use super::{Anime, NextAiringEpisode, STREAMING_FIELD_LIMIT, format_next_airing};
+    use crate::models::{
+        settings::{StreamingRegion, TitleDisplayPreference},
+        transformers::Transformers,
+    };
     use crate::utils::statics::{ANILIST_STATUS_FINISHED, ANILIST_STATUS_RELEASING};
     use serde_json::json;
 
     fn sample_anime(status: &str, episodes: Option<u32>, next_episode: Option<u32>) -> Anime {
-        serde_json::from_value(json!({
+        sample_anime_with_duration(status, episodes, next_episode, None)
+    }
+
+    fn sample_anime_with_duration(
+        status: &str,
+        episodes: Option<u32>,
+        next_episode: Option<u32>,
+        duration: Option<u32>,
+    ) -> Anime {
+        serde_json::from_value(sample_anime_json(status, episodes, next_episode, duration))
+            .expect("sample anime JSON should deserialize")
+    }
+
+    fn sample_anime_json(
+        status: &str,
+        episodes: Option<u32>,
+        next_episode: Option<u32>,
+        duration: Option<u32>,
+    ) -> serde_json::Value {
+        json!({
             "type": "ANIME",
             "id": 1,
             "idMal": null,
@@ -340,7 +493,7 @@ mod tests {
             "status": status,
             "episodes": episodes,
             "nextAiringEpisode": next_episode.map(|episode| json!({ "episode": episode })),
-            "duration": null,
+            "duration": duration,
             "genres": [],
             "source": null,
             "coverImage": {
@@ -356,8 +509,7 @@ mod tests {
             "trailer": null,
             "description": null,
             "tags": []
-        }))
-        .expect("sample anime JSON should deserialize")
+        })
     }
 
     #[test]
@@ -380,4 +532,175 @@ mod tests {
 
         assert_eq!(anime.transform_episodes(), "7");
     }
+
+    #[test]
+    fn duration_field_adds_an_approximate_total() {
+        let anime = sample_anime_with_duration(ANILIST_STATUS_FINISHED, Some(26), None, Some(24));
+
+        assert_eq!(anime.transform_duration_volumes(), "24 mins\n≈ 10h total");
+    }
+
+    #[test]
+    fn duration_field_skips_the_total_when_episodes_are_unknown() {
+        let anime = sample_anime_with_duration(ANILIST_STATUS_RELEASING, None, Some(8), Some(24));
+
+        assert_eq!(anime.transform_duration_volumes(), "24 mins");
+    }
+
+    #[test]
+    fn embed_ranks_tags_and_lists_content_warnings_separately() {
+        let mut value = sample_anime_json(ANILIST_STATUS_FINISHED, Some(12), None, Some(24));
+        value["tags"] = json!([
+            { "name": "Gore", "rank": 90, "category": "Theme-Other", "isMediaSpoiler": false, "isGeneralSpoiler": false },
+            { "name": "Tragedy", "rank": 61, "category": "Theme-Drama", "isMediaSpoiler": true, "isGeneralSpoiler": false },
+            { "name": "Survival", "rank": 85, "category": "Theme-Other", "isMediaSpoiler": false, "isGeneralSpoiler": false },
+            { "name": "Nudity", "rank": 20, "category": "Sexual Content", "isMediaSpoiler": false, "isGeneralSpoiler": true }
+        ]);
+        let anime: Anime = serde_json::from_value(value).expect("anime should deserialize");
+
+        assert_eq!(
+            anime.transform_tags(),
+            "*Survival* (85%)\n||*Tragedy* (61%)||"
+        );
+        assert_eq!(
+            anime.transform_content_warnings().as_deref(),
+            Some("Gore, ||Nudity||")
+        );
+        let embed = serde_json::to_value(anime.transform_response_embed(
+            None,
+            None,
+            TitleDisplayPreference::Matched,
+            false,
+            StreamingRegion::All,
+        ))
+        .expect("embed serializes");
+        assert!(
+            embed["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|field| field["name"] == "Content Warnings")
+        );
+    }
+
+    #[test]
+    fn description_hides_spoilers_unless_allowed() {
+        let mut value = sample_anime_json(ANILIST_STATUS_FINISHED, Some(12), None, Some(24));
+        value["description"] = json!(
+            "A hero sets out.<br><span class='markdown_spoiler'><span>The hero dies.</span></span>"
+        );
+        let anime: Anime = serde_json::from_value(value).expect("anime should deserialize");
+
+        let hidden = anime.transform_description_and_mal_link(false);
+        assert!(hidden.contains("A hero sets out."));
+        assert!(!hidden.contains("The hero dies."));
+        assert!(
+            anime
+                .transform_description_and_mal_link(true)
+                .contains("The hero dies.")
+        );
+    }
+
+    #[test]
+    fn streaming_links_are_filtered_and_grouped_by_region() {
+        let mut value = sample_anime_json(ANILIST_STATUS_FINISHED, Some(12), None, Some(24));
+        value["externalLinks"] = json!([
+            { "url": "https://www.crunchyroll.com/sample", "type": "STREAMING", "site": "Crunchyroll", "language": null, "isDisabled": false, "notes": null },
+            { "url": "https://www.hidive.com/sample", "type": "STREAMING", "site": "HIDIVE", "language": "English", "isDisabled": false, "notes": "Dub only" },
+            { "url": "https://abema.tv/sample", "type": "STREAMING", "site": "ABEMA", "language": "Japanese", "isDisabled": false, "notes": null },
+            { "url": "https://www.funimation.com/sample", "type": "STREAMING", "site": "Funimation", "language": "English", "isDisabled": true, "notes": null },
+            { "url": "https://twitter.com/sample", "type": "SOCIAL", "site": "Twitter", "language": null, "isDisabled": false, "notes": null }
+        ]);
+        let anime: Anime = serde_json::from_value(value).expect("anime should deserialize");
+
+        assert_eq!(
+            anime.transform_links(StreamingRegion::All),
+            "**Global**: [Crunchyroll](https://www.crunchyroll.com/sample)\n\
+             **English**: [HIDIVE](https://www.hidive.com/sample) (Dub only)\n\
+             **Japanese**: [ABEMA](https://abema.tv/sample)"
+        );
+        assert_eq!(
+            anime.transform_links(StreamingRegion::English),
+            "[Crunchyroll](https://www.crunchyroll.com/sample) [HIDIVE](https://www.hidive.com/sample) (Dub only)"
+        );
+        assert_eq!(
+            anime.transform_links(StreamingRegion::Korean),
+            "[Crunchyroll](https://www.crunchyroll.com/sample)"
+        );
+    }
+
+    #[test]
+    fn streaming_links_without_a_site_fall_back_to_known_services() {
+        let mut value = sample_anime_json(ANILIST_STATUS_FINISHED, Some(12), None, Some(24));
+        value["externalLinks"] = json!([
+            { "url": "https://www.netflix.com/title/1", "type": "STREAMING" },
+            { "url": "https://example.com/stream", "type": "STREAMING" }
+        ]);
+        let anime: Anime = serde_json::from_value(value).expect("anime should deserialize");
+
+        assert_eq!(
+            anime.transform_links(StreamingRegion::All),
+            "[Netflix](https://www.netflix.com/title/1)"
+        );
+    }
+
+    #[test]
+    fn long_streaming_groups_keep_the_links_that_fit() {
+        let mut value = sample_anime_json(ANILIST_STATUS_FINISHED, Some(12), None, Some(24));
+        value["externalLinks"] = json!(
+            (0..40)
+                .map(|index| json!({
+                    "url": format!("https://stream.example.com/{index}/{}", "x".repeat(20)),
+                    "type": "STREAMING",
+                    "site": format!("Service {index}")
+                }))
+                .collect::<Vec<_>>()
+        );
+        let anime: Anime = serde_json::from_value(value).expect("anime should deserialize");
+
+        let field = anime.transform_links(StreamingRegion::All);
+        assert!(field.chars().count() <= STREAMING_FIELD_LIMIT);
+        assert!(field.starts_with("[Service 0](https://stream.example.com/0/"));
+        assert!(field.ends_with(") …"));
+        assert!(!field.contains("Service 39"));
+        let kept = field.matches("](https://").count();
+        assert!(kept > 1);
+        assert_eq!(field.matches(')').count(), kept);
+    }
+
+    #[test]
+    fn episodes_field_counts_down_to_the_next_episode() {
+        let mut value = sample_anime_json(ANILIST_STATUS_RELEASING, Some(12), Some(8), Some(24));
+        value["nextAiringEpisode"] = json!({ "episode": 8, "airingAt": 4_102_444_800_i64 });
+        let anime: Anime = serde_json::from_value(value).expect("anime should deserialize");
+
+        assert_eq!(
+            anime.transform_episodes_chapters(),
+            "7/12\nEpisode 8 airs <t:4102444800:R>"
+        );
+    }
+
+    #[test]
+    fn next_airing_is_skipped_once_it_has_passed() {
+        let next = NextAiringEpisode {
+            episode: Some(8),
+            airing_at: Some(1_700_000_000),
+        };
+
+        assert_eq!(format_next_airing(Some(&next), 1_700_000_001), None);
+        assert_eq!(
+            format_next_airing(Some(&next), 1_699_999_999).as_deref(),
+            Some("Episode 8 airs <t:1700000000:R>")
+        );
+        assert_eq!(
+            format_next_airing(
+                Some(&NextAiringEpisode {
+                    episode: Some(8),
+                    airing_at: None,
+                }),
+                0
+            ),
+            None
+        );
+    }
 }
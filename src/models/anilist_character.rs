@@ -1,22 +1,21 @@
 use crate::utils::{
     formatter::{code, linker, titlecase},
+    spoilers::filter_spoilers,
     statics::EMPTY_STR,
 };
 
 use html2md::parse_html;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serenity::all::{CreateEmbed, CreateEmbedFooter};
 
 const DISCORD_EMBED_DESCRIPTION_LIMIT: usize = 4096;
 const DESCRIPTION_ELLIPSIS: &str = "...";
-const MARKDOWN_SPOILER_CLASS: &str = "markdown_spoiler";
-const SPAN_OPEN: &str = "<span";
-const SPAN_CLOSE: &str = "</span>";
+/// Members mentioned in the guild favourites field before summarising the rest.
+const GUILD_FAVOURITES_PREVIEW_LIMIT: usize = 20;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Character {
-    #[allow(dead_code)]
     id: u32,
     name: CharacterName,
     image: Option<CharacterImage>,
@@ -30,7 +29,7 @@ pub struct Character {
     media: Option<CharacterMediaConnection>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CharacterName {
     pub full: Option<String>,
@@ -40,25 +39,25 @@ pub struct CharacterName {
     pub user_preferred: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CharacterImage {
     pub large: Option<String>,
     pub medium: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CharacterDate {
     year: Option<u32>,
     month: Option<u32>,
     day: Option<u32>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CharacterMediaConnection {
     nodes: Option<Vec<CharacterMedia>>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CharacterMedia {
     #[allow(dead_code)]
@@ -70,7 +69,7 @@ pub struct CharacterMedia {
     is_adult: Option<bool>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CharacterMediaTitle {
     romaji: Option<String>,
     english: Option<String>,
@@ -166,12 +165,7 @@ impl Character {
             .description
             .as_deref()
             .unwrap_or("<i>No Description Yet</i>");
-        let filtered_description = if allow_spoilers {
-            description_html.to_string()
-        } else {
-            strip_spoiler_html(description_html)
-        };
-        let description = parse_html(&filtered_description);
+        let description = parse_html(&filter_spoilers(description_html, allow_spoilers));
 
         if description.chars().count() <= DISCORD_EMBED_DESCRIPTION_LIMIT {
             return description;
@@ -214,6 +208,10 @@ impl Character {
             .unwrap_or_else(|| EMPTY_STR.to_string())
     }
 
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
     pub fn transform_favourites(&self) -> String {
         self.favourites.map_or_else(
             || EMPTY_STR.to_string(),
@@ -282,8 +280,9 @@ impl Character {
         &self,
         allow_adult_media: bool,
         allow_spoilers: bool,
+        guild_favourites: Option<Vec<u64>>,
     ) -> CreateEmbed {
-        let embed = CreateEmbed::new()
+        let mut embed = CreateEmbed::new()
             .color(0x00_68_A8)
             .title(self.transform_name())
             .description(self.transform_description(allow_spoilers))
@@ -300,6 +299,14 @@ impl Character {
             ])
             .field("Appears In", self.transform_media(allow_adult_media), false);
 
+        if let Some(guild_favourites) = guild_favourites {
+            embed = embed.field(
+                "Favourited in this Server",
+                format_guild_favourites(&guild_favourites),
+                false,
+            );
+        }
+
         match self.transform_thumbnail() {
             Some(thumbnail) => embed.thumbnail(thumbnail),
             None => embed,
@@ -307,60 +314,29 @@ impl Character {
     }
 }
 
-fn strip_spoiler_html(html: &str) -> String {
-    let mut output = String::default();
-    let mut remaining = html;
-
-    while let Some(class_index) = remaining.find(MARKDOWN_SPOILER_CLASS) {
-        let Some(open_start) = remaining[..class_index].rfind(SPAN_OPEN) else {
-            output.push_str(&remaining[..class_index + MARKDOWN_SPOILER_CLASS.len()]);
-            remaining = &remaining[class_index + MARKDOWN_SPOILER_CLASS.len()..];
-            continue;
-        };
-
-        output.push_str(&remaining[..open_start]);
-
-        let Some(open_end_offset) = remaining[class_index..].find('>') else {
-            remaining = &remaining[open_start..];
-            break;
-        };
-        let mut cursor = class_index + open_end_offset + 1;
-        let mut span_depth = 1;
-
-        while span_depth > 0 {
-            let next_open = remaining[cursor..]
-                .find(SPAN_OPEN)
-                .map(|offset| cursor + offset);
-            let next_close = remaining[cursor..]
-                .find(SPAN_CLOSE)
-                .map(|offset| cursor + offset);
-
-            match (next_open, next_close) {
-                (Some(open), Some(close)) if open < close => {
-                    span_depth += 1;
-                    cursor = open + SPAN_OPEN.len();
-                }
-                (_, Some(close)) => {
-                    span_depth -= 1;
-                    cursor = close + SPAN_CLOSE.len();
-                }
-                _ => {
-                    cursor = remaining.len();
-                    break;
-                }
-            }
-        }
-
-        remaining = &remaining[cursor..];
+fn format_guild_favourites(discord_ids: &[u64]) -> String {
+    let mut mentions = discord_ids
+        .iter()
+        .take(GUILD_FAVOURITES_PREVIEW_LIMIT)
+        .map(|discord_id| format!("<@{discord_id}>"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if discord_ids.len() > GUILD_FAVOURITES_PREVIEW_LIMIT {
+        mentions.push_str(&format!(
+            " and {} more",
+            discord_ids.len() - GUILD_FAVOURITES_PREVIEW_LIMIT
+        ));
     }
-
-    output.push_str(remaining);
-    output
+    mentions
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Character, DESCRIPTION_ELLIPSIS, DISCORD_EMBED_DESCRIPTION_LIMIT, EMPTY_STR};
+    use super::{
+        Character, DESCRIPTION_ELLIPSIS, DISCORD_EMBED_DESCRIPTION_LIMIT, EMPTY_STR,
+        format_guild_favourites,
+    };
 
     fn sample_character() -> Character {
         serde_json::from_value(serde_json::json!({
@@ -610,7 +586,7 @@ mod tests {
         }))
         .expect("sample character JSON should deserialize");
 
-        let embed = character.transform_response_embed(false, false);
+        let embed = character.transform_response_embed(false, false, None);
         let value = serde_json::to_value(&embed).expect("embed serializes");
 
         assert!(character.transform_thumbnail().is_none());
@@ -619,11 +595,34 @@ mod tests {
 
     #[test]
     fn success_embed_serializes() {
-        let embed = sample_character().transform_response_embed(false, false);
+        let embed = sample_character().transform_response_embed(false, false, None);
         let value = serde_json::to_value(&embed).expect("embed serializes");
 
         assert_eq!(value["title"], "Lelouch Lamperouge");
         assert_eq!(value["url"], "https://anilist.co/character/40");
         assert_eq!(value["thumbnail"]["url"], "https://example.com/medium.jpg");
     }
+
+    #[test]
+    fn guild_favourites_are_listed_as_mentions() {
+        let embed = sample_character().transform_response_embed(false, false, Some(vec![1, 2]));
+        let value = serde_json::to_value(&embed).expect("embed serializes");
+        let field = value["fields"]
+            .as_array()
+            .and_then(|fields| fields.last())
+            .expect("embed has fields");
+
+        assert_eq!(field["name"], "Favourited in this Server");
+        assert_eq!(field["value"], "<@1>, <@2>");
+    }
+
+    #[test]
+    fn long_guild_favourite_lists_are_summarised() {
+        let discord_ids = (1..=25).collect::<Vec<u64>>();
+
+        let mentions = format_guild_favourites(&discord_ids);
+
+        assert!(mentions.starts_with("<@1>, <@2>"));
+        assert!(mentions.ends_with("<@20> and 5 more"));
+    }
 }
@@ -1,6 +1,10 @@
-use serde::Deserialize;
+use std::cmp::Reverse;
 
-#[derive(Deserialize, Debug, Clone)]
+use crate::utils::formatter::{italics, spoiler};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Title {
     pub romaji: Option<String>,
     pub english: Option<String>,
@@ -11,7 +15,7 @@ pub struct Title {
 ///
 /// Used to decide which variant is surfaced as the embed title vs the footer,
 /// so the primary title mirrors what the user typed.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TitleVariant {
     English,
     Romaji,
@@ -21,7 +25,7 @@ pub enum TitleVariant {
     Native,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CoverImage {
     pub extra_large: Option<String>,
@@ -31,13 +35,119 @@ pub struct CoverImage {
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct ExternalLinks {
     pub url: String,
     #[serde(alias = "type")]
     pub url_type: String,
+    /// The service's display name, e.g. "Crunchyroll".
+    pub site: Option<String>,
+    /// The audience a regional link is for, e.g. "English"; unset for
+    /// services available everywhere.
+    pub language: Option<String>,
+    pub is_disabled: Option<bool>,
+    pub notes: Option<String>,
 }
 
+/// Tag categories whose tags are listed as content warnings.
+const SENSITIVE_TAG_CATEGORIES: &[&str] = &["Sexual Content"];
+
+/// Tags outside those categories that still warrant a content warning.
+const SENSITIVE_TAG_NAMES: &[&str] = &[
+    "Animal Abuse",
+    "Bullying",
+    "Cannibalism",
+    "Drugs",
+    "Gore",
+    "Rape",
+    "Self-Harm",
+    "Suicide",
+    "Torture",
+];
+
 #[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Tag {
     pub name: String,
+    /// How relevant the tag is to this media, 0-100.
+    pub rank: Option<u32>,
+    pub category: Option<String>,
+    /// The tag spoils something about this media.
+    pub is_media_spoiler: Option<bool>,
+    /// The tag is a spoiler by nature, whatever the media.
+    pub is_general_spoiler: Option<bool>,
+}
+
+impl Tag {
+    pub fn is_spoiler(&self) -> bool {
+        self.is_media_spoiler.unwrap_or(false) || self.is_general_spoiler.unwrap_or(false)
+    }
+
+    pub fn is_content_warning(&self) -> bool {
+        self.category
+            .as_deref()
+            .is_some_and(|category| SENSITIVE_TAG_CATEGORIES.contains(&category))
+            || SENSITIVE_TAG_NAMES.contains(&self.name.as_str())
+    }
+
+    /// The tag name with its rank, e.g. `*Isekai* (94%)`, behind spoiler
+    /// markup when it is a spoiler.
+    pub fn display(&self) -> String {
+        let name = italics(&self.name);
+        let shown = match self.rank {
+            Some(rank) => format!("{name} ({rank}%)"),
+            None => name,
+        };
+        if self.is_spoiler() {
+            spoiler(&shown)
+        } else {
+            shown
+        }
+    }
+}
+
+/// Tags ordered by rank, most relevant first. Unranked tags go last and ties
+/// keep AniList's order.
+pub fn ranked_tags(tags: &[Tag]) -> Vec<&Tag> {
+    let mut ranked = tags.iter().collect::<Vec<_>>();
+    ranked.sort_by_key(|tag| Reverse(tag.rank));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags() -> Vec<Tag> {
+        serde_json::from_value(serde_json::json!([
+            { "name": "Time Skip", "rank": 70, "category": "Theme-Other", "isMediaSpoiler": true, "isGeneralSpoiler": false },
+            { "name": "Isekai", "rank": 94, "category": "Setting-Universe", "isMediaSpoiler": false, "isGeneralSpoiler": false },
+            { "name": "Gore", "rank": 60, "category": "Theme-Other", "isMediaSpoiler": false, "isGeneralSpoiler": false },
+            { "name": "Shounen" }
+        ]))
+        .expect("tags should deserialize")
+    }
+
+    #[test]
+    fn tags_are_ranked_with_unranked_tags_last() {
+        let tags = tags();
+
+        let names = ranked_tags(&tags)
+            .into_iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["Isekai", "Time Skip", "Gore", "Shounen"]);
+    }
+
+    #[test]
+    fn spoiler_tags_are_hidden_and_sensitive_tags_are_warnings() {
+        let tags = tags();
+
+        assert_eq!(tags[0].display(), "||*Time Skip* (70%)||");
+        assert_eq!(tags[1].display(), "*Isekai* (94%)");
+        assert_eq!(tags[3].display(), "*Shounen*");
+        assert!(tags[2].is_content_warning());
+        assert!(!tags[1].is_content_warning());
+    }
 }
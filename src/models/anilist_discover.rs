@@ -0,0 +1,175 @@
+use crate::models::anilist_recommendation::{PageInfo, RecommendedMedia};
+
+use serde::{Deserialize, Serialize};
+
+/// Most autocomplete choices Discord accepts in one response.
+pub const MAX_SUGGESTIONS: usize = 25;
+
+/// The one AniList genre reserved for adult titles.
+pub const ADULT_GENRE: &str = "Hentai";
+
+/// Every genre and tag AniList can filter by.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DiscoverCatalog {
+    #[serde(rename = "GenreCollection", default)]
+    pub genres: Vec<String>,
+    #[serde(rename = "MediaTagCollection", default)]
+    pub tags: Vec<MediaTagDefinition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaTagDefinition {
+    pub id: u32,
+    pub name: String,
+    pub category: Option<String>,
+    pub is_adult: Option<bool>,
+}
+
+impl MediaTagDefinition {
+    pub fn is_adult(&self) -> bool {
+        self.is_adult.unwrap_or(false)
+    }
+}
+
+impl DiscoverCatalog {
+    /// The catalogue's spelling of `name`, matched case-insensitively.
+    pub fn genre(&self, name: &str) -> Option<&str> {
+        self.genres
+            .iter()
+            .find(|genre| genre.eq_ignore_ascii_case(name.trim()))
+            .map(String::as_str)
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&MediaTagDefinition> {
+        self.tags
+            .iter()
+            .find(|tag| tag.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn tag_by_id(&self, id: u32) -> Option<&MediaTagDefinition> {
+        self.tags.iter().find(|tag| tag.id == id)
+    }
+
+    /// Genres matching what the user has typed so far.
+    pub fn suggest_genres(&self, typed: &str, allow_adult: bool) -> Vec<&str> {
+        suggest(
+            self.genres
+                .iter()
+                .map(String::as_str)
+                .filter(|genre| allow_adult || *genre != ADULT_GENRE),
+            typed,
+        )
+    }
+
+    /// Tags matching what the user has typed so far.
+    pub fn suggest_tags(&self, typed: &str, allow_adult: bool) -> Vec<&str> {
+        suggest(
+            self.tags
+                .iter()
+                .filter(|tag| allow_adult || !tag.is_adult())
+                .map(|tag| tag.name.as_str()),
+            typed,
+        )
+    }
+}
+
+/// Names starting with `typed` first, then names containing it, keeping the
+/// catalogue's order within each group.
+fn suggest<'a>(names: impl Iterator<Item = &'a str>, typed: &str) -> Vec<&'a str> {
+    let typed = typed.trim().to_lowercase();
+    let (mut prefixed, contained): (Vec<_>, Vec<_>) = names
+        .map(|name| (name, name.to_lowercase()))
+        .filter(|(_, lower)| lower.contains(&typed))
+        .partition(|(_, lower)| lower.starts_with(&typed));
+
+    prefixed.extend(contained);
+    prefixed
+        .into_iter()
+        .map(|(name, _)| name)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DiscoverPageData {
+    #[serde(rename = "Page")]
+    pub page: Option<DiscoverPage>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoverPage {
+    pub page_info: Option<PageInfo>,
+    #[serde(default)]
+    pub media: Vec<Option<RecommendedMedia>>,
+}
+
+impl DiscoverPage {
+    pub fn media(&self) -> impl Iterator<Item = &RecommendedMedia> {
+        self.media.iter().flatten()
+    }
+
+    pub fn has_next_page(&self) -> bool {
+        self.page_info
+            .as_ref()
+            .and_then(|page_info| page_info.has_next_page)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn catalog() -> DiscoverCatalog {
+        serde_json::from_value(json!({
+            "GenreCollection": ["Action", "Comedy", "Hentai", "Slice of Life"],
+            "MediaTagCollection": [
+                { "id": 1, "name": "Isekai", "category": "Setting-Universe", "isAdult": false },
+                { "id": 2, "name": "Time Skip", "category": "Theme-Other", "isAdult": false },
+                { "id": 3, "name": "Nudity", "category": "Sexual Content", "isAdult": true },
+                { "id": 4, "name": "Time Manipulation", "category": "Theme-Other", "isAdult": false }
+            ]
+        }))
+        .expect("catalog should deserialize")
+    }
+
+    #[test]
+    fn lookups_ignore_case() {
+        let catalog = catalog();
+
+        assert_eq!(catalog.genre("slice of life"), Some("Slice of Life"));
+        assert_eq!(catalog.tag("ISEKAI").map(|tag| tag.id), Some(1));
+        assert_eq!(
+            catalog.tag_by_id(2).map(|tag| tag.name.as_str()),
+            Some("Time Skip")
+        );
+        assert_eq!(catalog.genre("Mecha"), None);
+    }
+
+    #[test]
+    fn suggestions_put_prefix_matches_first_and_hide_adult_entries() {
+        let catalog = catalog();
+
+        assert_eq!(
+            catalog.suggest_genres("", false),
+            ["Action", "Comedy", "Slice of Life"]
+        );
+        assert_eq!(
+            catalog.suggest_genres("e", true),
+            ["Comedy", "Hentai", "Slice of Life"]
+        );
+        assert_eq!(
+            catalog.suggest_tags("time", false),
+            ["Time Skip", "Time Manipulation"]
+        );
+        assert_eq!(
+            catalog.suggest_tags("i", false),
+            ["Isekai", "Time Skip", "Time Manipulation"]
+        );
+        assert!(catalog.suggest_tags("nud", false).is_empty());
+        assert_eq!(catalog.suggest_tags("nud", true), ["Nudity"]);
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use crate::models::{anilist_common::CoverImage, anilist_recommendation::RecommendedMedia};
+
+use serde::Deserialize;
+
+/// One page of `/episodes`: the anime with its streaming episodes, and the
+/// airing schedule for the episodes on that page.
+#[derive(Deserialize, Debug, Clone)]
+pub struct EpisodePageData {
+    #[serde(rename = "Media")]
+    pub media: Option<EpisodeMedia>,
+    #[serde(rename = "Page")]
+    pub schedule: Option<AiringSchedulePage>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpisodeMedia {
+    #[serde(flatten)]
+    pub media: RecommendedMedia,
+    pub cover_image: Option<CoverImage>,
+    pub episodes: Option<u32>,
+    pub next_airing_episode: Option<AiringEpisode>,
+    streaming_episodes: Option<Vec<Option<StreamingEpisode>>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AiringSchedulePage {
+    airing_schedules: Option<Vec<Option<AiringEpisode>>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AiringEpisode {
+    pub episode: u32,
+    /// Unix timestamp, in seconds.
+    pub airing_at: i64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StreamingEpisode {
+    /// Usually `Episode 5 - Title`.
+    pub title: Option<String>,
+    pub thumbnail: Option<String>,
+    pub url: Option<String>,
+    pub site: Option<String>,
+}
+
+impl StreamingEpisode {
+    /// The episode number from an `Episode 5 - …` title.
+    pub fn episode_number(&self) -> Option<u32> {
+        let rest = self.title.as_deref()?.trim().strip_prefix("Episode ")?;
+        let digits = rest
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        digits.parse().ok()
+    }
+
+    /// The title without its `Episode 5 - ` prefix.
+    pub fn name(&self) -> Option<&str> {
+        let title = self.title.as_deref()?.trim();
+        let name = match title.split_once(" - ") {
+            Some((prefix, name)) if prefix.starts_with("Episode ") => name.trim(),
+            _ => title,
+        };
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+/// What `/episodes` knows about one episode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpisodeRow<'a> {
+    pub number: u32,
+    pub streaming: Option<&'a StreamingEpisode>,
+    pub airing_at: Option<i64>,
+}
+
+impl EpisodeMedia {
+    /// Streaming episodes by number. Episodes whose titles carry no number
+    /// take their position in AniList's list.
+    pub fn streaming_episodes(&self) -> BTreeMap<u32, &StreamingEpisode> {
+        let mut numbered = BTreeMap::new();
+        let episodes = self.streaming_episodes.iter().flatten().flatten();
+        for (position, episode) in episodes.enumerate() {
+            let number = episode.episode_number().unwrap_or(position as u32 + 1);
+            numbered.entry(number).or_insert(episode);
+        }
+        numbered
+    }
+
+    /// The highest episode number AniList knows of, aired or not.
+    pub fn episode_count(&self) -> u32 {
+        let streamed = self.streaming_episodes().keys().next_back().copied();
+        [
+            self.episodes,
+            self.next_airing_episode.map(|next| next.episode),
+            streamed,
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(0)
+    }
+
+    /// Episodes `first..=last`, with streaming links and air dates where
+    /// AniList has them.
+    pub fn episode_rows(
+        &self,
+        schedule: &AiringSchedulePage,
+        first: u32,
+        last: u32,
+    ) -> Vec<EpisodeRow<'_>> {
+        let streaming = self.streaming_episodes();
+        let airing = schedule
+            .airing_schedules
+            .iter()
+            .flatten()
+            .flatten()
+            .chain(self.next_airing_episode.as_ref())
+            .map(|airing| (airing.episode, airing.airing_at))
+            .collect::<BTreeMap<_, _>>();
+
+        (first..=last.min(self.episode_count()))
+            .map(|number| EpisodeRow {
+                number,
+                streaming: streaming.get(&number).copied(),
+                airing_at: airing.get(&number).copied(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn page() -> EpisodePageData {
+        serde_json::from_value(json!({
+            "Media": {
+                "type": "ANIME",
+                "id": 1,
+                "isAdult": false,
+                "title": { "romaji": "Sample", "english": null, "native": null },
+                "format": "TV",
+                "status": "RELEASING",
+                "genres": [],
+                "averageScore": null,
+                "siteUrl": "https://anilist.co/anime/1",
+                "coverImage": null,
+                "episodes": null,
+                "nextAiringEpisode": { "episode": 4, "airingAt": 1_900_000_000 },
+                "streamingEpisodes": [
+                    { "title": "Episode 2 - The Second", "thumbnail": null, "url": "https://example.com/2", "site": "Crunchyroll" },
+                    { "title": "Episode 1 - The First", "thumbnail": null, "url": "https://example.com/1", "site": "Crunchyroll" },
+                    null
+                ]
+            },
+            "Page": {
+                "airingSchedules": [
+                    { "episode": 1, "airingAt": 1_700_000_000 },
+                    { "episode": 2, "airingAt": 1_700_604_800 }
+                ]
+            }
+        }))
+        .expect("episode page should deserialize")
+    }
+
+    #[test]
+    fn streaming_titles_give_episode_numbers_and_names() {
+        let episode = |title: &str| StreamingEpisode {
+            title: Some(title.to_string()),
+            thumbnail: None,
+            url: None,
+            site: None,
+        };
+
+        assert_eq!(episode("Episode 12 - Finale").episode_number(), Some(12));
+        assert_eq!(episode("Episode 12 - Finale").name(), Some("Finale"));
+        assert_eq!(episode("Special - Recap").episode_number(), None);
+        assert_eq!(episode("Special - Recap").name(), Some("Special - Recap"));
+    }
+
+    #[test]
+    fn rows_merge_streaming_links_and_airing_dates() {
+        let page = page();
+        let media = page.media.as_ref().unwrap();
+        let rows = media.episode_rows(page.schedule.as_ref().unwrap(), 1, 10);
+
+        assert_eq!(media.episode_count(), 4);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[0].streaming.and_then(StreamingEpisode::name),
+            Some("The First")
+        );
+        assert_eq!(rows[1].airing_at, Some(1_700_604_800));
+        assert_eq!(
+            rows[2],
+            EpisodeRow {
+                number: 3,
+                streaming: None,
+                airing_at: None
+            }
+        );
+        assert_eq!(rows[3].airing_at, Some(1_900_000_000));
+    }
+}
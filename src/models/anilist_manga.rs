@@ -1,6 +1,7 @@
 use crate::{
     models::{
         anilist_common::{CoverImage, Tag, Title},
+        settings::StreamingRegion,
         transformers::Transformers,
     },
     utils::{
@@ -38,6 +39,10 @@ pub struct Manga {
     site_url: String,
     description: Option<String>,
     tags: Vec<Tag>,
+    /// Set when this entry was served from the stale cache tier because
+    /// AniList was unreachable.
+    #[serde(skip)]
+    stale: bool,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -89,6 +94,14 @@ impl Manga {
         }
     }
 
+    pub fn chapters(&self) -> Option<u32> {
+        self.chapters
+    }
+
+    pub fn volumes(&self) -> Option<u32> {
+        self.volumes
+    }
+
     pub fn transform_chapters(&self) -> String {
         match &self.chapters {
             Some(chapters) => {
@@ -274,6 +287,14 @@ impl Transformers for Manga {
         &self.tags
     }
 
+    fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+
     fn transform_mal_id(&self) -> Option<String> {
         self.id_mal
             .map(|mal_id| format!("https://www.myanimelist.net/manga/{mal_id}"))
@@ -295,7 +316,7 @@ impl Transformers for Manga {
         self.transform_staff()
     }
 
-    fn transform_links(&self) -> String {
+    fn transform_links(&self, _region: StreamingRegion) -> String {
         EMPTY_STR.to_string()
     }
 
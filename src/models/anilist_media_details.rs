@@ -0,0 +1,237 @@
+//! The extra pages behind the tab buttons on `/anime` and `/manga` replies.
+//! Each tab fetches only its own fields, so most of these are optional.
+
+use crate::models::{anilist_common::CoverImage, anilist_recommendation::RecommendedMedia};
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MediaDetailsData {
+    #[serde(rename = "Media")]
+    pub media: Option<MediaDetails>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaDetails {
+    /// Title, status and link, shared by every tab's header.
+    #[serde(flatten)]
+    pub media: RecommendedMedia,
+    pub cover_image: Option<CoverImage>,
+    pub popularity: Option<u32>,
+    pub favourites: Option<u32>,
+    rankings: Option<Vec<Option<MediaRank>>>,
+    stats: Option<MediaStats>,
+    relations: Option<RelationConnection>,
+    characters: Option<CharacterConnection>,
+    staff: Option<StaffConnection>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaRank {
+    pub rank: u32,
+    pub all_time: Option<bool>,
+    /// AniList's wording, e.g. "highest rated all time".
+    pub context: String,
+    pub season: Option<String>,
+    pub year: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct MediaStats {
+    score_distribution: Option<Vec<Option<ScoreDistribution>>>,
+    status_distribution: Option<Vec<Option<StatusDistribution>>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreDistribution {
+    pub score: u32,
+    pub amount: u32,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusDistribution {
+    pub status: String,
+    pub amount: u32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RelationConnection {
+    edges: Option<Vec<Option<RelationEdge>>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationEdge {
+    pub relation_type: Option<String>,
+    pub node: Option<RecommendedMedia>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct CharacterConnection {
+    edges: Option<Vec<Option<CharacterEdge>>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CharacterEdge {
+    /// `MAIN`, `SUPPORTING` or `BACKGROUND`.
+    pub role: Option<String>,
+    pub node: Option<Person>,
+    pub voice_actors: Option<Vec<Option<Person>>>,
+}
+
+impl CharacterEdge {
+    /// The first credited (Japanese) voice actor.
+    pub fn voice_actor(&self) -> Option<&Person> {
+        self.voice_actors.iter().flatten().flatten().next()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct StaffConnection {
+    edges: Option<Vec<Option<StaffEdge>>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StaffEdge {
+    pub role: Option<String>,
+    pub node: Option<Person>,
+}
+
+/// A character or staff member.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Person {
+    pub name: Option<PersonName>,
+    pub site_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PersonName {
+    pub full: Option<String>,
+}
+
+impl Person {
+    pub fn full_name(&self) -> Option<&str> {
+        self.name
+            .as_ref()
+            .and_then(|name| name.full.as_deref())
+            .filter(|name| !name.trim().is_empty())
+    }
+}
+
+/// `Option<Vec<Option<T>>>`, AniList's usual list shape, without the gaps.
+fn present<T>(items: &Option<Vec<Option<T>>>) -> impl Iterator<Item = &T> {
+    items.iter().flatten().flatten()
+}
+
+impl MediaDetails {
+    pub fn color(&self) -> Option<&str> {
+        self.cover_image
+            .as_ref()
+            .and_then(|cover| cover.color.as_deref())
+    }
+
+    pub fn thumbnail(&self) -> Option<&str> {
+        self.cover_image.as_ref().and_then(|cover| {
+            cover
+                .large
+                .as_deref()
+                .or(cover.medium.as_deref())
+                .or(cover.extra_large.as_deref())
+        })
+    }
+
+    pub fn rankings(&self) -> impl Iterator<Item = &MediaRank> {
+        present(&self.rankings)
+    }
+
+    /// Score buckets (10, 20, … 100) in ascending order.
+    pub fn score_distribution(&self) -> Vec<ScoreDistribution> {
+        let mut scores: Vec<_> = self
+            .stats
+            .as_ref()
+            .map(|stats| present(&stats.score_distribution).copied().collect())
+            .unwrap_or_default();
+        scores.sort_by_key(|bucket| bucket.score);
+        scores
+    }
+
+    /// List statuses, most common first.
+    pub fn status_distribution(&self) -> Vec<&StatusDistribution> {
+        let mut statuses: Vec<_> = self
+            .stats
+            .as_ref()
+            .map(|stats| present(&stats.status_distribution).collect())
+            .unwrap_or_default();
+        statuses.sort_by_key(|status| std::cmp::Reverse(status.amount));
+        statuses
+    }
+
+    pub fn relations(&self) -> impl Iterator<Item = &RelationEdge> {
+        self.relations
+            .iter()
+            .flat_map(|relations| present(&relations.edges))
+    }
+
+    pub fn characters(&self) -> impl Iterator<Item = &CharacterEdge> {
+        self.characters
+            .iter()
+            .flat_map(|characters| present(&characters.edges))
+    }
+
+    pub fn staff(&self) -> impl Iterator<Item = &StaffEdge> {
+        self.staff.iter().flat_map(|staff| present(&staff.edges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn stats_are_sorted_and_gaps_skipped() {
+        let details: MediaDetailsData = serde_json::from_value(json!({
+            "Media": {
+                "type": "ANIME",
+                "id": 1,
+                "isAdult": false,
+                "title": { "romaji": "Sample", "english": null, "native": null },
+                "format": "TV",
+                "status": "FINISHED",
+                "genres": [],
+                "averageScore": 80,
+                "siteUrl": "https://anilist.co/anime/1",
+                "stats": {
+                    "scoreDistribution": [
+                        { "score": 90, "amount": 40 },
+                        null,
+                        { "score": 10, "amount": 2 }
+                    ],
+                    "statusDistribution": [
+                        { "status": "PLANNING", "amount": 10 },
+                        { "status": "COMPLETED", "amount": 50 }
+                    ]
+                }
+            }
+        }))
+        .expect("details should deserialize");
+        let details = details.media.expect("media should be present");
+
+        assert_eq!(
+            details
+                .score_distribution()
+                .iter()
+                .map(|bucket| bucket.score)
+                .collect::<Vec<_>>(),
+            [10, 90]
+        );
+        assert_eq!(details.status_distribution()[0].status, "COMPLETED");
+        assert_eq!(details.relations().count(), 0);
+        assert!(details.thumbnail().is_none());
+    }
+}
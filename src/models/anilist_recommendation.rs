@@ -1,26 +1,15 @@
 use crate::{
     models::{
         anilist_common::{CoverImage, Tag, Title, TitleVariant},
-        settings::TitleDisplayPreference,
+        settings::{StreamingRegion, TitleDisplayPreference},
         transformers::Transformers,
     },
     utils::{formatter::titlecase, statics::EMPTY_STR},
 };
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug, Clone)]
-pub struct RecommendationMediaResponse {
-    pub data: Option<RecommendationMediaData>,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct RecommendationMediaData {
-    #[serde(rename = "Media")]
-    pub media: Option<RecommendationMedia>,
-}
-
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RecommendationMedia {
     #[serde(rename = "type")]
@@ -38,23 +27,45 @@ pub struct RecommendationMedia {
     recommendations: RecommendationConnection,
 }
 
+/// A page of a media's recommendations, as fetched by the follow-up page
+/// query.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RecommendationPageData {
+    #[serde(rename = "Media")]
+    pub media: Option<RecommendationPageMedia>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
+pub struct RecommendationPageMedia {
+    pub recommendations: RecommendationConnection,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct RecommendationConnection {
+    pub page_info: Option<PageInfo>,
     pub nodes: Option<Vec<Option<Recommendation>>>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    pub has_next_page: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Recommendation {
     pub rating: Option<i32>,
     pub media_recommendation: Option<RecommendedMedia>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RecommendedMedia {
     #[serde(rename = "type")]
     media_type: Option<String>,
+    id: Option<u32>,
     is_adult: Option<bool>,
     title: Title,
     format: Option<String>,
@@ -73,6 +84,29 @@ impl RecommendationMedia {
             .iter()
             .filter_map(Option::as_ref)
     }
+
+    /// Whether AniList has more recommendation pages than have been loaded.
+    pub fn has_more_recommendations(&self) -> bool {
+        self.recommendations.has_next_page()
+    }
+
+    /// Append a further page of recommendations.
+    pub fn extend_recommendations(&mut self, page: RecommendationConnection) {
+        self.recommendations
+            .nodes
+            .get_or_insert_with(Vec::new)
+            .extend(page.nodes.unwrap_or_default());
+        self.recommendations.page_info = page.page_info;
+    }
+}
+
+impl RecommendationConnection {
+    pub fn has_next_page(&self) -> bool {
+        self.page_info
+            .as_ref()
+            .and_then(|page_info| page_info.has_next_page)
+            .unwrap_or(false)
+    }
 }
 
 impl Recommendation {
@@ -143,6 +177,10 @@ impl RecommendedMedia {
             })
     }
 
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+
     pub fn media_type(&self) -> &str {
         normalize_media_type(self.media_type.as_deref())
     }
@@ -261,7 +299,7 @@ impl Transformers for RecommendationMedia {
         EMPTY_STR.to_string()
     }
 
-    fn transform_links(&self) -> String {
+    fn transform_links(&self, _region: StreamingRegion) -> String {
         EMPTY_STR.to_string()
     }
 
@@ -303,6 +341,7 @@ mod tests {
     fn matched_recommendation_title_uses_search_variant() {
         let media = RecommendedMedia {
             media_type: Some("ANIME".to_string()),
+            id: Some(154587),
             is_adult: Some(false),
             title: Title {
                 romaji: Some("Sousou no Frieren".to_string()),
@@ -331,62 +370,57 @@ mod tests {
     }
 
     #[test]
-    fn deserializes_recommendation_media_response() {
-        let response: RecommendationMediaResponse = serde_json::from_value(serde_json::json!({
-            "data": {
-                "Media": {
-                    "type": "ANIME",
-                    "id": 1,
-                    "isAdult": false,
-                    "title": {
-                        "romaji": "Cowboy Bebop",
-                        "english": "Cowboy Bebop",
-                        "native": "カウボーイビバップ"
-                    },
-                    "synonyms": [],
-                    "format": "TV",
-                    "status": "FINISHED",
-                    "genres": ["Action"],
-                    "coverImage": {
-                        "extraLarge": "https://example.com/base.jpg",
-                        "large": null,
-                        "medium": null,
-                        "color": "#abcdef"
-                    },
-                    "averageScore": 86,
-                    "siteUrl": "https://anilist.co/anime/1",
-                    "recommendations": {
-                        "nodes": [{
-                            "rating": 42,
-                            "mediaRecommendation": {
-                                "type": "ANIME",
-                                "id": 205,
-                                "isAdult": false,
-                                "title": {
-                                    "romaji": "Samurai Champloo",
-                                    "english": "Samurai Champloo",
-                                    "native": "サムライチャンプルー"
-                                },
-                                "format": "TV",
-                                "status": "FINISHED",
-                                "genres": ["Action", "Adventure"],
-                                "coverImage": {
-                                    "extraLarge": "https://example.com/recommendation.jpg",
-                                    "large": null,
-                                    "medium": null,
-                                    "color": "#123456"
-                                },
-                                "averageScore": 84,
-                                "siteUrl": "https://anilist.co/anime/205"
-                            }
-                        }]
+    fn deserializes_batched_recommendation_media() {
+        let media: RecommendationMedia = serde_json::from_value(serde_json::json!({
+            "type": "ANIME",
+            "id": 1,
+            "isAdult": false,
+            "title": {
+                "romaji": "Cowboy Bebop",
+                "english": "Cowboy Bebop",
+                "native": "カウボーイビバップ"
+            },
+            "synonyms": [],
+            "format": "TV",
+            "status": "FINISHED",
+            "genres": ["Action"],
+            "coverImage": {
+                "extraLarge": "https://example.com/base.jpg",
+                "large": null,
+                "medium": null,
+                "color": "#abcdef"
+            },
+            "averageScore": 86,
+            "siteUrl": "https://anilist.co/anime/1",
+            "recommendations": {
+                "nodes": [{
+                    "rating": 42,
+                    "mediaRecommendation": {
+                        "type": "ANIME",
+                        "id": 205,
+                        "isAdult": false,
+                        "title": {
+                            "romaji": "Samurai Champloo",
+                            "english": "Samurai Champloo",
+                            "native": "サムライチャンプルー"
+                        },
+                        "format": "TV",
+                        "status": "FINISHED",
+                        "genres": ["Action", "Adventure"],
+                        "coverImage": {
+                            "extraLarge": "https://example.com/recommendation.jpg",
+                            "large": null,
+                            "medium": null,
+                            "color": "#123456"
+                        },
+                        "averageScore": 84,
+                        "siteUrl": "https://anilist.co/anime/205"
                     }
-                }
+                }]
             }
         }))
-        .expect("recommendation response should deserialize");
+        .expect("batched recommendation media should deserialize");
 
-        let media = response.data.unwrap().media.unwrap();
         assert_eq!(media.get_type(), "anime");
 
         let recommendation = media.recommendations().next().unwrap();
@@ -1,12 +1,12 @@
-use crate::{models::anilist_character::Character, utils::fuzzy::fuzzy_matcher};
+use crate::{
+    models::anilist_character::Character,
+    utils::{fuzzy::fuzzy_matcher, requests::graphql::GraphQlResponse},
+};
 
 use serde::Deserialize;
 use tracing::{debug, info};
 
-#[derive(Deserialize, Debug)]
-pub struct FetchResponse {
-    pub data: Option<Page>,
-}
+pub type FetchResponse = GraphQlResponse<Page>;
 
 #[derive(Deserialize, Debug)]
 pub struct Page {
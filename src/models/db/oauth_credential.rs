@@ -88,29 +88,14 @@ impl OAuthCredential {
         .await
     }
 
-    /// Look up OAuth credential rows for any of the given Discord snowflakes.
-    ///
-    /// Useful for batch lookups across a guild. Returns the rows that exist;
-    /// missing users are simply absent from the result.
-    #[instrument(
-        name = "db.oauth_credential.get_by_discord_ids",
-        skip(pool, user_discord_ids),
-        fields(user_count = user_discord_ids.len())
-    )]
-    pub async fn get_by_discord_ids(
-        user_discord_ids: Vec<UserId>,
-        pool: &DbPool,
-    ) -> Result<Vec<OAuthCredential>, sqlx::Error> {
-        let ids: Vec<String> = user_discord_ids
-            .iter()
-            .map(|id| id.get().to_string())
-            .collect();
-
+    /// Every linked account. Guild overlays start from this set and check
+    /// guild membership per user, since Serenity only caches part of each
+    /// guild's member list.
+    #[instrument(name = "db.oauth_credential.get_all", skip(pool))]
+    pub async fn get_all(pool: &DbPool) -> Result<Vec<OAuthCredential>, sqlx::Error> {
         sqlx::query_as::<_, OAuthCredential>(
-            "SELECT discord_user_id, anilist_id, anilist_username FROM annie_auth.oauth_credentials \
-             WHERE discord_user_id = ANY($1)",
+            "SELECT discord_user_id, anilist_id, anilist_username FROM annie_auth.oauth_credentials",
         )
-        .bind(ids)
         .fetch_all(pool)
         .await
     }
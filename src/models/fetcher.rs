@@ -1,41 +1,45 @@
 use crate::{
     commands::{
-        anime::queries::{FETCH_ANIME, FETCH_ANIME_BY_ID},
-        character::queries::{FETCH_CHARACTER, FETCH_CHARACTER_BY_ID},
-        manga::queries::{FETCH_MANGA, FETCH_MANGA_BY_ID},
+        anime::queries::{ANIME_BY_ID, fetch_anime},
+        character::queries::{CHARACTER_BY_ID, fetch_character as character_search_query},
+        manga::queries::{MANGA_BY_ID, fetch_manga},
     },
     models::{
         anilist_character::Character, anilist_common::TitleVariant,
-        character_id_response::FetchResponse as CharacterIdResponse,
         character_response::FetchResponse as CharacterResponse,
-        id_response::FetchResponse as IdResponse, media_response::FetchResponse as MediaResponse,
-        media_type::MediaType as Type, transformers::Transformers,
+        media_response::FetchResponse as MediaResponse, media_type::MediaType as Type,
+        transformers::Transformers,
     },
     utils::{
-        fetch_by_arguments::{fetch_by_id, fetch_by_name, fetch_by_raw_name},
-        redis::{check_cache, try_to_cache_response},
+        cache::{Cache, DEFAULT_CACHE_TTL, SharedCache, ttl_for_media_status},
+        fetch_by_arguments::{fetch_by_name, fetch_by_raw_name},
+        requests::{batch::IdBatcher, graphql::AniListError},
+        single_flight::ANILIST_FLIGHTS,
     },
 };
 
-use redis::RedisResult;
-use tokio::task;
-use tracing::{debug, error, info, instrument};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tracing::{Instrument, debug, error, info, info_span, instrument, warn};
 
 pub struct AnimeConfig {
     argument: Argument,
-    id_query: String,
+    id_batcher: &'static IdBatcher,
     search_query: String,
 }
 
 pub struct MangaConfig {
     argument: Argument,
-    id_query: String,
+    id_batcher: &'static IdBatcher,
     search_query: String,
 }
 
 pub struct CharacterConfig {
     argument: Argument,
-    id_query: String,
+    id_batcher: &'static IdBatcher,
     search_query: String,
 }
 
@@ -47,226 +51,504 @@ pub enum Argument {
 pub trait Response {
     fn new(argument: Argument) -> Self;
     fn get_argument(&self) -> &Argument;
-    fn get_id_query(&self) -> String;
+    fn get_id_batcher(&self) -> &'static IdBatcher;
     fn get_search_query(&self) -> String;
 }
 
-#[instrument(
-    name = "anilist.fetch_from_network_and_cache",
-    skip(search_query),
-    fields(cache_key = %cache_key, lookup_len = lookup_value.len())
-)]
-async fn fetch_from_network_and_cache(
-    search_query: String,
-    lookup_value: String,
-    cache_key: String,
-) -> Option<String> {
-    let response = match fetch_by_name(search_query, lookup_value).await {
-        Ok(data) => data,
-        Err(err) => {
-            error!(error = %err, "Failed to fetch AniList data by name");
-            return None;
-        }
-    };
+/// Delays between background attempts to refresh an entry that was served
+/// from the stale tier.
+const REVALIDATION_DELAYS: [Duration; 4] = [
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(120),
+    Duration::from_secs(300),
+];
 
-    let cache_key_for_write = cache_key.clone();
-    let response_to_cache = response.clone();
-    if let Err(err) = task::spawn_blocking(move || {
-        write_cached_anilist_response(cache_key_for_write, response_to_cache)
-    })
-    .await
-    {
-        error!(error = %err, cache_key = %cache_key, "Failed to cache AniList response");
+/// Media keys with a background refresh already scheduled.
+static REVALIDATING: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(Default::default);
+
+/// A lookup resolved to a single title, as shared between coalesced callers.
+#[derive(Serialize, Deserialize)]
+struct ResolvedMedia {
+    variant: TitleVariant,
+    media: Value,
+    /// The payload came from the stale tier because AniList was unreachable.
+    stale: bool,
+}
+
+impl ResolvedMedia {
+    fn encode(&self) -> Option<String> {
+        serde_json::to_string(self).ok()
     }
+}
 
-    Some(response)
+/// Cached search-key → ID mapping. The media payload itself lives under
+/// [`media_cache_key`], so a title found by search also serves ID lookups.
+#[derive(Serialize, Deserialize)]
+struct SearchMatch {
+    id: u32,
+    variant: TitleVariant,
 }
 
-#[instrument(
-    name = "anilist.fetch_raw_from_network_and_cache",
-    skip(search_query),
-    fields(cache_key = %cache_key, lookup_len = lookup_value.len())
-)]
-async fn fetch_raw_from_network_and_cache(
-    search_query: String,
-    lookup_value: String,
-    cache_key: String,
-) -> Option<String> {
-    let response = match fetch_by_raw_name(search_query, lookup_value).await {
-        Ok(data) => data,
+fn search_cache_key(media_type: &Type, search: &str) -> String {
+    format!("{}:search:{search}", media_type.as_ref())
+}
+
+fn media_cache_key(media_type: &Type, id: u32) -> String {
+    format!("{}:id:{id}", media_type.as_ref())
+}
+
+fn character_cache_key(id: u32) -> String {
+    format!("character:id:{id}")
+}
+
+fn media_ttl(media: &Value) -> Duration {
+    ttl_for_media_status(media.get("status").and_then(Value::as_str))
+}
+
+fn deserialize_media<T: DeserializeOwned>(media: Value) -> Option<T> {
+    match serde_json::from_value(media) {
+        Ok(media) => Some(media),
         Err(err) => {
-            error!(error = %err, "Failed to fetch AniList data by raw name");
-            return None;
+            error!(error = %err, "Failed to deserialize AniList media payload");
+            None
         }
-    };
+    }
+}
 
-    let cache_key_for_write = cache_key.clone();
-    let response_to_cache = response.clone();
-    if let Err(err) = task::spawn_blocking(move || {
-        write_cached_anilist_response(cache_key_for_write, response_to_cache)
-    })
-    .await
-    {
-        error!(error = %err, cache_key = %cache_key, "Failed to cache AniList response");
+/// Write a media payload under `media_key`, plus its long-lived stale copy.
+async fn store_media(cache: &dyn Cache, media_key: &str, media: &Value) {
+    let payload = media.to_string();
+    cache.set(media_key, &payload, media_ttl(media)).await;
+    cache.set_stale(media_key, &payload).await;
+}
+
+async fn store_search_match(
+    cache: &dyn Cache,
+    search_key: &str,
+    search_match: &SearchMatch,
+    ttl: Duration,
+) {
+    if let Ok(encoded) = serde_json::to_string(search_match) {
+        cache.set(search_key, &encoded, ttl).await;
+        cache.set_stale(search_key, &encoded).await;
     }
+}
 
-    Some(response)
+/// Cache a character under its ID, so ID lookups and earlier searches share
+/// one payload.
+async fn store_character(cache: &dyn Cache, character: &Character) {
+    match serde_json::to_string(character) {
+        Ok(payload) => {
+            cache
+                .set(
+                    &character_cache_key(character.get_id()),
+                    &payload,
+                    DEFAULT_CACHE_TTL,
+                )
+                .await
+        }
+        Err(err) => warn!(error = %err, "Failed to encode AniList character for caching"),
+    }
 }
 
-#[instrument(name = "anilist.read_cache_blocking", skip(cache_key), fields(cache_key = %cache_key))]
-fn read_cached_anilist_response(cache_key: String) -> RedisResult<String> {
-    check_cache(&cache_key)
+async fn stale_media(cache: &dyn Cache, media_key: &str) -> Option<Value> {
+    let payload = cache.get_stale(media_key).await?;
+    serde_json::from_str(&payload).ok()
 }
 
-#[instrument(name = "anilist.write_cache_blocking", skip(cache_key, response), fields(cache_key = %cache_key))]
-fn write_cached_anilist_response(cache_key: String, response: String) {
-    try_to_cache_response(&cache_key, &response)
+async fn stale_search_match(
+    cache: &dyn Cache,
+    media_type: &Type,
+    search_key: &str,
+) -> Option<ResolvedMedia> {
+    let search_match: SearchMatch =
+        serde_json::from_str(&cache.get_stale(search_key).await?).ok()?;
+    let media = stale_media(cache, &media_cache_key(media_type, search_match.id)).await?;
+    Some(ResolvedMedia {
+        variant: search_match.variant,
+        media,
+        stale: true,
+    })
 }
 
-#[instrument(name = "anilist.fetch", skip(response_config), fields(media_type = ?media_type))]
-pub async fn fetch<
-    T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
->(
-    response_config: &impl Response,
-    media_type: Type,
+#[instrument(name = "anilist.cached_search_match", skip(cache), fields(search_key = %search_key))]
+async fn cached_search_match<T: DeserializeOwned>(
+    cache: &dyn Cache,
+    media_type: &Type,
+    search_key: &str,
 ) -> Option<(T, TitleVariant)> {
-    match response_config.get_argument() {
-        Argument::Id(value) => {
-            let fetched_data = match fetch_by_id(response_config.get_id_query(), *value).await {
+    let search_match: SearchMatch = serde_json::from_str(&cache.get(search_key).await?).ok()?;
+    // The payload may have expired independently of the mapping; treat that
+    // as a miss so the search is re-resolved.
+    let payload = cache
+        .get(&media_cache_key(media_type, search_match.id))
+        .await?;
+    let media = serde_json::from_str(&payload).ok()?;
+    Some((deserialize_media(media)?, search_match.variant))
+}
+
+/// Turn a flight result into media, scheduling a background refresh when it
+/// was served from the stale tier.
+fn finish_resolved<T: DeserializeOwned + Transformers>(
+    resolved: &str,
+    cache: &SharedCache,
+    id_batcher: &'static IdBatcher,
+    media_type: &Type,
+) -> Option<(T, TitleVariant)> {
+    let resolved: ResolvedMedia = serde_json::from_str(resolved).ok()?;
+    let mut media: T = deserialize_media(resolved.media)?;
+    if resolved.stale {
+        warn!(id = media.get_id(), "Serving stale AniList data");
+        media.mark_stale();
+        schedule_revalidation(cache.clone(), id_batcher, media_type, media.get_id());
+    }
+    Some((media, resolved.variant))
+}
+
+/// Refresh a stale entry by ID in the background once AniList recovers.
+///
+/// Only the ID → payload entry is refreshed; an expired search mapping is
+/// re-resolved by the next search for it.
+fn schedule_revalidation(
+    cache: SharedCache,
+    id_batcher: &'static IdBatcher,
+    media_type: &Type,
+    id: u32,
+) {
+    let media_key = media_cache_key(media_type, id);
+    {
+        let mut revalidating = REVALIDATING.lock().unwrap_or_else(|e| e.into_inner());
+        if !revalidating.insert(media_key.clone()) {
+            debug!(media_key = %media_key, "Revalidation already scheduled");
+            return;
+        }
+    }
+
+    tokio::spawn(
+        async move {
+            for delay in REVALIDATION_DELAYS {
+                tokio::time::sleep(delay).await;
+                match id_batcher.load(id).await {
+                    Ok(media) => {
+                        if let Some(media) = media {
+                            store_media(cache.as_ref(), &media_key, &media).await;
+                            info!("Refreshed stale AniList entry");
+                        }
+                        break;
+                    }
+                    Err(err) if err.is_upstream_unavailable() => {
+                        debug!(error = %err, "AniList still unavailable; will retry");
+                    }
+                    Err(err) => {
+                        warn!(error = %err, "Giving up on refreshing stale AniList entry");
+                        break;
+                    }
+                }
+            }
+
+            REVALIDATING
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&media_key);
+        }
+        .instrument(info_span!("anilist.revalidate", id)),
+    );
+}
+
+#[instrument(
+    name = "anilist.resolve_search_from_network_and_cache",
+    skip(cache, search_query),
+    fields(search_key = %search_key, lookup_len = lookup_value.len())
+)]
+async fn resolve_search_from_network_and_cache<
+    T: DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
+>(
+    cache: &dyn Cache,
+    search_query: String,
+    lookup_value: &str,
+    media_type: &Type,
+    search_key: String,
+) -> Result<Option<String>, AniListError> {
+    // Concurrent misses for the same key share a single AniList call.
+    ANILIST_FLIGHTS
+        .run(&search_key, || async {
+            let response = match fetch_by_name(search_query, lookup_value.to_string()).await {
                 Ok(data) => data,
+                Err(err) if err.is_upstream_unavailable() => {
+                    warn!(error = %err, "AniList unavailable; falling back to stale cache");
+                    return match stale_search_match(cache, media_type, &search_key).await {
+                        Some(resolved) => Ok(resolved.encode()),
+                        None => Err(err),
+                    };
+                }
                 Err(err) => {
-                    error!(error = %err, id = *value, "Failed to fetch AniList data by id");
-                    return None;
+                    error!(error = %err, "Failed to fetch AniList data by name");
+                    return Err(err);
                 }
             };
-            let fetch_response: IdResponse<T> = match serde_json::from_str(&fetched_data) {
+
+            let fetch_response: MediaResponse<T> = match serde_json::from_str(&response) {
                 Ok(response) => response,
                 Err(err) => {
-                    error!(error = %err, "Failed to deserialize AniList id response");
-                    return None;
+                    error!(error = %err, "Failed to deserialize AniList search response");
+                    return Err(AniListError::InvalidResponse(err.to_string()));
                 }
             };
             debug!("Deserialized response: {:#?}", fetch_response);
+            let Some((matched, variant)) =
+                fetch_response.fuzzy_match(lookup_value, media_type.clone())
+            else {
+                return Ok(None);
+            };
+            debug!("Fuzzy Response: {:#?}", matched);
+
+            let id = matched.get_id();
+            let raw: Value = serde_json::from_str(&response)
+                .map_err(|err| AniListError::InvalidResponse(err.to_string()))?;
+            let Some(media) = raw
+                .pointer("/data/Page/media")
+                .and_then(Value::as_array)
+                .and_then(|media| {
+                    media.iter().find(|media| {
+                        media.get("id").and_then(Value::as_u64) == Some(u64::from(id))
+                    })
+                })
+                .cloned()
+            else {
+                return Ok(None);
+            };
+
+            store_media(cache, &media_cache_key(media_type, id), &media).await;
+            store_search_match(
+                cache,
+                &search_key,
+                &SearchMatch { id, variant },
+                media_ttl(&media),
+            )
+            .await;
+
+            Ok(ResolvedMedia {
+                variant,
+                media,
+                stale: false,
+            }
+            .encode())
+        })
+        .await
+}
+
+#[instrument(
+    name = "anilist.fetch_media_by_id_from_network_and_cache",
+    skip(cache, id_batcher),
+    fields(media_key = %media_key)
+)]
+async fn fetch_media_by_id_from_network_and_cache(
+    cache: &dyn Cache,
+    id_batcher: &'static IdBatcher,
+    id: u32,
+    media_key: String,
+) -> Result<Option<String>, AniListError> {
+    // Concurrent misses for the same key share a single AniList call.
+    ANILIST_FLIGHTS
+        .run(&media_key, || async {
             // ID lookups bypass fuzzy matching, so we have no signal about
             // which variant the user prefers — default to Romaji to preserve
             // the existing primary-title behaviour.
-            fetch_response
-                .data
-                .and_then(|data| data.media)
-                .map(|media| (media, TitleVariant::Romaji))
-        }
-        Argument::Search(value) => {
-            let cache_key = format!("{}:{value}", media_type.as_ref());
-            let cache_key_for_lookup = cache_key.clone();
-            let search_query = response_config.get_search_query();
-            let lookup_value = value.to_string();
-
-            let fetched_data = match task::spawn_blocking(move || {
-                read_cached_anilist_response(cache_key_for_lookup)
-            })
-            .await
-            {
-                Ok(Ok(cached_value)) => {
-                    info!("Cache hit for {:#?}", cache_key);
-                    cached_value
+            let (media, stale) = match id_batcher.load(id).await {
+                Ok(Some(media)) => {
+                    store_media(cache, &media_key, &media).await;
+                    (media, false)
                 }
-                Ok(Err(err)) => {
-                    info!("Cache miss for {:#?} with error {:#?}", cache_key, err);
-                    fetch_from_network_and_cache(
-                        search_query.clone(),
-                        lookup_value.clone(),
-                        cache_key.clone(),
-                    )
-                    .await?
+                Ok(None) => return Ok(None),
+                Err(err) if err.is_upstream_unavailable() => {
+                    warn!(error = %err, id, "AniList unavailable; falling back to stale cache");
+                    match stale_media(cache, &media_key).await {
+                        Some(media) => (media, true),
+                        None => return Err((*err).clone()),
+                    }
                 }
                 Err(err) => {
-                    error!(error = %err, "Failed to read AniList cache");
-                    fetch_from_network_and_cache(search_query, lookup_value, cache_key.clone())
-                        .await?
+                    error!(error = %err, id, "Failed to fetch AniList data by id");
+                    return Err((*err).clone());
                 }
             };
-            let fetch_response: MediaResponse<T> = match serde_json::from_str(&fetched_data) {
-                Ok(response) => response,
+
+            Ok(ResolvedMedia {
+                variant: TitleVariant::Romaji,
+                media,
+                stale,
+            }
+            .encode())
+        })
+        .await
+}
+
+#[instrument(
+    name = "anilist.fetch_raw_from_network_and_cache",
+    skip(cache, search_query),
+    fields(cache_key = %cache_key, lookup_len = lookup_value.len())
+)]
+async fn fetch_raw_from_network_and_cache(
+    cache: &dyn Cache,
+    search_query: String,
+    lookup_value: String,
+    cache_key: String,
+) -> Result<Option<String>, AniListError> {
+    // Concurrent misses for the same key share a single AniList call.
+    ANILIST_FLIGHTS
+        .run(&cache_key, || async {
+            let response = match fetch_by_raw_name(search_query, lookup_value).await {
+                Ok(data) => data,
                 Err(err) => {
-                    error!(error = %err, "Failed to deserialize AniList search response");
-                    return None;
+                    error!(error = %err, "Failed to fetch AniList data by raw name");
+                    return Err(err);
                 }
             };
-            debug!("Deserialized response: {:#?}", fetch_response);
-            let result = fetch_response.fuzzy_match(value, media_type);
-            debug!("Fuzzy Response: {:#?}", result);
-            result
+
+            cache.set(&cache_key, &response, DEFAULT_CACHE_TTL).await;
+
+            Ok(Some(response))
+        })
+        .await
+}
+
+#[instrument(name = "anilist.fetch", skip(response_config, cache), fields(media_type = ?media_type))]
+pub async fn fetch<T: DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone>(
+    response_config: &impl Response,
+    media_type: Type,
+    cache: &SharedCache,
+) -> Result<Option<(T, TitleVariant)>, AniListError> {
+    let resolved = match response_config.get_argument() {
+        Argument::Id(value) => {
+            let media_key = media_cache_key(&media_type, *value);
+
+            if let Some(payload) = cache.get(&media_key).await {
+                info!("Cache hit for {:#?}", media_key);
+                let media: Option<T> = serde_json::from_str(&payload)
+                    .ok()
+                    .and_then(deserialize_media);
+                debug!("Deserialized response: {:#?}", media);
+                return Ok(media.map(|media| (media, TitleVariant::Romaji)));
+            }
+            info!("Cache miss for {:#?}", media_key);
+            fetch_media_by_id_from_network_and_cache(
+                cache.as_ref(),
+                response_config.get_id_batcher(),
+                *value,
+                media_key,
+            )
+            .await?
         }
-    }
+        Argument::Search(value) => {
+            let search_key = search_cache_key(&media_type, value);
+
+            if let Some(cached) =
+                cached_search_match(cache.as_ref(), &media_type, &search_key).await
+            {
+                info!("Cache hit for {:#?}", search_key);
+                return Ok(Some(cached));
+            }
+            info!("Cache miss for {:#?}", search_key);
+            resolve_search_from_network_and_cache::<T>(
+                cache.as_ref(),
+                response_config.get_search_query(),
+                value,
+                &media_type,
+                search_key,
+            )
+            .await?
+        }
+    };
+
+    let Some(resolved) = resolved else {
+        return Ok(None);
+    };
+    Ok(finish_resolved(
+        &resolved,
+        cache,
+        response_config.get_id_batcher(),
+        &media_type,
+    ))
 }
 
-#[instrument(name = "anilist.fetch_character", skip(response_config))]
+#[instrument(name = "anilist.fetch_character", skip(response_config, cache))]
 pub async fn fetch_character(
     response_config: &impl Response,
     allow_spoilers: bool,
-) -> Option<Character> {
+    cache: &dyn Cache,
+) -> Result<Option<Character>, AniListError> {
     match response_config.get_argument() {
         Argument::Id(value) => {
-            let fetched_data = match fetch_by_id(response_config.get_id_query(), *value).await {
-                Ok(data) => data,
+            let cache_key = character_cache_key(*value);
+            if let Some(character) = cache
+                .get(&cache_key)
+                .await
+                .and_then(|cached| serde_json::from_str::<Character>(&cached).ok())
+            {
+                info!("Cache hit for {:#?}", cache_key);
+                return Ok(Some(character));
+            }
+            info!("Cache miss for {:#?}", cache_key);
+
+            let character = match response_config.get_id_batcher().load(*value).await {
+                Ok(Some(character)) => character,
+                Ok(None) => return Ok(None),
                 Err(err) => {
                     error!(error = %err, id = *value, "Failed to fetch AniList character data by id");
-                    return None;
+                    return Err((*err).clone());
                 }
             };
-            let fetch_response: CharacterIdResponse<Character> =
-                match serde_json::from_str(&fetched_data) {
-                    Ok(response) => response,
-                    Err(err) => {
-                        error!(error = %err, "Failed to deserialize AniList character id response");
-                        return None;
-                    }
-                };
-            debug!("Deserialized character id response: {:#?}", fetch_response);
-            fetch_response.data.and_then(|data| data.character)
+            match serde_json::from_value::<Character>(character) {
+                Ok(character) => {
+                    store_character(cache, &character).await;
+                    Ok(Some(character))
+                }
+                Err(err) => {
+                    error!(error = %err, "Failed to deserialize AniList character id response");
+                    Err(AniListError::InvalidResponse(err.to_string()))
+                }
+            }
         }
         Argument::Search(value) => {
             let cache_key = format!("character:v2:{value}");
-            let cache_key_for_lookup = cache_key.clone();
-            let search_query = response_config.get_search_query();
-            let lookup_value = value.to_string();
 
-            let fetched_data = match task::spawn_blocking(move || {
-                read_cached_anilist_response(cache_key_for_lookup)
-            })
-            .await
-            {
-                Ok(Ok(cached_value)) => {
+            let fetched_data = match cache.get(&cache_key).await {
+                Some(cached_value) => {
                     info!("Cache hit for {:#?}", cache_key);
                     cached_value
                 }
-                Ok(Err(err)) => {
-                    info!("Cache miss for {:#?} with error {:#?}", cache_key, err);
-                    fetch_raw_from_network_and_cache(
-                        search_query.clone(),
-                        lookup_value.clone(),
-                        cache_key.clone(),
+                None => {
+                    info!("Cache miss for {:#?}", cache_key);
+                    match fetch_raw_from_network_and_cache(
+                        cache,
+                        response_config.get_search_query(),
+                        value.to_string(),
+                        cache_key,
                     )
                     .await?
-                }
-                Err(err) => {
-                    error!(error = %err, "Failed to read AniList character cache");
-                    fetch_raw_from_network_and_cache(search_query, lookup_value, cache_key.clone())
-                        .await?
+                    {
+                        Some(fetched_data) => fetched_data,
+                        None => return Ok(None),
+                    }
                 }
             };
             let fetch_response: CharacterResponse = match serde_json::from_str(&fetched_data) {
                 Ok(response) => response,
                 Err(err) => {
                     error!(error = %err, "Failed to deserialize AniList character search response");
-                    return None;
+                    return Err(AniListError::InvalidResponse(err.to_string()));
                 }
             };
             debug!(
                 "Deserialized character search response: {:#?}",
                 fetch_response
             );
-            fetch_response.fuzzy_match(value, allow_spoilers)
+            let Some(character) = fetch_response.fuzzy_match(value, allow_spoilers) else {
+                return Ok(None);
+            };
+            store_character(cache, &character).await;
+            Ok(Some(character))
         }
     }
 }
@@ -275,8 +557,8 @@ impl Response for AnimeConfig {
     fn new(argument: Argument) -> AnimeConfig {
         AnimeConfig {
             argument,
-            id_query: FETCH_ANIME_BY_ID.to_string(),
-            search_query: FETCH_ANIME.to_string(),
+            id_batcher: &ANIME_BY_ID,
+            search_query: fetch_anime(),
         }
     }
 
@@ -284,8 +566,8 @@ impl Response for AnimeConfig {
         &self.argument
     }
 
-    fn get_id_query(&self) -> String {
-        self.id_query.to_owned()
+    fn get_id_batcher(&self) -> &'static IdBatcher {
+        self.id_batcher
     }
 
     fn get_search_query(&self) -> String {
@@ -297,8 +579,8 @@ impl Response for MangaConfig {
     fn new(argument: Argument) -> MangaConfig {
         MangaConfig {
             argument,
-            id_query: FETCH_MANGA_BY_ID.to_string(),
-            search_query: FETCH_MANGA.to_string(),
+            id_batcher: &MANGA_BY_ID,
+            search_query: fetch_manga(),
         }
     }
 
@@ -306,8 +588,8 @@ impl Response for MangaConfig {
         &self.argument
     }
 
-    fn get_id_query(&self) -> String {
-        self.id_query.to_owned()
+    fn get_id_batcher(&self) -> &'static IdBatcher {
+        self.id_batcher
     }
 
     fn get_search_query(&self) -> String {
@@ -319,8 +601,8 @@ impl Response for CharacterConfig {
     fn new(argument: Argument) -> CharacterConfig {
         CharacterConfig {
             argument,
-            id_query: FETCH_CHARACTER_BY_ID.to_string(),
-            search_query: FETCH_CHARACTER.to_string(),
+            id_batcher: &CHARACTER_BY_ID,
+            search_query: character_search_query(),
         }
     }
 
@@ -328,11 +610,239 @@ impl Response for CharacterConfig {
         &self.argument
     }
 
-    fn get_id_query(&self) -> String {
-        self.id_query.to_owned()
+    fn get_id_batcher(&self) -> &'static IdBatcher {
+        self.id_batcher
     }
 
     fn get_search_query(&self) -> String {
         self.search_query.to_owned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::{
+        models::anilist_anime::Anime,
+        utils::cache::{FINISHED_MEDIA_TTL, InMemoryCache, ONGOING_MEDIA_TTL, STALE_ENTRY_TTL},
+    };
+
+    fn cached_media_payload() -> String {
+        serde_json::json!({
+            "type": "ANIME",
+            "id": 5114,
+            "idMal": 5114,
+            "isAdult": false,
+            "title": {
+                "romaji": "Hagane no Renkinjutsushi: Fullmetal Alchemist",
+                "english": "Fullmetal Alchemist: Brotherhood",
+                "native": "鋼の錬金術師 FULLMETAL ALCHEMIST"
+            },
+            "synonyms": [],
+            "season": "SPRING",
+            "seasonYear": 2009,
+            "format": "TV",
+            "status": "FINISHED",
+            "episodes": 64,
+            "duration": 24,
+            "genres": [],
+            "source": "MANGA",
+            "coverImage": {
+                "extraLarge": null,
+                "large": null,
+                "medium": null,
+                "color": null
+            },
+            "averageScore": 90,
+            "studios": { "edges": [], "nodes": [] },
+            "siteUrl": "https://anilist.co/anime/5114",
+            "externalLinks": [],
+            "trailer": null,
+            "description": "",
+            "tags": []
+        })
+        .to_string()
+    }
+
+    async fn cache_with_search_match(search: &str) -> Arc<InMemoryCache> {
+        let cache = Arc::new(InMemoryCache::with_entry(
+            "Anime:id:5114",
+            &cached_media_payload(),
+        ));
+        cache
+            .set(
+                &format!("Anime:search:{search}"),
+                r#"{"id":5114,"variant":"English"}"#,
+                FINISHED_MEDIA_TTL,
+            )
+            .await;
+        cache
+    }
+
+    #[tokio::test]
+    async fn search_is_served_from_injected_cache() {
+        let search = "Fullmetal Alchemist: Brotherhood";
+        let cache: SharedCache = cache_with_search_match(search).await;
+        let config = AnimeConfig::new(Argument::Search(search.to_string()));
+
+        let (anime, variant) = fetch::<Anime>(&config, Type::Anime, &cache)
+            .await
+            .ok()
+            .flatten()
+            .expect("cached search should resolve without a network call");
+
+        assert_eq!(anime.get_id(), 5114);
+        assert_eq!(variant, TitleVariant::English);
+    }
+
+    #[tokio::test]
+    async fn id_lookup_is_served_from_payload_cached_by_search() {
+        let cache: SharedCache = cache_with_search_match("FMA").await;
+        let config = AnimeConfig::new(Argument::Id(5114));
+
+        let (anime, variant) = fetch::<Anime>(&config, Type::Anime, &cache)
+            .await
+            .ok()
+            .flatten()
+            .expect("cached payload should resolve without a network call");
+
+        assert_eq!(anime.get_id(), 5114);
+        assert_eq!(variant, TitleVariant::Romaji);
+    }
+
+    #[test]
+    fn media_ttl_follows_payload_status() {
+        let finished: Value = serde_json::from_str(&cached_media_payload()).unwrap();
+        let releasing = serde_json::json!({ "id": 1, "status": "RELEASING" });
+
+        assert_eq!(media_ttl(&finished), FINISHED_MEDIA_TTL);
+        assert_eq!(media_ttl(&releasing), ONGOING_MEDIA_TTL);
+    }
+
+    #[test]
+    fn search_match_round_trips_through_cache_format() {
+        let encoded = serde_json::to_string(&SearchMatch {
+            id: 5114,
+            variant: TitleVariant::English,
+        })
+        .unwrap();
+        let decoded: SearchMatch = serde_json::from_str(&encoded).unwrap();
+
+        assert_eq!(decoded.id, 5114);
+        assert_eq!(decoded.variant, TitleVariant::English);
+    }
+
+    #[tokio::test]
+    async fn stored_media_gets_a_long_lived_stale_copy() {
+        let cache = InMemoryCache::default();
+        let media: Value = serde_json::from_str(&cached_media_payload()).unwrap();
+
+        store_media(&cache, "Anime:id:5114", &media).await;
+
+        assert_eq!(cache.ttl("Anime:id:5114"), Some(FINISHED_MEDIA_TTL));
+        assert_eq!(cache.ttl("stale:Anime:id:5114"), Some(STALE_ENTRY_TTL));
+    }
+
+    #[tokio::test]
+    async fn stale_search_match_reads_only_the_stale_tier() {
+        let cache = InMemoryCache::default();
+        let media: Value = serde_json::from_str(&cached_media_payload()).unwrap();
+        assert!(
+            stale_search_match(&cache, &Type::Anime, "Anime:search:FMA")
+                .await
+                .is_none()
+        );
+
+        store_media(&cache, "Anime:id:5114", &media).await;
+        store_search_match(
+            &cache,
+            "Anime:search:FMA",
+            &SearchMatch {
+                id: 5114,
+                variant: TitleVariant::English,
+            },
+            FINISHED_MEDIA_TTL,
+        )
+        .await;
+
+        let resolved = stale_search_match(&cache, &Type::Anime, "Anime:search:FMA")
+            .await
+            .expect("stale mapping and payload should resolve");
+        assert!(resolved.stale);
+        assert_eq!(resolved.variant, TitleVariant::English);
+        assert_eq!(resolved.media["id"], 5114);
+    }
+
+    #[tokio::test]
+    async fn stale_resolution_marks_media_as_stale() {
+        let cache: SharedCache = Arc::new(InMemoryCache::default());
+        let resolved = ResolvedMedia {
+            variant: TitleVariant::Romaji,
+            media: serde_json::from_str(&cached_media_payload()).unwrap(),
+            stale: true,
+        }
+        .encode()
+        .unwrap();
+
+        let (anime, _) =
+            finish_resolved::<Anime>(&resolved, &cache, &ANIME_BY_ID, &Type::Anime).unwrap();
+
+        assert!(anime.is_stale());
+    }
+
+    fn cached_character_search_payload() -> String {
+        serde_json::json!({
+            "data": {
+                "Page": {
+                    "characters": [{
+                        "id": 40,
+                        "name": {
+                            "full": "Monkey D. Luffy",
+                            "native": null,
+                            "alternative": [],
+                            "alternativeSpoiler": [],
+                            "userPreferred": "Monkey D. Luffy"
+                        },
+                        "image": null,
+                        "description": null,
+                        "gender": null,
+                        "dateOfBirth": null,
+                        "age": null,
+                        "bloodType": null,
+                        "favourites": null,
+                        "siteUrl": "https://anilist.co/character/40",
+                        "media": { "nodes": [] }
+                    }]
+                }
+            }
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn character_id_lookup_is_served_from_payload_cached_by_search() {
+        let cache =
+            InMemoryCache::with_entry("character:v2:Luffy", &cached_character_search_payload());
+
+        let searched = fetch_character(
+            &CharacterConfig::new(Argument::Search("Luffy".to_string())),
+            false,
+            &cache,
+        )
+        .await
+        .ok()
+        .flatten()
+        .expect("cached search should resolve without a network call");
+        let by_id = fetch_character(&CharacterConfig::new(Argument::Id(40)), false, &cache)
+            .await
+            .ok()
+            .flatten()
+            .expect("search should have cached the character by ID");
+
+        assert_eq!(searched.get_id(), 40);
+        assert_eq!(by_id.transform_name(), "Monkey D. Luffy");
+        assert_eq!(cache.ttl("character:id:40"), Some(DEFAULT_CACHE_TTL));
+    }
+}
@@ -1,15 +1,15 @@
 use crate::{
     models::{anilist_common::TitleVariant, media_type::MediaType, transformers::Transformers},
-    utils::fuzzy::{fuzzy_matcher, fuzzy_matcher_synonyms},
+    utils::{
+        fuzzy::{fuzzy_matcher, fuzzy_matcher_synonyms},
+        requests::graphql::GraphQlResponse,
+    },
 };
 
 use serde::Deserialize;
 use tracing::{debug, info};
 
-#[derive(Deserialize, Debug)]
-pub struct FetchResponse<T> {
-    pub data: Option<Page<T>>,
-}
+pub type FetchResponse<T> = GraphQlResponse<Page<T>>;
 
 #[derive(Deserialize, Debug)]
 pub struct Page<T> {
@@ -1,6 +1,6 @@
 use strum::AsRefStr;
 
-#[derive(AsRefStr, Debug, Clone)]
+#[derive(AsRefStr, Debug, Clone, PartialEq, Eq)]
 pub enum MediaType {
     Anime,
     Manga,
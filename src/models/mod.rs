@@ -1,17 +1,19 @@
 pub mod anilist_anime;
 pub mod anilist_character;
 pub mod anilist_common;
+pub mod anilist_discover;
+pub mod anilist_episodes;
 pub mod anilist_manga;
+pub mod anilist_media_details;
 pub mod anilist_recommendation;
 pub mod anilist_studio;
-pub mod character_id_response;
 pub mod character_response;
 pub mod db;
 pub mod fetcher;
-pub mod id_response;
 pub mod mal_response;
 pub mod media_response;
 pub mod media_type;
 pub mod settings;
 pub mod transformers;
+pub mod user_favourites;
 pub mod user_media_list;
@@ -26,12 +26,16 @@ pub enum SettingKey {
     TitleDisplay,
     AnalyticsPrivacy,
     GuildScores,
+    Spoilers,
+    StreamingRegion,
 }
 
-pub const ALL_SETTING_KEYS: [SettingKey; 3] = [
+pub const ALL_SETTING_KEYS: [SettingKey; 5] = [
     SettingKey::TitleDisplay,
     SettingKey::AnalyticsPrivacy,
     SettingKey::GuildScores,
+    SettingKey::Spoilers,
+    SettingKey::StreamingRegion,
 ];
 
 impl SettingKey {
@@ -40,6 +44,8 @@ impl SettingKey {
             "title_display" | "title" | "titles" => Some(Self::TitleDisplay),
             "analytics_privacy" | "analytics" | "privacy" => Some(Self::AnalyticsPrivacy),
             "guild_scores" | "guild_score" | "scores" => Some(Self::GuildScores),
+            "spoilers" | "spoiler" => Some(Self::Spoilers),
+            "streaming_region" | "region" | "streaming" => Some(Self::StreamingRegion),
             _ => None,
         }
     }
@@ -49,6 +55,8 @@ impl SettingKey {
             Self::TitleDisplay => "title_display",
             Self::AnalyticsPrivacy => "analytics_privacy",
             Self::GuildScores => "guild_scores",
+            Self::Spoilers => "spoilers",
+            Self::StreamingRegion => "streaming_region",
         }
     }
 
@@ -57,6 +65,8 @@ impl SettingKey {
             Self::TitleDisplay => "Title display",
             Self::AnalyticsPrivacy => "Analytics privacy",
             Self::GuildScores => "Guild scores",
+            Self::Spoilers => "Spoilers",
+            Self::StreamingRegion => "Streaming region",
         }
     }
 
@@ -69,9 +79,20 @@ impl SettingKey {
             Self::GuildScores => {
                 "Control whether Annie Mei shows server members' AniList status and scores. Server disable wins; users who opt out are always excluded."
             }
+            Self::Spoilers => {
+                "Choose whether descriptions show spoilers behind click-to-reveal tags or leave them out. The `spoilers` option on `/anime` and `/manga` overrides this."
+            }
+            Self::StreamingRegion => {
+                "Choose which streaming services `/anime` lists. AniList tags regional services by language; services without a tag are always shown."
+            }
         }
     }
 
+    /// Personal preferences that servers cannot set for their members.
+    pub fn is_user_only(self) -> bool {
+        matches!(self, Self::AnalyticsPrivacy | Self::Spoilers)
+    }
+
     pub fn default_value(self) -> SettingValue {
         match self {
             Self::TitleDisplay => SettingValue::TitleDisplay(TitleDisplayPreference::Matched),
@@ -79,6 +100,8 @@ impl SettingKey {
                 SettingValue::AnalyticsPrivacy(AnalyticsPrivacyPreference::Standard)
             }
             Self::GuildScores => SettingValue::GuildScores(GuildScoresPreference::Enabled),
+            Self::Spoilers => SettingValue::Spoilers(SpoilerPreference::Hidden),
+            Self::StreamingRegion => SettingValue::StreamingRegion(StreamingRegion::All),
         }
     }
 
@@ -87,6 +110,8 @@ impl SettingKey {
             Self::TitleDisplay => &["matched", "romaji", "english", "native"],
             Self::AnalyticsPrivacy => &["standard", "opted_out"],
             Self::GuildScores => &["enabled", "disabled", "opted_out"],
+            Self::Spoilers => &["hidden", "shown"],
+            Self::StreamingRegion => StreamingRegion::STORAGE_VALUES,
         }
     }
 
@@ -127,6 +152,24 @@ impl SettingKey {
                 }
                 _ => return Err(SettingValidationError::new(self, raw)),
             },
+            Self::Spoilers => match normalized.as_str() {
+                "hidden" | "hide" | "disallow" | "off" | "default" => {
+                    SettingValue::Spoilers(SpoilerPreference::Hidden)
+                }
+                "shown" | "show" | "allow" | "on" => {
+                    SettingValue::Spoilers(SpoilerPreference::Shown)
+                }
+                _ => return Err(SettingValidationError::new(self, raw)),
+            },
+            Self::StreamingRegion => match normalized.as_str() {
+                "any" | "everywhere" | "default" => {
+                    SettingValue::StreamingRegion(StreamingRegion::All)
+                }
+                other => match StreamingRegion::from_storage_value(other) {
+                    Some(region) => SettingValue::StreamingRegion(region),
+                    None => return Err(SettingValidationError::new(self, raw)),
+                },
+            },
         };
 
         Ok(value)
@@ -160,11 +203,115 @@ pub enum GuildScoresPreference {
     OptedOut,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpoilerPreference {
+    Hidden,
+    Shown,
+}
+
+impl SpoilerPreference {
+    pub fn allows_spoilers(self) -> bool {
+        matches!(self, Self::Shown)
+    }
+}
+
+/// Where a member watches from, matched against the `language` AniList
+/// tags regional streaming links with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamingRegion {
+    All,
+    English,
+    Japanese,
+    Spanish,
+    Portuguese,
+    French,
+    German,
+    Italian,
+    Korean,
+    Chinese,
+}
+
+impl StreamingRegion {
+    const ALL_REGIONS: [Self; 10] = [
+        Self::All,
+        Self::English,
+        Self::Japanese,
+        Self::Spanish,
+        Self::Portuguese,
+        Self::French,
+        Self::German,
+        Self::Italian,
+        Self::Korean,
+        Self::Chinese,
+    ];
+
+    const STORAGE_VALUES: &'static [&'static str] = &[
+        "all",
+        "english",
+        "japanese",
+        "spanish",
+        "portuguese",
+        "french",
+        "german",
+        "italian",
+        "korean",
+        "chinese",
+    ];
+
+    fn from_storage_value(raw: &str) -> Option<Self> {
+        Self::ALL_REGIONS
+            .into_iter()
+            .find(|region| region.as_storage_value() == raw)
+    }
+
+    fn as_storage_value(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::English => "english",
+            Self::Japanese => "japanese",
+            Self::Spanish => "spanish",
+            Self::Portuguese => "portuguese",
+            Self::French => "french",
+            Self::German => "german",
+            Self::Italian => "italian",
+            Self::Korean => "korean",
+            Self::Chinese => "chinese",
+        }
+    }
+
+    /// The AniList link language for this region; `None` for `All`.
+    pub fn language(self) -> Option<&'static str> {
+        match self {
+            Self::All => None,
+            Self::English => Some("English"),
+            Self::Japanese => Some("Japanese"),
+            Self::Spanish => Some("Spanish"),
+            Self::Portuguese => Some("Portuguese"),
+            Self::French => Some("French"),
+            Self::German => Some("German"),
+            Self::Italian => Some("Italian"),
+            Self::Korean => Some("Korean"),
+            Self::Chinese => Some("Chinese"),
+        }
+    }
+
+    /// Whether a link tagged with `language` is available here. Untagged
+    /// links are treated as available everywhere.
+    pub fn includes(self, language: Option<&str>) -> bool {
+        match (self.language(), language) {
+            (None, _) | (_, None) => true,
+            (Some(region), Some(language)) => region.eq_ignore_ascii_case(language.trim()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SettingValue {
     TitleDisplay(TitleDisplayPreference),
     AnalyticsPrivacy(AnalyticsPrivacyPreference),
     GuildScores(GuildScoresPreference),
+    Spoilers(SpoilerPreference),
+    StreamingRegion(StreamingRegion),
 }
 
 impl SettingValue {
@@ -173,6 +320,8 @@ impl SettingValue {
             Self::TitleDisplay(_) => SettingKey::TitleDisplay,
             Self::AnalyticsPrivacy(_) => SettingKey::AnalyticsPrivacy,
             Self::GuildScores(_) => SettingKey::GuildScores,
+            Self::Spoilers(_) => SettingKey::Spoilers,
+            Self::StreamingRegion(_) => SettingKey::StreamingRegion,
         }
     }
 
@@ -187,6 +336,9 @@ impl SettingValue {
             Self::GuildScores(GuildScoresPreference::Enabled) => "enabled",
             Self::GuildScores(GuildScoresPreference::Disabled) => "disabled",
             Self::GuildScores(GuildScoresPreference::OptedOut) => "opted_out",
+            Self::Spoilers(SpoilerPreference::Hidden) => "hidden",
+            Self::Spoilers(SpoilerPreference::Shown) => "shown",
+            Self::StreamingRegion(region) => region.as_storage_value(),
         }
     }
 
@@ -203,6 +355,18 @@ impl SettingValue {
             Self::GuildScores(GuildScoresPreference::Enabled) => "guild scores enabled",
             Self::GuildScores(GuildScoresPreference::Disabled) => "guild scores disabled",
             Self::GuildScores(GuildScoresPreference::OptedOut) => "opted out of guild scores",
+            Self::Spoilers(SpoilerPreference::Hidden) => "spoilers hidden",
+            Self::Spoilers(SpoilerPreference::Shown) => "spoilers shown",
+            Self::StreamingRegion(StreamingRegion::All) => "all regions",
+            Self::StreamingRegion(StreamingRegion::English) => "English-language services",
+            Self::StreamingRegion(StreamingRegion::Japanese) => "Japanese-language services",
+            Self::StreamingRegion(StreamingRegion::Spanish) => "Spanish-language services",
+            Self::StreamingRegion(StreamingRegion::Portuguese) => "Portuguese-language services",
+            Self::StreamingRegion(StreamingRegion::French) => "French-language services",
+            Self::StreamingRegion(StreamingRegion::German) => "German-language services",
+            Self::StreamingRegion(StreamingRegion::Italian) => "Italian-language services",
+            Self::StreamingRegion(StreamingRegion::Korean) => "Korean-language services",
+            Self::StreamingRegion(StreamingRegion::Chinese) => "Chinese-language services",
         }
     }
 }
@@ -261,7 +425,7 @@ pub fn resolve_setting(key: SettingKey, values: ScopedSettingValues) -> Resolved
         return resolve_guild_scores_setting(values);
     }
 
-    if key == SettingKey::AnalyticsPrivacy {
+    if key.is_user_only() {
         return resolve_user_only_setting(key, values.user);
     }
 
@@ -409,6 +573,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn streaming_regions_match_link_languages() {
+        assert_eq!(
+            SettingKey::parse("region"),
+            Some(SettingKey::StreamingRegion)
+        );
+        assert_eq!(
+            SettingKey::StreamingRegion.parse_value("any"),
+            Ok(SettingValue::StreamingRegion(StreamingRegion::All))
+        );
+        assert!(StreamingRegion::English.includes(Some("english")));
+        assert!(StreamingRegion::English.includes(None));
+        assert!(!StreamingRegion::English.includes(Some("Japanese")));
+        assert!(StreamingRegion::All.includes(Some("Japanese")));
+    }
+
     #[test]
     fn setting_validation_rejects_invalid_values_with_allowed_values() {
         let error = SettingKey::TitleDisplay
@@ -485,6 +665,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolve_spoilers_ignores_guild_values() {
+        let guild = SettingKey::Spoilers.parse_value("shown").ok();
+
+        let resolved = resolve_setting(
+            SettingKey::Spoilers,
+            ScopedSettingValues { user: None, guild },
+        );
+        assert_eq!(resolved.source, SettingSource::Default);
+        assert_eq!(
+            resolved.value,
+            SettingValue::Spoilers(SpoilerPreference::Hidden)
+        );
+
+        let user = SettingKey::Spoilers.parse_value("allow").ok();
+        let resolved = resolve_setting(SettingKey::Spoilers, ScopedSettingValues { user, guild });
+        assert_eq!(resolved.source, SettingSource::User);
+        assert_eq!(
+            resolved.value,
+            SettingValue::Spoilers(SpoilerPreference::Shown)
+        );
+    }
+
     #[test]
     fn analytics_privacy_opted_out_helper_identifies_opt_out() {
         assert!(!AnalyticsPrivacyPreference::Standard.opted_out());
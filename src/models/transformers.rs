@@ -2,17 +2,24 @@ use std::collections::HashMap;
 
 use crate::{
     models::{
-        anilist_common::{CoverImage, Tag, TitleVariant},
-        settings::TitleDisplayPreference,
-        user_media_list::MediaListData,
+        anilist_common::{CoverImage, Tag, TitleVariant, ranked_tags},
+        settings::{StreamingRegion, TitleDisplayPreference},
+        user_media_list::{MediaListData, format_guild_preview},
+    },
+    utils::{
+        formatter::*,
+        spoilers::filter_spoilers,
+        statics::{EMPTY_STR, STALE_DATA_NOTICE},
     },
-    utils::{formatter::*, statics::EMPTY_STR},
 };
 
 use html2md::parse_html;
 use serenity::all::{CreateEmbed, CreateEmbedFooter};
 use tracing::instrument;
 
+/// Tags shown in the embed's tag field.
+const TOP_TAG_COUNT: usize = 3;
+
 pub trait Transformers {
     fn get_id(&self) -> u32;
     fn get_type(&self) -> &str;
@@ -32,12 +39,21 @@ pub trait Transformers {
     fn get_description(&self) -> Option<&str>;
     fn get_tags(&self) -> &[Tag];
 
+    /// Whether this entry came from the stale cache tier rather than AniList.
+    /// Only types rendered through [`Transformers::transform_response_embed`]
+    /// need to track this.
+    fn is_stale(&self) -> bool {
+        false
+    }
+    fn mark_stale(&mut self) {}
+
     fn transform_mal_id(&self) -> Option<String>;
     fn transform_season_serialization(&self) -> String;
     fn transform_episodes_chapters(&self) -> String;
     fn transform_duration_volumes(&self) -> String;
     fn transform_studios_staff(&self) -> String;
-    fn transform_links(&self) -> String;
+    /// Streaming services available in `region`.
+    fn transform_links(&self, region: StreamingRegion) -> String;
     fn transform_trailer(&self) -> String;
 
     fn get_season_serialization_text(&self) -> &str;
@@ -162,8 +178,11 @@ pub trait Transformers {
         self.get_site_url().to_string()
     }
 
-    fn transform_description_and_mal_link(&self) -> String {
-        let description = parse_html(self.get_description().unwrap_or("<i>No Description Yet<i>"));
+    fn transform_description_and_mal_link(&self, allow_spoilers: bool) -> String {
+        let description = parse_html(&filter_spoilers(
+            self.get_description().unwrap_or("<i>No Description Yet<i>"),
+            allow_spoilers,
+        ));
 
         let url = self.transform_mal_id();
 
@@ -173,20 +192,47 @@ pub trait Transformers {
         }
     }
 
+    /// The most relevant tags, spoilers hidden. Content warnings are listed
+    /// separately by [`Transformers::transform_content_warnings`].
     fn transform_tags(&self) -> String {
-        let tags_list = self.get_tags();
+        let tags = ranked_tags(self.get_tags())
+            .into_iter()
+            .filter(|tag| !tag.is_content_warning())
+            .take(TOP_TAG_COUNT)
+            .map(Tag::display)
+            .collect::<Vec<_>>();
 
-        match tags_list.first() {
-            Some(tag) => italics(&tag.name),
-            None => EMPTY_STR.to_string(),
+        if tags.is_empty() {
+            EMPTY_STR.to_string()
+        } else {
+            tags.join("\n")
         }
     }
 
+    /// Sensitive tags, spoilers hidden, or `None` when there are none.
+    fn transform_content_warnings(&self) -> Option<String> {
+        let warnings = ranked_tags(self.get_tags())
+            .into_iter()
+            .filter(|tag| tag.is_content_warning())
+            .map(|tag| {
+                if tag.is_spoiler() {
+                    spoiler(&tag.name)
+                } else {
+                    tag.name.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        (!warnings.is_empty()).then(|| warnings.join(", "))
+    }
+
     fn transform_response_embed(
         &self,
         guild_members_data: Option<HashMap<u64, MediaListData>>,
         title_variant: Option<TitleVariant>,
         title_preference: TitleDisplayPreference,
+        allow_spoilers: bool,
+        streaming_region: StreamingRegion,
     ) -> CreateEmbed {
         let is_anime = self.get_type() == "anime";
 
@@ -194,13 +240,16 @@ pub trait Transformers {
         // park the other one in the footer. Default (no signal) keeps the
         // long-standing Romaji-as-title behaviour.
         let primary_title = self.transform_preferred_title(title_variant, title_preference);
-        let footer_title = self.transform_footer_title(title_variant, title_preference);
+        let mut footer_title = self.transform_footer_title(title_variant, title_preference);
+        if self.is_stale() {
+            footer_title = format!("{footer_title} • {STALE_DATA_NOTICE}");
+        }
 
         let mut embed = CreateEmbed::new()
             // General Embed Fields
             .color(self.transform_color())
             .title(primary_title)
-            .description(self.transform_description_and_mal_link())
+            .description(self.transform_description_and_mal_link(allow_spoilers))
             .url(self.transform_anilist())
             .thumbnail(self.transform_thumbnail())
             .footer(CreateEmbedFooter::new(footer_title))
@@ -234,10 +283,17 @@ pub trait Transformers {
                 ("Source", self.transform_source(), true),       // Field 6
                 ("Average Score", self.transform_score(), true), // Field 7
                 // ("\u{200b}", &"\u{200b}".to_string(), true), // Would add a blank field
-                ("Top Tag", self.transform_tags(), true), // Field 8
+                ("Top Tags", self.transform_tags(), true), // Field 8
             ])
             // Fourth line after MAL link
-            .fields(vec![("Genres", self.transform_genres(), false)])
+            .fields(vec![("Genres", self.transform_genres(), false)]);
+
+        // Content warnings, only when the media has any
+        if let Some(warnings) = self.transform_content_warnings() {
+            embed = embed.field("Content Warnings", warnings, false);
+        }
+
+        embed = embed
             // Fifth line after MAL
             .field(
                 self.get_studios_staff_text(),
@@ -248,22 +304,18 @@ pub trait Transformers {
         // Sixth line after MAL link (Only for Anime response)
         if is_anime {
             embed = embed.fields(vec![
-                ("Streaming", self.transform_links(), true), // Field 11
-                ("Trailer", self.transform_trailer(), true), // Field 12
+                ("Streaming", self.transform_links(streaming_region), true), // Field 11
+                ("Trailer", self.transform_trailer(), true),                 // Field 12
             ]);
         }
 
         // Build the scores field and return the embed
         match guild_members_data {
-            Some(guild_members_data) => {
-                let mut guild_members_data_string = String::default();
-                for (user_id, score) in guild_members_data {
-                    let current_member_data = score.format_for_embed(is_anime);
-                    guild_members_data_string
-                        .push_str(&format!("<@{user_id}>: {current_member_data}\n"));
-                }
-                embed.field("Guild Members", &guild_members_data_string, false)
-            }
+            Some(guild_members_data) => embed.field(
+                "Guild Members",
+                format_guild_preview(guild_members_data, is_anime),
+                false,
+            ),
             None => embed,
         }
     }
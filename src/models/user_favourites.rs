@@ -0,0 +1,85 @@
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::models::anilist_recommendation::PageInfo;
+
+/// The part of an AniList `User` that guild favourite lookups select.
+#[derive(Deserialize, Debug)]
+pub struct UserFavouritesData {
+    pub favourites: Option<Favourites>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Favourites {
+    pub characters: Option<FavouriteCharacterConnection>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FavouriteCharacterConnection {
+    pub page_info: Option<PageInfo>,
+    pub nodes: Option<Vec<Option<FavouriteNode>>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FavouriteNode {
+    pub id: u32,
+}
+
+impl UserFavouritesData {
+    /// Whether AniList has another page of favourite characters.
+    pub fn has_more_characters(&self) -> bool {
+        self.favourites
+            .as_ref()
+            .and_then(|favourites| favourites.characters.as_ref())
+            .and_then(|characters| characters.page_info.as_ref())
+            .and_then(|page_info| page_info.has_next_page)
+            .unwrap_or(false)
+    }
+
+    #[instrument(skip(self))]
+    pub fn character_ids(self) -> Vec<u32> {
+        self.favourites
+            .and_then(|favourites| favourites.characters)
+            .and_then(|characters| characters.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .map(|node| node.id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn character_ids_skip_missing_nodes() {
+        let data: UserFavouritesData = serde_json::from_value(serde_json::json!({
+            "favourites": { "characters": { "nodes": [{ "id": 40 }, null, { "id": 417 }] } }
+        }))
+        .unwrap();
+
+        assert_eq!(data.character_ids(), vec![40, 417]);
+    }
+
+    #[test]
+    fn has_more_characters_follows_page_info() {
+        let data: UserFavouritesData = serde_json::from_value(serde_json::json!({
+            "favourites": { "characters": { "pageInfo": { "hasNextPage": true }, "nodes": [] } }
+        }))
+        .unwrap();
+
+        assert!(data.has_more_characters());
+    }
+
+    #[test]
+    fn missing_favourites_are_empty() {
+        let data: UserFavouritesData =
+            serde_json::from_value(serde_json::json!({ "favourites": null })).unwrap();
+
+        assert!(!data.has_more_characters());
+        assert!(data.character_ids().is_empty());
+    }
+}
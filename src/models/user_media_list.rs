@@ -1,8 +1,27 @@
-use serde::Deserialize;
-use std::fmt;
+use crate::{
+    models::{
+        anilist_anime::{NextAiringEpisode, released_episodes},
+        anilist_common::Title,
+        settings::TitleDisplayPreference,
+    },
+    utils::{
+        formatter::titlecase,
+        statics::{ANILIST_STATUS_FINISHED, EMPTY_STR},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt,
+};
 use tracing::instrument;
 
-#[derive(Deserialize, Debug)]
+/// Members listed in the embed before pointing at the full view.
+pub const GUILD_PREVIEW_LIMIT: usize = 5;
+
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct MediaListData {
     pub status: Option<MediaListStatus>,
@@ -11,7 +30,141 @@ pub struct MediaListData {
     pub progress_volumes: Option<u32>,
 }
 
+/// One user's entry for one title, from `MediaList`.
+#[derive(Deserialize, Debug)]
+pub struct MediaListEntryData {
+    #[serde(rename = "MediaList")]
+    pub entry: Option<MediaListData>,
+}
+
+/// A user's whole list for one media type, from `MediaListCollection`.
+#[derive(Deserialize, Debug)]
+pub struct MediaListCollectionData {
+    #[serde(rename = "MediaListCollection")]
+    pub collection: Option<MediaListCollection>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MediaListCollection {
+    pub lists: Option<Vec<Option<MediaListGroup>>>,
+}
+
 #[derive(Deserialize, Debug)]
+pub struct MediaListGroup {
+    pub entries: Option<Vec<Option<MediaListEntry>>>,
+}
+
+/// One entry of a user's list, with the media details per-user commands use.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaListEntry {
+    pub media_id: u32,
+    pub status: Option<MediaListStatus>,
+    pub score: Option<u32>,
+    pub progress: Option<u32>,
+    pub media: Option<MediaListEntryMedia>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaListEntryMedia {
+    #[serde(default)]
+    pub genres: Vec<String>,
+    pub title: Option<Title>,
+    pub is_adult: Option<bool>,
+    pub format: Option<String>,
+    pub status: Option<String>,
+    pub episodes: Option<u32>,
+    pub chapters: Option<u32>,
+    pub duration: Option<u32>,
+    pub next_airing_episode: Option<NextAiringEpisode>,
+    pub average_score: Option<u32>,
+    pub site_url: Option<String>,
+}
+
+impl MediaListCollection {
+    /// Every entry once. Custom lists repeat entries from the status lists,
+    /// so later copies of a media are dropped.
+    #[instrument(name = "media_list.collection_entries", skip(self))]
+    pub fn into_entries(self) -> Vec<MediaListEntry> {
+        let mut seen = HashSet::new();
+        self.lists
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .flat_map(|list| list.entries.unwrap_or_default())
+            .flatten()
+            .filter(|entry| seen.insert(entry.media_id))
+            .collect()
+    }
+}
+
+impl MediaListEntry {
+    pub fn genres(&self) -> &[String] {
+        self.media
+            .as_ref()
+            .map(|media| media.genres.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Minutes left to watch the episodes released so far, past the user's
+    /// progress. `None` when the episode count or length is unknown.
+    pub fn remaining_minutes(&self) -> Option<u32> {
+        let media = self.media.as_ref()?;
+        let remaining = media
+            .released_episodes()?
+            .saturating_sub(self.progress.unwrap_or_default());
+        Some(remaining * media.duration?)
+    }
+}
+
+impl MediaListEntryMedia {
+    pub fn display_title(&self, title_preference: TitleDisplayPreference) -> String {
+        let Some(title) = &self.title else {
+            return EMPTY_STR.to_string();
+        };
+        let english = title.english.as_deref();
+        let romaji = title.romaji.as_deref();
+
+        match title_preference {
+            TitleDisplayPreference::Native => match title.native.as_deref() {
+                Some(native) => native.to_string(),
+                None => titlecase(romaji.or(english).unwrap_or_default()),
+            },
+            TitleDisplayPreference::English => titlecase(
+                english
+                    .or(romaji)
+                    .or(title.native.as_deref())
+                    .unwrap_or_default(),
+            ),
+            TitleDisplayPreference::Matched | TitleDisplayPreference::Romaji => titlecase(
+                romaji
+                    .or(english)
+                    .or(title.native.as_deref())
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    pub fn is_adult(&self) -> bool {
+        self.is_adult.unwrap_or(false)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.status.as_deref() == Some(ANILIST_STATUS_FINISHED)
+    }
+
+    /// Episodes out so far: those aired while airing, otherwise the total.
+    pub fn released_episodes(&self) -> Option<u32> {
+        released_episodes(
+            self.status.as_deref(),
+            self.episodes,
+            self.next_airing_episode.as_ref(),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MediaListStatus {
     #[serde(rename = "CURRENT")]
     Current,
@@ -83,6 +236,198 @@ impl MediaListData {
     }
 }
 
+impl MediaListStatus {
+    /// Position when sorting by status: active first, planning last.
+    fn sort_rank(&self) -> u8 {
+        match self {
+            MediaListStatus::Current => 0,
+            MediaListStatus::Repeating => 1,
+            MediaListStatus::Completed => 2,
+            MediaListStatus::Paused => 3,
+            MediaListStatus::Dropped => 4,
+            MediaListStatus::Planning => 5,
+        }
+    }
+}
+
+/// How guild member entries are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuildScoreSort {
+    Score,
+    Progress,
+    Status,
+}
+
+impl GuildScoreSort {
+    pub const ALL: [GuildScoreSort; 3] = [Self::Score, Self::Progress, Self::Status];
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "score" => Some(Self::Score),
+            "progress" => Some(Self::Progress),
+            "status" => Some(Self::Status),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Score => "score",
+            Self::Progress => "progress",
+            Self::Status => "status",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Score => "Score",
+            Self::Progress => "Progress",
+            Self::Status => "Status",
+        }
+    }
+}
+
+/// Server-wide aggregates over the members' list entries for one media.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GuildScoreSummary {
+    pub members: usize,
+    /// Members who gave a non-zero score.
+    pub scored: usize,
+    pub mean_score: Option<u32>,
+    /// Current and repeating entries.
+    pub watching: usize,
+    pub completed: usize,
+    pub paused: usize,
+    pub dropped: usize,
+    pub planning: usize,
+}
+
+impl GuildScoreSummary {
+    #[instrument(name = "guild_scores.summarize", skip(entries), fields(member_count = entries.len()))]
+    pub fn from_entries(entries: &HashMap<u64, MediaListData>) -> Self {
+        let mut summary = Self {
+            members: entries.len(),
+            ..Self::default()
+        };
+        let mut score_total = 0;
+
+        for entry in entries.values() {
+            if let Some(score) = scored(entry) {
+                summary.scored += 1;
+                score_total += score;
+            }
+            match entry.status {
+                Some(MediaListStatus::Current | MediaListStatus::Repeating) => {
+                    summary.watching += 1
+                }
+                Some(MediaListStatus::Completed) => summary.completed += 1,
+                Some(MediaListStatus::Paused) => summary.paused += 1,
+                Some(MediaListStatus::Dropped) => summary.dropped += 1,
+                Some(MediaListStatus::Planning) => summary.planning += 1,
+                None => {}
+            }
+        }
+
+        if summary.scored > 0 {
+            let scored = summary.scored as u32;
+            summary.mean_score = Some((score_total + scored / 2) / scored);
+        }
+        summary
+    }
+
+    /// Mean score and status distribution, one line each.
+    #[instrument(skip(self))]
+    pub fn format_for_embed(&self, is_anime: bool) -> String {
+        let mean = match self.mean_score {
+            Some(mean) => format!(
+                "Server mean: **{mean}/100** from {} {}",
+                self.scored,
+                if self.scored == 1 {
+                    "rating"
+                } else {
+                    "ratings"
+                }
+            ),
+            None => "Server mean: no ratings yet".to_string(),
+        };
+
+        let counts = [
+            (self.watching, if is_anime { "watching" } else { "reading" }),
+            (self.completed, "completed"),
+            (self.paused, "paused"),
+            (self.dropped, "dropped"),
+            (self.planning, "planning"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect::<Vec<_>>();
+
+        if counts.is_empty() {
+            mean
+        } else {
+            format!("{mean}\n{}", counts.join(" · "))
+        }
+    }
+}
+
+/// Order guild entries by `sort`, falling back to score and then user ID so
+/// pages stay stable between clicks.
+#[instrument(name = "guild_scores.sort", skip(entries), fields(member_count = entries.len()))]
+pub fn sort_guild_entries(
+    entries: HashMap<u64, MediaListData>,
+    sort: GuildScoreSort,
+) -> Vec<(u64, MediaListData)> {
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    entries.sort_by(|(left_id, left), (right_id, right)| {
+        let by_score = scored(right).cmp(&scored(left));
+        let primary = match sort {
+            GuildScoreSort::Score => Ordering::Equal,
+            GuildScoreSort::Progress => right.progress.cmp(&left.progress),
+            GuildScoreSort::Status => status_rank(left).cmp(&status_rank(right)),
+        };
+        primary.then(by_score).then(left_id.cmp(right_id))
+    });
+    entries
+}
+
+/// The "Guild Members" embed field: aggregates, then the top-scored members.
+#[instrument(name = "guild_scores.format_preview", skip(entries), fields(member_count = entries.len()))]
+pub fn format_guild_preview(entries: HashMap<u64, MediaListData>, is_anime: bool) -> String {
+    let summary = GuildScoreSummary::from_entries(&entries);
+    let mut preview = summary.format_for_embed(is_anime);
+    preview.push('\n');
+
+    let sorted = sort_guild_entries(entries, GuildScoreSort::Score);
+    for (user_id, entry) in sorted.iter().take(GUILD_PREVIEW_LIMIT) {
+        preview.push_str(&format!(
+            "\n<@{user_id}>: {}",
+            entry.format_for_embed(is_anime)
+        ));
+    }
+    if sorted.len() > GUILD_PREVIEW_LIMIT {
+        preview.push_str(&format!(
+            "\n…and {} more. Use **View all** to see everyone.",
+            sorted.len() - GUILD_PREVIEW_LIMIT
+        ));
+    }
+
+    preview
+}
+
+/// Scores of 0 mean "unscored" on AniList.
+fn scored(entry: &MediaListData) -> Option<u32> {
+    entry.score.filter(|score| *score > 0)
+}
+
+/// Entries without a status sort after every status.
+fn status_rank(entry: &MediaListData) -> u8 {
+    entry
+        .status
+        .as_ref()
+        .map_or(u8::MAX, MediaListStatus::sort_rank)
+}
+
 #[instrument]
 fn status_phrase(status: &MediaListStatus, is_anime: bool) -> &'static str {
     match status {
@@ -217,6 +562,87 @@ mod tests {
         );
     }
 
+    fn entry(status: MediaListStatus, score: Option<u32>, progress: u32) -> MediaListData {
+        MediaListData {
+            status: Some(status),
+            score,
+            progress: Some(progress),
+            progress_volumes: None,
+        }
+    }
+
+    #[test]
+    fn summary_averages_non_zero_scores_and_counts_statuses() {
+        let entries = HashMap::from([
+            (1, entry(MediaListStatus::Completed, Some(90), 12)),
+            (2, entry(MediaListStatus::Current, Some(75), 4)),
+            (3, entry(MediaListStatus::Repeating, Some(0), 2)),
+            (4, entry(MediaListStatus::Dropped, None, 1)),
+        ]);
+
+        let summary = GuildScoreSummary::from_entries(&entries);
+
+        assert_eq!(summary.members, 4);
+        assert_eq!(summary.scored, 2);
+        assert_eq!(summary.mean_score, Some(83));
+        assert_eq!(
+            summary.format_for_embed(true),
+            "Server mean: **83/100** from 2 ratings\n2 watching · 1 completed · 1 dropped"
+        );
+    }
+
+    #[test]
+    fn summary_without_scores_says_so() {
+        let entries = HashMap::from([(1, entry(MediaListStatus::Current, None, 3))]);
+
+        assert_eq!(
+            GuildScoreSummary::from_entries(&entries).format_for_embed(false),
+            "Server mean: no ratings yet\n1 reading"
+        );
+    }
+
+    #[test]
+    fn entries_sort_by_the_requested_key_then_score() {
+        let entries = || {
+            HashMap::from([
+                (1, entry(MediaListStatus::Completed, Some(70), 12)),
+                (2, entry(MediaListStatus::Current, Some(95), 3)),
+                (3, entry(MediaListStatus::Planning, None, 0)),
+                (4, entry(MediaListStatus::Current, Some(80), 8)),
+            ])
+        };
+        let order = |sort| {
+            sort_guild_entries(entries(), sort)
+                .into_iter()
+                .map(|(user_id, _)| user_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(order(GuildScoreSort::Score), vec![2, 4, 1, 3]);
+        assert_eq!(order(GuildScoreSort::Progress), vec![1, 4, 2, 3]);
+        assert_eq!(order(GuildScoreSort::Status), vec![2, 4, 1, 3]);
+    }
+
+    #[test]
+    fn preview_lists_top_scores_and_counts_the_rest() {
+        let entries = (1..=7)
+            .map(|user_id| {
+                (
+                    user_id,
+                    entry(MediaListStatus::Completed, Some(50 + user_id as u32), 12),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        let preview = format_guild_preview(entries, true);
+
+        assert!(
+            preview.starts_with("Server mean: **54/100** from 7 ratings\n7 completed\n\n<@7>:")
+        );
+        assert!(!preview.contains("<@2>"));
+        assert!(preview.ends_with("…and 2 more. Use **View all** to see everyone."));
+    }
+
     #[test]
     fn paused_progress_reads_as_single_phrase() {
         let data = MediaListData {
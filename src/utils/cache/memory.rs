@@ -0,0 +1,158 @@
+//! Bounded in-process LRU cache used as the first cache tier.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serenity::async_trait;
+use tracing::debug;
+
+use super::Cache;
+
+/// Default maximum number of entries kept in memory.
+pub const MEMORY_CACHE_MAX_ENTRIES: usize = 2_000;
+/// Default maximum combined key + value size kept in memory (32 MiB).
+pub const MEMORY_CACHE_MAX_BYTES: usize = 32 * 1024 * 1024;
+
+struct Entry {
+    value: String,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+impl Entry {
+    fn size(&self, key: &str) -> usize {
+        key.len() + self.value.len()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<String, Entry>,
+    /// Recency index: `last_used` tick → key. The smallest tick is the least
+    /// recently used entry.
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    bytes: usize,
+}
+
+impl State {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(&entry.last_used);
+        self.bytes -= entry.size(key);
+        Some(entry)
+    }
+
+    fn evict_least_recently_used(&mut self) -> bool {
+        let Some((_, key)) = self.recency.pop_first() else {
+            return false;
+        };
+        if let Some(entry) = self.entries.remove(&key) {
+            self.bytes -= entry.size(&key);
+        }
+        true
+    }
+}
+
+/// LRU cache bounded by both entry count and total bytes, with per-entry
+/// expiry.
+pub struct MemoryCache {
+    max_entries: usize,
+    max_bytes: usize,
+    state: Mutex<State>,
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self::new(MEMORY_CACHE_MAX_ENTRIES, MEMORY_CACHE_MAX_BYTES)
+    }
+}
+
+impl MemoryCache {
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        Self {
+            max_entries,
+            max_bytes,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub fn get_value(&self, key: &str) -> Option<String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        let expired = state.entries.get(key)?.expires_at <= now;
+        if expired {
+            state.remove(key);
+            return None;
+        }
+
+        let tick = state.next_tick();
+        let entry = state.entries.get_mut(key)?;
+        let previous_tick = std::mem::replace(&mut entry.last_used, tick);
+        let value = entry.value.clone();
+        state.recency.remove(&previous_tick);
+        state.recency.insert(tick, key.to_string());
+        Some(value)
+    }
+
+    pub fn set_value(&self, key: &str, value: &str, ttl: Duration) {
+        let size = key.len() + value.len();
+        if size > self.max_bytes || self.max_entries == 0 {
+            debug!(key = %key, size, "Value too large for in-memory cache");
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.remove(key);
+
+        while state.entries.len() >= self.max_entries || state.bytes + size > self.max_bytes {
+            if !state.evict_least_recently_used() {
+                break;
+            }
+        }
+
+        let tick = state.next_tick();
+        state.recency.insert(tick, key.to_string());
+        state.bytes += size;
+        state.entries.insert(
+            key.to_string(),
+            Entry {
+                value: value.to_string(),
+                expires_at: Instant::now() + ttl,
+                last_used: tick,
+            },
+        );
+    }
+
+    #[cfg(test)]
+    pub fn entry_count(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entries
+            .len()
+    }
+
+    #[cfg(test)]
+    pub fn byte_size(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).bytes
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.get_value(key)
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) {
+        self.set_value(key, value, ttl);
+    }
+}
@@ -0,0 +1,259 @@
+//! Async cache abstraction shared by AniList, recommendation and Spotify
+//! lookups.
+//!
+//! Production code stores a [`SharedCache`] in the Serenity `TypeMap` under
+//! [`CacheKey`]; fetchers take `&dyn Cache` so tests can inject an in-memory
+//! implementation instead of talking to Redis.
+//!
+//! The production cache is a [`TieredCache`]: a bounded in-process LRU
+//! ([`MemoryCache`]) in front of Redis. Popular titles are served without a
+//! network round trip, and the bot keeps caching in memory when Redis is not
+//! configured or unreachable.
+//!
+//! AniList media entries are additionally written to a long-lived stale copy
+//! (see [`stale_cache_key`]) that is served when AniList itself is down.
+
+mod memory;
+
+pub use memory::MemoryCache;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::{async_trait, client::Context, prelude::TypeMapKey};
+use tracing::{debug, instrument, warn};
+
+use crate::utils::statics::{
+    ANILIST_STATUS_CANCELLED, ANILIST_STATUS_FINISHED, ANILIST_STATUS_NOT_YET_RELEASED,
+    ANILIST_STATUS_RELEASING,
+};
+
+/// Default time-to-live for cached upstream responses (5 hours).
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(18_000);
+
+/// TTL for media that is still airing/publishing or not yet out (1 hour), so
+/// episode counts and airing dates stay fresh.
+pub const ONGOING_MEDIA_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// TTL for media that has finished or been cancelled (7 days); its AniList
+/// entry rarely changes.
+pub const FINISHED_MEDIA_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How long the stale copy of an entry outlives it (30 days). Stale copies are
+/// only read when AniList is unreachable.
+pub const STALE_ENTRY_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// TTL for a member's cached list entry in guild score lookups (10 minutes),
+/// so repeat lookups of a title don't re-query every member.
+pub const GUILD_LIST_ENTRY_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// TTL for a cached guild membership check (1 hour). Members who join or
+/// leave show up in guild scores within this window.
+pub const GUILD_MEMBERSHIP_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// TTL for a cached "not a member" answer (24 hours). Most linked users are
+/// not in any given guild, so these are kept far longer than positive
+/// answers to avoid re-checking them with Discord on every lookup.
+pub const GUILD_NON_MEMBERSHIP_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// TTL for a member's cached favourite characters (6 hours). Favourites
+/// change rarely, and one lookup serves every character.
+pub const GUILD_FAVOURITES_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// TTL for a user's cached AniList list (10 minutes), long enough to page
+/// through results without refetching it.
+pub const USER_MEDIA_LIST_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// TTL for AniList's genre and tag catalogue (24 hours); it only changes
+/// when AniList adds a tag.
+pub const DISCOVER_CATALOG_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Key under which the stale copy of `key` is stored.
+pub fn stale_cache_key(key: &str) -> String {
+    format!("stale:{key}")
+}
+
+/// Pick a cache TTL from an AniList `MediaStatus`. Unknown statuses (e.g.
+/// `HIATUS`) fall back to [`DEFAULT_CACHE_TTL`].
+pub fn ttl_for_media_status(status: Option<&str>) -> Duration {
+    match status {
+        Some(ANILIST_STATUS_RELEASING | ANILIST_STATUS_NOT_YET_RELEASED) => ONGOING_MEDIA_TTL,
+        Some(ANILIST_STATUS_FINISHED | ANILIST_STATUS_CANCELLED) => FINISHED_MEDIA_TTL,
+        _ => DEFAULT_CACHE_TTL,
+    }
+}
+
+/// How long a value read from the remote tier is kept in memory. The remote
+/// TTL is unknown at read time, so promoted copies are kept short-lived.
+const PROMOTED_ENTRY_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[async_trait]
+pub trait Cache: Send + Sync {
+    /// Return the cached value for `key`, or `None` on a miss.
+    ///
+    /// Backend errors are logged by the implementation and reported as a miss
+    /// so callers can always fall through to the network.
+    async fn get(&self, key: &str) -> Option<String>;
+
+    /// Store `value` under `key` for `ttl`.
+    ///
+    /// Caching is best-effort: failures are logged, never surfaced.
+    async fn set(&self, key: &str, value: &str, ttl: Duration);
+
+    /// Store the long-lived stale copy of `key` for [`STALE_ENTRY_TTL`].
+    async fn set_stale(&self, key: &str, value: &str) {
+        self.set(&stale_cache_key(key), value, STALE_ENTRY_TTL)
+            .await;
+    }
+
+    /// Return the stale copy of `key`, read only when AniList is unreachable.
+    async fn get_stale(&self, key: &str) -> Option<String> {
+        self.get(&stale_cache_key(key)).await
+    }
+}
+
+pub type SharedCache = Arc<dyn Cache>;
+
+pub struct CacheKey;
+
+impl TypeMapKey for CacheKey {
+    type Value = SharedCache;
+}
+
+/// Cache that never stores anything; used when no backend is configured.
+pub struct NoopCache;
+
+#[async_trait]
+impl Cache for NoopCache {
+    async fn get(&self, _key: &str) -> Option<String> {
+        None
+    }
+
+    async fn set(&self, _key: &str, _value: &str, _ttl: Duration) {}
+}
+
+/// In-memory L1 in front of an optional remote L2 (Redis).
+pub struct TieredCache {
+    memory: MemoryCache,
+    remote: Option<SharedCache>,
+}
+
+impl TieredCache {
+    pub fn new(memory: MemoryCache, remote: Option<SharedCache>) -> Self {
+        Self { memory, remote }
+    }
+}
+
+#[async_trait]
+impl Cache for TieredCache {
+    #[instrument(name = "cache.tiered.get", skip(self), fields(key_len = key.len()))]
+    async fn get(&self, key: &str) -> Option<String> {
+        if let Some(value) = self.memory.get_value(key) {
+            debug!(tier = "memory", "Cache hit");
+            return Some(value);
+        }
+
+        let value = self.remote.as_ref()?.get(key).await?;
+        debug!(tier = "remote", "Cache hit");
+        self.memory.set_value(key, &value, PROMOTED_ENTRY_TTL);
+        Some(value)
+    }
+
+    #[instrument(name = "cache.tiered.set", skip(self, value), fields(key_len = key.len(), value_len = value.len()))]
+    async fn set(&self, key: &str, value: &str, ttl: Duration) {
+        self.memory.set_value(key, value, ttl);
+        if let Some(remote) = &self.remote {
+            remote.set(key, value, ttl).await;
+        }
+    }
+
+    /// Stale copies go to the remote tier when there is one, so they never
+    /// crowd hot entries out of the bounded memory tier. Without one they
+    /// are kept in memory, so the fallback still works.
+    #[instrument(name = "cache.tiered.set_stale", skip(self, value), fields(key_len = key.len(), value_len = value.len()))]
+    async fn set_stale(&self, key: &str, value: &str) {
+        match &self.remote {
+            Some(remote) => remote.set_stale(key, value).await,
+            None => self
+                .memory
+                .set_value(&stale_cache_key(key), value, STALE_ENTRY_TTL),
+        }
+    }
+
+    /// Read from the remote tier without promoting into memory.
+    #[instrument(name = "cache.tiered.get_stale", skip(self), fields(key_len = key.len()))]
+    async fn get_stale(&self, key: &str) -> Option<String> {
+        match &self.remote {
+            Some(remote) => remote.get_stale(key).await,
+            None => self.memory.get_value(&stale_cache_key(key)),
+        }
+    }
+}
+
+#[instrument(name = "cache.from_context", skip(ctx))]
+pub async fn get_cache_from_context(ctx: &Context) -> SharedCache {
+    let data = ctx.data.read().await;
+    match data.get::<CacheKey>() {
+        Some(cache) => cache.clone(),
+        None => {
+            warn!("Cache is not available in Serenity context; caching disabled");
+            Arc::new(NoopCache)
+        }
+    }
+}
+
+#[cfg(test)]
+pub use in_memory::InMemoryCache;
+
+#[cfg(test)]
+mod in_memory {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use serenity::async_trait;
+
+    use super::Cache;
+
+    /// Process-local [`Cache`] for tests. TTLs are recorded but not enforced.
+    #[derive(Default)]
+    pub struct InMemoryCache {
+        entries: Mutex<HashMap<String, (String, Duration)>>,
+    }
+
+    impl InMemoryCache {
+        pub fn with_entry(key: &str, value: &str) -> Self {
+            let cache = Self::default();
+            cache.entries.lock().unwrap().insert(
+                key.to_string(),
+                (value.to_string(), super::DEFAULT_CACHE_TTL),
+            );
+            cache
+        }
+
+        pub fn ttl(&self, key: &str) -> Option<Duration> {
+            self.entries.lock().unwrap().get(key).map(|(_, ttl)| *ttl)
+        }
+    }
+
+    #[async_trait]
+    impl Cache for InMemoryCache {
+        async fn get(&self, key: &str) -> Option<String> {
+            self.entries
+                .lock()
+                .unwrap()
+                .get(key)
+                .map(|(value, _)| value.clone())
+        }
+
+        async fn set(&self, key: &str, value: &str, ttl: Duration) {
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), (value.to_string(), ttl));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use super::*;
+
+#[tokio::test]
+async fn noop_cache_never_returns_values() {
+    let cache = NoopCache;
+    cache.set("key", "value", DEFAULT_CACHE_TTL).await;
+
+    assert_eq!(cache.get("key").await, None);
+}
+
+#[tokio::test]
+async fn in_memory_cache_round_trips_values_and_ttls() {
+    let cache = InMemoryCache::default();
+    cache.set("key", "value", Duration::from_secs(60)).await;
+
+    assert_eq!(cache.get("key").await.as_deref(), Some("value"));
+    assert_eq!(cache.ttl("key"), Some(Duration::from_secs(60)));
+    assert_eq!(cache.get("missing").await, None);
+}
+
+#[test]
+fn memory_cache_evicts_least_recently_used_entry_when_full() {
+    let cache = MemoryCache::new(2, usize::MAX);
+    cache.set_value("a", "1", DEFAULT_CACHE_TTL);
+    cache.set_value("b", "2", DEFAULT_CACHE_TTL);
+
+    // Touch `a` so `b` becomes the eviction candidate.
+    assert_eq!(cache.get_value("a").as_deref(), Some("1"));
+    cache.set_value("c", "3", DEFAULT_CACHE_TTL);
+
+    assert_eq!(cache.entry_count(), 2);
+    assert_eq!(cache.get_value("a").as_deref(), Some("1"));
+    assert_eq!(cache.get_value("b"), None);
+    assert_eq!(cache.get_value("c").as_deref(), Some("3"));
+}
+
+#[test]
+fn memory_cache_evicts_until_within_byte_budget() {
+    let cache = MemoryCache::new(100, 10);
+    cache.set_value("a", "1234", DEFAULT_CACHE_TTL);
+    cache.set_value("b", "1234", DEFAULT_CACHE_TTL);
+    assert_eq!(cache.byte_size(), 10);
+
+    cache.set_value("c", "12", DEFAULT_CACHE_TTL);
+
+    assert_eq!(cache.get_value("a"), None);
+    assert_eq!(cache.byte_size(), 8);
+}
+
+#[test]
+fn memory_cache_skips_values_larger_than_budget() {
+    let cache = MemoryCache::new(100, 4);
+    cache.set_value("key", "too large", DEFAULT_CACHE_TTL);
+
+    assert_eq!(cache.entry_count(), 0);
+    assert_eq!(cache.get_value("key"), None);
+}
+
+#[test]
+fn memory_cache_replaces_existing_key_without_leaking_bytes() {
+    let cache = MemoryCache::new(100, 100);
+    cache.set_value("key", "first", DEFAULT_CACHE_TTL);
+    cache.set_value("key", "2nd", DEFAULT_CACHE_TTL);
+
+    assert_eq!(cache.entry_count(), 1);
+    assert_eq!(cache.byte_size(), "key".len() + "2nd".len());
+    assert_eq!(cache.get_value("key").as_deref(), Some("2nd"));
+}
+
+#[test]
+fn memory_cache_expires_entries() {
+    let cache = MemoryCache::new(100, 100);
+    cache.set_value("key", "value", Duration::ZERO);
+
+    assert_eq!(cache.get_value("key"), None);
+    assert_eq!(cache.entry_count(), 0);
+}
+
+#[tokio::test]
+async fn tiered_cache_works_without_remote_tier() {
+    let cache = TieredCache::new(MemoryCache::default(), None);
+    cache.set("key", "value", DEFAULT_CACHE_TTL).await;
+
+    assert_eq!(cache.get("key").await.as_deref(), Some("value"));
+}
+
+#[tokio::test]
+async fn tiered_cache_writes_through_to_remote_tier() {
+    let remote = Arc::new(InMemoryCache::default());
+    let cache = TieredCache::new(MemoryCache::default(), Some(remote.clone()));
+    cache.set("key", "value", Duration::from_secs(60)).await;
+
+    assert_eq!(remote.get("key").await.as_deref(), Some("value"));
+    assert_eq!(remote.ttl("key"), Some(Duration::from_secs(60)));
+}
+
+#[tokio::test]
+async fn tiered_cache_promotes_remote_hits_into_memory() {
+    let remote = Arc::new(InMemoryCache::with_entry("key", "value"));
+    let memory = MemoryCache::default();
+    let cache = TieredCache::new(memory, Some(remote));
+
+    assert_eq!(cache.get("key").await.as_deref(), Some("value"));
+    assert_eq!(cache.memory.get_value("key").as_deref(), Some("value"));
+}
+
+#[tokio::test]
+async fn tiered_cache_keeps_stale_copies_out_of_memory() {
+    let remote = Arc::new(InMemoryCache::default());
+    let cache = TieredCache::new(MemoryCache::default(), Some(remote.clone()));
+    cache.set_stale("key", "value").await;
+
+    assert_eq!(cache.memory.entry_count(), 0);
+    assert_eq!(remote.ttl("stale:key"), Some(STALE_ENTRY_TTL));
+    assert_eq!(cache.get_stale("key").await.as_deref(), Some("value"));
+    assert_eq!(cache.memory.entry_count(), 0);
+}
+
+#[tokio::test]
+async fn tiered_cache_keeps_stale_copies_in_memory_without_remote_tier() {
+    let cache = TieredCache::new(MemoryCache::default(), None);
+    cache.set_stale("key", "value").await;
+
+    assert_eq!(cache.get_stale("key").await.as_deref(), Some("value"));
+    assert_eq!(cache.get("key").await, None);
+}
+
+#[test]
+fn media_status_selects_ttl() {
+    assert_eq!(ttl_for_media_status(Some("RELEASING")), ONGOING_MEDIA_TTL);
+    assert_eq!(
+        ttl_for_media_status(Some("NOT_YET_RELEASED")),
+        ONGOING_MEDIA_TTL
+    );
+    assert_eq!(ttl_for_media_status(Some("FINISHED")), FINISHED_MEDIA_TTL);
+    assert_eq!(ttl_for_media_status(Some("CANCELLED")), FINISHED_MEDIA_TTL);
+    assert_eq!(ttl_for_media_status(Some("HIATUS")), DEFAULT_CACHE_TTL);
+    assert_eq!(ttl_for_media_status(None), DEFAULT_CACHE_TTL);
+}
@@ -1,4 +1,7 @@
-use crate::utils::requests::anilist::{AniListRequestError, send_request};
+use crate::utils::requests::{
+    anilist::send_request,
+    graphql::{AniListError, check_response},
+};
 
 use serde_json::json;
 use tracing::{info, instrument, warn};
@@ -102,26 +105,16 @@ fn has_media_results(response: &str) -> Option<bool> {
 async fn fetch_name_search(
     query: &str,
     search: &str,
-) -> Result<(String, Option<bool>), AniListRequestError> {
+) -> Result<(String, Option<bool>), AniListError> {
     let json = json!({"query": query, "variables": {"search": search}});
-    let result = send_request(json).await?;
+    let result = check_response(send_request(json).await)?;
     let has_results = has_media_results(&result);
 
     Ok((result, has_results))
 }
 
-#[instrument(name = "anilist.fetch_by_id", skip(query), fields(id = id))]
-pub async fn fetch_by_id(query: String, id: u32) -> Result<String, AniListRequestError> {
-    let json = json!({"query": query, "variables": {"id":id}});
-    let result = send_request(json).await?;
-
-    info!("Fetched By ID: {:#?}", id);
-
-    Ok(result)
-}
-
 #[instrument(name = "anilist.fetch_by_name", skip(query), fields(name_len = name.len()))]
-pub async fn fetch_by_name(query: String, name: String) -> Result<String, AniListRequestError> {
+pub async fn fetch_by_name(query: String, name: String) -> Result<String, AniListError> {
     if !name.as_str().is_japanese() {
         let (result, _) = fetch_name_search(&query, &name).await?;
         info!("Fetched By Name: {:#?}", name);
@@ -182,9 +175,9 @@ pub async fn fetch_by_name(query: String, name: String) -> Result<String, AniLis
 }
 
 #[instrument(name = "anilist.fetch_by_raw_name", skip(query), fields(name_len = name.len()))]
-pub async fn fetch_by_raw_name(query: String, name: String) -> Result<String, AniListRequestError> {
+pub async fn fetch_by_raw_name(query: String, name: String) -> Result<String, AniListError> {
     let json = json!({"query": query, "variables": {"search": name}});
-    let result = send_request(json).await?;
+    let result = check_response(send_request(json).await)?;
 
     info!("Fetched By Raw Name");
 
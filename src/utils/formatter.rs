@@ -12,6 +12,11 @@ pub fn code(input: &str) -> String {
     format!("`{input}`")
 }
 
+/// Discord spoiler markup: hidden until clicked.
+pub fn spoiler(input: &str) -> String {
+    format!("||{input}||")
+}
+
 #[allow(dead_code)]
 pub fn strike(input: &str) -> String {
     format!("~~{input}~~")
@@ -31,3 +36,33 @@ pub fn remove_underscores_and_titlecase(text: &str) -> String {
 pub fn titlecase(text: &str) -> String {
     imported_titlecase(text)
 }
+
+/// A length of time in minutes as hours and minutes, e.g. `4h 48m`.
+pub fn format_runtime(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+/// A rough length of time: whole hours past an hour, e.g. `≈ 10h`.
+pub fn format_approximate_runtime(minutes: u32) -> String {
+    if minutes < 60 {
+        return format!("≈ {minutes}m");
+    }
+    format!("≈ {}h", (minutes + 30) / 60)
+}
+
+/// A count with thousands separators, e.g. `1,234,567`.
+pub fn format_count(count: u32) -> String {
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
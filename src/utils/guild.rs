@@ -1,35 +1,42 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
 
 use crate::{
     models::{
         db::{oauth_credential::OAuthCredential, settings::get_user_settings_for_discord_ids},
         settings::SettingKey,
         transformers::Transformers,
+        user_favourites::UserFavouritesData,
         user_media_list::MediaListData,
     },
     utils::{
-        database::get_pool_from_context,
-        requests::anilist::send_request,
+        cache::{
+            GUILD_FAVOURITES_TTL, GUILD_LIST_ENTRY_TTL, GUILD_MEMBERSHIP_TTL,
+            GUILD_NON_MEMBERSHIP_TTL, SharedCache, get_cache_from_context,
+        },
+        database::{DbPool, get_pool_from_context},
+        privacy::hash_user_id,
+        requests::{
+            anilist::send_request,
+            fragments::media_list_entry,
+            graphql::{AliasedResponse, classify_aliased},
+            query::{Field, Fragment, Query, Selection, VariableType},
+        },
         settings::{participates_in_guild_scores, resolve_guild_scores_enabled_with_pool},
     },
 };
 
 use serenity::{
-    all::CommandInteraction,
+    Error as SerenityError,
     client::Context,
-    model::prelude::{Guild, GuildId, UserId},
+    http::Http,
+    model::prelude::{GuildId, UserId},
 };
 
-use serde::Deserialize;
 use serde_json::json;
-use tracing::{error, info, instrument};
-
-#[derive(Deserialize, Debug)]
-struct BatchUserMediaListResponse {
-    data: Option<HashMap<String, Option<MediaListData>>>,
-}
-
-const MEDIA_LIST_QUERY_FIELDS: &str = "status\nscore(format: POINT_100)\nprogress\nprogressVolumes";
+use tokio::{sync::Semaphore, task::JoinSet};
+use tracing::{Instrument, error, info, instrument, warn};
 
 #[instrument(name = "guild.media_alias")]
 fn media_alias(index: usize) -> String {
@@ -38,97 +45,357 @@ fn media_alias(index: usize) -> String {
 
 #[instrument(name = "guild.build_batch_media_list_query", skip(guild_members), fields(member_count = guild_members.len()))]
 fn build_batch_media_list_query(guild_members: &[OAuthCredential]) -> String {
-    let media_lookups = guild_members
+    let entry = Selection::new().spread(&media_list_entry());
+
+    guild_members
         .iter()
         .enumerate()
-        .map(|(index, credential)| {
-            format!(
-                "  {}: MediaList(userId: {}, type: $type, mediaId: $mediaId) {{\n    {}\n  }}",
-                media_alias(index),
-                credential.anilist_id,
-                MEDIA_LIST_QUERY_FIELDS
-            )
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+        .fold(
+            Query::new()
+                .variable("type", VariableType::MediaType)
+                .variable("mediaId", VariableType::Int),
+            |query, (index, credential)| {
+                query.field(
+                    Field::new("MediaList")
+                        .alias(media_alias(index))
+                        .arg("userId", credential.anilist_id)
+                        .arg("type", "$type")
+                        .arg("mediaId", "$mediaId")
+                        .select(entry.clone()),
+                )
+            },
+        )
+        .render()
+}
 
+/// Concurrent Discord member lookups while checking linked users.
+const MEMBERSHIP_CHECK_CONCURRENCY: usize = 5;
+/// Discord member lookups one interaction waits for. Any further unknown
+/// users are checked in the background and show up on a later lookup.
+const MEMBERSHIP_CHECKS_PER_INTERACTION: usize = 10;
+/// How long the linked-account list is reused before it is read again.
+const LINKED_ACCOUNTS_TTL: Duration = Duration::from_secs(5 * 60);
+
+struct LinkedAccounts {
+    fetched_at: Instant,
+    credentials: Arc<Vec<OAuthCredential>>,
+}
+
+/// Every linked account, kept in process so each guild lookup doesn't read
+/// the whole table. Not put in the shared cache, since it holds raw Discord
+/// IDs.
+static LINKED_ACCOUNTS: LazyLock<Mutex<Option<LinkedAccounts>>> = LazyLock::new(Default::default);
+
+/// Membership cache keys with a background check already running.
+static MEMBERSHIP_CHECKS_PENDING: LazyLock<Mutex<HashSet<String>>> =
+    LazyLock::new(Default::default);
+
+/// Every linked account, read from the database at most once per
+/// [`LINKED_ACCOUNTS_TTL`].
+#[instrument(name = "guild.linked_accounts", skip(database_pool))]
+async fn linked_accounts(database_pool: &DbPool) -> Result<Arc<Vec<OAuthCredential>>, sqlx::Error> {
+    {
+        let linked = LINKED_ACCOUNTS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(linked) = linked.as_ref()
+            && linked.fetched_at.elapsed() < LINKED_ACCOUNTS_TTL
+        {
+            return Ok(linked.credentials.clone());
+        }
+    }
+
+    let credentials = Arc::new(OAuthCredential::get_all(database_pool).await?);
+    *LINKED_ACCOUNTS.lock().unwrap_or_else(|e| e.into_inner()) = Some(LinkedAccounts {
+        fetched_at: Instant::now(),
+        credentials: credentials.clone(),
+    });
+    Ok(credentials)
+}
+
+/// Drop the in-process linked-account list, e.g. after a user unlinks, so
+/// they leave guild lookups straight away.
+pub fn forget_linked_accounts() {
+    *LINKED_ACCOUNTS.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+#[instrument(name = "guild.membership_cache_key", skip(discord_id))]
+fn membership_cache_key(guild_id: GuildId, discord_id: u64) -> String {
     format!(
-        "query ($type: MediaType, $mediaId: Int) {{\n{}\n}}",
-        media_lookups
+        "guild:member:{}:{}",
+        guild_id.get(),
+        hash_user_id(discord_id)
     )
 }
 
-#[instrument(name = "discord.guild.member_ids", skip(guild), fields(member_count = guild.members.len()))]
-fn get_guild_member_ids(guild: &Guild) -> Vec<UserId> {
-    let members: Vec<UserId> = guild.members.keys().copied().collect();
-    info!("Found {:#?} members in guild", members.len());
+/// Members of `guild_id` that Serenity's cache already knows about. Without
+/// the privileged members intent this is only a subset of the guild.
+#[instrument(name = "discord.guild.cached_member_ids", skip(ctx))]
+fn cached_member_ids(ctx: &Context, guild_id: GuildId) -> HashSet<UserId> {
+    guild_id
+        .to_guild_cached(&ctx.cache)
+        .map(|guild| guild.members.keys().copied().collect())
+        .unwrap_or_default()
+}
+
+/// Ask Discord whether `user_id` is in `guild_id`. `None` when the answer is
+/// unknown (e.g. Discord errored), so it is neither cached nor trusted.
+#[instrument(name = "discord.guild.check_membership", skip(http, user_id))]
+async fn check_membership(http: &Http, guild_id: GuildId, user_id: UserId) -> Option<bool> {
+    match http.get_member(guild_id, user_id).await {
+        Ok(_) => Some(true),
+        Err(SerenityError::Http(error))
+            if error.status_code().map(|status| status.as_u16()) == Some(404) =>
+        {
+            Some(false)
+        }
+        Err(error) => {
+            warn!(error = %error, "Failed to check guild membership");
+            None
+        }
+    }
+}
+
+/// Narrow linked users down to members of `guild_id`.
+///
+/// Starts from the linked accounts rather than the guild's member list, which
+/// Serenity only partially caches. Users in the Serenity cache are known
+/// members; everyone else is checked through Discord's member endpoint, with
+/// the answer cached for [`GUILD_MEMBERSHIP_TTL`] (or
+/// [`GUILD_NON_MEMBERSHIP_TTL`] for non-members). Only
+/// [`MEMBERSHIP_CHECKS_PER_INTERACTION`] checks are waited for; the rest run
+/// in the background.
+#[instrument(name = "guild.linked_members", skip(ctx, cache, credentials), fields(linked_count = credentials.len()))]
+async fn linked_guild_members(
+    ctx: &Context,
+    cache: &SharedCache,
+    guild_id: GuildId,
+    credentials: Vec<OAuthCredential>,
+) -> Vec<OAuthCredential> {
+    let cached_members = cached_member_ids(ctx, guild_id);
+    let mut members = Vec::new();
+    let mut unknown = Vec::new();
+
+    for credential in credentials {
+        let Some(discord_id) = credential.discord_id_u64() else {
+            continue;
+        };
+        if cached_members.contains(&UserId::new(discord_id)) {
+            members.push(credential);
+            continue;
+        }
+        match cache
+            .get(&membership_cache_key(guild_id, discord_id))
+            .await
+            .as_deref()
+        {
+            Some("1") => members.push(credential),
+            Some(_) => {}
+            None => unknown.push((discord_id, credential)),
+        }
+    }
+
+    info!(
+        known = members.len(),
+        unchecked = unknown.len(),
+        "Resolved guild membership from caches"
+    );
+
+    let deferred = unknown.split_off(unknown.len().min(MEMBERSHIP_CHECKS_PER_INTERACTION));
+    check_memberships_in_background(ctx.http.clone(), cache.clone(), guild_id, deferred);
+    members.extend(check_memberships(ctx.http.clone(), cache, guild_id, unknown).await);
+
     members
 }
 
-#[instrument(name = "discord.guild.from_interaction", skip(ctx, interaction), fields(has_guild_id = interaction.guild_id.is_some()))]
-fn get_guild_from_interaction(ctx: &Context, interaction: &CommandInteraction) -> Option<Guild> {
-    interaction
-        .guild_id
-        .and_then(|guild_id| guild_id.to_guild_cached(&ctx.cache))
-        // skipcq: RS-W1206 - CacheRef requires explicit clone via Deref
-        .map(|g| g.clone())
+/// Check each user with Discord and cache the answers, returning the users
+/// who are members.
+#[instrument(name = "guild.check_memberships", skip(http, cache, users), fields(user_count = users.len()))]
+async fn check_memberships(
+    http: Arc<Http>,
+    cache: &SharedCache,
+    guild_id: GuildId,
+    users: Vec<(u64, OAuthCredential)>,
+) -> Vec<OAuthCredential> {
+    let permits = Arc::new(Semaphore::new(MEMBERSHIP_CHECK_CONCURRENCY));
+    let mut checks = JoinSet::new();
+    for (discord_id, credential) in users {
+        let http = http.clone();
+        let permits = permits.clone();
+        checks.spawn(
+            async move {
+                let _permit = permits.acquire_owned().await.ok()?;
+                let is_member = check_membership(&http, guild_id, UserId::new(discord_id)).await?;
+                Some((discord_id, credential, is_member))
+            }
+            .in_current_span(),
+        );
+    }
+
+    let mut members = Vec::new();
+    while let Some(result) = checks.join_next().await {
+        let Ok(Some((discord_id, credential, is_member))) = result else {
+            continue;
+        };
+        let (value, ttl) = if is_member {
+            ("1", GUILD_MEMBERSHIP_TTL)
+        } else {
+            ("0", GUILD_NON_MEMBERSHIP_TTL)
+        };
+        cache
+            .set(&membership_cache_key(guild_id, discord_id), value, ttl)
+            .await;
+        if is_member {
+            members.push(credential);
+        }
+    }
+
+    members
 }
 
-#[instrument(name = "discord.guild.current_members", skip(ctx, interaction), fields(has_guild_id = interaction.guild_id.is_some()))]
-pub fn get_current_guild_members(ctx: &Context, interaction: &CommandInteraction) -> Vec<UserId> {
-    get_guild_from_interaction(ctx, interaction)
-        .as_ref()
-        .map(get_guild_member_ids)
-        .unwrap_or_default()
+/// Warm the membership cache for `users` without holding up the interaction.
+/// Users already being checked in the background are skipped.
+#[instrument(name = "guild.check_memberships_in_background", skip(http, cache, users), fields(user_count = users.len()))]
+fn check_memberships_in_background(
+    http: Arc<Http>,
+    cache: SharedCache,
+    guild_id: GuildId,
+    users: Vec<(u64, OAuthCredential)>,
+) {
+    let (keys, users): (Vec<_>, Vec<_>) = {
+        let mut pending = MEMBERSHIP_CHECKS_PENDING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        users
+            .into_iter()
+            .filter_map(|(discord_id, credential)| {
+                let key = membership_cache_key(guild_id, discord_id);
+                pending
+                    .insert(key.clone())
+                    .then_some((key, (discord_id, credential)))
+            })
+            .unzip()
+    };
+    if users.is_empty() {
+        return;
+    }
+
+    info!(
+        deferred = users.len(),
+        "Checking remaining guild members in the background"
+    );
+    tokio::spawn(
+        async move {
+            check_memberships(http, &cache, guild_id, users).await;
+
+            let mut pending = MEMBERSHIP_CHECKS_PENDING
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            for key in &keys {
+                pending.remove(key);
+            }
+        }
+        .in_current_span(),
+    );
 }
 
-#[instrument(name = "guild.fetch_media_data", skip(ctx, media, guild_members), fields(member_count = guild_members.len()))]
+#[instrument(name = "guild.fetch_media_data", skip(ctx, media))]
 pub async fn get_guild_data_for_media<T: Transformers>(
     ctx: &Context,
     media: &T,
     guild_id: Option<GuildId>,
-    guild_members: Vec<UserId>,
 ) -> HashMap<u64, MediaListData> {
+    get_guild_data(ctx, guild_id, media.get_id(), media.get_type()).await
+}
+
+/// Guild members' list entries for `media_id`, for callers that only have
+/// the ID and type (e.g. components on an earlier response).
+#[instrument(name = "guild.fetch_data", skip(ctx))]
+pub async fn get_guild_data(
+    ctx: &Context,
+    guild_id: Option<GuildId>,
+    media_id: u32,
+    media_type: &str,
+) -> HashMap<u64, MediaListData> {
+    let Some((cache, guild_members)) = participating_guild_members(ctx, guild_id).await else {
+        return HashMap::new();
+    };
+
+    get_guild_anilist_data(&cache, guild_members, media_id, media_type.to_owned()).await
+}
+
+/// Discord IDs of guild members who have `character_id` in their AniList
+/// favourites, in ascending order.
+#[instrument(name = "guild.fetch_character_favourites", skip(ctx))]
+pub async fn get_guild_favourites_for_character(
+    ctx: &Context,
+    guild_id: Option<GuildId>,
+    character_id: u32,
+) -> Vec<u64> {
+    let Some((cache, guild_members)) = participating_guild_members(ctx, guild_id).await else {
+        return Vec::new();
+    };
+
+    let mut favourited_by = get_guild_favourite_characters(&cache, guild_members)
+        .await
+        .into_iter()
+        .filter(|(_, character_ids)| character_ids.contains(&character_id))
+        .map(|(discord_id, _)| discord_id)
+        .collect::<Vec<_>>();
+    favourited_by.sort_unstable();
+    favourited_by
+}
+
+/// Linked, opted-in members of `guild_id`, or `None` when guild scores don't
+/// apply (outside a guild, disabled by the guild, no linked members or a
+/// storage failure).
+#[instrument(name = "guild.participating_members", skip(ctx))]
+async fn participating_guild_members(
+    ctx: &Context,
+    guild_id: Option<GuildId>,
+) -> Option<(SharedCache, Vec<OAuthCredential>)> {
+    let guild_id = guild_id?;
+
     let Some(database_pool) = get_pool_from_context(ctx).await else {
         error!("Database pool is not available in Serenity context");
-        return HashMap::new();
+        return None;
     };
 
-    if !resolve_guild_scores_enabled_with_pool(&database_pool, guild_id).await {
+    if !resolve_guild_scores_enabled_with_pool(&database_pool, Some(guild_id)).await {
         info!("Guild scores are disabled for this interaction");
-        return HashMap::new();
+        return None;
     }
 
-    let anilist_users =
-        match OAuthCredential::get_by_discord_ids(guild_members, &database_pool).await {
-            Ok(users) => users,
-            Err(err) => {
-                error!(error = %err, "Failed to fetch registered guild members from database");
-                return HashMap::new();
-            }
-        };
+    let linked_users = match linked_accounts(&database_pool).await {
+        Ok(users) => users.as_ref().clone(),
+        Err(err) => {
+            error!(error = %err, "Failed to fetch linked users from database");
+            return None;
+        }
+    };
 
+    // Drop opted-out users first so they don't cost a membership check.
     let participating_users =
-        match filter_guild_score_participants(anilist_users, &database_pool).await {
+        match filter_guild_score_participants(linked_users, &database_pool).await {
             Ok(users) => users,
             Err(err) => {
                 error!(error = %err, "Failed to resolve guild score opt-outs");
-                return HashMap::new();
+                return None;
             }
         };
 
-    get_guild_anilist_data(
-        participating_users,
-        media.get_id(),
-        media.get_type().to_owned(),
-    )
-    .await
+    let cache = get_cache_from_context(ctx).await;
+    let guild_members = linked_guild_members(ctx, &cache, guild_id, participating_users).await;
+    if guild_members.is_empty() {
+        info!("No linked users found in guild");
+        return None;
+    }
+
+    Some((cache, guild_members))
 }
 
 #[instrument(name = "guild.filter_score_participants", skip(guild_members, database_pool), fields(member_count = guild_members.len()))]
 async fn filter_guild_score_participants(
     guild_members: Vec<OAuthCredential>,
-    database_pool: &crate::utils::database::DbPool,
+    database_pool: &DbPool,
 ) -> Result<Vec<OAuthCredential>, crate::models::db::settings::SettingsStorageError> {
     let discord_user_ids = guild_members
         .iter()
@@ -149,32 +416,143 @@ async fn filter_guild_score_participants(
         .collect())
 }
 
-#[instrument(name = "guild.fetch_anilist_data", skip(guild_members, media_type), fields(member_count = guild_members.len(), media_id = media_id, media_type = %media_type))]
+/// Members per batched `MediaList` request, keeping each document well
+/// within AniList's query complexity limit.
+const GUILD_BATCH_CHUNK_SIZE: usize = 25;
+/// Chunk requests in flight at once for one lookup.
+const GUILD_BATCH_CONCURRENCY: usize = 3;
+
+#[instrument(name = "guild.list_entry_cache_key", skip(media_type))]
+fn list_entry_cache_key(anilist_id: i64, media_type: &str, media_id: u32) -> String {
+    format!(
+        "guild:list:{anilist_id}:{}:{media_id}",
+        media_type.to_uppercase()
+    )
+}
+
+#[instrument(name = "guild.fetch_anilist_data", skip(cache, guild_members, media_type), fields(member_count = guild_members.len(), media_id = media_id, media_type = %media_type))]
 async fn get_guild_anilist_data(
+    cache: &SharedCache,
     guild_members: Vec<OAuthCredential>,
     media_id: u32,
     media_type: String,
 ) -> HashMap<u64, MediaListData> {
-    if guild_members.is_empty() {
-        return HashMap::new();
-    }
+    let mut guild_members_data: HashMap<u64, MediaListData> = HashMap::new();
+    let mut uncached_members = Vec::new();
 
     // Skip credentials whose stored discord_user_id is not a valid u64; we
     // index back into the per-guild HashMap by Discord snowflake (u64).
-    let discord_ids_by_media_alias: HashMap<String, u64> = guild_members
-        .iter()
-        .enumerate()
-        .filter_map(|(index, credential)| {
-            credential
-                .discord_id_u64()
-                .map(|discord_id| (media_alias(index), discord_id))
-        })
-        .collect();
+    for credential in guild_members {
+        let Some(discord_id) = credential.discord_id_u64() else {
+            continue;
+        };
+        let cache_key = list_entry_cache_key(credential.anilist_id, &media_type, media_id);
+        match cache
+            .get(&cache_key)
+            .await
+            .and_then(|cached| serde_json::from_str::<Option<MediaListData>>(&cached).ok())
+        {
+            Some(entry) => {
+                if let Some(data) = entry {
+                    guild_members_data.insert(discord_id, data);
+                }
+            }
+            None => uncached_members.push((discord_id, credential)),
+        }
+    }
 
-    let query = build_batch_media_list_query(&guild_members);
+    info!(
+        cached = guild_members_data.len(),
+        uncached = uncached_members.len(),
+        "Resolved cached guild list entries"
+    );
+    if uncached_members.is_empty() {
+        return guild_members_data;
+    }
 
+    let permits = Arc::new(Semaphore::new(GUILD_BATCH_CONCURRENCY));
+    let mut chunks = JoinSet::new();
+    for chunk in uncached_members.chunks(GUILD_BATCH_CHUNK_SIZE) {
+        let chunk = chunk.to_vec();
+        let permits = permits.clone();
+        let media_type = media_type.clone();
+        chunks.spawn(
+            async move {
+                let _permit = permits.acquire_owned().await.ok()?;
+                fetch_list_entry_chunk(&chunk, media_id, &media_type)
+                    .await
+                    .map(|entries| (chunk, entries))
+            }
+            .in_current_span(),
+        );
+    }
+
+    // A failed chunk only loses its own members; the rest are still shown.
+    while let Some(result) = chunks.join_next().await {
+        let Ok(Some((chunk, entries))) = result else {
+            continue;
+        };
+        store_list_entry_chunk(
+            cache,
+            chunk,
+            entries,
+            &media_type,
+            media_id,
+            &mut guild_members_data,
+        )
+        .await;
+    }
+
+    guild_members_data
+}
+
+/// Cache and collect one chunk's list entries. Members whose own lookup
+/// failed are skipped and left uncached, so the next lookup retries them.
+#[instrument(name = "guild.store_list_entry_chunk", skip_all, fields(member_count = chunk.len()))]
+async fn store_list_entry_chunk(
+    cache: &SharedCache,
+    chunk: Vec<(u64, OAuthCredential)>,
+    mut entries: AliasedResponse<MediaListData>,
+    media_type: &str,
+    media_id: u32,
+    guild_members_data: &mut HashMap<u64, MediaListData>,
+) {
+    for (index, (discord_id, credential)) in chunk.into_iter().enumerate() {
+        let entry = match entries.remove(&media_alias(index)) {
+            Ok(entry) => entry,
+            Err(err) => {
+                warn!(
+                    error = %err,
+                    discord_user_id = %hash_user_id(discord_id),
+                    "AniList list entry lookup failed for one member"
+                );
+                continue;
+            }
+        };
+        if let Ok(encoded) = serde_json::to_string(&entry) {
+            let cache_key = list_entry_cache_key(credential.anilist_id, media_type, media_id);
+            cache.set(&cache_key, &encoded, GUILD_LIST_ENTRY_TTL).await;
+        }
+        if let Some(data) = entry {
+            guild_members_data.insert(discord_id, data);
+        }
+    }
+}
+
+/// Fetch one chunk's list entries, keyed by [`media_alias`] of each member's
+/// index within the chunk. `None` when the whole request fails.
+#[instrument(name = "guild.fetch_list_entry_chunk", skip(chunk, media_type), fields(member_count = chunk.len()))]
+async fn fetch_list_entry_chunk(
+    chunk: &[(u64, OAuthCredential)],
+    media_id: u32,
+    media_type: &str,
+) -> Option<AliasedResponse<MediaListData>> {
+    let credentials = chunk
+        .iter()
+        .map(|(_, credential)| credential.clone())
+        .collect::<Vec<_>>();
     let body = json!({
-        "query": query,
+        "query": build_batch_media_list_query(&credentials),
         "variables": {
             "type": media_type.to_uppercase(),
             "mediaId": media_id
@@ -185,46 +563,271 @@ async fn get_guild_anilist_data(
         request_body_len = body.to_string().len(),
         "Sending batch AniList media list query"
     );
-    let user_media_list_response = match send_request(body).await {
-        Ok(response) => response,
+    // Members without an entry for this media come back as 404 errors next to
+    // the partial data, and an error on one member's alias only fails that
+    // member.
+    match classify_aliased::<MediaListData>(send_request(body).await) {
+        Ok(entries) => Some(entries),
         Err(err) => {
             error!(error = %err, "AniList batch media list request failed");
-            return HashMap::new();
+            None
         }
-    };
+    }
+}
+
+/// Members per batched favourites request. Each member selects a page of
+/// favourites, so chunks are smaller than [`GUILD_BATCH_CHUNK_SIZE`].
+const GUILD_FAVOURITES_CHUNK_SIZE: usize = 10;
+/// Favourite characters read per page. AniList pages favourites.
+const FAVOURITE_CHARACTERS_PER_PAGE: u32 = 50;
+/// Pages of favourite characters read per member. Members with more
+/// favourites than this are cached with the ones read so far.
+const FAVOURITE_CHARACTER_PAGES: u32 = 4;
+
+fn user_favourite_characters() -> Fragment {
+    Fragment::new(
+        "UserFavouriteCharacters",
+        "User",
+        Selection::new().object(
+            "favourites",
+            Selection::new().field(
+                Field::new("characters")
+                    .arg("perPage", FAVOURITE_CHARACTERS_PER_PAGE)
+                    .arg("page", "$page")
+                    .select(
+                        Selection::new()
+                            .object("pageInfo", Selection::new().field("hasNextPage"))
+                            .object("nodes", Selection::new().field("id")),
+                    ),
+            ),
+        ),
+    )
+}
+
+#[instrument(name = "guild.build_batch_favourites_query", skip(guild_members), fields(member_count = guild_members.len()))]
+fn build_batch_favourites_query(guild_members: &[OAuthCredential]) -> String {
+    let favourites = Selection::new().spread(&user_favourite_characters());
+
+    guild_members
+        .iter()
+        .enumerate()
+        .fold(
+            Query::new().variable("page", VariableType::Int),
+            |query, (index, credential)| {
+                query.field(
+                    Field::new("User")
+                        .alias(user_alias(index))
+                        .arg("id", credential.anilist_id)
+                        .select(favourites.clone()),
+                )
+            },
+        )
+        .render()
+}
+
+#[instrument(name = "guild.user_alias")]
+fn user_alias(index: usize) -> String {
+    format!("user_{index}")
+}
+
+#[instrument(name = "guild.favourites_cache_key")]
+fn favourites_cache_key(anilist_id: i64) -> String {
+    format!("guild:favourites:characters:{anilist_id}")
+}
+
+/// Each member's favourite character IDs, keyed by Discord ID. Cached per
+/// member for [`GUILD_FAVOURITES_TTL`] regardless of which character was
+/// looked up.
+#[instrument(name = "guild.fetch_favourite_characters", skip(cache, guild_members), fields(member_count = guild_members.len()))]
+async fn get_guild_favourite_characters(
+    cache: &SharedCache,
+    guild_members: Vec<OAuthCredential>,
+) -> HashMap<u64, Vec<u32>> {
+    let mut favourites: HashMap<u64, Vec<u32>> = HashMap::new();
+    let mut uncached_members = Vec::new();
+
+    for credential in guild_members {
+        let Some(discord_id) = credential.discord_id_u64() else {
+            continue;
+        };
+        match cache
+            .get(&favourites_cache_key(credential.anilist_id))
+            .await
+            .and_then(|cached| serde_json::from_str::<Vec<u32>>(&cached).ok())
+        {
+            Some(character_ids) => {
+                favourites.insert(discord_id, character_ids);
+            }
+            None => uncached_members.push((discord_id, credential)),
+        }
+    }
+
+    info!(
+        cached = favourites.len(),
+        uncached = uncached_members.len(),
+        "Resolved cached guild favourites"
+    );
+    if uncached_members.is_empty() {
+        return favourites;
+    }
+
+    // Each round reads the next page for the members AniList has more
+    // favourites for, up to FAVOURITE_CHARACTER_PAGES.
+    let mut partial = HashMap::new();
+    let mut pending = uncached_members;
+    for page in 1..=FAVOURITE_CHARACTER_PAGES {
+        if pending.is_empty() {
+            break;
+        }
+
+        let permits = Arc::new(Semaphore::new(GUILD_BATCH_CONCURRENCY));
+        let mut chunks = JoinSet::new();
+        for chunk in pending.chunks(GUILD_FAVOURITES_CHUNK_SIZE) {
+            let chunk = chunk.to_vec();
+            let permits = permits.clone();
+            chunks.spawn(
+                async move {
+                    let _permit = permits.acquire_owned().await.ok()?;
+                    fetch_favourites_chunk(&chunk, page)
+                        .await
+                        .map(|users| (chunk, users))
+                }
+                .in_current_span(),
+            );
+        }
+
+        // A failed chunk only loses its own members; the rest are still shown.
+        let mut next_pending = Vec::new();
+        while let Some(result) = chunks.join_next().await {
+            let Ok(Some((chunk, users))) = result else {
+                continue;
+            };
+            next_pending.extend(
+                store_favourites_chunk(cache, chunk, users, page, &mut partial, &mut favourites)
+                    .await,
+            );
+        }
+        pending = next_pending;
+    }
+
+    favourites
+}
 
-    let user_media_list_response: BatchUserMediaListResponse =
-        match serde_json::from_str::<BatchUserMediaListResponse>(&user_media_list_response) {
-            Ok(response) => response,
+/// Add one chunk's page of favourites to `partial`. Members with every page
+/// read (or [`FAVOURITE_CHARACTER_PAGES`] reached) are cached and moved into
+/// `favourites`; members with more pages are returned. Members whose own
+/// lookup failed are skipped and left uncached, so the next lookup retries
+/// them.
+#[instrument(name = "guild.store_favourites_chunk", skip_all, fields(member_count = chunk.len(), page))]
+async fn store_favourites_chunk(
+    cache: &SharedCache,
+    chunk: Vec<(u64, OAuthCredential)>,
+    mut users: AliasedResponse<UserFavouritesData>,
+    page: u32,
+    partial: &mut HashMap<u64, Vec<u32>>,
+    favourites: &mut HashMap<u64, Vec<u32>>,
+) -> Vec<(u64, OAuthCredential)> {
+    let mut more = Vec::new();
+    for (index, (discord_id, credential)) in chunk.into_iter().enumerate() {
+        let user = match users.remove(&user_alias(index)) {
+            Ok(user) => user,
             Err(err) => {
-                error!("Failed to parse guild AniList media data response: {err}");
-                return HashMap::new();
+                warn!(
+                    error = %err,
+                    discord_user_id = %hash_user_id(discord_id),
+                    "AniList favourites lookup failed for one member"
+                );
+                partial.remove(&discord_id);
+                continue;
             }
         };
+        let has_more = user
+            .as_ref()
+            .is_some_and(UserFavouritesData::has_more_characters);
+        let mut character_ids = partial.remove(&discord_id).unwrap_or_default();
+        character_ids.extend(
+            user.map(UserFavouritesData::character_ids)
+                .unwrap_or_default(),
+        );
 
-    let mut guild_members_data: HashMap<u64, MediaListData> = HashMap::new();
-    if let Some(media_lookup_data) = user_media_list_response.data {
-        for (media_alias, media_list_data) in media_lookup_data {
-            if let (Some(discord_id), Some(data)) = (
-                discord_ids_by_media_alias.get(&media_alias),
-                media_list_data,
-            ) {
-                guild_members_data.insert(*discord_id, data);
-            }
+        if has_more && page < FAVOURITE_CHARACTER_PAGES {
+            partial.insert(discord_id, character_ids);
+            more.push((discord_id, credential));
+            continue;
         }
+        if let Ok(encoded) = serde_json::to_string(&character_ids) {
+            cache
+                .set(
+                    &favourites_cache_key(credential.anilist_id),
+                    &encoded,
+                    GUILD_FAVOURITES_TTL,
+                )
+                .await;
+        }
+        favourites.insert(discord_id, character_ids);
     }
+    more
+}
 
-    guild_members_data
+/// Fetch one chunk's page of favourites, keyed by [`user_alias`] of each
+/// member's index within the chunk. `None` when the whole request fails.
+#[instrument(name = "guild.fetch_favourites_chunk", skip(chunk), fields(member_count = chunk.len()))]
+async fn fetch_favourites_chunk(
+    chunk: &[(u64, OAuthCredential)],
+    page: u32,
+) -> Option<AliasedResponse<UserFavouritesData>> {
+    let credentials = chunk
+        .iter()
+        .map(|(_, credential)| credential.clone())
+        .collect::<Vec<_>>();
+    let body = json!({
+        "query": build_batch_favourites_query(&credentials),
+        "variables": { "page": page }
+    });
+
+    // Deleted AniList accounts come back as 404 errors next to the partial
+    // data, and an error on one member's alias only fails that member.
+    match classify_aliased::<UserFavouritesData>(send_request(body).await) {
+        Ok(users) => Some(users),
+        Err(err) => {
+            error!(error = %err, "AniList batch favourites request failed");
+            None
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::build_batch_media_list_query;
-    use crate::models::{
-        db::oauth_credential::OAuthCredential,
-        settings::{GuildScoresPreference, SettingValue, user_participates_in_guild_scores},
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use super::{
+        FAVOURITE_CHARACTER_PAGES, build_batch_favourites_query, build_batch_media_list_query,
+        favourites_cache_key, get_guild_anilist_data, get_guild_favourite_characters,
+        list_entry_cache_key, store_favourites_chunk, store_list_entry_chunk,
+    };
+    use crate::{
+        models::{
+            db::oauth_credential::OAuthCredential,
+            settings::{GuildScoresPreference, SettingValue, user_participates_in_guild_scores},
+        },
+        utils::{
+            cache::{Cache, InMemoryCache, SharedCache},
+            requests::graphql::classify_aliased,
+        },
     };
 
+    fn member(discord_id: u64, anilist_id: i64) -> (u64, OAuthCredential) {
+        (
+            discord_id,
+            OAuthCredential {
+                discord_user_id: discord_id.to_string(),
+                anilist_id,
+                anilist_username: None,
+            },
+        )
+    }
+
     #[test]
     fn guild_score_participation_defaults_to_include_and_honors_opt_out() {
         assert!(user_participates_in_guild_scores(None));
@@ -256,4 +859,175 @@ mod tests {
         assert!(query.contains("media_0: MediaList(userId: 100, type: $type, mediaId: $mediaId)"));
         assert!(query.contains("media_1: MediaList(userId: 200, type: $type, mediaId: $mediaId)"));
     }
+
+    #[tokio::test]
+    async fn cached_list_entries_are_served_without_querying_anilist() {
+        let cache = InMemoryCache::with_entry(
+            &list_entry_cache_key(100, "anime", 1),
+            r#"{"status":"COMPLETED","score":90,"progress":12,"progressVolumes":null}"#,
+        );
+        // A member cached as having no entry is skipped rather than re-queried.
+        cache
+            .set(
+                &list_entry_cache_key(200, "anime", 1),
+                "null",
+                std::time::Duration::from_secs(60),
+            )
+            .await;
+        let cache: SharedCache = Arc::new(cache);
+        let guild_members = vec![
+            OAuthCredential {
+                discord_user_id: "1".to_string(),
+                anilist_id: 100,
+                anilist_username: None,
+            },
+            OAuthCredential {
+                discord_user_id: "2".to_string(),
+                anilist_id: 200,
+                anilist_username: None,
+            },
+        ];
+
+        let data = get_guild_anilist_data(&cache, guild_members, 1, "anime".to_string()).await;
+
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[&1].score, Some(90));
+    }
+
+    #[tokio::test]
+    async fn a_failed_member_does_not_drop_the_rest_of_the_chunk() {
+        let entries = classify_aliased(Ok(r#"{"data":{"media_0":{"status":"CURRENT","score":80,"progress":3,"progressVolumes":null},"media_1":null,"media_2":null},"errors":[{"message":"Internal Server Error","status":500,"path":["media_1"]},{"message":"Not Found.","status":404,"path":["media_2"]}]}"#.to_string()))
+        .unwrap();
+        let cache: SharedCache = Arc::new(InMemoryCache::default());
+        let mut data = HashMap::new();
+
+        store_list_entry_chunk(
+            &cache,
+            vec![member(1, 100), member(2, 200), member(3, 300)],
+            entries,
+            "anime",
+            1,
+            &mut data,
+        )
+        .await;
+
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[&1].score, Some(80));
+        assert!(
+            cache
+                .get(&list_entry_cache_key(200, "anime", 1))
+                .await
+                .is_none()
+        );
+        assert_eq!(
+            cache
+                .get(&list_entry_cache_key(300, "anime", 1))
+                .await
+                .as_deref(),
+            Some("null")
+        );
+    }
+
+    #[test]
+    fn build_batch_favourites_query_adds_one_user_per_member() {
+        let guild_members = vec![OAuthCredential {
+            discord_user_id: "1".to_string(),
+            anilist_id: 100,
+            anilist_username: None,
+        }];
+
+        let query = build_batch_favourites_query(&guild_members);
+
+        assert!(query.contains("user_0: User(id: 100) {\n    ...UserFavouriteCharacters\n"));
+        assert!(query.contains("query ($page: Int) {"));
+        assert!(query.contains("characters(perPage: 50, page: $page) {"));
+        assert!(query.contains("pageInfo {\n"));
+    }
+
+    #[tokio::test]
+    async fn cached_favourites_are_served_without_querying_anilist() {
+        let cache: SharedCache = Arc::new(InMemoryCache::with_entry(
+            &favourites_cache_key(100),
+            "[40,417]",
+        ));
+        let guild_members = vec![OAuthCredential {
+            discord_user_id: "1".to_string(),
+            anilist_id: 100,
+            anilist_username: None,
+        }];
+
+        let favourites = get_guild_favourite_characters(&cache, guild_members).await;
+
+        assert_eq!(favourites[&1], vec![40, 417]);
+    }
+
+    #[tokio::test]
+    async fn a_failed_member_does_not_drop_the_rest_of_the_favourites_chunk() {
+        let users = classify_aliased(Ok(r#"{"data":{"user_0":{"favourites":{"characters":{"nodes":[{"id":40}]}}},"user_1":null},"errors":[{"message":"Internal Server Error","status":500,"path":["user_1"]}]}"#.to_string()))
+        .unwrap();
+        let cache: SharedCache = Arc::new(InMemoryCache::default());
+        let mut favourites = HashMap::new();
+
+        let more = store_favourites_chunk(
+            &cache,
+            vec![member(1, 100), member(2, 200)],
+            users,
+            1,
+            &mut HashMap::new(),
+            &mut favourites,
+        )
+        .await;
+
+        assert!(more.is_empty());
+        assert_eq!(favourites.len(), 1);
+        assert_eq!(favourites[&1], vec![40]);
+        assert!(cache.get(&favourites_cache_key(200)).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn favourites_are_read_across_pages_up_to_the_limit() {
+        let page = |ids: &[u32], has_next_page: bool| {
+            let nodes = ids
+                .iter()
+                .map(|id| format!(r#"{{"id":{id}}}"#))
+                .collect::<Vec<_>>()
+                .join(",");
+            classify_aliased(Ok(format!(
+                r#"{{"data":{{"user_0":{{"favourites":{{"characters":{{"pageInfo":{{"hasNextPage":{has_next_page}}},"nodes":[{nodes}]}}}}}}}}}}"#
+            )))
+            .unwrap()
+        };
+        let cache: SharedCache = Arc::new(InMemoryCache::default());
+        let mut partial = HashMap::new();
+        let mut favourites = HashMap::new();
+
+        let more = store_favourites_chunk(
+            &cache,
+            vec![member(1, 100)],
+            page(&[40], true),
+            1,
+            &mut partial,
+            &mut favourites,
+        )
+        .await;
+        assert_eq!(more.len(), 1);
+        assert!(favourites.is_empty());
+        assert!(cache.get(&favourites_cache_key(100)).await.is_none());
+
+        let more = store_favourites_chunk(
+            &cache,
+            more,
+            page(&[417], true),
+            FAVOURITE_CHARACTER_PAGES,
+            &mut partial,
+            &mut favourites,
+        )
+        .await;
+        assert!(more.is_empty());
+        assert_eq!(favourites[&1], vec![40, 417]);
+        assert_eq!(
+            cache.get(&favourites_cache_key(100)).await.as_deref(),
+            Some("[40,417]")
+        );
+    }
 }
@@ -0,0 +1,74 @@
+//! Helpers shared by the commands that answer a deferred interaction and read
+//! the caller's linked AniList account (e.g. `/next`, `/binge`, personal
+//! recommendations).
+
+use serenity::all::{CommandInteraction, EditInteractionResponse};
+use serenity::client::Context;
+use tracing::{error, instrument};
+
+use crate::{
+    models::{db::oauth_credential::OAuthCredential, media_type::MediaType},
+    utils::{database::get_pool_from_context, privacy::hash_user_id},
+};
+
+/// Replaces the deferred response with `content`.
+#[instrument(name = "utils.interaction.respond", skip(ctx, interaction))]
+pub async fn respond(ctx: &Context, interaction: &CommandInteraction, content: &str) {
+    let builder = EditInteractionResponse::new().content(content);
+    let _ = interaction.edit_response(&ctx.http, builder).await;
+}
+
+/// The caller's linked AniList account.
+///
+/// When there is none, or it can't be read, this has already answered the
+/// interaction (with `not_linked` for a user who never ran `/register`) and
+/// returns `None`.
+#[instrument(name = "utils.interaction.linked_profile", skip(ctx, interaction))]
+pub async fn linked_profile(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    not_linked: &str,
+) -> Option<OAuthCredential> {
+    let user_id = interaction.user.id;
+
+    let Some(database_pool) = get_pool_from_context(ctx).await else {
+        respond(
+            ctx,
+            interaction,
+            "I can't reach my database right now. Please try again later.",
+        )
+        .await;
+        return None;
+    };
+
+    match OAuthCredential::get_by_discord_id(user_id, &database_pool).await {
+        Ok(Some(profile)) => Some(profile),
+        Ok(None) => {
+            respond(ctx, interaction, not_linked).await;
+            None
+        }
+        Err(err) => {
+            error!(
+                error = %err,
+                discord_user_id = %hash_user_id(user_id.get()),
+                "Failed to fetch linked AniList account"
+            );
+            respond(
+                ctx,
+                interaction,
+                "I couldn't look up your AniList account right now. Please try again later.",
+            )
+            .await;
+            None
+        }
+    }
+}
+
+/// The `MediaType` enum value AniList's GraphQL API expects.
+#[instrument(name = "utils.interaction.anilist_type")]
+pub fn anilist_type(media_type: &MediaType) -> &'static str {
+    match media_type {
+        MediaType::Anime => "ANIME",
+        MediaType::Manga => "MANGA",
+    }
+}
@@ -25,7 +25,7 @@
 use std::env;
 use std::fmt;
 use std::future::Future;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 
 use reqwest::Client;
@@ -37,6 +37,7 @@ use uuid::Uuid;
 
 use crate::utils::{
     posthog::{LlmTelemetryContext, PostHogClient},
+    requests::resilience::{CircuitBreaker, FailureKind, RetryPolicy, Upstream},
     statics::{GEMINI_API_KEY, LLM_BASE_URL, LLM_MODEL},
     tls::install_rustls_crypto_provider,
 };
@@ -45,6 +46,22 @@ const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta
 const DEFAULT_MODEL: &str = "gemini-3.1-flash-lite";
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
+/// Completions are billed and not idempotent: a timeout may come after the
+/// API accepted the request, so a retry could pay for a second generation.
+/// They are never retried; failures still count towards the breaker, which
+/// has a longer cool-down than the metadata APIs.
+static LLM_UPSTREAM: LazyLock<Upstream> = LazyLock::new(|| {
+    Upstream::new(
+        "llm",
+        RetryPolicy {
+            max_attempts: 1,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(2),
+        },
+        CircuitBreaker::new(3, Duration::from_secs(60)),
+    )
+});
+
 /// Return the configured LLM model name without requiring an API key.
 #[instrument(name = "llm.configured_model_name")]
 pub fn configured_model_name() -> String {
@@ -86,6 +103,8 @@ pub enum LlmError {
     InvalidTemperature(f32),
     /// Failed to build the HTTP client.
     ClientBuild(String),
+    /// The API has failed repeatedly; the request was not attempted.
+    CircuitOpen,
 }
 
 impl fmt::Display for LlmError {
@@ -113,12 +132,23 @@ impl fmt::Display for LlmError {
                 )
             }
             LlmError::ClientBuild(e) => write!(f, "failed to build HTTP client: {e}"),
+            LlmError::CircuitOpen => write!(f, "API is failing repeatedly; request skipped"),
         }
     }
 }
 
 impl std::error::Error for LlmError {}
 
+impl LlmError {
+    fn failure_kind(&self) -> FailureKind {
+        match self {
+            LlmError::Request(_) | LlmError::ResponseBody(_) => FailureKind::Transient,
+            LlmError::ApiError { status, .. } if *status >= 500 => FailureKind::Transient,
+            _ => FailureKind::Permanent,
+        }
+    }
+}
+
 // ── Request types ────────────────────────────────────────────────────
 
 /// A single message in the chat conversation.
@@ -391,12 +421,30 @@ impl GeminiClient {
         let body_json =
             serde_json::to_string(&body).map_err(|e| LlmError::Serialization(e.to_string()))?;
 
+        let text = LLM_UPSTREAM
+            .call(
+                || self.post_completion(&url, &body_json),
+                LlmError::failure_kind,
+                || LlmError::CircuitOpen,
+            )
+            .await?;
+
+        serde_json::from_str::<ChatCompletionResponse>(&text).map_err(|e| {
+            LlmError::Deserialization {
+                message: e.to_string(),
+                body: text,
+            }
+        })
+    }
+
+    /// One POST attempt; returns the raw body of a successful response.
+    async fn post_completion(&self, url: &str, body_json: &str) -> Result<String, LlmError> {
         let response = self
             .http
-            .post(&url)
+            .post(url)
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .body(body_json)
+            .body(body_json.to_string())
             .send()
             .await
             .map_err(|e| LlmError::Request(e.to_string()))?;
@@ -414,12 +462,7 @@ impl GeminiClient {
             });
         }
 
-        serde_json::from_str::<ChatCompletionResponse>(&text).map_err(|e| {
-            LlmError::Deserialization {
-                message: e.to_string(),
-                body: text,
-            }
-        })
+        Ok(text)
     }
 
     /// Send a chat completion and include safe LLM Analytics context when PostHog is configured.
@@ -558,6 +601,7 @@ fn telemetry_error_label(error: &LlmError) -> String {
         LlmError::EmptyResponse => "empty_response".to_string(),
         LlmError::InvalidTemperature(_) => "invalid_temperature".to_string(),
         LlmError::ClientBuild(_) => "client_build".to_string(),
+        LlmError::CircuitOpen => "circuit_open".to_string(),
     }
 }
 
@@ -246,3 +246,31 @@ fn llm_error_display() {
     let err = LlmError::InvalidTemperature(3.0);
     assert!(err.to_string().contains("invalid temperature 3"));
 }
+
+#[test]
+fn only_transport_and_server_errors_count_towards_the_breaker() {
+    assert_eq!(
+        LlmError::Request("timeout".to_string()).failure_kind(),
+        FailureKind::Transient
+    );
+    assert_eq!(
+        LlmError::ApiError {
+            status: 503,
+            body: String::new()
+        }
+        .failure_kind(),
+        FailureKind::Transient
+    );
+    assert_eq!(
+        LlmError::ApiError {
+            status: 400,
+            body: String::new()
+        }
+        .failure_kind(),
+        FailureKind::Permanent
+    );
+    assert_eq!(
+        LlmError::EmptyResponse.failure_kind(),
+        FailureKind::Permanent
+    );
+}
@@ -0,0 +1,246 @@
+//! A linked user's AniList list for one media type.
+//!
+//! Per-user commands (e.g. personal recommendations) read the whole
+//! `MediaListCollection` once, with the media details they need on each
+//! entry, and work from the cached entries rather than asking AniList about
+//! each title separately. Commands that only need one title's entry (e.g.
+//! `/binge`) read just that entry instead.
+
+use serde_json::json;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    models::user_media_list::{
+        MediaListCollection, MediaListCollectionData, MediaListData, MediaListEntry,
+        MediaListEntryData,
+    },
+    utils::{
+        cache::{Cache, USER_MEDIA_LIST_TTL},
+        requests::{
+            anilist::send_request,
+            fragments::{media_list_entry, title},
+            graphql::{AniListError, classify},
+            query::{Field, Query, Selection, VariableType},
+        },
+    },
+};
+
+/// Media details carried on each entry, enough to rank and describe titles
+/// without looking each one up.
+fn list_media_fields() -> Selection {
+    Selection::new()
+        .fields(&["genres", "isAdult"])
+        .object("title", title())
+        .fields(&[
+            "format",
+            "status",
+            "episodes",
+            "chapters",
+            "duration",
+            "averageScore",
+            "siteUrl",
+        ])
+        .object("nextAiringEpisode", Selection::new().field("episode"))
+}
+
+#[instrument(name = "media_list.collection_query")]
+fn media_list_collection_query(media_type: &str) -> Query {
+    Query::new().variable("userId", VariableType::Int).field(
+        Field::new("MediaListCollection")
+            .arg("userId", "$userId")
+            .arg("type", media_type)
+            .select(
+                Selection::new().object(
+                    "lists",
+                    Selection::new().object(
+                        "entries",
+                        Selection::new()
+                            .field("mediaId")
+                            .spread(&media_list_entry())
+                            .object("media", list_media_fields()),
+                    ),
+                ),
+            ),
+    )
+}
+
+#[instrument(name = "media_list.cache_key")]
+fn media_list_cache_key(anilist_id: i64, media_type: &str) -> String {
+    format!("user:list:{anilist_id}:{media_type}")
+}
+
+/// Every entry on `anilist_id`'s list for `media_type` (`ANIME` or `MANGA`),
+/// cached for [`USER_MEDIA_LIST_TTL`]. Private or missing lists are empty.
+#[instrument(name = "media_list.fetch", skip(cache))]
+pub async fn fetch_user_media_list(
+    cache: &dyn Cache,
+    anilist_id: i64,
+    media_type: &str,
+) -> Result<Vec<MediaListEntry>, AniListError> {
+    let cache_key = media_list_cache_key(anilist_id, media_type);
+    if let Some(entries) = cache
+        .get(&cache_key)
+        .await
+        .and_then(|cached| serde_json::from_str::<Vec<MediaListEntry>>(&cached).ok())
+    {
+        info!(entries = entries.len(), "Cache hit for user media list");
+        return Ok(entries);
+    }
+
+    let body = media_list_collection_query(media_type).request(json!({ "userId": anilist_id }));
+    let entries = classify::<MediaListCollectionData>(send_request(body).await)?
+        .and_then(|data| data.collection)
+        .map(MediaListCollection::into_entries)
+        .unwrap_or_default();
+
+    match serde_json::to_string(&entries) {
+        Ok(encoded) => cache.set(&cache_key, &encoded, USER_MEDIA_LIST_TTL).await,
+        Err(error) => warn!(error = %error, "Failed to encode user media list for caching"),
+    }
+    info!(entries = entries.len(), "Fetched user media list");
+    Ok(entries)
+}
+
+#[instrument(name = "media_list.entry_query")]
+fn media_list_entry_query(media_type: &str) -> Query {
+    Query::new()
+        .variable("userId", VariableType::Int)
+        .variable("mediaId", VariableType::Int)
+        .field(
+            Field::new("MediaList")
+                .arg("userId", "$userId")
+                .arg("mediaId", "$mediaId")
+                .arg("type", media_type)
+                .select(Selection::new().spread(&media_list_entry())),
+        )
+}
+
+#[instrument(name = "media_list.entry_cache_key")]
+fn media_list_entry_cache_key(anilist_id: i64, media_type: &str, media_id: u32) -> String {
+    format!("user:list:{anilist_id}:{media_type}:{media_id}")
+}
+
+/// `anilist_id`'s entry for `media_id`, or `None` when it isn't on their list.
+/// Served from their cached whole list when one is cached; otherwise only
+/// that entry is requested, and cached for [`USER_MEDIA_LIST_TTL`].
+#[instrument(name = "media_list.fetch_entry", skip(cache))]
+pub async fn fetch_user_media_list_entry(
+    cache: &dyn Cache,
+    anilist_id: i64,
+    media_type: &str,
+    media_id: u32,
+) -> Result<Option<MediaListData>, AniListError> {
+    if let Some(entries) = cache
+        .get(&media_list_cache_key(anilist_id, media_type))
+        .await
+        .and_then(|cached| serde_json::from_str::<Vec<MediaListEntry>>(&cached).ok())
+    {
+        info!("Cache hit for user media list");
+        return Ok(entries
+            .into_iter()
+            .find(|entry| entry.media_id == media_id)
+            .map(|entry| MediaListData {
+                status: entry.status,
+                score: entry.score,
+                progress: entry.progress,
+                progress_volumes: None,
+            }));
+    }
+
+    let cache_key = media_list_entry_cache_key(anilist_id, media_type, media_id);
+    if let Some(entry) = cache
+        .get(&cache_key)
+        .await
+        .and_then(|cached| serde_json::from_str::<Option<MediaListData>>(&cached).ok())
+    {
+        info!("Cache hit for user media list entry");
+        return Ok(entry);
+    }
+
+    let body = media_list_entry_query(media_type)
+        .request(json!({ "userId": anilist_id, "mediaId": media_id }));
+    let entry =
+        classify::<MediaListEntryData>(send_request(body).await)?.and_then(|data| data.entry);
+
+    match serde_json::to_string(&entry) {
+        Ok(encoded) => cache.set(&cache_key, &encoded, USER_MEDIA_LIST_TTL).await,
+        Err(error) => warn!(error = %error, "Failed to encode user media list entry for caching"),
+    }
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cache::InMemoryCache;
+
+    #[test]
+    fn collection_query_selects_entries_with_genres() {
+        let query = media_list_collection_query("ANIME").render();
+
+        assert!(query.contains("MediaListCollection(userId: $userId, type: ANIME) {"));
+        assert!(
+            query.contains(
+                "mediaId\n        ...MediaListEntry\n        media {\n          genres\n"
+            )
+        );
+        assert!(query.contains("fragment MediaListEntry on MediaList {"));
+    }
+
+    #[tokio::test]
+    async fn cached_lists_are_served_without_querying_anilist() {
+        let cache = InMemoryCache::with_entry(
+            &media_list_cache_key(7, "ANIME"),
+            r#"[{"mediaId":1,"status":"COMPLETED","score":90,"progress":26,"media":{"genres":["Action"]}}]"#,
+        );
+
+        let entries = fetch_user_media_list(&cache, 7, "ANIME").await.unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].genres(), ["Action".to_string()]);
+    }
+
+    #[test]
+    fn entry_query_selects_one_title() {
+        let query = media_list_entry_query("MANGA").render();
+
+        assert!(query.contains(
+            "MediaList(userId: $userId, mediaId: $mediaId, type: MANGA) {\n    ...MediaListEntry\n"
+        ));
+    }
+
+    #[tokio::test]
+    async fn entries_are_read_from_a_cached_list_without_querying_anilist() {
+        let cache = InMemoryCache::with_entry(
+            &media_list_cache_key(7, "ANIME"),
+            r#"[{"mediaId":1,"status":"CURRENT","score":0,"progress":12}]"#,
+        );
+
+        let entry = fetch_user_media_list_entry(&cache, 7, "ANIME", 1)
+            .await
+            .unwrap()
+            .expect("listed title");
+        let missing = fetch_user_media_list_entry(&cache, 7, "ANIME", 2)
+            .await
+            .unwrap();
+
+        assert_eq!(entry.progress, Some(12));
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn cached_entries_are_served_without_querying_anilist() {
+        let cache = InMemoryCache::with_entry(
+            &media_list_entry_cache_key(7, "MANGA", 3),
+            r#"{"status":"PAUSED","score":70,"progress":40,"progressVolumes":5}"#,
+        );
+
+        let entry = fetch_user_media_list_entry(&cache, 7, "MANGA", 3)
+            .await
+            .unwrap()
+            .expect("cached entry");
+
+        assert_eq!(entry.progress, Some(40));
+        assert_eq!(entry.progress_volumes, Some(5));
+    }
+}
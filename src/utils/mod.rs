@@ -1,10 +1,13 @@
+pub mod cache;
 pub mod channel;
 pub mod database;
 pub mod fetch_by_arguments;
 pub mod formatter;
 pub mod fuzzy;
 pub mod guild;
+pub mod interaction;
 pub mod llm;
+pub mod media_list;
 pub mod oauth;
 pub mod posthog;
 pub mod privacy;
@@ -12,6 +15,8 @@ pub mod redis;
 pub mod requests;
 pub mod response_fetcher;
 pub mod settings;
+pub mod single_flight;
+pub mod spoilers;
 pub mod spotify;
 pub mod statics;
 pub mod tls;
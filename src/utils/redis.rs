@@ -1,50 +1,237 @@
-use redis::{Commands, Connection, ErrorKind, RedisResult};
+use redis::{AsyncCommands, ErrorKind, RedisError, RedisResult, aio::ConnectionManager};
+use serenity::async_trait;
 use std::env;
-use tracing::{info, instrument};
-
-use crate::utils::{statics::REDIS_URL, tls::install_rustls_crypto_provider};
-
-#[instrument(name = "redis.get_connection", skip_all)]
-fn get_redis_client() -> RedisResult<Connection> {
-    let redis_url = env::var(REDIS_URL).map_err(|e| {
-        (
-            ErrorKind::InvalidClientConfig,
-            "Missing REDIS_URL environment variable",
-            e.to_string(),
-        )
-    })?;
-
-    install_rustls_crypto_provider();
-
-    let client = redis::Client::open(redis_url)?;
-    let connection = client.get_connection()?;
-    info!("Redis connection established");
-    Ok(connection)
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{Instrument, debug, info, instrument, warn};
+
+use crate::utils::{cache::Cache, statics::REDIS_URL, tls::install_rustls_crypto_provider};
+
+/// How long Redis is skipped after a failed command before it is tried again.
+const REDIS_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// [`Cache`] backed by a single multiplexed Redis connection.
+///
+/// [`ConnectionManager`] is cheap to clone and reconnects on its own, so one
+/// instance is shared by every command for the lifetime of the process.
+/// After a failed command Redis is skipped for [`REDIS_RETRY_AFTER`], so an
+/// outage costs one warning instead of a failed round trip per request.
+///
+/// If Redis is down when the bot starts, the manager is built later: the
+/// first read or write after each back-off starts a connection attempt in the
+/// background and is served without Redis meanwhile.
+#[derive(Clone)]
+pub struct RedisCache {
+    client: redis::Client,
+    connection: Arc<Mutex<Option<ConnectionManager>>>,
+    connecting: Arc<AtomicBool>,
+    health: Arc<BackendHealth>,
+}
+
+impl RedisCache {
+    /// Fails only when Redis isn't configured; an unreachable server is
+    /// retried later.
+    #[instrument(name = "redis.connect", skip_all)]
+    pub async fn connect() -> RedisResult<Self> {
+        let redis_url = env::var(REDIS_URL).map_err(|e| {
+            (
+                ErrorKind::InvalidClientConfig,
+                "Missing REDIS_URL environment variable",
+                e.to_string(),
+            )
+        })?;
+
+        install_rustls_crypto_provider();
+
+        let cache = Self::new(redis::Client::open(redis_url)?);
+        cache.connecting.store(true, Ordering::Release);
+        cache.establish().await;
+        Ok(cache)
+    }
+
+    fn new(client: redis::Client) -> Self {
+        Self {
+            client,
+            connection: Arc::default(),
+            connecting: Arc::default(),
+            health: Arc::new(BackendHealth::new(REDIS_RETRY_AFTER)),
+        }
+    }
+
+    /// The shared connection, or `None` while Redis is backed off or not yet
+    /// connected. A missing connection starts one background attempt.
+    fn connection(&self) -> Option<ConnectionManager> {
+        if !self.health.is_available(Instant::now()) {
+            return None;
+        }
+
+        if let Some(connection) = self
+            .connection
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+        {
+            return Some(connection);
+        }
+
+        if !self.connecting.swap(true, Ordering::AcqRel) {
+            let cache = self.clone();
+            tokio::spawn(async move { cache.establish().await }.in_current_span());
+        }
+        None
+    }
+
+    /// Build the connection manager. Callers set `connecting` first.
+    #[instrument(name = "redis.establish", skip_all)]
+    async fn establish(&self) {
+        match ConnectionManager::new(self.client.clone()).await {
+            Ok(connection) => {
+                *self.connection.lock().unwrap_or_else(|e| e.into_inner()) = Some(connection);
+                self.health.record_success();
+                info!("Redis connection established");
+            }
+            Err(e) => self.health.record_failure(Instant::now(), &e),
+        }
+        self.connecting.store(false, Ordering::Release);
+    }
 }
 
-#[instrument(name = "redis.check_cache", fields(key = %key, key_len = key.len()))]
-pub fn check_cache(key: &str) -> RedisResult<String> {
-    let mut redis_client_connection = get_redis_client()?;
-    let cached_value: String = redis_client_connection.get(key)?;
-    Ok(cached_value)
+#[async_trait]
+impl Cache for RedisCache {
+    #[instrument(name = "redis.check_cache", skip(self), fields(key = %key, key_len = key.len()))]
+    async fn get(&self, key: &str) -> Option<String> {
+        let Some(mut connection) = self.connection() else {
+            debug!("Skipping Redis read while it is unavailable");
+            return None;
+        };
+
+        match connection.get::<_, Option<String>>(key).await {
+            Ok(value) => {
+                self.health.record_success();
+                value
+            }
+            Err(e) => {
+                self.health.record_failure(Instant::now(), &e);
+                None
+            }
+        }
+    }
+
+    #[instrument(name = "redis.cache_response", skip(self, value), fields(key = %key, key_len = key.len(), response_len = value.len()))]
+    async fn set(&self, key: &str, value: &str, ttl: Duration) {
+        let Some(mut connection) = self.connection() else {
+            debug!("Skipping Redis write while it is unavailable");
+            return;
+        };
+
+        match connection
+            .set_ex::<_, _, ()>(key, value, ttl.as_secs())
+            .await
+        {
+            Ok(()) => {
+                self.health.record_success();
+                info!("Successfully cached {:#?}", key);
+            }
+            Err(e) => {
+                self.health.record_failure(Instant::now(), &e);
+            }
+        }
+    }
 }
 
-#[instrument(name = "redis.cache_response", skip(response), fields(key = %key, key_len = key.len(), response_len = response.len()))]
-fn cache_response(key: &str, response: &str) -> RedisResult<()> {
-    let mut redis_client_connection = get_redis_client()?;
-    // Expires cached value in 5 hours
-    redis_client_connection.set_ex::<_, _, ()>(key, response, 18_000)?;
-    Ok(())
+/// Tracks whether Redis recently failed so callers can skip it for a while.
+struct BackendHealth {
+    retry_after: Duration,
+    unavailable_until: Mutex<Option<Instant>>,
 }
 
-#[instrument(name = "redis.try_cache_response", skip(response), fields(key = %key, key_len = key.len(), response_len = response.len()))]
-pub fn try_to_cache_response(key: &str, response: &str) {
-    match cache_response(key, response) {
-        Ok(()) => {
-            info!("Successfully cached {:#?}", key);
+impl BackendHealth {
+    fn new(retry_after: Duration) -> Self {
+        Self {
+            retry_after,
+            unavailable_until: Mutex::new(None),
         }
-        Err(e) => {
-            info!("Failed to cache {:#?} with error {:#?}", key, e);
+    }
+
+    fn is_available(&self, now: Instant) -> bool {
+        match *self
+            .unavailable_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+        {
+            Some(until) => now >= until,
+            None => true,
+        }
+    }
+
+    fn record_success(&self) {
+        let mut unavailable_until = self
+            .unavailable_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if unavailable_until.take().is_some() {
+            info!("Redis is reachable again; re-enabling remote cache");
         }
     }
+
+    fn record_failure(&self, now: Instant, error: &RedisError) {
+        let mut unavailable_until = self
+            .unavailable_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if unavailable_until.is_none() {
+            warn!(
+                error = %error,
+                retry_after_secs = self.retry_after.as_secs(),
+                "Redis command failed; serving from in-memory cache only"
+            );
+        } else {
+            debug!(error = %error, "Redis command failed again");
+        }
+        *unavailable_until = Some(now + self.retry_after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn io_error() -> RedisError {
+        RedisError::from(std::io::Error::other("connection refused"))
+    }
+
+    #[test]
+    fn backend_health_skips_redis_until_retry_window_passes() {
+        let health = BackendHealth::new(Duration::from_secs(30));
+        let now = Instant::now();
+        assert!(health.is_available(now));
+
+        health.record_failure(now, &io_error());
+
+        assert!(!health.is_available(now + Duration::from_secs(29)));
+        assert!(health.is_available(now + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn backend_health_recovers_after_success() {
+        let health = BackendHealth::new(Duration::from_secs(30));
+        let now = Instant::now();
+        health.record_failure(now, &io_error());
+
+        health.record_success();
+
+        assert!(health.is_available(now));
+    }
+
+    #[tokio::test]
+    async fn unconnected_cache_misses_and_starts_one_reconnect() {
+        let cache = RedisCache::new(redis::Client::open("redis://127.0.0.1:1").unwrap());
+
+        assert!(cache.get("key").await.is_none());
+        cache.set("key", "value", Duration::from_secs(1)).await;
+
+        assert!(cache.connecting.load(Ordering::Acquire));
+        assert!(cache.connection.lock().unwrap().is_none());
+    }
 }
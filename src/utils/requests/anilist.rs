@@ -1,18 +1,33 @@
 use std::sync::LazyLock;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde_json::Value;
-use tracing::instrument;
+use tracing::{instrument, warn};
 
-use crate::utils::tls::install_rustls_crypto_provider;
+use crate::utils::{
+    requests::{
+        rate_limit::{RateLimitHeaders, RateLimiter},
+        resilience::{CircuitBreaker, FailureKind, RetryPolicy, Upstream},
+    },
+    tls::install_rustls_crypto_provider,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum AniListRequestError {
     ClientBuild(String),
     RequestFailed(String),
-    NonSuccessStatus { status: u16, body: String },
+    NonSuccessStatus {
+        status: u16,
+        body: String,
+    },
     ResponseBodyReadFailed(String),
+    /// AniList's request budget is spent; retry after the given delay.
+    RateLimited {
+        retry_after: Duration,
+    },
+    /// AniList has failed repeatedly; the request was not attempted.
+    CircuitOpen,
 }
 
 impl std::fmt::Display for AniListRequestError {
@@ -33,13 +48,86 @@ impl std::fmt::Display for AniListRequestError {
             AniListRequestError::ResponseBodyReadFailed(error) => {
                 write!(f, "Failed to read AniList response body: {error}")
             }
+            AniListRequestError::RateLimited { retry_after } => {
+                write!(
+                    f,
+                    "AniList rate limit reached; retry after {}s",
+                    retry_after.as_secs()
+                )
+            }
+            AniListRequestError::CircuitOpen => {
+                write!(f, "AniList is failing repeatedly; request skipped")
+            }
         }
     }
 }
 
 impl std::error::Error for AniListRequestError {}
 
+impl AniListRequestError {
+    /// Whether AniList itself is failing (network error, 5xx or rate limit),
+    /// as opposed to rejecting this particular request.
+    pub fn is_upstream_unavailable(&self) -> bool {
+        match self {
+            AniListRequestError::ClientBuild(_) => false,
+            AniListRequestError::RequestFailed(_)
+            | AniListRequestError::ResponseBodyReadFailed(_)
+            | AniListRequestError::RateLimited { .. }
+            | AniListRequestError::CircuitOpen => true,
+            AniListRequestError::NonSuccessStatus { status, .. } => {
+                *status == 429 || *status >= 500
+            }
+        }
+    }
+
+    /// Whether the request is worth retrying. Rate limits are handled by the
+    /// rate limiter, so only outages and 5xx responses are retried here, and
+    /// rate-limited requests don't count for or against the breaker.
+    fn failure_kind(&self) -> FailureKind {
+        match self {
+            AniListRequestError::RateLimited { .. } => FailureKind::Throttled,
+            AniListRequestError::NonSuccessStatus { status: 429, .. } => FailureKind::Throttled,
+            AniListRequestError::RequestFailed(_)
+            | AniListRequestError::ResponseBodyReadFailed(_) => FailureKind::Transient,
+            AniListRequestError::NonSuccessStatus { status, .. } if *status >= 500 => {
+                FailureKind::Transient
+            }
+            _ => FailureKind::Permanent,
+        }
+    }
+}
+
 const ANILIST_TIMEOUT_SECS: u64 = 10;
+/// AniList's documented budget; response headers correct it at runtime.
+const ANILIST_REQUESTS_PER_MINUTE: u32 = 90;
+/// Longest a request queues for budget (or a 429 back-off) before it is shed.
+const ANILIST_MAX_QUEUE_WAIT: Duration = Duration::from_secs(15);
+/// Back-off used when a 429 carries no `Retry-After` header.
+const ANILIST_DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// How many times a 429 is retried after waiting out `Retry-After`.
+const ANILIST_MAX_RATE_LIMIT_RETRIES: u32 = 1;
+
+static ANILIST_UPSTREAM: LazyLock<Upstream> = LazyLock::new(|| {
+    Upstream::new(
+        "anilist",
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(2),
+        },
+        CircuitBreaker::new(5, Duration::from_secs(30)),
+    )
+});
+
+/// Shared by every AniList caller so guild lookups, searches and
+/// recommendations draw from one budget.
+static ANILIST_RATE_LIMITER: LazyLock<RateLimiter> = LazyLock::new(|| {
+    RateLimiter::new(
+        ANILIST_REQUESTS_PER_MINUTE,
+        Duration::from_secs(60),
+        ANILIST_MAX_QUEUE_WAIT,
+    )
+});
 
 static ANILIST_CLIENT: LazyLock<Result<Client, String>> = LazyLock::new(|| {
     install_rustls_crypto_provider();
@@ -65,28 +153,120 @@ fn get_client() -> Result<&'static Client, AniListRequestError> {
 )]
 pub async fn send_request(json: Value) -> Result<String, AniListRequestError> {
     let client = get_client()?;
+    let body = json.to_string();
 
-    let response = client
-        .post("https://graphql.anilist.co/")
-        .header("Content-Type", "application/json")
-        .header("Accept", "application/json")
-        .body(json.to_string())
-        .send()
+    // GraphQL queries are read-only, so every request is safe to retry.
+    ANILIST_UPSTREAM
+        .call(
+            || send_rate_limited(client, &body),
+            AniListRequestError::failure_kind,
+            || AniListRequestError::CircuitOpen,
+        )
         .await
-        .map_err(|error| AniListRequestError::RequestFailed(error.to_string()))?;
+}
 
-    let status = response.status();
-    let body = response
-        .text()
-        .await
-        .map_err(|error| AniListRequestError::ResponseBodyReadFailed(error.to_string()))?;
+async fn send_rate_limited(client: &Client, body: &str) -> Result<String, AniListRequestError> {
+    let mut retries = 0;
+
+    loop {
+        ANILIST_RATE_LIMITER
+            .acquire()
+            .await
+            .map_err(|retry_after| AniListRequestError::RateLimited { retry_after })?;
+
+        let response = client
+            .post("https://graphql.anilist.co/")
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|error| AniListRequestError::RequestFailed(error.to_string()))?;
+
+        let status = response.status();
+        let rate_limit = RateLimitHeaders::from_headers(response.headers());
+        ANILIST_RATE_LIMITER.observe(&rate_limit, Instant::now());
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = rate_limit
+                .retry_after
+                .unwrap_or(ANILIST_DEFAULT_RETRY_AFTER);
+            ANILIST_RATE_LIMITER.block_for(retry_after, Instant::now());
+            warn!(
+                retry_after_secs = retry_after.as_secs(),
+                retries, "AniList rate limit hit"
+            );
+
+            if retries < ANILIST_MAX_RATE_LIMIT_RETRIES
+                && retry_after <= ANILIST_RATE_LIMITER.max_wait()
+            {
+                retries += 1;
+                continue;
+            }
+            return Err(AniListRequestError::RateLimited { retry_after });
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|error| AniListRequestError::ResponseBodyReadFailed(error.to_string()))?;
+
+        if !status.is_success() {
+            return Err(AniListRequestError::NonSuccessStatus {
+                status: status.as_u16(),
+                body,
+            });
+        }
 
-    if !status.is_success() {
-        return Err(AniListRequestError::NonSuccessStatus {
-            status: status.as_u16(),
-            body,
-        });
+        return Ok(body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: u16) -> AniListRequestError {
+        AniListRequestError::NonSuccessStatus {
+            status,
+            body: String::new(),
+        }
     }
 
-    Ok(body)
+    #[test]
+    fn only_outages_and_server_errors_are_retried() {
+        assert_eq!(
+            AniListRequestError::RequestFailed("timeout".into()).failure_kind(),
+            FailureKind::Transient
+        );
+        assert_eq!(status(502).failure_kind(), FailureKind::Transient);
+        assert_eq!(status(404).failure_kind(), FailureKind::Permanent);
+        assert_eq!(status(429).failure_kind(), FailureKind::Throttled);
+        assert_eq!(
+            AniListRequestError::RateLimited {
+                retry_after: Duration::from_secs(60)
+            }
+            .failure_kind(),
+            FailureKind::Throttled
+        );
+        assert_eq!(
+            AniListRequestError::CircuitOpen.failure_kind(),
+            FailureKind::Permanent
+        );
+    }
+
+    #[test]
+    fn outages_and_rate_limits_count_as_upstream_unavailable() {
+        assert!(AniListRequestError::RequestFailed("timeout".into()).is_upstream_unavailable());
+        assert!(status(429).is_upstream_unavailable());
+        assert!(status(503).is_upstream_unavailable());
+        assert!(!status(404).is_upstream_unavailable());
+        assert!(!AniListRequestError::ClientBuild("tls".into()).is_upstream_unavailable());
+        assert!(
+            AniListRequestError::RateLimited {
+                retry_after: Duration::from_secs(60)
+            }
+            .is_upstream_unavailable()
+        );
+    }
 }
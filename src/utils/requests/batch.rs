@@ -0,0 +1,281 @@
+//! Batching of AniList lookups by ID.
+//!
+//! [`IdBatcher`] collects the IDs requested within a short window and sends
+//! them as one GraphQL request, aliasing one root field per ID (the same
+//! technique the guild list-entry query uses). Each caller then receives only
+//! its own entry, or the error AniList reported for it. Callers asking for the
+//! same ID in one window share it.
+
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use tokio::sync::oneshot;
+use tracing::{Instrument, debug, info_span, instrument, warn};
+
+use crate::utils::requests::{
+    anilist::{AniListRequestError, send_request},
+    graphql::{AniListError, classify_aliased},
+    query::{Field, Query, Selection},
+};
+
+/// How long the first lookup in a batch waits for others to join it.
+const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(20);
+/// Most lookups sent in one request, keeping it well within AniList's query
+/// complexity limit.
+const DEFAULT_MAX_BATCH: usize = 25;
+
+pub type BatchResult = Result<Option<Value>, Arc<AniListError>>;
+
+type SendFuture = Pin<Box<dyn Future<Output = Result<String, AniListRequestError>> + Send>>;
+type Sender = Box<dyn Fn(Value) -> SendFuture + Send + Sync>;
+
+struct Pending {
+    /// Bumped whenever a batch is taken, so a window timer only flushes the
+    /// batch it was started for.
+    generation: u64,
+    waiters: Vec<(u32, oneshot::Sender<BatchResult>)>,
+}
+
+pub struct IdBatcher {
+    root: &'static str,
+    arguments: Vec<(&'static str, &'static str)>,
+    selection: Selection,
+    window: Duration,
+    max_batch: usize,
+    send: Sender,
+    pending: Mutex<Pending>,
+}
+
+impl IdBatcher {
+    /// Batch `root(id: <id>, <arguments>)` lookups selecting `selection`,
+    /// e.g. `Media(id: 1, type: ANIME)`.
+    pub fn new(
+        root: &'static str,
+        arguments: &[(&'static str, &'static str)],
+        selection: Selection,
+    ) -> Self {
+        Self::with_sender(root, arguments, selection, |body| {
+            Box::pin(send_request(body))
+        })
+    }
+
+    fn with_sender(
+        root: &'static str,
+        arguments: &[(&'static str, &'static str)],
+        selection: Selection,
+        send: impl Fn(Value) -> SendFuture + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            root,
+            arguments: arguments.to_vec(),
+            selection,
+            window: DEFAULT_BATCH_WINDOW,
+            max_batch: DEFAULT_MAX_BATCH,
+            send: Box::new(send),
+            pending: Mutex::new(Pending {
+                generation: 0,
+                waiters: Vec::new(),
+            }),
+        }
+    }
+
+    /// Send at most `max_batch` lookups per request, for selections heavy
+    /// enough that the default would exceed AniList's complexity limit.
+    pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch.max(1);
+        self
+    }
+
+    /// Look up `id`, sharing a request with other lookups made in the same
+    /// window. `Ok(None)` means AniList has no matching entry.
+    #[instrument(name = "anilist.batch.load", skip(self), fields(root = self.root))]
+    pub async fn load(&'static self, id: u32) -> BatchResult {
+        let (sender, receiver) = oneshot::channel();
+        self.enqueue(id, sender);
+
+        receiver.await.unwrap_or_else(|_| {
+            Err(Arc::new(AniListError::InvalidResponse(
+                "batched lookup was dropped".to_string(),
+            )))
+        })
+    }
+
+    fn enqueue(&'static self, id: u32, sender: oneshot::Sender<BatchResult>) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.waiters.push((id, sender));
+
+        if pending.waiters.len() >= self.max_batch {
+            let waiters = Self::take(&mut pending);
+            drop(pending);
+            self.spawn_flush(waiters);
+        } else if pending.waiters.len() == 1 {
+            let generation = pending.generation;
+            tokio::spawn(async move {
+                tokio::time::sleep(self.window).await;
+                let waiters = {
+                    let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+                    if pending.generation != generation {
+                        return;
+                    }
+                    Self::take(&mut pending)
+                };
+                self.flush(waiters).await;
+            });
+        }
+    }
+
+    fn take(pending: &mut Pending) -> Vec<(u32, oneshot::Sender<BatchResult>)> {
+        pending.generation = pending.generation.wrapping_add(1);
+        std::mem::take(&mut pending.waiters)
+    }
+
+    fn spawn_flush(&'static self, waiters: Vec<(u32, oneshot::Sender<BatchResult>)>) {
+        tokio::spawn(self.flush(waiters));
+    }
+
+    async fn flush(&self, waiters: Vec<(u32, oneshot::Sender<BatchResult>)>) {
+        let ids: BTreeSet<u32> = waiters.iter().map(|(id, _)| *id).collect();
+        let span = info_span!("anilist.batch.flush", root = self.root, ids = ids.len());
+        let result = async {
+            debug!(callers = waiters.len(), "Sending batched AniList lookup");
+            classify_aliased::<Value>((self.send)(self.build_query(&ids).request(json!({}))).await)
+        }
+        .instrument(span)
+        .await;
+
+        match result {
+            Ok(response) => {
+                for (id, sender) in waiters {
+                    let entry = response
+                        .get(&alias(id))
+                        .map(|entry| entry.cloned())
+                        .map_err(|error| {
+                            warn!(error = %error, id, "Batched AniList lookup failed for one ID");
+                            Arc::new(error)
+                        });
+                    let _ = sender.send(entry);
+                }
+            }
+            Err(error) => {
+                warn!(error = %error, "Batched AniList lookup failed");
+                let error = Arc::new(error);
+                for (_, sender) in waiters {
+                    let _ = sender.send(Err(error.clone()));
+                }
+            }
+        }
+    }
+
+    fn build_query(&self, ids: &BTreeSet<u32>) -> Query {
+        ids.iter().fold(Query::new(), |query, id| {
+            let field = self.arguments.iter().fold(
+                Field::new(self.root).alias(alias(*id)).arg("id", id),
+                |field, (name, value)| field.arg(name, value),
+            );
+            query.field(field.select(self.selection.clone()))
+        })
+    }
+}
+
+fn alias(id: u32) -> String {
+    format!("id_{id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Answers every aliased lookup with `{"id": <id>}`, except ID 404,
+    /// which is missing, and ID 500, which fails on its own.
+    fn fake_send(body: Value) -> SendFuture {
+        let query = body["query"].as_str().unwrap_or_default().to_string();
+        Box::pin(async move {
+            let ids = query
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("id_"))
+                .filter_map(|rest| rest.split(':').next()?.parse::<u32>().ok())
+                .collect::<Vec<_>>();
+            let data: serde_json::Map<String, Value> = ids
+                .iter()
+                .map(|&id| {
+                    let entry = (id != 404 && id != 500).then(|| json!({ "id": id }));
+                    (alias(id), entry.unwrap_or(Value::Null))
+                })
+                .collect();
+            let errors = ids
+                .iter()
+                .filter(|&&id| id == 500)
+                .map(|&id| json!({ "message": "Internal Server Error", "status": 500, "path": [alias(id)] }))
+                .collect::<Vec<_>>();
+            Ok(json!({ "data": data, "errors": errors }).to_string())
+        })
+    }
+
+    /// A batcher of its own over [`fake_send`], and how many requests it sent.
+    fn batcher() -> (&'static IdBatcher, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let batcher = IdBatcher::with_sender(
+            "Media",
+            &[("type", "ANIME")],
+            Selection::new().field("id"),
+            move |body| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                fake_send(body)
+            },
+        );
+        (Box::leak(Box::new(batcher)), requests)
+    }
+
+    #[test]
+    fn query_aliases_one_root_field_per_id() {
+        let (batcher, _) = batcher();
+        let query = batcher.build_query(&BTreeSet::from([1, 20])).render();
+
+        assert!(query.contains("  id_1: Media(id: 1, type: ANIME) {\n    id\n  }\n"));
+        assert!(query.contains("  id_20: Media(id: 20, type: ANIME) {"));
+    }
+
+    #[tokio::test]
+    async fn lookups_in_one_window_share_a_request() {
+        let (batcher, requests) = batcher();
+
+        let (first, second, duplicate, missing) = tokio::join!(
+            batcher.load(1),
+            batcher.load(2),
+            batcher.load(1),
+            batcher.load(404),
+        );
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(first.unwrap(), Some(json!({ "id": 1 })));
+        assert_eq!(second.unwrap(), Some(json!({ "id": 2 })));
+        assert_eq!(duplicate.unwrap(), Some(json!({ "id": 1 })));
+        assert_eq!(missing.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn a_failed_id_does_not_fail_the_rest_of_the_batch() {
+        let (batcher, requests) = batcher();
+
+        let (found, failed) = tokio::join!(batcher.load(3), batcher.load(500));
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(found.unwrap(), Some(json!({ "id": 3 })));
+        assert!(failed.unwrap_err().is_upstream_unavailable());
+    }
+
+    #[test]
+    fn max_batch_is_at_least_one() {
+        let batcher =
+            IdBatcher::with_sender("Media", &[], Selection::new(), fake_send).with_max_batch(0);
+
+        assert_eq!(batcher.max_batch, 1);
+    }
+}
@@ -0,0 +1,147 @@
+//! Reusable AniList fragments and the ID/search query shapes built on them.
+
+use crate::utils::requests::query::{Field, Fragment, Query, Selection, VariableType};
+
+pub fn title() -> Selection {
+    Selection::new().fields(&["romaji", "english", "native"])
+}
+
+pub fn fuzzy_date() -> Selection {
+    Selection::new().fields(&["year", "month", "day"])
+}
+
+/// A media's type, titles, format, status, genres, score and link: the
+/// fields `RecommendedMedia` reads.
+pub fn media_summary() -> Selection {
+    Selection::new()
+        .fields(&["type", "id", "isAdult"])
+        .object("title", title())
+        .fields(&["format", "status", "genres", "averageScore", "siteUrl"])
+}
+
+/// Fields every media embed and fuzzy match relies on.
+pub fn media_core() -> Fragment {
+    Fragment::new(
+        "MediaCore",
+        "Media",
+        Selection::new()
+            .fields(&["type", "id", "isAdult"])
+            .object("title", title())
+            .fields(&[
+                "synonyms",
+                "format",
+                "status",
+                "genres",
+                "averageScore",
+                "siteUrl",
+            ]),
+    )
+}
+
+pub fn cover_image() -> Fragment {
+    Fragment::new(
+        "MediaCoverImage",
+        "Media",
+        Selection::new().object(
+            "coverImage",
+            Selection::new().fields(&["extraLarge", "large", "medium", "color"]),
+        ),
+    )
+}
+
+pub fn studios() -> Fragment {
+    Fragment::new(
+        "MediaStudios",
+        "Media",
+        Selection::new().object(
+            "studios",
+            Selection::new()
+                .object("edges", Selection::new().fields(&["id", "isMain"]))
+                .object("nodes", Selection::new().fields(&["id", "name"])),
+        ),
+    )
+}
+
+pub fn tags() -> Fragment {
+    Fragment::new(
+        "MediaTags",
+        "Media",
+        Selection::new().object(
+            "tags",
+            Selection::new().fields(&[
+                "name",
+                "rank",
+                "category",
+                "isMediaSpoiler",
+                "isGeneralSpoiler",
+            ]),
+        ),
+    )
+}
+
+/// A user's status, score and progress on one media, as in `MediaListData`.
+pub fn media_list_entry() -> Fragment {
+    Fragment::new(
+        "MediaListEntry",
+        "MediaList",
+        Selection::new()
+            .field(Field::new("score").arg("format", "POINT_100"))
+            .fields(&["status", "progress", "progressVolumes"]),
+    )
+}
+
+/// `Media(id: $id, type: <media_type>)` selecting `selection`.
+pub fn media_by_id(media_type: &str, selection: Selection) -> Query {
+    Query::new().variable("id", VariableType::Int).field(
+        Field::new("Media")
+            .arg("id", "$id")
+            .arg("type", media_type)
+            .select(selection),
+    )
+}
+
+/// A paged `media(search: $search, type: <media_type>)` search selecting the
+/// same fields as [`media_by_id`].
+pub fn media_search(media_type: &str, selection: Selection) -> Query {
+    Query::new()
+        .variable("page", VariableType::Int)
+        .variable("perPage", VariableType::Int)
+        .variable("search", VariableType::String)
+        .field(
+            Field::new("Page")
+                .arg("page", "$page")
+                .arg("perPage", "$perPage")
+                .select(
+                    Selection::new().field(
+                        Field::new("media")
+                            .arg("search", "$search")
+                            .arg("type", media_type)
+                            .select(selection),
+                    ),
+                ),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn id_and_search_variants_share_fields_and_fragments() {
+        let selection = Selection::new()
+            .spread(&media_core())
+            .spread(&cover_image())
+            .field("episodes");
+
+        let by_id = media_by_id("ANIME", selection.clone()).render();
+        let search = media_search("ANIME", selection).render();
+
+        assert!(by_id.contains("Media(id: $id, type: ANIME) {\n    ...MediaCore\n"));
+        assert!(search.contains("media(search: $search, type: ANIME) {\n      ...MediaCore\n"));
+        for query in [&by_id, &search] {
+            assert!(query.contains("fragment MediaCore on Media {"));
+            assert!(query.contains("fragment MediaCoverImage on Media {"));
+            assert!(query.contains("episodes\n"));
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! GraphQL response envelope and error classification for AniList.
+//!
+//! AniList reports problems in the GraphQL `errors` array, usually alongside
+//! a matching HTTP status (404 for a missing ID, 429 when rate limited, 5xx
+//! on outages). Every AniList call goes through [`classify`] or
+//! [`check_response`] so "not found" is told apart from rate limits, bad
+//! queries and server errors, instead of all of them looking like an empty
+//! result.
+//!
+//! Not-found errors are not treated as failures: batched queries (e.g. guild
+//! list entries) return partial `data` with a 404 error for each missing
+//! entry, and single lookups simply come back with `null` data. Batched
+//! queries go through [`classify_aliased`], so an error whose `path` names
+//! one alias only fails that alias.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, de::DeserializeOwned, de::IgnoredAny};
+use serde_json::Value;
+use tracing::{debug, error, instrument, warn};
+
+use crate::utils::{
+    requests::anilist::AniListRequestError,
+    statics::{ANILIST_RATE_LIMITED, ANILIST_UNAVAILABLE},
+};
+
+/// Standard GraphQL response: `data` plus an optional `errors` array.
+#[derive(Deserialize, Debug, Clone)]
+pub struct GraphQlResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GraphQlError {
+    pub message: String,
+    pub status: Option<u16>,
+    /// Where in `data` the error happened, starting with the root field or
+    /// its alias.
+    #[serde(default)]
+    pub path: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphQlErrorKind {
+    NotFound,
+    RateLimited,
+    /// The query or its variables were rejected (validation errors, 4xx).
+    Query,
+    Server,
+}
+
+impl GraphQlError {
+    pub fn kind(&self) -> GraphQlErrorKind {
+        match self.status {
+            Some(404) => GraphQlErrorKind::NotFound,
+            Some(429) => GraphQlErrorKind::RateLimited,
+            Some(status) if status >= 500 => GraphQlErrorKind::Server,
+            _ => GraphQlErrorKind::Query,
+        }
+    }
+
+    /// The root field or alias the error belongs to, if AniList said.
+    pub fn alias(&self) -> Option<&str> {
+        self.path.first()?.as_str()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AniListError {
+    /// The request itself failed (network, HTTP status, rate limiter,
+    /// circuit breaker).
+    Request(AniListRequestError),
+    /// AniList answered with GraphQL errors other than "not found".
+    GraphQl {
+        kind: GraphQlErrorKind,
+        message: String,
+    },
+    /// The body was not a valid GraphQL response.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for AniListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AniListError::Request(error) => write!(f, "{error}"),
+            AniListError::GraphQl { kind, message } => {
+                write!(f, "AniList returned a GraphQL error ({kind:?}): {message}")
+            }
+            AniListError::InvalidResponse(error) => {
+                write!(f, "AniList returned an invalid response: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AniListError {}
+
+impl From<AniListRequestError> for AniListError {
+    fn from(error: AniListRequestError) -> Self {
+        AniListError::Request(error)
+    }
+}
+
+impl AniListError {
+    /// Whether AniList itself is failing or throttling us, as opposed to
+    /// rejecting this particular request.
+    pub fn is_upstream_unavailable(&self) -> bool {
+        match self {
+            AniListError::Request(error) => error.is_upstream_unavailable(),
+            AniListError::GraphQl { kind, .. } => {
+                matches!(
+                    kind,
+                    GraphQlErrorKind::RateLimited | GraphQlErrorKind::Server
+                )
+            }
+            AniListError::InvalidResponse(_) => false,
+        }
+    }
+
+    /// A message that explains the failure to users, when there is a more
+    /// useful one than the command's generic error.
+    pub fn user_message(&self) -> Option<&'static str> {
+        match self {
+            AniListError::Request(AniListRequestError::RateLimited { .. })
+            | AniListError::GraphQl {
+                kind: GraphQlErrorKind::RateLimited,
+                ..
+            } => Some(ANILIST_RATE_LIMITED),
+            error if error.is_upstream_unavailable() => Some(ANILIST_UNAVAILABLE),
+            _ => None,
+        }
+    }
+}
+
+impl<T> GraphQlResponse<T> {
+    /// Return `data`, or the first error that is not a "not found".
+    pub fn into_result(self) -> Result<Option<T>, AniListError> {
+        match first_failure(&self.errors) {
+            Some(error) => Err(error),
+            None => Ok(self.data),
+        }
+    }
+}
+
+/// The first error in `errors` that is not a "not found", with every message
+/// joined into it.
+fn first_failure<'a>(errors: impl IntoIterator<Item = &'a GraphQlError>) -> Option<AniListError> {
+    let errors = errors.into_iter().collect::<Vec<_>>();
+    let Some(kind) = errors
+        .iter()
+        .map(|error| error.kind())
+        .find(|kind| *kind != GraphQlErrorKind::NotFound)
+    else {
+        if !errors.is_empty() {
+            debug!(errors = errors.len(), "AniList reported missing entries");
+        }
+        return None;
+    };
+
+    let message = errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    match kind {
+        GraphQlErrorKind::RateLimited => {
+            warn!(message = %message, "AniList rate limited the query")
+        }
+        _ => error!(kind = ?kind, message = %message, "AniList GraphQL error"),
+    }
+    Some(AniListError::GraphQl { kind, message })
+}
+
+/// A batched response with one top-level alias per lookup, classified alias
+/// by alias.
+#[derive(Debug)]
+pub struct AliasedResponse<T> {
+    entries: HashMap<String, Option<T>>,
+    errors: HashMap<String, AniListError>,
+}
+
+impl<T> AliasedResponse<T> {
+    /// The alias's entry, `Ok(None)` when AniList has none, or the error
+    /// that failed it.
+    pub fn get(&self, alias: &str) -> Result<Option<&T>, AniListError> {
+        match self.errors.get(alias) {
+            Some(error) => Err(error.clone()),
+            None => Ok(self.entries.get(alias).and_then(Option::as_ref)),
+        }
+    }
+
+    /// Like [`AliasedResponse::get`], but moves the entry out.
+    pub fn remove(&mut self, alias: &str) -> Result<Option<T>, AniListError> {
+        match self.errors.remove(alias) {
+            Some(error) => Err(error),
+            None => Ok(self.entries.remove(alias).flatten()),
+        }
+    }
+}
+
+/// Deserialize a response body into the envelope and classify its errors.
+#[instrument(name = "anilist.graphql.parse_response", skip(body), fields(body_len = body.len()))]
+pub fn parse_response<T: DeserializeOwned>(body: &str) -> Result<Option<T>, AniListError> {
+    serde_json::from_str::<GraphQlResponse<T>>(body)
+        .map_err(|error| AniListError::InvalidResponse(error.to_string()))?
+        .into_result()
+}
+
+/// Classify a `send_request` result and deserialize its `data`.
+pub fn classify<T: DeserializeOwned>(
+    result: Result<String, AniListRequestError>,
+) -> Result<Option<T>, AniListError> {
+    parse_response(&response_body(result)?)
+}
+
+/// Classify a batched `send_request` result. Errors whose `path` names an
+/// alias only fail that alias; errors without a path fail the whole request.
+pub fn classify_aliased<T: DeserializeOwned>(
+    result: Result<String, AniListRequestError>,
+) -> Result<AliasedResponse<T>, AniListError> {
+    let response = serde_json::from_str::<GraphQlResponse<HashMap<String, Option<T>>>>(
+        &response_body(result)?,
+    )
+    .map_err(|error| AniListError::InvalidResponse(error.to_string()))?;
+
+    let mut by_alias: HashMap<&str, Vec<&GraphQlError>> = HashMap::new();
+    let mut unattributed = Vec::new();
+    for error in &response.errors {
+        match error.alias() {
+            Some(alias) => by_alias.entry(alias).or_default().push(error),
+            None => unattributed.push(error),
+        }
+    }
+    if let Some(error) = first_failure(unattributed) {
+        return Err(error);
+    }
+    let errors = by_alias
+        .into_iter()
+        .filter_map(|(alias, errors)| Some((alias.to_string(), first_failure(errors)?)))
+        .collect();
+
+    Ok(AliasedResponse {
+        entries: response.data.unwrap_or_default(),
+        errors,
+    })
+}
+
+/// Classify a `send_request` result but keep the raw body, for callers that
+/// cache the payload before deserializing it.
+pub fn check_response(result: Result<String, AniListRequestError>) -> Result<String, AniListError> {
+    let body = response_body(result)?;
+    parse_response::<IgnoredAny>(&body)?;
+    Ok(body)
+}
+
+/// AniList sends GraphQL error bodies with non-2xx statuses, so classify
+/// those by their body rather than by the status alone.
+fn response_body(result: Result<String, AniListRequestError>) -> Result<String, AniListError> {
+    match result {
+        Ok(body) => Ok(body),
+        Err(AniListRequestError::NonSuccessStatus { status, body })
+            if serde_json::from_str::<GraphQlResponse<IgnoredAny>>(&body)
+                .is_ok_and(|response| !response.errors.is_empty()) =>
+        {
+            debug!(status, "Classifying AniList error response by its body");
+            Ok(body)
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Media {
+        id: u32,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct MediaData {
+        #[serde(rename = "Media")]
+        media: Option<Media>,
+    }
+
+    fn http_error(status: u16, body: &str) -> Result<String, AniListRequestError> {
+        Err(AniListRequestError::NonSuccessStatus {
+            status,
+            body: body.to_string(),
+        })
+    }
+
+    #[test]
+    fn successful_response_returns_data() {
+        let data = classify::<MediaData>(Ok(r#"{"data":{"Media":{"id":1}}}"#.to_string()))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(data.media, Some(Media { id: 1 }));
+    }
+
+    #[test]
+    fn http_not_found_with_graphql_body_is_an_empty_result() {
+        let data = classify::<MediaData>(http_error(
+            404,
+            r#"{"data":{"Media":null},"errors":[{"message":"Not Found.","status":404}]}"#,
+        ))
+        .unwrap();
+
+        assert!(data.and_then(|data| data.media).is_none());
+    }
+
+    #[test]
+    fn partial_batch_results_survive_not_found_errors() {
+        let data = classify::<HashMap<String, Option<Media>>>(http_error(
+            404,
+            r#"{"data":{"media_0":{"id":1},"media_1":null},"errors":[{"message":"Not Found.","status":404}]}"#,
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(data["media_0"], Some(Media { id: 1 }));
+        assert_eq!(data["media_1"], None);
+    }
+
+    #[test]
+    fn aliased_errors_only_fail_their_own_alias() {
+        let response = classify_aliased::<Media>(http_error(
+            500,
+            r#"{"data":{"id_1":{"id":1},"id_2":null,"id_3":null},"errors":[{"message":"Internal Server Error","status":500,"path":["id_2"]},{"message":"Not Found.","status":404,"path":["id_3"]}]}"#,
+        ))
+        .unwrap();
+
+        assert_eq!(response.get("id_1").unwrap(), Some(&Media { id: 1 }));
+        assert!(matches!(
+            response.get("id_2"),
+            Err(AniListError::GraphQl {
+                kind: GraphQlErrorKind::Server,
+                ..
+            })
+        ));
+        assert_eq!(response.get("id_3").unwrap(), None);
+    }
+
+    #[test]
+    fn aliased_errors_without_a_path_fail_the_whole_batch() {
+        let error = classify_aliased::<Media>(http_error(
+            429,
+            r#"{"data":null,"errors":[{"message":"Too Many Requests.","status":429}]}"#,
+        ))
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            AniListError::GraphQl {
+                kind: GraphQlErrorKind::RateLimited,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn errors_are_classified_by_status() {
+        let classify_status = |status: u16| match classify::<MediaData>(http_error(
+            status,
+            &format!(r#"{{"data":null,"errors":[{{"message":"x","status":{status}}}]}}"#),
+        )) {
+            Err(AniListError::GraphQl { kind, .. }) => kind,
+            other => panic!("expected a GraphQL error, got {other:?}"),
+        };
+
+        assert_eq!(classify_status(429), GraphQlErrorKind::RateLimited);
+        assert_eq!(classify_status(400), GraphQlErrorKind::Query);
+        assert_eq!(classify_status(500), GraphQlErrorKind::Server);
+    }
+
+    #[test]
+    fn errors_without_status_are_query_errors() {
+        let error = check_response(Ok(
+            r#"{"data":null,"errors":[{"message":"Unavailable"}]}"#.to_string()
+        ))
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            AniListError::GraphQl {
+                kind: GraphQlErrorKind::Query,
+                ..
+            }
+        ));
+        assert!(error.user_message().is_none());
+    }
+
+    #[test]
+    fn server_errors_and_rate_limits_have_user_messages() {
+        let server = AniListError::GraphQl {
+            kind: GraphQlErrorKind::Server,
+            message: String::new(),
+        };
+        let rate_limited = AniListError::Request(AniListRequestError::RateLimited {
+            retry_after: std::time::Duration::from_secs(60),
+        });
+
+        assert_eq!(server.user_message(), Some(ANILIST_UNAVAILABLE));
+        assert_eq!(rate_limited.user_message(), Some(ANILIST_RATE_LIMITED));
+    }
+
+    #[test]
+    fn non_graphql_error_bodies_keep_the_http_error() {
+        let error = check_response(http_error(502, "Bad Gateway")).unwrap_err();
+
+        assert!(matches!(
+            error,
+            AniListError::Request(AniListRequestError::NonSuccessStatus { status: 502, .. })
+        ));
+        assert!(error.is_upstream_unavailable());
+    }
+}
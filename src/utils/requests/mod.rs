@@ -1,2 +1,8 @@
 pub mod anilist;
+pub mod batch;
+pub mod fragments;
+pub mod graphql;
 pub mod my_anime_list;
+pub mod query;
+pub mod rate_limit;
+pub mod resilience;
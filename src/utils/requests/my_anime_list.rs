@@ -5,14 +5,23 @@ use std::time::Duration;
 use reqwest::Client;
 use tracing::{info, instrument};
 
-use crate::utils::{statics::MAL_CLIENT_ID, tls::install_rustls_crypto_provider};
+use crate::utils::{
+    requests::resilience::{CircuitBreaker, FailureKind, RetryPolicy, Upstream},
+    statics::MAL_CLIENT_ID,
+    tls::install_rustls_crypto_provider,
+};
 
 #[derive(Debug)]
 pub enum MalRequestError {
     ClientBuild(String),
     RequestFailed(String),
-    NonSuccessStatus { status: u16, body: String },
+    NonSuccessStatus {
+        status: u16,
+        body: String,
+    },
     ResponseBodyReadFailed(String),
+    /// MAL has failed repeatedly; the request was not attempted.
+    CircuitOpen,
 }
 
 impl std::fmt::Display for MalRequestError {
@@ -30,12 +39,29 @@ impl std::fmt::Display for MalRequestError {
             MalRequestError::ResponseBodyReadFailed(error) => {
                 write!(f, "Failed to read MAL response body: {error}")
             }
+            MalRequestError::CircuitOpen => {
+                write!(f, "MAL is failing repeatedly; request skipped")
+            }
         }
     }
 }
 
 impl std::error::Error for MalRequestError {}
 
+impl MalRequestError {
+    fn failure_kind(&self) -> FailureKind {
+        match self {
+            MalRequestError::RequestFailed(_) | MalRequestError::ResponseBodyReadFailed(_) => {
+                FailureKind::Transient
+            }
+            MalRequestError::NonSuccessStatus { status, .. } if *status >= 500 => {
+                FailureKind::Transient
+            }
+            _ => FailureKind::Permanent,
+        }
+    }
+}
+
 const MY_ANIME_LIST_BASE: &str = "https://api.myanimelist.net/v2";
 const FIELDS_TO_FETCH: [&str; 3] = ["id", "opening_themes", "ending_themes"];
 const MAL_TIMEOUT_SECS: u64 = 10;
@@ -49,6 +75,18 @@ static MAL_CLIENT: LazyLock<Result<Client, String>> = LazyLock::new(|| {
         .map_err(|error| error.to_string())
 });
 
+static MAL_UPSTREAM: LazyLock<Upstream> = LazyLock::new(|| {
+    Upstream::new(
+        "myanimelist",
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(2),
+        },
+        CircuitBreaker::new(5, Duration::from_secs(60)),
+    )
+});
+
 #[instrument(name = "http.mal.client", level = "trace")]
 fn get_client() -> Result<&'static Client, MalRequestError> {
     match &*MAL_CLIENT {
@@ -73,9 +111,24 @@ pub async fn send_request(mal_id: u32) -> Result<String, MalRequestError> {
     let mal_client_id = env::var(MAL_CLIENT_ID).expect("Expected MAL_CLIENT_ID in the environment");
 
     let client = get_client()?;
+    let url = build_mal_url(mal_id);
+
+    MAL_UPSTREAM
+        .call(
+            || send_once(client, &url, &mal_client_id),
+            MalRequestError::failure_kind,
+            || MalRequestError::CircuitOpen,
+        )
+        .await
+}
 
+async fn send_once(
+    client: &Client,
+    url: &str,
+    mal_client_id: &str,
+) -> Result<String, MalRequestError> {
     let response = client
-        .get(build_mal_url(mal_id))
+        .get(url)
         .header("X-MAL-CLIENT-ID", mal_client_id)
         .send()
         .await
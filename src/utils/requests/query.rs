@@ -0,0 +1,321 @@
+//! Small typed builder for GraphQL query documents.
+//!
+//! Queries are assembled from [`Selection`]s, which can be nested under
+//! [`Field`]s (with arguments and aliases) and shared through named
+//! [`Fragment`]s. Rendering a [`Query`] appends every fragment it references,
+//! so callers only list the selections they need and ID and search variants
+//! built from the same selection can't drift apart.
+
+use std::fmt::Write;
+
+use serde_json::{Value, json};
+
+/// GraphQL types used for query variables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableType {
+    Int,
+    String,
+    Boolean,
+    MediaType,
+    MediaFormat,
+    MediaStatus,
+    CountryCode,
+}
+
+impl VariableType {
+    fn as_str(self) -> &'static str {
+        match self {
+            VariableType::Int => "Int",
+            VariableType::String => "String",
+            VariableType::Boolean => "Boolean",
+            VariableType::MediaType => "MediaType",
+            VariableType::MediaFormat => "MediaFormat",
+            VariableType::MediaStatus => "MediaStatus",
+            VariableType::CountryCode => "CountryCode",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Field(Field),
+    Spread(Fragment),
+}
+
+/// The set of fields requested from an object.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selection(Vec<Node>);
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, field: impl Into<Field>) -> Self {
+        self.0.push(Node::Field(field.into()));
+        self
+    }
+
+    /// Add several scalar fields at once.
+    pub fn fields(self, names: &[&str]) -> Self {
+        names
+            .iter()
+            .fold(self, |selection, name| selection.field(*name))
+    }
+
+    /// Add an object field without arguments.
+    pub fn object(self, name: &str, selection: Selection) -> Self {
+        self.field(Field::new(name).select(selection))
+    }
+
+    pub fn spread(mut self, fragment: &Fragment) -> Self {
+        self.0.push(Node::Spread(fragment.clone()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn render(&self, out: &mut String, depth: usize) {
+        for node in &self.0 {
+            match node {
+                Node::Field(field) => field.render(out, depth),
+                Node::Spread(fragment) => {
+                    let _ = writeln!(out, "{}...{}", indent(depth), fragment.name);
+                }
+            }
+        }
+    }
+
+    fn collect_fragments<'a>(&'a self, fragments: &mut Vec<&'a Fragment>) {
+        for node in &self.0 {
+            match node {
+                Node::Field(field) => field.selection.collect_fragments(fragments),
+                Node::Spread(fragment) => {
+                    if fragments.iter().all(|known| known.name != fragment.name) {
+                        fragments.push(fragment);
+                        fragment.selection.collect_fragments(fragments);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    alias: Option<String>,
+    name: String,
+    arguments: Vec<(String, String)>,
+    selection: Selection,
+}
+
+impl Field {
+    pub fn new(name: &str) -> Self {
+        Self {
+            alias: None,
+            name: name.to_string(),
+            arguments: Vec::new(),
+            selection: Selection::new(),
+        }
+    }
+
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+
+    /// Add an argument. `value` is GraphQL source, e.g. `"$id"`, `"ANIME"`
+    /// or `"POPULARITY_DESC"`.
+    pub fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.arguments.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn select(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    fn render(&self, out: &mut String, depth: usize) {
+        out.push_str(&indent(depth));
+        if let Some(alias) = &self.alias {
+            let _ = write!(out, "{alias}: ");
+        }
+        out.push_str(&self.name);
+        if !self.arguments.is_empty() {
+            let arguments = self
+                .arguments
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let _ = write!(out, "({arguments})");
+        }
+        if self.selection.is_empty() {
+            out.push('\n');
+            return;
+        }
+        out.push_str(" {\n");
+        self.selection.render(out, depth + 1);
+        let _ = writeln!(out, "{}}}", indent(depth));
+    }
+}
+
+impl From<&str> for Field {
+    fn from(name: &str) -> Self {
+        Field::new(name)
+    }
+}
+
+/// A named selection on a GraphQL type, rendered once per query however
+/// many times it is spread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    name: &'static str,
+    on: &'static str,
+    selection: Selection,
+}
+
+impl Fragment {
+    pub fn new(name: &'static str, on: &'static str, selection: Selection) -> Self {
+        Self {
+            name,
+            on,
+            selection,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    variables: Vec<(String, VariableType)>,
+    selection: Selection,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare `$name` with the given type.
+    pub fn variable(mut self, name: &str, variable_type: VariableType) -> Self {
+        self.variables.push((name.to_string(), variable_type));
+        self
+    }
+
+    /// Add a root field.
+    pub fn field(mut self, field: impl Into<Field>) -> Self {
+        self.selection = self.selection.field(field);
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::from("query");
+        if !self.variables.is_empty() {
+            let variables = self
+                .variables
+                .iter()
+                .map(|(name, variable_type)| format!("${name}: {}", variable_type.as_str()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let _ = write!(out, " ({variables})");
+        }
+        out.push_str(" {\n");
+        self.selection.render(&mut out, 1);
+        out.push_str("}\n");
+
+        let mut fragments = Vec::new();
+        self.selection.collect_fragments(&mut fragments);
+        for fragment in fragments {
+            let _ = writeln!(out, "\nfragment {} on {} {{", fragment.name, fragment.on);
+            fragment.selection.render(&mut out, 1);
+            out.push_str("}\n");
+        }
+
+        out
+    }
+
+    /// The JSON body sent to the GraphQL endpoint.
+    pub fn request(&self, variables: Value) -> Value {
+        json!({ "query": self.render(), "variables": variables })
+    }
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_variables_arguments_and_nested_selections() {
+        let query = Query::new().variable("id", VariableType::Int).field(
+            Field::new("Media")
+                .arg("id", "$id")
+                .arg("type", "ANIME")
+                .select(
+                    Selection::new()
+                        .field("id")
+                        .object("title", Selection::new().fields(&["romaji", "english"])),
+                ),
+        );
+
+        assert_eq!(
+            query.render(),
+            "query ($id: Int) {\n  Media(id: $id, type: ANIME) {\n    id\n    title {\n      romaji\n      english\n    }\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn fragments_are_rendered_once_after_the_operation() {
+        let title = Fragment::new("TitleFields", "Media", Selection::new().field("siteUrl"));
+        let media = Selection::new().field("id").spread(&title);
+        let query = Query::new()
+            .field(Field::new("Media").alias("first").select(media.clone()))
+            .field(Field::new("Media").alias("second").select(media));
+
+        let rendered = query.render();
+
+        assert!(rendered.starts_with("query {\n  first: Media {\n    id\n    ...TitleFields\n"));
+        assert_eq!(
+            rendered.matches("fragment TitleFields on Media {").count(),
+            1
+        );
+        assert!(rendered.ends_with("fragment TitleFields on Media {\n  siteUrl\n}\n"));
+    }
+
+    #[test]
+    fn nested_fragments_are_collected() {
+        let inner = Fragment::new("Inner", "Media", Selection::new().field("id"));
+        let outer = Fragment::new("Outer", "Media", Selection::new().spread(&inner));
+        let query = Query::new().field(Field::new("Media").select(Selection::new().spread(&outer)));
+
+        let rendered = query.render();
+
+        assert!(rendered.contains("fragment Outer on Media {\n  ...Inner\n}"));
+        assert!(rendered.contains("fragment Inner on Media {\n  id\n}"));
+    }
+
+    #[test]
+    fn request_wraps_query_and_variables() {
+        let query = Query::new().variable("search", VariableType::String).field(
+            Field::new("Studio")
+                .arg("search", "$search")
+                .select(Selection::new().field("id")),
+        );
+
+        let request = query.request(json!({ "search": "Bones" }));
+
+        assert_eq!(request["variables"]["search"], "Bones");
+        assert!(
+            request["query"]
+                .as_str()
+                .unwrap()
+                .starts_with("query ($search: String) {")
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Process-wide request budget for rate-limited upstream APIs.
+//!
+//! [`RateLimiter`] is a token bucket that starts from a configured budget and
+//! is corrected by the `X-RateLimit-*` headers the upstream returns. After a
+//! 429 it blocks every caller until the advertised `Retry-After` has passed.
+//! Callers wait for a free slot for at most `max_wait`; beyond that the
+//! request is shed so a command can tell the user to try again shortly.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use tracing::{debug, instrument};
+
+const RATE_LIMIT_LIMIT: &str = "x-ratelimit-limit";
+const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// Rate-limit information read from an upstream response.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RateLimitHeaders {
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    /// Unix timestamp (seconds) at which the budget resets.
+    pub reset: Option<u64>,
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitHeaders {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        fn number<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+            headers.get(name)?.to_str().ok()?.trim().parse().ok()
+        }
+
+        Self {
+            limit: number(headers, RATE_LIMIT_LIMIT),
+            remaining: number(headers, RATE_LIMIT_REMAINING),
+            reset: number(headers, RATE_LIMIT_RESET),
+            retry_after: number::<u64>(headers, RETRY_AFTER.as_str()).map(Duration::from_secs),
+        }
+    }
+}
+
+struct Bucket {
+    capacity: u32,
+    remaining: u32,
+    resets_at: Instant,
+    blocked_until: Option<Instant>,
+}
+
+pub struct RateLimiter {
+    window: Duration,
+    max_wait: Duration,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Allow `capacity` requests per `window`, queueing callers for at most
+    /// `max_wait` when the budget is spent.
+    pub fn new(capacity: u32, window: Duration, max_wait: Duration) -> Self {
+        Self {
+            window,
+            max_wait,
+            bucket: Mutex::new(Bucket {
+                capacity,
+                remaining: capacity,
+                resets_at: Instant::now() + window,
+                blocked_until: None,
+            }),
+        }
+    }
+
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// Wait for a request slot.
+    ///
+    /// Returns `Err` with the remaining wait when a slot would not free up
+    /// within `max_wait`; the request should then be shed.
+    #[instrument(name = "rate_limit.acquire", skip(self))]
+    pub async fn acquire(&self) -> Result<(), Duration> {
+        let deadline = Instant::now() + self.max_wait;
+        loop {
+            let now = Instant::now();
+            match self.try_acquire(now) {
+                Ok(()) => return Ok(()),
+                Err(wait) if now + wait <= deadline => {
+                    debug!(
+                        wait_ms = wait.as_millis() as u64,
+                        "Waiting for rate-limit slot"
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+                Err(wait) => return Err(wait),
+            }
+        }
+    }
+
+    /// Reserve one slot at `now`, or return how long until one frees up.
+    fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(until) = bucket.blocked_until {
+            if now < until {
+                return Err(until - now);
+            }
+            bucket.blocked_until = None;
+        }
+
+        if now >= bucket.resets_at {
+            bucket.remaining = bucket.capacity;
+            bucket.resets_at = now + self.window;
+        }
+
+        if bucket.remaining == 0 {
+            return Err(bucket.resets_at - now);
+        }
+
+        bucket.remaining -= 1;
+        Ok(())
+    }
+
+    /// Correct the local budget from the upstream's rate-limit headers.
+    ///
+    /// Within one window the header can only lower the budget: it may come
+    /// from a request that started before slots were taken for others still
+    /// in flight. Once the window has rolled over it is taken as-is.
+    pub fn observe(&self, headers: &RateLimitHeaders, now: Instant) {
+        self.observe_at(headers, now, unix_now());
+    }
+
+    fn observe_at(&self, headers: &RateLimitHeaders, now: Instant, now_unix: u64) {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+
+        let rolled_over = now >= bucket.resets_at;
+        if let Some(limit) = headers.limit {
+            bucket.capacity = limit;
+        }
+        if let Some(remaining) = headers.remaining {
+            bucket.remaining = if rolled_over {
+                remaining
+            } else {
+                bucket.remaining.min(remaining)
+            };
+        }
+        if let Some(reset) = headers.reset {
+            bucket.resets_at = now + Duration::from_secs(reset.saturating_sub(now_unix));
+        } else if rolled_over {
+            bucket.resets_at = now + self.window;
+        }
+    }
+
+    /// Block every caller for `retry_after`, e.g. after a 429.
+    pub fn block_for(&self, retry_after: Duration, now: Instant) {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let until = now + retry_after;
+        bucket.blocked_until = Some(
+            bucket
+                .blocked_until
+                .map_or(until, |current| current.max(until)),
+        );
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn budget_is_spent_then_refilled_after_the_window() {
+        let limiter = RateLimiter::new(2, WINDOW, Duration::ZERO);
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now).is_err());
+
+        assert!(limiter.try_acquire(now + WINDOW).is_ok());
+    }
+
+    #[test]
+    fn headers_override_local_budget() {
+        let limiter = RateLimiter::new(90, WINDOW, Duration::ZERO);
+        let now = Instant::now();
+
+        limiter.observe_at(
+            &RateLimitHeaders {
+                limit: Some(30),
+                remaining: Some(0),
+                reset: Some(1_000 + 20),
+                retry_after: None,
+            },
+            now,
+            1_000,
+        );
+
+        assert_eq!(limiter.try_acquire(now), Err(Duration::from_secs(20)));
+        assert!(limiter.try_acquire(now + Duration::from_secs(20)).is_ok());
+    }
+
+    #[test]
+    fn stale_headers_do_not_raise_the_budget_within_a_window() {
+        let limiter = RateLimiter::new(2, WINDOW, Duration::ZERO);
+        let now = Instant::now();
+        let headers = RateLimitHeaders {
+            remaining: Some(2),
+            ..RateLimitHeaders::default()
+        };
+
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now).is_ok());
+        limiter.observe_at(&headers, now, 1_000);
+        assert!(limiter.try_acquire(now).is_err());
+
+        limiter.observe_at(&headers, now + WINDOW, 1_060);
+        assert!(limiter.try_acquire(now + WINDOW).is_ok());
+    }
+
+    #[test]
+    fn block_for_rejects_until_retry_after_passes() {
+        let limiter = RateLimiter::new(90, WINDOW, Duration::ZERO);
+        let now = Instant::now();
+
+        limiter.block_for(Duration::from_secs(5), now);
+
+        assert_eq!(limiter.try_acquire(now), Err(Duration::from_secs(5)));
+        assert!(limiter.try_acquire(now + Duration::from_secs(5)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn acquire_sheds_when_wait_exceeds_max_wait() {
+        let limiter = RateLimiter::new(90, WINDOW, Duration::from_secs(1));
+        limiter.block_for(Duration::from_secs(30), Instant::now());
+
+        assert!(limiter.acquire().await.is_err());
+    }
+
+    #[test]
+    fn parses_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from_static("90"));
+        headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from_static("12"));
+        headers.insert(RATE_LIMIT_RESET, HeaderValue::from_static("1700000000"));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("60"));
+
+        assert_eq!(
+            RateLimitHeaders::from_headers(&headers),
+            RateLimitHeaders {
+                limit: Some(90),
+                remaining: Some(12),
+                reset: Some(1_700_000_000),
+                retry_after: Some(Duration::from_secs(60)),
+            }
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Retries and circuit breaking shared by the outbound HTTP clients.
+//!
+//! Each upstream (AniList, MyAnimeList, Gemini) owns one [`Upstream`]. Calls
+//! made through [`Upstream::call`] are retried on transient failures with
+//! jittered exponential backoff. Once a call has exhausted its retries it
+//! counts towards the circuit breaker; after enough consecutive failures the
+//! circuit opens and further calls fail immediately until it cools down, so
+//! commands can tell users the upstream is having trouble instead of waiting
+//! on timeouts.
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::{debug, info, warn};
+
+/// How a failed attempt should be handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// Timeouts, connection errors and 5xx responses: retry, and count
+    /// towards the circuit breaker if retries run out.
+    Transient,
+    /// The upstream answered but rejected this request (4xx, bad payload):
+    /// return immediately. The upstream is healthy, so the breaker resets.
+    Permanent,
+    /// The request was rate limited, or shed before it was sent: return
+    /// immediately and leave the breaker alone, since nothing succeeded.
+    Throttled,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with full jitter: a random delay between zero and
+    /// `base_delay * 2^(retry - 1)`, capped at `max_delay`. `jitter` is a
+    /// fraction in `0.0..=1.0`.
+    fn delay_for(&self, retry: u32, jitter: f64) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)));
+        exponential
+            .min(self.max_delay)
+            .mul_f64(jitter.clamp(0.0, 1.0))
+    }
+}
+
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Opens after `failure_threshold` consecutive failed calls and rejects calls
+/// for `open_for`. Once that has passed calls are let through again; the next
+/// failure re-opens it straight away and a success closes it.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_for,
+            state: Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                open_until: None,
+            }),
+        }
+    }
+
+    pub fn is_open(&self, now: Instant) -> bool {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .open_until
+            .is_some_and(|until| now < until)
+    }
+
+    /// Returns `true` if the circuit was open before this success.
+    fn record_success(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.consecutive_failures = 0;
+        state.open_until.take().is_some()
+    }
+
+    /// Returns `true` if this failure opened the circuit.
+    fn record_failure(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.consecutive_failures < self.failure_threshold {
+            return false;
+        }
+        let was_open = state.open_until.is_some_and(|until| now < until);
+        state.open_until = Some(now + self.open_for);
+        !was_open
+    }
+}
+
+/// Retry policy and circuit breaker for one upstream service.
+pub struct Upstream {
+    name: &'static str,
+    retry: RetryPolicy,
+    breaker: CircuitBreaker,
+}
+
+impl Upstream {
+    pub fn new(name: &'static str, retry: RetryPolicy, breaker: CircuitBreaker) -> Self {
+        Self {
+            name,
+            retry,
+            breaker,
+        }
+    }
+
+    /// Run `attempt` with retries, failing fast while the circuit is open.
+    ///
+    /// Only use this for idempotent requests. `classify` decides whether an
+    /// error is worth retrying; `circuit_open` builds the error returned when
+    /// the call is rejected without being attempted.
+    pub async fn call<T, E, F, Fut>(
+        &self,
+        mut attempt: F,
+        classify: impl Fn(&E) -> FailureKind,
+        circuit_open: impl FnOnce() -> E,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: std::fmt::Display,
+    {
+        if self.breaker.is_open(Instant::now()) {
+            debug!(upstream = self.name, "Circuit open; failing fast");
+            return Err(circuit_open());
+        }
+
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let error = match attempt().await {
+                Ok(value) => {
+                    self.record_success();
+                    return Ok(value);
+                }
+                Err(error) => error,
+            };
+
+            match classify(&error) {
+                FailureKind::Permanent => {
+                    self.record_success();
+                    return Err(error);
+                }
+                FailureKind::Throttled => return Err(error),
+                FailureKind::Transient => {}
+            }
+
+            if attempts >= self.retry.max_attempts {
+                if self.breaker.record_failure(Instant::now()) {
+                    warn!(
+                        upstream = self.name,
+                        open_for_secs = self.breaker.open_for.as_secs(),
+                        error = %error,
+                        "Circuit opened after repeated failures"
+                    );
+                }
+                return Err(error);
+            }
+
+            let delay = self.retry.delay_for(attempts, jitter());
+            debug!(
+                upstream = self.name,
+                attempt = attempts,
+                delay_ms = delay.as_millis() as u64,
+                error = %error,
+                "Retrying after transient failure"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn record_success(&self) {
+        if self.breaker.record_success() {
+            info!(upstream = self.name, "Circuit closed; upstream recovered");
+        }
+    }
+}
+
+/// Random fraction in `0.0..=1.0`; falls back to no jitter if the OS RNG is
+/// unavailable.
+fn jitter() -> f64 {
+    getrandom::u32()
+        .map(|value| f64::from(value) / f64::from(u32::MAX))
+        .unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    fn upstream(max_attempts: u32, failure_threshold: u32) -> Upstream {
+        Upstream::new(
+            "test",
+            RetryPolicy {
+                max_attempts,
+                base_delay: Duration::ZERO,
+                max_delay: Duration::ZERO,
+            },
+            CircuitBreaker::new(failure_threshold, Duration::from_secs(30)),
+        )
+    }
+
+    fn transient(_: &String) -> FailureKind {
+        FailureKind::Transient
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+        };
+
+        assert_eq!(policy.delay_for(1, 1.0), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2, 1.0), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3, 1.0), Duration::from_millis(300));
+        assert_eq!(policy.delay_for(3, 0.5), Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_until_success() {
+        let upstream = upstream(3, 5);
+        let calls = AtomicU32::new(0);
+
+        let result = upstream
+            .call(
+                || async {
+                    if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                        Err("timeout".to_string())
+                    } else {
+                        Ok(42)
+                    }
+                },
+                transient,
+                || "open".to_string(),
+            )
+            .await;
+
+        assert_eq!(result, Ok(42));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn permanent_failures_are_not_retried() {
+        let upstream = upstream(3, 5);
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), String> = upstream
+            .call(
+                || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err("not found".to_string())
+                },
+                |_| FailureKind::Permanent,
+                || "open".to_string(),
+            )
+            .await;
+
+        assert_eq!(result, Err("not found".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_repeated_failures_and_fails_fast() {
+        let upstream = upstream(1, 2);
+        let calls = AtomicU32::new(0);
+        let failing = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>("503".to_string())
+        };
+
+        for _ in 0..2 {
+            let _ = upstream
+                .call(failing, transient, || "open".to_string())
+                .await;
+        }
+        assert!(upstream.breaker.is_open(Instant::now()));
+
+        let result = upstream
+            .call(failing, transient, || "open".to_string())
+            .await;
+        assert_eq!(result, Err("open".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn throttled_failures_do_not_reset_the_breaker() {
+        let upstream = upstream(1, 2);
+        let failing = || async { Err::<(), _>("503".to_string()) };
+
+        let _ = upstream
+            .call(failing, transient, || "open".to_string())
+            .await;
+        let _ = upstream
+            .call(
+                || async { Err::<(), _>("429".to_string()) },
+                |_| FailureKind::Throttled,
+                || "open".to_string(),
+            )
+            .await;
+        let _ = upstream
+            .call(failing, transient, || "open".to_string())
+            .await;
+
+        assert!(upstream.breaker.is_open(Instant::now()));
+    }
+
+    #[test]
+    fn breaker_closes_after_cooldown_success() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        let now = Instant::now();
+
+        assert!(breaker.record_failure(now));
+        assert!(breaker.is_open(now));
+        assert!(!breaker.is_open(now + Duration::from_secs(30)));
+
+        assert!(breaker.record_success());
+        assert!(!breaker.is_open(now));
+    }
+}
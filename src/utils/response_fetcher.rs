@@ -1,11 +1,17 @@
-use crate::models::{
-    anilist_character::Character,
-    anilist_common::TitleVariant,
-    fetcher::{
-        AnimeConfig, Argument, CharacterConfig, MangaConfig, Response, fetch, fetch_character,
+use crate::{
+    models::{
+        anilist_character::Character,
+        anilist_common::TitleVariant,
+        fetcher::{
+            AnimeConfig, Argument, CharacterConfig, MangaConfig, Response, fetch, fetch_character,
+        },
+        media_type::MediaType as Type,
+        transformers::Transformers,
+    },
+    utils::{
+        cache::{Cache, SharedCache},
+        requests::graphql::AniListError,
     },
-    media_type::MediaType as Type,
-    transformers::Transformers,
 };
 use serenity::all::CommandDataOptionValue;
 use tracing::{error, info, instrument};
@@ -34,35 +40,41 @@ fn return_argument(arg: CommandDataOptionValue) -> Option<Argument> {
     }
 }
 
-#[instrument(name = "fetcher.fetch", skip(arg), fields(media_type = ?media_type))]
+#[instrument(name = "fetcher.fetch", skip(arg, cache), fields(media_type = ?media_type))]
 pub async fn fetcher<
     T: serde::de::DeserializeOwned + Transformers + std::fmt::Debug + std::clone::Clone,
 >(
     media_type: Type,
     arg: CommandDataOptionValue,
-) -> Option<(T, TitleVariant)> {
+    cache: &SharedCache,
+) -> Result<Option<(T, TitleVariant)>, AniListError> {
     info!("Fetcher found arg: {:#?}", arg);
-    let argument = return_argument(arg)?;
+    let Some(argument) = return_argument(arg) else {
+        return Ok(None);
+    };
 
     match media_type {
         Type::Anime => {
             let anime_response: AnimeConfig = Response::new(argument);
-            fetch::<T>(&anime_response, media_type).await
+            fetch::<T>(&anime_response, media_type, cache).await
         }
         Type::Manga => {
             let manga_response: MangaConfig = Response::new(argument);
-            fetch::<T>(&manga_response, media_type).await
+            fetch::<T>(&manga_response, media_type, cache).await
         }
     }
 }
 
-#[instrument(name = "fetcher.fetch_character", skip(arg))]
+#[instrument(name = "fetcher.fetch_character", skip(arg, cache))]
 pub async fn character_fetcher(
     arg: CommandDataOptionValue,
     allow_spoilers: bool,
-) -> Option<Character> {
+    cache: &dyn Cache,
+) -> Result<Option<Character>, AniListError> {
     info!("Character fetcher found arg: {:#?}", arg);
-    let argument = return_argument(arg)?;
+    let Some(argument) = return_argument(arg) else {
+        return Ok(None);
+    };
     let character_response: CharacterConfig = Response::new(argument);
-    fetch_character(&character_response, allow_spoilers).await
+    fetch_character(&character_response, allow_spoilers, cache).await
 }
@@ -5,8 +5,9 @@ use crate::{
     models::{
         db::settings::{get_guild_setting, get_user_setting, resolve_setting_layers},
         settings::{
-            AnalyticsPrivacyPreference, SettingKey, SettingValue, TitleDisplayPreference,
-            guild_scores_enabled, user_participates_in_guild_scores,
+            AnalyticsPrivacyPreference, SettingKey, SettingValue, SpoilerPreference,
+            StreamingRegion, TitleDisplayPreference, guild_scores_enabled,
+            user_participates_in_guild_scores,
         },
     },
     utils::database::{DbPool, get_pool_from_context},
@@ -26,7 +27,10 @@ pub async fn resolve_title_display_preference(
     match resolve_setting_layers(&pool, user_id, guild_id, SettingKey::TitleDisplay).await {
         Ok(layers) => match layers.effective.value {
             SettingValue::TitleDisplay(preference) => preference,
-            SettingValue::AnalyticsPrivacy(_) | SettingValue::GuildScores(_) => {
+            SettingValue::AnalyticsPrivacy(_)
+            | SettingValue::GuildScores(_)
+            | SettingValue::Spoilers(_)
+            | SettingValue::StreamingRegion(_) => {
                 warn!("Unexpected non-title value for title display key; using default");
                 default_title_display_preference()
             }
@@ -62,13 +66,75 @@ pub async fn resolve_analytics_privacy_preference(
     }
 }
 
+/// Whether descriptions may show spoilers. An explicit `spoilers` command
+/// option wins over the user's saved preference.
+#[instrument(name = "settings.resolve_spoilers", skip(ctx, user_id))]
+pub async fn resolve_spoiler_preference(
+    ctx: &Context,
+    user_id: UserId,
+    requested: Option<bool>,
+) -> bool {
+    if let Some(allow_spoilers) = requested {
+        return allow_spoilers;
+    }
+
+    let Some(pool) = get_pool_from_context(ctx).await else {
+        warn!("Database pool unavailable; hiding spoilers");
+        return false;
+    };
+
+    match get_user_setting(&pool, user_id, SettingKey::Spoilers).await {
+        Ok(Some(SettingValue::Spoilers(preference))) => preference.allows_spoilers(),
+        Ok(Some(_)) => {
+            warn!("Unexpected non-spoiler value for spoilers key; hiding spoilers");
+            false
+        }
+        Ok(None) => SpoilerPreference::Hidden.allows_spoilers(),
+        Err(error) => {
+            warn!(error = %error, "Failed to resolve spoiler preference; hiding spoilers");
+            false
+        }
+    }
+}
+
+/// Which streaming services to list: the user's region, else the server's.
+#[instrument(
+    name = "settings.resolve_streaming_region",
+    skip(ctx, user_id, guild_id)
+)]
+pub async fn resolve_streaming_region(
+    ctx: &Context,
+    user_id: UserId,
+    guild_id: Option<GuildId>,
+) -> StreamingRegion {
+    let Some(pool) = get_pool_from_context(ctx).await else {
+        warn!("Database pool unavailable; listing streaming services for all regions");
+        return StreamingRegion::All;
+    };
+
+    match resolve_setting_layers(&pool, user_id, guild_id, SettingKey::StreamingRegion).await {
+        Ok(layers) => match layers.effective.value {
+            SettingValue::StreamingRegion(region) => region,
+            _ => {
+                warn!("Unexpected non-region value for streaming region key; using all regions");
+                StreamingRegion::All
+            }
+        },
+        Err(error) => {
+            warn!(error = %error, "Failed to resolve streaming region; using all regions");
+            StreamingRegion::All
+        }
+    }
+}
+
 #[instrument(name = "settings.default_title_display")]
 fn default_title_display_preference() -> TitleDisplayPreference {
     match SettingKey::TitleDisplay.default_value() {
         SettingValue::TitleDisplay(preference) => preference,
-        SettingValue::AnalyticsPrivacy(_) | SettingValue::GuildScores(_) => {
-            TitleDisplayPreference::Matched
-        }
+        SettingValue::AnalyticsPrivacy(_)
+        | SettingValue::GuildScores(_)
+        | SettingValue::Spoilers(_)
+        | SettingValue::StreamingRegion(_) => TitleDisplayPreference::Matched,
     }
 }
 
@@ -76,9 +142,10 @@ fn default_title_display_preference() -> TitleDisplayPreference {
 pub fn default_analytics_privacy_preference() -> AnalyticsPrivacyPreference {
     match SettingKey::AnalyticsPrivacy.default_value() {
         SettingValue::AnalyticsPrivacy(preference) => preference,
-        SettingValue::TitleDisplay(_) | SettingValue::GuildScores(_) => {
-            AnalyticsPrivacyPreference::OptedOut
-        }
+        SettingValue::TitleDisplay(_)
+        | SettingValue::GuildScores(_)
+        | SettingValue::Spoilers(_)
+        | SettingValue::StreamingRegion(_) => AnalyticsPrivacyPreference::OptedOut,
     }
 }
 
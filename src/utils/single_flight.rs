@@ -0,0 +1,126 @@
+//! Request coalescing for identical concurrent upstream lookups.
+//!
+//! When several users search the same title at once, every caller misses the
+//! cache at the same time. [`SingleFlight`] lets the first caller for a key do
+//! the work while later callers for the same key wait for, and share, its
+//! result.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use tokio::sync::OnceCell;
+use tracing::{debug, instrument};
+
+use crate::utils::requests::graphql::AniListError;
+
+/// In-flight AniList lookups keyed by their cache key.
+///
+/// Cache keys are already namespaced per query (`Anime:…`, `character:v2:…`,
+/// `recommendation:…`), so one map can be shared by every AniList fetcher.
+/// Errors are shared too, so every waiter can explain the failure.
+pub static ANILIST_FLIGHTS: LazyLock<SingleFlight<Result<Option<String>, AniListError>>> =
+    LazyLock::new(SingleFlight::default);
+
+pub struct SingleFlight<T> {
+    in_flight: Mutex<HashMap<String, Arc<OnceCell<T>>>>,
+}
+
+impl<T> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> SingleFlight<T> {
+    /// Run `work` for `key`, or wait for the call already in flight for it.
+    ///
+    /// If the leading caller is cancelled, one of the waiters takes over and
+    /// runs its own `work`. Once a flight finishes the key is released, so the
+    /// next call after that starts a fresh lookup (normally a cache hit).
+    #[instrument(name = "single_flight.run", skip(self, work), fields(key = %key))]
+    pub async fn run<F, Fut>(&self, key: &str, work: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let cell = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            let cell = in_flight.entry(key.to_string()).or_default();
+            if cell.initialized() || Arc::strong_count(cell) > 1 {
+                debug!("Joining in-flight request");
+            }
+            cell.clone()
+        };
+
+        let value = cell.get_or_init(work).await.clone();
+
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if in_flight
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(key);
+        }
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_calls_for_same_key_share_one_execution() {
+        let flights = Arc::new(SingleFlight::<String>::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let handles = (0..8)
+            .map(|_| {
+                let flights = flights.clone();
+                let calls = calls.clone();
+                tokio::spawn(async move {
+                    flights
+                        .run("anime:frieren", || async {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            "payload".to_string()
+                        })
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), "payload");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn different_keys_run_independently() {
+        let flights = SingleFlight::<u32>::default();
+
+        let (first, second) = tokio::join!(
+            flights.run("a", || async { 1 }),
+            flights.run("b", || async { 2 }),
+        );
+
+        assert_eq!((first, second), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn finished_flight_releases_its_key() {
+        let flights = SingleFlight::<u32>::default();
+
+        assert_eq!(flights.run("key", || async { 1 }).await, 1);
+        assert_eq!(flights.run("key", || async { 2 }).await, 2);
+        assert!(flights.in_flight.lock().unwrap().is_empty());
+    }
+}
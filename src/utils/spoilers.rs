@@ -0,0 +1,171 @@
+//! AniList spoiler markup, shared by media and character descriptions.
+//!
+//! Descriptions mark spoilers two ways: rendered HTML wraps them in
+//! `<span class='markdown_spoiler'>`, while raw markdown leaves them between
+//! `~!` and `!~`. The `spoilers` command option that chooses between them is
+//! shared by `/anime`, `/manga` and `/character`.
+
+use serenity::all::{
+    CommandDataOption, CommandDataOptionValue, CommandOptionType, CreateCommandOption,
+};
+
+use super::formatter::spoiler;
+
+const SPOILERS_OPTION: &str = "spoilers";
+const ALLOW_SPOILERS: &str = "allow";
+const DISALLOW_SPOILERS: &str = "disallow";
+
+const MARKDOWN_SPOILER_CLASS: &str = "markdown_spoiler";
+const SPAN_OPEN: &str = "<span";
+const SPAN_CLOSE: &str = "</span>";
+const MARKER_OPEN: &str = "~!";
+const MARKER_CLOSE: &str = "!~";
+
+/// The allow/disallow `spoilers` option.
+pub fn spoilers_option(description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, SPOILERS_OPTION, description)
+        .add_string_choice("Allow", ALLOW_SPOILERS)
+        .add_string_choice("Disallow", DISALLOW_SPOILERS)
+}
+
+/// The `spoilers` choice, or `None` when it was left out.
+pub fn parse_spoilers_option(options: &[CommandDataOption]) -> Option<bool> {
+    options
+        .iter()
+        .find(|option| option.name == SPOILERS_OPTION)
+        .and_then(|option| match &option.value {
+            CommandDataOptionValue::String(value) => match value.as_str() {
+                ALLOW_SPOILERS => Some(true),
+                DISALLOW_SPOILERS => Some(false),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+/// Prepare a description for display. Allowed spoilers stay readable behind
+/// Discord's click-to-reveal markup; disallowed ones are removed entirely.
+pub fn filter_spoilers(html: &str, allow_spoilers: bool) -> String {
+    if allow_spoilers {
+        replace_spoiler_markers(&replace_spoiler_spans(html, spoiler), spoiler)
+    } else {
+        let remove = |_: &str| String::default();
+        replace_spoiler_markers(&replace_spoiler_spans(html, remove), remove)
+    }
+}
+
+/// Rewrite every `~!…!~` block with `replace`. An unclosed marker runs to
+/// the end of the text, the same way AniList renders it.
+fn replace_spoiler_markers(text: &str, replace: impl Fn(&str) -> String) -> String {
+    let mut output = String::default();
+    let mut remaining = text;
+
+    while let Some(open) = remaining.find(MARKER_OPEN) {
+        output.push_str(&remaining[..open]);
+        let inner = &remaining[open + MARKER_OPEN.len()..];
+        let (hidden, rest) = match inner.find(MARKER_CLOSE) {
+            Some(close) => (&inner[..close], &inner[close + MARKER_CLOSE.len()..]),
+            None => (inner, ""),
+        };
+        output.push_str(&replace(hidden));
+        remaining = rest;
+    }
+
+    output.push_str(remaining);
+    output
+}
+
+/// Rewrite every `markdown_spoiler` span with `replace`, which gets the
+/// span's content (nested spans included). An unclosed span runs to the end
+/// of the text.
+fn replace_spoiler_spans(html: &str, replace: impl Fn(&str) -> String) -> String {
+    let mut output = String::default();
+    let mut remaining = html;
+
+    while let Some(class_index) = remaining.find(MARKDOWN_SPOILER_CLASS) {
+        let Some(open_start) = remaining[..class_index].rfind(SPAN_OPEN) else {
+            output.push_str(&remaining[..class_index + MARKDOWN_SPOILER_CLASS.len()]);
+            remaining = &remaining[class_index + MARKDOWN_SPOILER_CLASS.len()..];
+            continue;
+        };
+
+        output.push_str(&remaining[..open_start]);
+
+        let Some(open_end_offset) = remaining[class_index..].find('>') else {
+            remaining = &remaining[open_start..];
+            break;
+        };
+        let content_start = class_index + open_end_offset + 1;
+        let mut cursor = content_start;
+        let mut content_end = cursor;
+        let mut span_depth = 1;
+
+        while span_depth > 0 {
+            let next_open = remaining[cursor..]
+                .find(SPAN_OPEN)
+                .map(|offset| cursor + offset);
+            let next_close = remaining[cursor..]
+                .find(SPAN_CLOSE)
+                .map(|offset| cursor + offset);
+
+            match (next_open, next_close) {
+                (Some(open), Some(close)) if open < close => {
+                    span_depth += 1;
+                    cursor = open + SPAN_OPEN.len();
+                }
+                (_, Some(close)) => {
+                    span_depth -= 1;
+                    content_end = close;
+                    cursor = close + SPAN_CLOSE.len();
+                }
+                _ => {
+                    content_end = remaining.len();
+                    cursor = remaining.len();
+                    break;
+                }
+            }
+        }
+
+        output.push_str(&replace(&remaining[content_start..content_end]));
+        remaining = &remaining[cursor..];
+    }
+
+    output.push_str(remaining);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::filter_spoilers;
+
+    #[test]
+    fn spoiler_markers_are_hidden_or_removed() {
+        let description = "Captain of the crew. ~!He becomes king.!~ Loves meat.";
+
+        assert_eq!(
+            filter_spoilers(description, true),
+            "Captain of the crew. ||He becomes king.|| Loves meat."
+        );
+        assert_eq!(
+            filter_spoilers(description, false),
+            "Captain of the crew.  Loves meat."
+        );
+    }
+
+    #[test]
+    fn unclosed_marker_hides_the_rest_of_the_text() {
+        assert_eq!(filter_spoilers("Intro ~!twist", false), "Intro ");
+        assert_eq!(filter_spoilers("Intro ~!twist", true), "Intro ||twist||");
+    }
+
+    #[test]
+    fn spoiler_spans_are_kept_only_when_allowed() {
+        let html = "Known.<span class='markdown_spoiler'><span>Secret.</span></span> After.";
+
+        assert_eq!(
+            filter_spoilers(html, true),
+            "Known.||<span>Secret.</span>|| After."
+        );
+        assert_eq!(filter_spoilers(html, false), "Known. After.");
+    }
+}
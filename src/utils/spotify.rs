@@ -1,7 +1,7 @@
 use crate::{
     models::mal_response::ParsedSong,
     utils::{
-        redis::{check_cache, try_to_cache_response},
+        cache::{Cache, DEFAULT_CACHE_TTL},
         statics::{SPOTIFY_CLIENT_ID, SPOTIFY_CLIENT_SECRET},
     },
 };
@@ -13,6 +13,7 @@ use rspotify::{
 };
 
 use std::env;
+use tokio::task;
 use tracing::{error, info, instrument};
 
 #[instrument(name = "spotify.get_client", skip_all)]
@@ -30,64 +31,79 @@ fn get_spotify_client() -> ClientCredsSpotify {
     spotify
 }
 
-#[instrument(name = "spotify.get_song_url", skip(kana_name, romaji_name, artist_name), fields(song = %romaji_name, artist = %artist_name))]
-pub fn get_song_url(
+#[instrument(name = "spotify.get_song_url", skip(cache, kana_name, romaji_name, artist_name), fields(song = %romaji_name, artist = %artist_name))]
+pub async fn get_song_url(
+    cache: &dyn Cache,
     romaji_name: String,
     kana_name: Option<String>,
     artist_name: String,
 ) -> Option<String> {
     // If cached response if found, return it
     let cache_key = format!("{romaji_name}:{kana_name:#?}:{artist_name}");
-    match check_cache(&cache_key) {
-        Ok(value) => {
-            info!("Cache hit for {:#?}", cache_key);
-            return match value.as_str() {
-                "None" => None,
-                _ => Some(value),
-            };
-        }
-        Err(e) => {
-            info!("Cache miss for {:#?} with error {:#?}", cache_key, e);
+    if let Some(value) = cache.get(&cache_key).await {
+        info!("Cache hit for {:#?}", cache_key);
+        return match value.as_str() {
+            "None" => None,
+            _ => Some(value),
+        };
+    }
+    info!("Cache miss for {:#?}", cache_key);
+
+    // rspotify's ureq client is blocking, so only the Spotify lookup runs on
+    // the blocking pool; cache I/O stays async.
+    let url = match task::spawn_blocking(move || {
+        search_song_url(&romaji_name, kana_name.as_deref(), &artist_name)
+    })
+    .await
+    {
+        Ok(url) => url,
+        Err(err) => {
+            error!(error = %err, "Spotify search task panicked");
+            return None;
         }
     };
 
-    let romaji_search = send_search_request(&romaji_name, &artist_name);
+    cache
+        .set(
+            &cache_key,
+            url.as_deref().unwrap_or("None"),
+            DEFAULT_CACHE_TTL,
+        )
+        .await;
+    url
+}
+
+#[instrument(name = "spotify.search_song_url", skip_all, fields(song = %romaji_name, artist = %artist_name))]
+fn search_song_url(
+    romaji_name: &str,
+    kana_name: Option<&str>,
+    artist_name: &str,
+) -> Option<String> {
+    let romaji_search = send_search_request(romaji_name, artist_name);
     match romaji_search {
         Ok(search_result) => {
             info!("Searched track: {search_result:#?}");
             if let Some(url) = get_url_from_search_result(search_result) {
-                try_to_cache_response(&cache_key, &url);
                 return Some(url);
             } else if let Some(kana_name) = kana_name {
-                let kana_search = send_search_request(&kana_name, &artist_name);
+                let kana_search = send_search_request(kana_name, artist_name);
                 match kana_search {
                     Ok(search_result) => {
                         info!(
                             "Searched track using Track: {kana_name:#?} Artist: {artist_name:#?}: {search_result:#?}"
                         );
-                        match get_url_from_search_result(search_result) {
-                            Some(url) => {
-                                try_to_cache_response(&cache_key, &url);
-                                return Some(url);
-                            }
-                            None => {
-                                try_to_cache_response(&cache_key, "None");
-                                return None;
-                            }
-                        }
+                        return get_url_from_search_result(search_result);
                     }
                     Err(e) => {
                         info!("Error searching track: {e:#?}");
                     }
                 }
             } else {
-                try_to_cache_response(&cache_key, "None");
                 return None;
             }
         }
         Err(err) => info!("Could not find track: {err:#?}"),
     }
-    try_to_cache_response(&cache_key, "None");
     None
 }
 
@@ -126,17 +142,44 @@ fn get_url_from_search_result(search_result: SearchResult) -> Option<String> {
 }
 
 /// Fill in `spotify_url` for each [`ParsedSong`] that has an artist.
-/// This performs synchronous Spotify + Redis I/O and is intended to run
-/// inside `spawn_blocking`.
-#[instrument(name = "spotify.enrich_songs", skip(songs), fields(count = songs.len()))]
-pub fn enrich_songs_with_spotify(songs: &mut [ParsedSong]) {
+/// Cached links are served from `cache`; misses run the blocking Spotify
+/// search on Tokio's blocking pool.
+#[instrument(name = "spotify.enrich_songs", skip(cache, songs), fields(count = songs.len()))]
+pub async fn enrich_songs_with_spotify(cache: &dyn Cache, songs: &mut [ParsedSong]) {
     for song in songs.iter_mut() {
         if let Some(ref artist) = song.artist_names {
             song.spotify_url = get_song_url(
+                cache,
                 song.romaji_name.clone(),
                 song.kana_name.clone(),
                 artist.clone(),
-            );
+            )
+            .await;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cache::InMemoryCache;
+
+    #[tokio::test]
+    async fn cached_song_url_skips_spotify_search() {
+        let url = "https://open.spotify.com/track/abc";
+        let cache = InMemoryCache::with_entry("Gurenge:None:LiSA", url);
+
+        let result = get_song_url(&cache, "Gurenge".to_string(), None, "LiSA".to_string()).await;
+
+        assert_eq!(result.as_deref(), Some(url));
+    }
+
+    #[tokio::test]
+    async fn cached_negative_lookup_returns_none() {
+        let cache = InMemoryCache::with_entry("Unknown:None:Nobody", "None");
+
+        let result = get_song_url(&cache, "Unknown".to_string(), None, "Nobody".to_string()).await;
+
+        assert_eq!(result, None);
+    }
+}
@@ -3,10 +3,20 @@ pub const NOT_FOUND_MANGA: &str = "I couldn't find that manga on AniList.";
 pub const NOT_FOUND_CHARACTER: &str = "I couldn't find that character on AniList.";
 pub const NSFW_NOT_ALLOWED: &str =
     "That result is age-restricted, so I can only show it in an NSFW channel.";
+pub const ANILIST_RATE_LIMITED: &str =
+    "AniList is getting too many requests right now. Please try again in a minute.";
+pub const ANILIST_UNAVAILABLE: &str =
+    "AniList is having trouble right now. Please try again in a few minutes.";
+pub const ANILIST_LOOKUP_FAILED: &str =
+    "I couldn't look that up on AniList right now. Please try again later.";
+pub const MAL_UNAVAILABLE: &str =
+    "MyAnimeList is having trouble right now. Please try again in a few minutes.";
 pub const EMPTY_STR: &str = "-";
+pub const STALE_DATA_NOTICE: &str = "AniList is unreachable, so this data may be out of date";
 pub const ANILIST_STATUS_RELEASING: &str = "RELEASING";
-#[cfg(test)]
+pub const ANILIST_STATUS_NOT_YET_RELEASED: &str = "NOT_YET_RELEASED";
 pub const ANILIST_STATUS_FINISHED: &str = "FINISHED";
+pub const ANILIST_STATUS_CANCELLED: &str = "CANCELLED";
 
 // Environment variables
 pub const ENV: &str = "ENV";